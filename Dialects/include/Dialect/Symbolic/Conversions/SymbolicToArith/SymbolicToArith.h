@@ -4,7 +4,7 @@
 #include <mlir/Dialect/Func/IR/FuncOps.h>
 #include <mlir/Pass/Pass.h>
 
-#define BINARY_OP_CONVERTER(SYM_OP, ARITH_OP)                                                                          \
+#define BINARY_OP_CONVERTER(SYM_OP, INT_ARITH_OP, FLOAT_ARITH_OP)                                                      \
     struct Convert##SYM_OP : public OpConversionPattern<symbolic::SYM_OP##Op>                                          \
     {                                                                                                                  \
         using OpConversionPattern::OpConversionPattern;                                                                \
@@ -12,13 +12,18 @@
         llvm::LogicalResult matchAndRewrite(symbolic::SYM_OP##Op op, OpAdaptor adaptor,                                \
                                             ConversionPatternRewriter &rewriter) const override                        \
         {                                                                                                              \
-            auto newOp = arith::ARITH_OP::create(rewriter, op.getLoc(), adaptor.getLhs(), adaptor.getRhs());           \
-            rewriter.replaceOp(op.getOperation(), newOp);                                                              \
+            SymExprType exprTy = llvm::cast<SymExprType>(op.getType());                                                \
+            if (exprTy.isFloat())                                                                                      \
+                rewriter.replaceOp(op.getOperation(), arith::FLOAT_ARITH_OP::create(                                   \
+                                                          rewriter, op.getLoc(), adaptor.getLhs(), adaptor.getRhs())); \
+            else                                                                                                       \
+                rewriter.replaceOp(op.getOperation(), arith::INT_ARITH_OP::create(                                     \
+                                                          rewriter, op.getLoc(), adaptor.getLhs(), adaptor.getRhs())); \
             return llvm::success();                                                                                    \
         }                                                                                                              \
     };
 
-#define SIGNEDNESS_BINARY_OP_CONVERTER(SYM_OP, SIGNED_ARITH_OP, UNSIGNED_ARITH_OP)                                     \
+#define SIGNEDNESS_BINARY_OP_CONVERTER(SYM_OP, SIGNED_ARITH_OP, UNSIGNED_ARITH_OP, FLOAT_ARITH_OP)                     \
     struct Convert##SYM_OP : public OpConversionPattern<symbolic::SYM_OP##Op>                                          \
     {                                                                                                                  \
         using OpConversionPattern::OpConversionPattern;                                                                \
@@ -27,7 +32,10 @@
                                             ConversionPatternRewriter &rewriter) const override                        \
         {                                                                                                              \
             SymExprType exprTy = llvm::cast<SymExprType>(op.getType());                                                \
-            if (exprTy.getIsSigned())                                                                                  \
+            if (exprTy.isFloat())                                                                                      \
+                rewriter.replaceOp(op.getOperation(), arith::FLOAT_ARITH_OP::create(                                   \
+                                                          rewriter, op.getLoc(), adaptor.getLhs(), adaptor.getRhs())); \
+            else if (exprTy.getIsSigned())                                                                             \
                 rewriter.replaceOp(op.getOperation(), arith::SIGNED_ARITH_OP::create(                                  \
                                                           rewriter, op.getLoc(), adaptor.getLhs(), adaptor.getRhs())); \
             else                                                                                                       \
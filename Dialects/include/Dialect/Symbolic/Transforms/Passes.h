@@ -1,6 +1,9 @@
 #pragma once
 
+#include "Dialect/Symbolic/Transforms/SymbolicExpand.h"
 #include "Dialect/Symbolic/Transforms/SymbolicExtractEval.h"
+#include "Dialect/Symbolic/Transforms/SymbolicInline.h"
+#include "Dialect/Symbolic/Transforms/SymbolicSimplify.h"
 
 namespace mlir
 {
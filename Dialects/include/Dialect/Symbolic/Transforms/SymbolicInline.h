@@ -0,0 +1,22 @@
+#pragma once
+
+#include <mlir/Dialect/Func/IR/FuncOps.h>
+#include <mlir/IR/BuiltinOps.h>
+#include <mlir/Pass/Pass.h>
+
+namespace mlir
+{
+namespace symbolic
+{
+#define GEN_PASS_DECL_SYMBOLICINLINE
+#include "Dialect/Symbolic/Transforms/Passes.h.inc"
+
+/// Copies into `dest` the body of every function with symbolic expressions in
+/// its signature that `dest` declares and `src` defines.
+///
+/// Each module is compiled on its own, but a function taking or returning
+/// symbolic expressions can only be inlined if its body is available. Returns
+/// whether any body was imported.
+bool importSymbolicFunctions(ModuleOp dest, ModuleOp src);
+} // namespace symbolic
+} // namespace mlir
@@ -0,0 +1,13 @@
+#pragma once
+
+#include <mlir/Dialect/Arith/IR/Arith.h>
+#include <mlir/Pass/Pass.h>
+
+namespace mlir
+{
+namespace symbolic
+{
+#define GEN_PASS_DECL_SYMBOLICSIMPLIFY
+#include "Dialect/Symbolic/Transforms/Passes.h.inc"
+} // namespace symbolic
+} // namespace mlir
@@ -13,6 +13,7 @@ extern "C"
     MLIR_DECLARE_CAPI_DIALECT_REGISTRATION(Symbolic, symbolic);
     MLIR_CAPI_EXPORTED void mlirInsertSymbolicDialect(MlirDialectRegistry registry);
     MLIR_CAPI_EXPORTED MlirType getSymExprType(MlirContext ctx, MlirType innerType, bool isSigned);
+    MLIR_CAPI_EXPORTED bool mlirSymbolicImportFunctions(MlirModule dest, MlirModule src);
 
 #ifdef __cplusplus
 }
@@ -25,15 +25,15 @@ class SymbolicToArithTypeConverter : public TypeConverter
     SymbolicToArithTypeConverter(MLIRContext *ctx)
     {
         addConversion([](Type ty) { return ty; });
-        /// For now, every !symbolic.expr is converted to float64.
+        /// Every !symbolic.expr is converted to its inner numeric type.
         addConversion([](SymExprType exprTy) -> Type { return exprTy.getInnerType(); });
     }
 };
 
-BINARY_OP_CONVERTER(Add, AddIOp)
-BINARY_OP_CONVERTER(Sub, SubIOp)
-BINARY_OP_CONVERTER(Mul, MulIOp)
-SIGNEDNESS_BINARY_OP_CONVERTER(Div, DivSIOp, DivUIOp)
+BINARY_OP_CONVERTER(Add, AddIOp, AddFOp)
+BINARY_OP_CONVERTER(Sub, SubIOp, SubFOp)
+BINARY_OP_CONVERTER(Mul, MulIOp, MulFOp)
+SIGNEDNESS_BINARY_OP_CONVERTER(Div, DivSIOp, DivUIOp, DivFOp)
 
 /// Replace symbols witht the function's actual argument to be evaluated.
 struct ConvertSym : public OpConversionPattern<symbolic::SymOp>
@@ -56,6 +56,19 @@ struct ConvertSym : public OpConversionPattern<symbolic::SymOp>
     }
 };
 
+/// Replace symbolic constants with plain arith constants.
+struct ConvertConstant : public OpConversionPattern<symbolic::ConstantOp>
+{
+    using OpConversionPattern::OpConversionPattern;
+
+    llvm::LogicalResult matchAndRewrite(symbolic::ConstantOp op, OpAdaptor adaptor,
+                                        ConversionPatternRewriter &rewriter) const override
+    {
+        rewriter.replaceOp(op, arith::ConstantOp::create(rewriter, op.getLoc(), op.getValue()));
+        return llvm::success();
+    }
+};
+
 /// Get rid of UnrealizedConversionCast operations.
 struct ConvertCast : public OpConversionPattern<UnrealizedConversionCastOp>
 {
@@ -98,7 +111,8 @@ struct SymbolicToArith : impl::SymbolicToArithBase<SymbolicToArith>
 
         mlir::RewritePatternSet patterns(&getContext());
 
-        patterns.add<ConvertAdd, ConvertSub, ConvertMul, ConvertDiv, ConvertSym, ConvertCast>(typeConverter, ctx);
+        patterns.add<ConvertAdd, ConvertSub, ConvertMul, ConvertDiv, ConvertSym, ConvertConstant, ConvertCast>(typeConverter,
+                                                                                                    ctx);
 
         // Propagate the type convertions across functions' signatures.
         populateFunctionOpInterfaceTypeConversionPattern<func::FuncOp>(patterns, typeConverter);
@@ -1,3 +1,5 @@
+#include <mlir/Transforms/InliningUtils.h>
+
 #include "Dialect/Symbolic/IR/SymbolicDialect.h"
 #include "Dialect/Symbolic/IR/SymbolicOps.h"
 #include "Dialect/Symbolic/IR/SymbolicTypes.h"
@@ -13,6 +15,19 @@ namespace mlir
 {
 namespace symbolic
 {
+/// Symbolic operations are pure and don't depend on the function they live in,
+/// so they can always be inlined. This is what allows functions to take and
+/// return symbolic expressions (see the `symbolic-inline` pass).
+struct SymbolicInlinerInterface : public DialectInlinerInterface
+{
+    using DialectInlinerInterface::DialectInlinerInterface;
+
+    bool isLegalToInline(Operation *, Region *, bool, IRMapping &) const final
+    {
+        return true;
+    }
+};
+
 void SymbolicDialect::initialize()
 {
     addTypes<
@@ -23,6 +38,7 @@ void SymbolicDialect::initialize()
 #define GET_OP_LIST
 #include "Dialect/Symbolic/IR/SymbolicOps.cpp.inc"
         >();
+    addInterfaces<SymbolicInlinerInterface>();
 }
 } // namespace symbolic
 } // namespace mlir
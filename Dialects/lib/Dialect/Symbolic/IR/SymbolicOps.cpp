@@ -4,5 +4,30 @@ namespace mlir
 {
 namespace symbolic
 {
+LogicalResult ConstantOp::verify()
+{
+    SymExprType exprTy = llvm::cast<SymExprType>(getRes().getType());
+
+    if (getValue().getType() != exprTy.getInnerType())
+        return emitOpError("value type must match the expression's inner type");
+
+    return success();
+}
+
+LogicalResult SubsOp::verify()
+{
+    if (getSyms().size() != getValues().size())
+        return emitOpError("expected one value per symbol");
+
+    SymExprType exprTy = llvm::cast<SymExprType>(getExpr().getType());
+
+    for (Value value : getValues())
+    {
+        if (value.getType() != exprTy && value.getType() != exprTy.getInnerType())
+            return emitOpError("values must be expressions of the same type or of its inner type");
+    }
+
+    return success();
+}
 } // namespace symbolic
 } // namespace mlir
@@ -5,6 +5,13 @@ namespace mlir
 {
 namespace symbolic
 {
+llvm::LogicalResult SymExprType::verify(llvm::function_ref<InFlightDiagnostic()> emitError, Type innerType,
+                                        bool isSigned)
+{
+    if (!mlir::isa<IntegerType, FloatType>(innerType))
+        return emitError() << "expected an integer or float inner type, got " << innerType;
 
+    return llvm::success();
+}
 } // namespace symbolic
 } // namespace mlir
@@ -0,0 +1,362 @@
+#include <llvm/ADT/DenseMap.h>
+#include <llvm/ADT/DenseSet.h>
+#include <llvm/ADT/StringMap.h>
+#include <llvm/ADT/TypeSwitch.h>
+#include <llvm/Support/Casting.h>
+#include <mlir/Dialect/Arith/IR/Arith.h>
+#include <mlir/IR/BuiltinAttributes.h>
+#include <mlir/IR/BuiltinTypes.h>
+#include <mlir/IR/IRMapping.h>
+#include <mlir/IR/Matchers.h>
+#include <mlir/IR/PatternMatch.h>
+#include <mlir/IR/Value.h>
+#include <mlir/Support/LLVM.h>
+#include <mlir/Transforms/GreedyPatternRewriteDriver.h>
+
+#include "Dialect/Symbolic/IR/SymbolicOps.h"
+#include "Dialect/Symbolic/IR/SymbolicTypes.h"
+#include "Dialect/Symbolic/Transforms/SymbolicExpand.h"
+
+namespace
+{
+using namespace mlir;
+using namespace symbolic;
+
+/// Checks that every node of the expression is known, so it can be rebuilt.
+///
+/// Values of numeric types are constants with respect to any symbol. Symbolic
+/// values must be built out of known operations, since there is no way to
+/// differentiate or substitute an opaque expression (e.g a function
+/// argument). Nested diff and subs operations must be expanded first.
+static bool isExpandable(Value val, DenseSet<Value> &visited)
+{
+    if (!visited.insert(val).second)
+        return true;
+
+    if (!isa<SymExprType>(val.getType()))
+        return true;
+
+    Operation *op = val.getDefiningOp();
+
+    if (!op)
+        return false;
+
+    return llvm::TypeSwitch<Operation *, bool>(op)
+        .Case<SymOp, ConstantOp>([](auto) { return true; })
+        .Case<AddOp, SubOp, MulOp, DivOp>([&](Operation *binop) {
+            return isExpandable(binop->getOperand(0), visited) && isExpandable(binop->getOperand(1), visited);
+        })
+        .Default([](Operation *) { return false; });
+}
+
+/// Builds the derivative of an expression with respect to a single symbol.
+///
+/// Derivatives are memoized per value so shared subexpressions are only
+/// differentiated once. Trivial operations involving the constants zero and
+/// one are folded on the fly, otherwise the product and quotient rules would
+/// make the expression grow quickly.
+class Differentiator
+{
+  public:
+    Differentiator(PatternRewriter &rewriter, Location loc, SymExprType exprTy, StringRef sym)
+        : rewriter(rewriter), loc(loc), exprTy(exprTy), sym(sym)
+    {
+    }
+
+    Value derive(Value val)
+    {
+        if (Value cached = derivatives.lookup(val))
+            return cached;
+
+        Value result;
+        Operation *op = val.getDefiningOp();
+
+        if (!op || !isa<SymExprType>(val.getType()))
+            result = getConstant(0);
+        else
+            result = llvm::TypeSwitch<Operation *, Value>(op)
+                         .Case<SymOp>([&](SymOp symOp) { return getConstant(symOp.getName() == sym ? 1 : 0); })
+                         .Case<ConstantOp>([&](ConstantOp) { return getConstant(0); })
+                         .Case<AddOp>([&](AddOp add) {
+                             return createAdd(derive(add.getLhs()), derive(add.getRhs()));
+                         })
+                         .Case<SubOp>([&](SubOp sub) {
+                             return createSub(derive(sub.getLhs()), derive(sub.getRhs()));
+                         })
+                         .Case<MulOp>([&](MulOp mul) {
+                             // (f * g)' = f' * g + f * g'
+                             Value lhs = mul.getLhs();
+                             Value rhs = mul.getRhs();
+
+                             return createAdd(createMul(derive(lhs), rhs), createMul(lhs, derive(rhs)));
+                         })
+                         .Case<DivOp>([&](DivOp div) {
+                             // (f / g)' = (f' * g - f * g') / g^2
+                             Value lhs = div.getLhs();
+                             Value rhs = div.getRhs();
+                             Value dRhs = derive(rhs);
+
+                             if (isZero(dRhs))
+                                 return createDiv(derive(lhs), rhs);
+
+                             Value numerator = createSub(createMul(derive(lhs), rhs), createMul(lhs, dRhs));
+
+                             return createDiv(numerator, createMul(rhs, rhs));
+                         });
+
+        derivatives[val] = result;
+
+        return result;
+    }
+
+  private:
+    Value getConstant(int64_t value)
+    {
+        Value &cst = value == 0 ? zero : one;
+
+        if (cst)
+            return cst;
+
+        Type innerTy = exprTy.getInnerType();
+        TypedAttr attr = exprTy.isFloat() ? TypedAttr(rewriter.getFloatAttr(innerTy, static_cast<double>(value)))
+                                          : TypedAttr(rewriter.getIntegerAttr(innerTy, value));
+
+        cst = arith::ConstantOp::create(rewriter, loc, attr);
+
+        return cst;
+    }
+
+    static bool isZero(Value val)
+    {
+        return matchPattern(val, m_Zero()) || matchPattern(val, m_AnyZeroFloat());
+    }
+
+    static bool isOne(Value val)
+    {
+        return matchPattern(val, m_One()) || matchPattern(val, m_OneFloat());
+    }
+
+    Value createAdd(Value lhs, Value rhs)
+    {
+        if (isZero(lhs))
+            return rhs;
+        if (isZero(rhs))
+            return lhs;
+
+        return AddOp::create(rewriter, loc, exprTy, lhs, rhs);
+    }
+
+    Value createSub(Value lhs, Value rhs)
+    {
+        if (isZero(rhs))
+            return lhs;
+
+        return SubOp::create(rewriter, loc, exprTy, lhs, rhs);
+    }
+
+    Value createMul(Value lhs, Value rhs)
+    {
+        if (isZero(lhs) || isZero(rhs))
+            return getConstant(0);
+        if (isOne(lhs))
+            return rhs;
+        if (isOne(rhs))
+            return lhs;
+
+        return MulOp::create(rewriter, loc, exprTy, lhs, rhs);
+    }
+
+    Value createDiv(Value lhs, Value rhs)
+    {
+        if (isZero(lhs))
+            return getConstant(0);
+        if (isOne(rhs))
+            return lhs;
+
+        return DivOp::create(rewriter, loc, exprTy, lhs, rhs);
+    }
+
+    PatternRewriter &rewriter;
+    Location loc;
+    SymExprType exprTy;
+    StringRef sym;
+    Value zero;
+    Value one;
+    DenseMap<Value, Value> derivatives;
+};
+
+/// Rebuilds an expression with some of its symbols replaced.
+///
+/// Only the nodes depending on a replaced symbol are recreated, any other
+/// subexpression is reused as is. Rebuilt values are memoized so shared
+/// subexpressions stay shared.
+class Substituter
+{
+  public:
+    Substituter(PatternRewriter &rewriter, const llvm::StringMap<Value> &replacements)
+        : rewriter(rewriter), replacements(replacements)
+    {
+    }
+
+    Value substitute(Value val)
+    {
+        if (Value cached = substituted.lookup(val))
+            return cached;
+
+        Value result = val;
+        Operation *op = val.getDefiningOp();
+
+        if (auto symOp = dyn_cast_or_null<SymOp>(op))
+        {
+            auto it = replacements.find(symOp.getName());
+
+            if (it != replacements.end())
+                result = it->second;
+        }
+        else if (isa_and_nonnull<AddOp, SubOp, MulOp, DivOp>(op))
+        {
+            Value lhs = op->getOperand(0);
+            Value rhs = op->getOperand(1);
+            Value newLhs = substitute(lhs);
+            Value newRhs = substitute(rhs);
+
+            if (newLhs != lhs || newRhs != rhs)
+            {
+                IRMapping mapping;
+                mapping.map(lhs, newLhs);
+                mapping.map(rhs, newRhs);
+
+                result = rewriter.clone(*op, mapping)->getResult(0);
+            }
+        }
+
+        substituted[val] = result;
+
+        return result;
+    }
+
+  private:
+    PatternRewriter &rewriter;
+    const llvm::StringMap<Value> &replacements;
+    DenseMap<Value, Value> substituted;
+};
+
+/// Lifts a numeric value back into a symbolic expression.
+///
+/// Expanding an operation may fold its result to a plain number (e.g d(x)/dx
+/// or x[x = 2]). Users expect a symbolic expression, so constants become a
+/// symbolic.constant and any other value is added to zero.
+static Value liftToExpr(PatternRewriter &rewriter, Location loc, SymExprType exprTy, Value val)
+{
+    if (isa<SymExprType>(val.getType()))
+        return val;
+
+    TypedAttr attr;
+
+    if (matchPattern(val, m_Constant(&attr)))
+        return ConstantOp::create(rewriter, loc, exprTy, attr);
+
+    Type innerTy = exprTy.getInnerType();
+    TypedAttr zeroAttr = exprTy.isFloat() ? TypedAttr(rewriter.getFloatAttr(innerTy, 0.0))
+                                          : TypedAttr(rewriter.getIntegerAttr(innerTy, 0));
+    Value zero = arith::ConstantOp::create(rewriter, loc, zeroAttr);
+
+    return AddOp::create(rewriter, loc, exprTy, val, zero);
+}
+} // namespace
+
+namespace mlir
+{
+namespace symbolic
+{
+#define GEN_PASS_DEF_SYMBOLICEXPAND
+#include "Dialect/Symbolic/Transforms/Passes.h.inc"
+
+struct ExpandDiffPattern : public OpRewritePattern<DiffOp>
+{
+    using OpRewritePattern<DiffOp>::OpRewritePattern;
+
+    LogicalResult matchAndRewrite(DiffOp op, PatternRewriter &rewriter) const override
+    {
+        DenseSet<Value> visited;
+
+        if (!isExpandable(op.getExpr(), visited))
+            return rewriter.notifyMatchFailure(op, "expression can't be differentiated");
+
+        SymExprType exprTy = llvm::cast<SymExprType>(op.getExpr().getType());
+
+        rewriter.setInsertionPoint(op);
+
+        Differentiator differentiator(rewriter, op.getLoc(), exprTy, op.getSym());
+        Value derivative = differentiator.derive(op.getExpr());
+
+        rewriter.replaceOp(op, liftToExpr(rewriter, op.getLoc(), exprTy, derivative));
+
+        return success();
+    }
+};
+
+struct ExpandSubsPattern : public OpRewritePattern<SubsOp>
+{
+    using OpRewritePattern<SubsOp>::OpRewritePattern;
+
+    LogicalResult matchAndRewrite(SubsOp op, PatternRewriter &rewriter) const override
+    {
+        DenseSet<Value> visited;
+
+        if (!isExpandable(op.getExpr(), visited))
+            return rewriter.notifyMatchFailure(op, "expression can't be substituted");
+
+        llvm::StringMap<Value> replacements;
+
+        for (auto [sym, value] : llvm::zip_equal(op.getSyms().getAsValueRange<StringAttr>(), op.getValues()))
+            replacements[sym] = value;
+
+        SymExprType exprTy = llvm::cast<SymExprType>(op.getExpr().getType());
+
+        rewriter.setInsertionPoint(op);
+
+        Substituter substituter(rewriter, replacements);
+        Value result = substituter.substitute(op.getExpr());
+
+        rewriter.replaceOp(op, liftToExpr(rewriter, op.getLoc(), exprTy, result));
+
+        return success();
+    }
+};
+
+/// Pass to expand diff and subs operations.
+///
+/// Nested operations are expanded from the innermost one outwards, the greedy
+/// driver revisits the outer operation once its operand is rewritten. This is
+/// why both live in the same pass: a substitution over a derivative can only
+/// be expanded once the derivative is.
+struct SymbolicExpand : impl::SymbolicExpandBase<SymbolicExpand>
+{
+    using SymbolicExpandBase::SymbolicExpandBase;
+
+    void runOnOperation() override
+    {
+        mlir::RewritePatternSet patterns(&getContext());
+
+        patterns.add<ExpandDiffPattern, ExpandSubsPattern>(&getContext());
+
+        (void)applyPatternsGreedily(getOperation(), std::move(patterns));
+
+        WalkResult result = getOperation()->walk([](Operation *op) {
+            if (isa<DiffOp>(op))
+                op->emitOpError("expression can't be differentiated");
+            else if (isa<SubsOp>(op))
+                op->emitOpError("expression can't be substituted");
+            else
+                return WalkResult::advance();
+
+            return WalkResult::interrupt();
+        });
+
+        if (result.wasInterrupted())
+            signalPassFailure();
+    }
+};
+} // namespace symbolic
+} // namespace mlir
@@ -1,5 +1,6 @@
 #include <cstdint>
 #include <llvm/ADT/Hashing.h>
+#include <llvm/ADT/StringSet.h>
 #include <llvm/ADT/TypeSwitch.h>
 #include <llvm/Support/Casting.h>
 #include <mlir/Dialect/Arith/IR/Arith.h>
@@ -40,7 +41,7 @@ static std::optional<llvm::hash_code> getExpressionHash(mlir::Value value)
         return llvm::hash_combine(value.getType());
 
     return llvm::TypeSwitch<Operation *, std::optional<llvm::hash_code>>(op)
-        .Case<arith::ConstantOp>([&](auto cst) { return llvm::hash_combine(cst.getValue()); })
+        .Case<arith::ConstantOp, symbolic::ConstantOp>([&](auto cst) { return llvm::hash_combine(cst.getValue()); })
         .Case<symbolic::SymOp>([&](auto sym) { return llvm::hash_combine(sym.getName()); })
         .Case<symbolic::AddOp, symbolic::SubOp, symbolic::MulOp, symbolic::DivOp>(
             [&](Operation *binop) -> std::optional<llvm::hash_code> {
@@ -71,6 +72,27 @@ static void collectFreeVars(Value val, DenseSet<Value> &freeVars)
         collectFreeVars(operand, freeVars);
 }
 
+/// Collects the names of the symbols the expression depends on.
+static void collectSymbols(Value val, DenseSet<Value> &visited, llvm::StringSet<> &syms)
+{
+    if (!visited.insert(val).second)
+        return;
+
+    Operation *op = val.getDefiningOp();
+
+    if (!op)
+        return;
+
+    if (auto symOp = llvm::dyn_cast<symbolic::SymOp>(op))
+    {
+        syms.insert(symOp.getName());
+        return;
+    }
+
+    for (Value operand : op->getOperands())
+        collectSymbols(operand, visited, syms);
+}
+
 /// Recursively clones the expression tree into the current builder insertion
 /// point, using `mapper` to deduplicate already-cloned values.
 /// Free variables must already be mapped to function arguments before calling.
@@ -231,6 +253,33 @@ struct SymbolicExtractEval : impl::SymbolicExtractEvalBase<SymbolicExtractEval>
 
     void runOnOperation() override
     {
+        // Every symbol must be given a value. An expression coming from
+        // another function may use symbols the caller doesn't know about.
+        WalkResult result = getOperation()->walk([](EvalOp op) {
+            DenseSet<Value> visited;
+            llvm::StringSet<> used;
+            llvm::StringSet<> bound;
+
+            collectSymbols(op.getExpr(), visited, used);
+
+            for (Attribute sym : op.getSyms())
+                bound.insert(mlir::cast<StringAttr>(sym).getValue());
+
+            for (const auto &sym : used)
+            {
+                if (!bound.contains(sym.getKey()))
+                {
+                    op.emitOpError("symbol '") << sym.getKey() << "' is not given a value";
+                    return WalkResult::interrupt();
+                }
+            }
+
+            return WalkResult::advance();
+        });
+
+        if (result.wasInterrupted())
+            return signalPassFailure();
+
         mlir::RewritePatternSet patterns(&getContext());
         EvalToFuncState state;
 
@@ -0,0 +1,203 @@
+#include <llvm/ADT/STLExtras.h>
+#include <llvm/Support/Casting.h>
+#include <mlir/Dialect/Func/IR/FuncOps.h>
+#include <mlir/Dialect/LLVMIR/LLVMTypes.h>
+#include <mlir/IR/Builders.h>
+#include <mlir/IR/BuiltinOps.h>
+#include <mlir/IR/IRMapping.h>
+#include <mlir/IR/PatternMatch.h>
+#include <mlir/IR/SymbolTable.h>
+#include <mlir/Support/LLVM.h>
+#include <mlir/Transforms/GreedyPatternRewriteDriver.h>
+#include <mlir/Transforms/InliningUtils.h>
+
+#include "Dialect/Symbolic/IR/SymbolicTypes.h"
+#include "Dialect/Symbolic/Transforms/SymbolicInline.h"
+
+namespace
+{
+using namespace mlir;
+using namespace symbolic;
+
+/// Whether the type is a symbolic expression or a struct holding one.
+static bool holdsSymExpr(Type ty)
+{
+    if (auto structTy = dyn_cast<LLVM::LLVMStructType>(ty))
+        return llvm::any_of(structTy.getBody(), holdsSymExpr);
+
+    return isa<SymExprType>(ty);
+}
+
+/// Whether the function takes or returns symbolic expressions.
+static bool hasSymbolicSignature(func::FuncOp fn)
+{
+    FunctionType fnTy = fn.getFunctionType();
+
+    return llvm::any_of(fnTy.getInputs(), holdsSymExpr) || llvm::any_of(fnTy.getResults(), holdsSymExpr);
+}
+
+/// Returns the callee of a call if it has a symbolic signature.
+static func::FuncOp getSymbolicCallee(func::CallOp call, SymbolTable &symbolTable)
+{
+    auto callee = symbolTable.lookup<func::FuncOp>(call.getCallee());
+
+    if (!callee || !hasSymbolicSignature(callee))
+        return nullptr;
+
+    return callee;
+}
+
+/// Whether the function calls another function with a symbolic signature.
+static bool callsSymbolicFunction(func::FuncOp fn, SymbolTable &symbolTable)
+{
+    WalkResult result = fn.walk([&](func::CallOp call) {
+        return getSymbolicCallee(call, symbolTable) ? WalkResult::interrupt() : WalkResult::advance();
+    });
+
+    return result.wasInterrupted();
+}
+} // namespace
+
+namespace mlir
+{
+namespace symbolic
+{
+#define GEN_PASS_DEF_SYMBOLICINLINE
+#include "Dialect/Symbolic/Transforms/Passes.h.inc"
+
+bool importSymbolicFunctions(ModuleOp dest, ModuleOp src)
+{
+    SymbolTable destTable(dest);
+    SymbolTable srcTable(src);
+    SmallVector<func::FuncOp> worklist;
+    bool imported = false;
+
+    for (func::FuncOp fn : dest.getOps<func::FuncOp>())
+    {
+        if (fn.isExternal() && hasSymbolicSignature(fn))
+            worklist.push_back(fn);
+    }
+
+    while (!worklist.empty())
+    {
+        func::FuncOp decl = worklist.pop_back_val();
+        auto def = srcTable.lookup<func::FuncOp>(decl.getSymName());
+
+        if (!def || def.isExternal())
+            continue;
+
+        IRMapping mapping;
+        def.getBody().cloneInto(&decl.getBody(), mapping);
+        imported = true;
+
+        // The imported body may reference functions that only live in the
+        // source module, so we declare them as well. Symbolic ones need their
+        // body too.
+        OpBuilder builder = OpBuilder::atBlockEnd(dest.getBody());
+
+        decl.walk([&](func::CallOp call) {
+            if (destTable.lookup(call.getCallee()))
+                return;
+
+            auto callee = srcTable.lookup<func::FuncOp>(call.getCallee());
+
+            if (!callee)
+                return;
+
+            auto calleeDecl =
+                func::FuncOp::create(builder, callee.getLoc(), callee.getSymName(), callee.getFunctionType());
+            calleeDecl.setPrivate();
+            destTable.insert(calleeDecl);
+
+            if (hasSymbolicSignature(calleeDecl))
+                worklist.push_back(calleeDecl);
+        });
+    }
+
+    return imported;
+}
+
+/// Pass to inline functions with symbolic expressions in their signature.
+///
+/// Symbolic values are plain SSA values, the expression DAG can't cross a
+/// function boundary. Inlining every call to those functions makes the whole
+/// expression visible to the rest of the symbolic passes. Calls are inlined
+/// bottom-up, so a recursive function is never fully inlined and is reported.
+/// Once inlined, the functions are erased.
+///
+/// Structs holding symbolic expressions are SSA values too, built with
+/// llvm.insertvalue and read with llvm.extractvalue. Once everything is
+/// inlined, reads are folded into the expressions they read, so the structs
+/// are left unused and removed.
+struct SymbolicInline : impl::SymbolicInlineBase<SymbolicInline>
+{
+    using SymbolicInlineBase::SymbolicInlineBase;
+
+    void runOnOperation() override
+    {
+        ModuleOp module = getOperation();
+        SymbolTable symbolTable(module);
+        InlinerInterface interface(&getContext());
+        InlinerConfig config;
+        bool changed = true;
+
+        while (changed)
+        {
+            changed = false;
+
+            SmallVector<func::CallOp> calls;
+            module.walk([&](func::CallOp call) {
+                if (getSymbolicCallee(call, symbolTable))
+                    calls.push_back(call);
+            });
+
+            for (func::CallOp call : calls)
+            {
+                func::FuncOp callee = getSymbolicCallee(call, symbolTable);
+
+                // Leave the callee until the functions it calls are inlined.
+                if (callee.isExternal() || callsSymbolicFunction(callee, symbolTable))
+                    continue;
+
+                if (failed(inlineCall(interface, config.getCloneCallback(), call, callee, &callee.getBody())))
+                {
+                    call.emitOpError("could not inline function with symbolic expressions in its signature");
+                    return signalPassFailure();
+                }
+
+                call.erase();
+                changed = true;
+            }
+        }
+
+        WalkResult result = module.walk([&](func::CallOp call) {
+            func::FuncOp callee = getSymbolicCallee(call, symbolTable);
+
+            if (!callee)
+                return WalkResult::advance();
+
+            if (callee.isExternal())
+                call.emitOpError("function '") << callee.getSymName() << "' has no body to inline";
+            else
+                call.emitOpError("recursive function '")
+                    << callee.getSymName() << "' can't take or return symbolic expressions";
+
+            return WalkResult::interrupt();
+        });
+
+        if (result.wasInterrupted())
+            return signalPassFailure();
+
+        for (func::FuncOp fn : llvm::make_early_inc_range(module.getOps<func::FuncOp>()))
+        {
+            if (hasSymbolicSignature(fn))
+                symbolTable.erase(fn);
+        }
+
+        // Without patterns, the driver only folds operations and removes the
+        // dead ones.
+        (void)applyPatternsGreedily(module, FrozenRewritePatternSet());
+    }
+};
+} // namespace symbolic
+} // namespace mlir
@@ -0,0 +1,614 @@
+#include <map>
+#include <optional>
+#include <type_traits>
+
+#include <llvm/ADT/APFloat.h>
+#include <llvm/ADT/APInt.h>
+#include <llvm/ADT/DenseMap.h>
+#include <llvm/ADT/STLExtras.h>
+#include <llvm/ADT/SmallVector.h>
+#include <llvm/ADT/StringMap.h>
+#include <llvm/Support/Casting.h>
+#include <mlir/Dialect/Arith/IR/Arith.h>
+#include <mlir/IR/Builders.h>
+#include <mlir/IR/BuiltinAttributes.h>
+#include <mlir/IR/BuiltinTypes.h>
+#include <mlir/IR/PatternMatch.h>
+#include <mlir/IR/Value.h>
+#include <mlir/Support/LLVM.h>
+#include <mlir/Transforms/GreedyPatternRewriteDriver.h>
+
+#include "Dialect/Symbolic/IR/SymbolicOps.h"
+#include "Dialect/Symbolic/IR/SymbolicTypes.h"
+#include "Dialect/Symbolic/Transforms/SymbolicSimplify.h"
+
+namespace
+{
+using namespace mlir;
+using namespace symbolic;
+
+static bool isSymbolicArithOp(Operation *op)
+{
+    return isa<AddOp, SubOp, MulOp, DivOp>(op);
+}
+
+/// Returns the constant held by a value, either an arith.constant or a
+/// symbolic.constant.
+static std::optional<TypedAttr> getConstant(Value val)
+{
+    Operation *op = val.getDefiningOp();
+
+    if (auto cst = llvm::dyn_cast_or_null<arith::ConstantOp>(op))
+        return cst.getValue();
+    if (auto cst = llvm::dyn_cast_or_null<symbolic::ConstantOp>(op))
+        return cst.getValue();
+
+    return std::nullopt;
+}
+
+static std::optional<APInt> getConstantInt(Value val)
+{
+    std::optional<TypedAttr> attr = getConstant(val);
+
+    if (auto intAttr = llvm::dyn_cast_or_null<IntegerAttr>(attr.value_or(TypedAttr())))
+        return intAttr.getValue();
+
+    return std::nullopt;
+}
+
+static std::optional<APFloat> getConstantFloat(Value val)
+{
+    std::optional<TypedAttr> attr = getConstant(val);
+
+    if (auto floatAttr = llvm::dyn_cast_or_null<FloatAttr>(attr.value_or(TypedAttr())))
+        return floatAttr.getValue();
+
+    return std::nullopt;
+}
+
+static Value createIntConstant(OpBuilder &builder, Location loc, Type innerTy, const APInt &value)
+{
+    return arith::ConstantOp::create(builder, loc, builder.getIntegerAttr(innerTy, value));
+}
+
+/// A sum of monomials with integer coefficients.
+///
+/// A monomial is represented by the sorted identifiers of its factors, so
+/// `3 * x * x * y` is stored as `{x, x, y} -> 3`. The constant term is stored
+/// with an empty monomial. Terms with a zero coefficient are never stored.
+using Monomial = SmallVector<unsigned, 4>;
+using Polynomial = std::map<Monomial, APInt>;
+
+/// Rewrites an integer expression into a flat sum of products.
+///
+/// Nested additions and multiplications are flattened, like terms are merged
+/// and constants are folded. Products of sums are not distributed, since
+/// that could make the expression grow exponentially; they are kept as
+/// opaque factors instead. Divisions are kept as opaque factors too, with
+/// their operands normalized on their own.
+///
+/// Integer arithmetic wraps around, so reassociating is always safe.
+class IntNormalizer
+{
+  public:
+    IntNormalizer(OpBuilder &builder, Location loc, SymExprType exprTy)
+        : builder(builder), loc(loc), exprTy(exprTy), width(exprTy.getInnerType().getIntOrFloatBitWidth())
+    {
+    }
+
+    /// Returns the normalized expression or a null value if it can't be
+    /// represented as a symbolic expression (e.g it collapses into a free
+    /// variable).
+    Value normalize(Value root)
+    {
+        Value result = rebuild(flatten(root));
+
+        if (!result || !isa<SymExprType>(result.getType()))
+            return Value();
+
+        return result;
+    }
+
+  private:
+    Polynomial flatten(Value val)
+    {
+        auto cached = polynomials.find(val);
+
+        if (cached != polynomials.end())
+            return cached->second;
+
+        Polynomial poly;
+        Operation *op = val.getDefiningOp();
+
+        if (std::optional<APInt> cst = getConstantInt(val))
+        {
+            if (!cst->isZero())
+                poly.emplace(Monomial(), *cst);
+        }
+        else if (auto add = llvm::dyn_cast_or_null<AddOp>(op))
+        {
+            poly = flatten(add.getLhs());
+            accumulate(poly, flatten(add.getRhs()), /*negate=*/false);
+        }
+        else if (auto sub = llvm::dyn_cast_or_null<SubOp>(op))
+        {
+            poly = flatten(sub.getLhs());
+            accumulate(poly, flatten(sub.getRhs()), /*negate=*/true);
+        }
+        else if (auto mul = llvm::dyn_cast_or_null<MulOp>(op))
+        {
+            poly = multiply(flatten(mul.getLhs()), flatten(mul.getRhs()));
+        }
+        else if (auto div = llvm::dyn_cast_or_null<DivOp>(op))
+        {
+            poly = divide(flatten(div.getLhs()), flatten(div.getRhs()));
+        }
+        else
+        {
+            poly.emplace(Monomial{getAtomId(val)}, APInt(width, 1));
+        }
+
+        polynomials[val] = poly;
+
+        return poly;
+    }
+
+    static void accumulate(Polynomial &poly, const Polynomial &other, bool negate)
+    {
+        for (const auto &[monomial, coeff] : other)
+        {
+            APInt value = negate ? -coeff : coeff;
+            auto [it, inserted] = poly.emplace(monomial, value);
+
+            if (inserted)
+                continue;
+
+            it->second += value;
+
+            if (it->second.isZero())
+                poly.erase(it);
+        }
+    }
+
+    std::optional<APInt> getConstantTerm(const Polynomial &poly) const
+    {
+        if (poly.empty())
+            return APInt(width, 0);
+        if (poly.size() == 1 && poly.begin()->first.empty())
+            return poly.begin()->second;
+
+        return std::nullopt;
+    }
+
+    Polynomial multiply(const Polynomial &lhs, const Polynomial &rhs)
+    {
+        if (lhs.empty() || rhs.empty())
+            return {};
+
+        // Constants scale every term of the other operand.
+        if (std::optional<APInt> cst = getConstantTerm(lhs))
+            return scale(rhs, *cst);
+        if (std::optional<APInt> cst = getConstantTerm(rhs))
+            return scale(lhs, *cst);
+
+        // Single terms can be merged, otherwise sums become opaque factors.
+        auto [lhsMonomial, lhsCoeff] = asSingleTerm(lhs);
+        auto [rhsMonomial, rhsCoeff] = asSingleTerm(rhs);
+        APInt coeff = lhsCoeff * rhsCoeff;
+
+        if (coeff.isZero())
+            return {};
+
+        Monomial monomial(lhsMonomial);
+
+        monomial.append(rhsMonomial.begin(), rhsMonomial.end());
+        llvm::sort(monomial);
+
+        Polynomial result;
+
+        result.emplace(monomial, coeff);
+
+        return result;
+    }
+
+    static Polynomial scale(const Polynomial &poly, const APInt &factor)
+    {
+        Polynomial result;
+
+        for (const auto &[monomial, coeff] : poly)
+        {
+            APInt value = coeff * factor;
+
+            if (!value.isZero())
+                result.emplace(monomial, value);
+        }
+
+        return result;
+    }
+
+    Polynomial divide(const Polynomial &lhs, const Polynomial &rhs)
+    {
+        std::optional<APInt> lhsCst = getConstantTerm(lhs);
+        std::optional<APInt> rhsCst = getConstantTerm(rhs);
+
+        if (rhsCst && rhsCst->isOne())
+            return lhs;
+
+        // Division by zero, and signed overflow, are left to the runtime.
+        if (lhsCst && rhsCst && !rhsCst->isZero())
+        {
+            bool overflows = exprTy.getIsSigned() && lhsCst->isMinSignedValue() && rhsCst->isAllOnes();
+
+            if (!overflows)
+            {
+                APInt value = exprTy.getIsSigned() ? lhsCst->sdiv(*rhsCst) : lhsCst->udiv(*rhsCst);
+                Polynomial result;
+
+                if (!value.isZero())
+                    result.emplace(Monomial(), value);
+
+                return result;
+            }
+        }
+
+        Value div = DivOp::create(builder, loc, exprTy, materialize(lhs), materialize(rhs));
+        Polynomial result;
+
+        result.emplace(Monomial{getAtomId(div)}, APInt(width, 1));
+
+        return result;
+    }
+
+    /// Returns the polynomial as a single monomial with its coefficient. Sums
+    /// are turned into an opaque factor.
+    std::pair<Monomial, APInt> asSingleTerm(const Polynomial &poly)
+    {
+        if (poly.size() == 1)
+            return *poly.begin();
+
+        return {Monomial{getAtomId(materialize(poly))}, APInt(width, 1)};
+    }
+
+    unsigned getAtomId(Value val)
+    {
+        // Symbols with the same name are the same variable, even if they come
+        // from different sym operations.
+        if (auto sym = llvm::dyn_cast_or_null<SymOp>(val.getDefiningOp()))
+        {
+            auto [it, inserted] = symIds.try_emplace(sym.getName(), atoms.size());
+
+            if (inserted)
+                atoms.push_back(val);
+
+            return it->second;
+        }
+
+        auto [it, inserted] = atomIds.try_emplace(val, atoms.size());
+
+        if (inserted)
+            atoms.push_back(val);
+
+        return it->second;
+    }
+
+    /// Builds a value for the polynomial, constants are built as plain arith
+    /// constants so they can be used as operands.
+    Value materialize(const Polynomial &poly)
+    {
+        if (std::optional<APInt> cst = getConstantTerm(poly))
+            return createIntConstant(builder, loc, exprTy.getInnerType(), *cst);
+
+        return rebuild(poly);
+    }
+
+    Value buildTerm(const Monomial &monomial, const APInt &coeff)
+    {
+        if (monomial.empty())
+            return createIntConstant(builder, loc, exprTy.getInnerType(), coeff);
+
+        Value product = atoms[monomial.front()];
+
+        for (unsigned atomId : llvm::drop_begin(monomial))
+            product = MulOp::create(builder, loc, exprTy, product, atoms[atomId]);
+
+        if (coeff.isOne())
+            return product;
+
+        Value coeffVal = createIntConstant(builder, loc, exprTy.getInnerType(), coeff);
+
+        return MulOp::create(builder, loc, exprTy, coeffVal, product);
+    }
+
+    Value rebuild(const Polynomial &poly)
+    {
+        if (std::optional<APInt> cst = getConstantTerm(poly))
+            return symbolic::ConstantOp::create(builder, loc, exprTy, builder.getIntegerAttr(exprTy.getInnerType(), *cst));
+
+        Value result;
+        std::optional<APInt> constantTerm;
+
+        for (const auto &[monomial, coeff] : poly)
+        {
+            // The constant term goes last, to keep the `a * x + b` shape.
+            if (monomial.empty())
+            {
+                constantTerm = coeff;
+                continue;
+            }
+
+            result = appendTerm(result, monomial, coeff);
+        }
+
+        if (constantTerm)
+            result = appendTerm(result, Monomial(), *constantTerm);
+
+        return result;
+    }
+
+    Value appendTerm(Value sum, const Monomial &monomial, const APInt &coeff)
+    {
+        if (!sum)
+            return buildTerm(monomial, coeff);
+
+        if (coeff.isNegative())
+            return SubOp::create(builder, loc, exprTy, sum, buildTerm(monomial, -coeff));
+
+        return AddOp::create(builder, loc, exprTy, sum, buildTerm(monomial, coeff));
+    }
+
+    OpBuilder &builder;
+    Location loc;
+    SymExprType exprTy;
+    unsigned width;
+    SmallVector<Value> atoms;
+    DenseMap<Value, unsigned> atomIds;
+    llvm::StringMap<unsigned> symIds;
+    DenseMap<Value, Polynomial> polynomials;
+};
+
+/// Replaces `op` with `operand` when the types allow it. A concrete operand
+/// can't replace a symbolic expression.
+static LogicalResult replaceWithOperand(Operation *op, Value operand, PatternRewriter &rewriter)
+{
+    if (operand.getType() != op->getResult(0).getType())
+        return failure();
+
+    rewriter.replaceOp(op, operand);
+
+    return success();
+}
+
+static LogicalResult replaceWithConstant(Operation *op, TypedAttr value, PatternRewriter &rewriter)
+{
+    rewriter.replaceOpWithNewOp<symbolic::ConstantOp>(op, op->getResult(0).getType(), value);
+
+    return success();
+}
+
+/// Folds binary operations whose operands are both constants.
+template <typename OpTy> struct FoldConstantOperands : public OpRewritePattern<OpTy>
+{
+    using OpRewritePattern<OpTy>::OpRewritePattern;
+
+    LogicalResult matchAndRewrite(OpTy op, PatternRewriter &rewriter) const override
+    {
+        SymExprType exprTy = llvm::cast<SymExprType>(op.getType());
+        Type innerTy = exprTy.getInnerType();
+
+        if (exprTy.isFloat())
+        {
+            std::optional<APFloat> lhs = getConstantFloat(op.getLhs());
+            std::optional<APFloat> rhs = getConstantFloat(op.getRhs());
+
+            if (!lhs || !rhs)
+                return failure();
+
+            APFloat result = *lhs;
+            llvm::RoundingMode rounding = llvm::RoundingMode::NearestTiesToEven;
+
+            if constexpr (std::is_same_v<OpTy, AddOp>)
+                result.add(*rhs, rounding);
+            else if constexpr (std::is_same_v<OpTy, SubOp>)
+                result.subtract(*rhs, rounding);
+            else if constexpr (std::is_same_v<OpTy, MulOp>)
+                result.multiply(*rhs, rounding);
+            else
+                result.divide(*rhs, rounding);
+
+            return replaceWithConstant(op, rewriter.getFloatAttr(innerTy, result), rewriter);
+        }
+
+        std::optional<APInt> lhs = getConstantInt(op.getLhs());
+        std::optional<APInt> rhs = getConstantInt(op.getRhs());
+
+        if (!lhs || !rhs)
+            return failure();
+
+        APInt result;
+
+        if constexpr (std::is_same_v<OpTy, AddOp>)
+            result = *lhs + *rhs;
+        else if constexpr (std::is_same_v<OpTy, SubOp>)
+            result = *lhs - *rhs;
+        else if constexpr (std::is_same_v<OpTy, MulOp>)
+            result = *lhs * *rhs;
+        else
+        {
+            // Division by zero, and signed overflow, are left to the runtime.
+            if (rhs->isZero() || (exprTy.getIsSigned() && lhs->isMinSignedValue() && rhs->isAllOnes()))
+                return failure();
+
+            result = exprTy.getIsSigned() ? lhs->sdiv(*rhs) : lhs->udiv(*rhs);
+        }
+
+        return replaceWithConstant(op, rewriter.getIntegerAttr(innerTy, result), rewriter);
+    }
+};
+
+/// Applies the identities `x + 0`, `0 + x`, `x - 0`, `x * 1`, `1 * x` and
+/// `x / 1`, and for integers also `x * 0`, `0 * x` and `x - x`.
+///
+/// Floating point identities follow IEEE 754 semantics: `x * 0` and `x - x`
+/// are not folded since they depend on `x` being finite, and only `x + -0.0`
+/// and `x - 0.0` preserve the sign of zero.
+template <typename OpTy> struct FoldIdentities : public OpRewritePattern<OpTy>
+{
+    using OpRewritePattern<OpTy>::OpRewritePattern;
+
+    LogicalResult matchAndRewrite(OpTy op, PatternRewriter &rewriter) const override
+    {
+        SymExprType exprTy = llvm::cast<SymExprType>(op.getType());
+        Value lhs = op.getLhs();
+        Value rhs = op.getRhs();
+
+        if (exprTy.isFloat())
+        {
+            auto isNegZero = [](Value val) {
+                std::optional<APFloat> cst = getConstantFloat(val);
+                return cst && cst->isNegZero();
+            };
+            auto isPosZero = [](Value val) {
+                std::optional<APFloat> cst = getConstantFloat(val);
+                return cst && cst->isPosZero();
+            };
+            auto isOne = [](Value val) {
+                std::optional<APFloat> cst = getConstantFloat(val);
+                return cst && cst->isExactlyValue(1.0);
+            };
+
+            if constexpr (std::is_same_v<OpTy, AddOp>)
+            {
+                if (isNegZero(rhs))
+                    return replaceWithOperand(op, lhs, rewriter);
+                if (isNegZero(lhs))
+                    return replaceWithOperand(op, rhs, rewriter);
+            }
+            else if constexpr (std::is_same_v<OpTy, SubOp>)
+            {
+                if (isPosZero(rhs))
+                    return replaceWithOperand(op, lhs, rewriter);
+            }
+            else if constexpr (std::is_same_v<OpTy, MulOp>)
+            {
+                if (isOne(rhs))
+                    return replaceWithOperand(op, lhs, rewriter);
+                if (isOne(lhs))
+                    return replaceWithOperand(op, rhs, rewriter);
+            }
+            else
+            {
+                if (isOne(rhs))
+                    return replaceWithOperand(op, lhs, rewriter);
+            }
+
+            return failure();
+        }
+
+        auto isZero = [](Value val) {
+            std::optional<APInt> cst = getConstantInt(val);
+            return cst && cst->isZero();
+        };
+        auto isOne = [](Value val) {
+            std::optional<APInt> cst = getConstantInt(val);
+            return cst && cst->isOne();
+        };
+        TypedAttr zero = rewriter.getIntegerAttr(exprTy.getInnerType(), 0);
+
+        if constexpr (std::is_same_v<OpTy, AddOp>)
+        {
+            if (isZero(rhs))
+                return replaceWithOperand(op, lhs, rewriter);
+            if (isZero(lhs))
+                return replaceWithOperand(op, rhs, rewriter);
+        }
+        else if constexpr (std::is_same_v<OpTy, SubOp>)
+        {
+            if (lhs == rhs)
+                return replaceWithConstant(op, zero, rewriter);
+            if (isZero(rhs))
+                return replaceWithOperand(op, lhs, rewriter);
+        }
+        else if constexpr (std::is_same_v<OpTy, MulOp>)
+        {
+            if (isZero(lhs) || isZero(rhs))
+                return replaceWithConstant(op, zero, rewriter);
+            if (isOne(rhs))
+                return replaceWithOperand(op, lhs, rewriter);
+            if (isOne(lhs))
+                return replaceWithOperand(op, rhs, rewriter);
+        }
+        else
+        {
+            if (isOne(rhs))
+                return replaceWithOperand(op, lhs, rewriter);
+        }
+
+        return failure();
+    }
+};
+} // namespace
+
+namespace mlir
+{
+namespace symbolic
+{
+#define GEN_PASS_DEF_SYMBOLICSIMPLIFY
+#include "Dialect/Symbolic/Transforms/Passes.h.inc"
+
+/// Pass to simplify symbolic expressions.
+///
+/// Integer expressions are first normalized into a flat sum of products, see
+/// `IntNormalizer`. Then local identities and constant folding are applied
+/// to every expression, which also gets rid of the dead operations left
+/// behind by the normalization.
+struct SymbolicSimplify : impl::SymbolicSimplifyBase<SymbolicSimplify>
+{
+    using SymbolicSimplifyBase::SymbolicSimplifyBase;
+
+    void runOnOperation() override
+    {
+        normalizeIntExpressions();
+
+        mlir::RewritePatternSet patterns(&getContext());
+
+        patterns.add<FoldConstantOperands<AddOp>, FoldConstantOperands<SubOp>, FoldConstantOperands<MulOp>,
+                     FoldConstantOperands<DivOp>, FoldIdentities<AddOp>, FoldIdentities<SubOp>, FoldIdentities<MulOp>,
+                     FoldIdentities<DivOp>>(&getContext());
+
+        (void)applyPatternsGreedily(getOperation(), std::move(patterns));
+    }
+
+  private:
+    /// Normalizes the expression trees rooted at operations whose result is
+    /// used outside of the tree (e.g by an eval).
+    void normalizeIntExpressions()
+    {
+        SmallVector<Operation *> roots;
+
+        getOperation()->walk([&](Operation *op) {
+            if (!isSymbolicArithOp(op) || llvm::cast<SymExprType>(op->getResult(0).getType()).isFloat())
+                return;
+
+            bool isRoot = llvm::any_of(op->getUsers(), [](Operation *user) { return !isSymbolicArithOp(user); });
+
+            if (isRoot)
+                roots.push_back(op);
+        });
+
+        OpBuilder builder(&getContext());
+
+        for (Operation *root : roots)
+        {
+            builder.setInsertionPoint(root);
+
+            SymExprType exprTy = llvm::cast<SymExprType>(root->getResult(0).getType());
+            IntNormalizer normalizer(builder, root->getLoc(), exprTy);
+            Value normalized = normalizer.normalize(root->getResult(0));
+
+            if (normalized)
+                root->getResult(0).replaceAllUsesWith(normalized);
+        }
+    }
+};
+} // namespace symbolic
+} // namespace mlir
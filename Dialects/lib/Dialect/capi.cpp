@@ -20,8 +20,7 @@ extern "C" void mlirInsertSymbolicDialect(MlirDialectRegistry registry)
 
 MlirType getSymExprType(MlirContext ctx, MlirType innerType, bool isSigned)
 {
-    return wrap(
-        mlir::symbolic::SymExprType::get(unwrap(ctx), llvm::cast<mlir::IntegerType>(unwrap(innerType)), isSigned));
+    return wrap(mlir::symbolic::SymExprType::get(unwrap(ctx), unwrap(innerType), isSigned));
 }
 
 using namespace mlir;
@@ -29,6 +28,16 @@ using namespace mlir;
 extern "C"
 {
 
+    MlirPass mlirCreateSymbolicExpand()
+    {
+        return wrap(mlir::symbolic::createSymbolicExpand().release());
+    }
+
+    void mlirRegisterSymbolicExpand()
+    {
+        mlir::symbolic::registerSymbolicExpand();
+    }
+
     MlirPass mlirCreateSymbolicExtractEval()
     {
         return wrap(mlir::symbolic::createSymbolicExtractEval().release());
@@ -39,6 +48,31 @@ extern "C"
         mlir::symbolic::registerSymbolicExtractEval();
     }
 
+    MlirPass mlirCreateSymbolicInline()
+    {
+        return wrap(mlir::symbolic::createSymbolicInline().release());
+    }
+
+    void mlirRegisterSymbolicInline()
+    {
+        mlir::symbolic::registerSymbolicInline();
+    }
+
+    bool mlirSymbolicImportFunctions(MlirModule dest, MlirModule src)
+    {
+        return mlir::symbolic::importSymbolicFunctions(unwrap(dest), unwrap(src));
+    }
+
+    MlirPass mlirCreateSymbolicSimplify()
+    {
+        return wrap(mlir::symbolic::createSymbolicSimplify().release());
+    }
+
+    void mlirRegisterSymbolicSimplify()
+    {
+        mlir::symbolic::registerSymbolicSimplify();
+    }
+
     MlirPass mlirCreateSymbolicToArith()
     {
         return wrap(mlir::symbolic::createSymbolicToArith().release());
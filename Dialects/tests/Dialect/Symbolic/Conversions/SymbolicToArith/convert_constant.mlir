@@ -0,0 +1,11 @@
+// RUN: dialect-driver --symbolic-to-arith %s | FileCheck %s
+
+// CHECK-LABEL: func.func private @test_constant
+// CHECK-SAME: (%arg0: i32) -> i32 {
+// CHECK-NEXT:   %[[C:.*]] = arith.constant 7 : i32
+// CHECK-NEXT:   return %[[C]] : i32
+
+func.func private @test_constant(%arg0: i32) -> !symbolic.expr<i32, isSigned = true> {
+  %0 = symbolic.constant 7 : i32 : !symbolic.expr<i32, isSigned = true>
+  return %0 : !symbolic.expr<i32, isSigned = true>
+}
@@ -0,0 +1,24 @@
+// RUN: dialect-driver --symbolic-extract-eval --symbolic-to-arith %s | FileCheck %s
+
+module {
+  // CHECK:      func.func private @__eval_op_
+  // CHECK-SAME: (%arg0: f32, %arg1: f32) -> f32 {
+  // CHECK-NEXT:   %0 = arith.mulf %arg0, %arg0 : f32
+  // CHECK-NEXT:   %1 = arith.addf %0, %arg1 : f32
+  // CHECK-NEXT:   return %1 : f32
+  // CHECK-NEXT: }
+
+  // CHECK-LABEL: func @test_f32_freevar
+  func.func @test_f32_freevar(%x_val: f32, %b: f32) -> f32 {
+    %x = symbolic.sym "x" : !symbolic.expr<f32, isSigned = true>
+    %e = symbolic.mul %x, %x : (!symbolic.expr<f32, isSigned = true>, !symbolic.expr<f32, isSigned = true>) -> !symbolic.expr<f32, isSigned = true>
+    %s = symbolic.add %e, %b : (!symbolic.expr<f32, isSigned = true>, f32) -> !symbolic.expr<f32, isSigned = true>
+    // CHECK: call @__eval_op_
+    %res = symbolic.eval %s, ["x"], %x_val : (!symbolic.expr<f32, isSigned = true>, f32) -> f32
+    // CHECK: return
+    return %res : f32
+  }
+
+  // CHECK-NOT: symbolic.
+  // CHECK-NOT: unrealized_conversion_cast
+}
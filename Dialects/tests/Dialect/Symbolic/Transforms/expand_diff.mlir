@@ -0,0 +1,66 @@
+// RUN: dialect-driver --symbolic-expand %s | FileCheck %s
+
+module {
+  // CHECK-LABEL: func @test_diff_mul
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK-NOT:   symbolic.diff
+  // CHECK:       %[[D:.*]] = symbolic.add %[[X]], %[[X]]
+  // CHECK:       symbolic.eval %[[D]], ["x"]
+  func.func @test_diff_mul(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %xx = symbolic.mul %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %d = symbolic.diff %xx, "x" : !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %d, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_diff_other_sym
+  // CHECK:       %[[Y:.*]] = symbolic.sym "y"
+  // CHECK-NOT:   symbolic.diff
+  // CHECK:       symbolic.eval %[[Y]], ["x", "y"]
+  func.func @test_diff_other_sym(%x_val: i32, %y_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %xy = symbolic.mul %x, %y : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %d = symbolic.diff %xy, "x" : !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %d, ["x", "y"], %x_val, %y_val : (!symbolic.expr<i32, isSigned = true>, i32, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_diff_constant_result
+  // CHECK-NOT:   symbolic.diff
+  // CHECK:       %[[D:.*]] = symbolic.constant 1 : i32 : !symbolic.expr<i32, isSigned = true>
+  // CHECK:       symbolic.eval %[[D]], ["x"]
+  func.func @test_diff_constant_result(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %d = symbolic.diff %x, "x" : !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %d, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_diff_quotient_f64
+  // CHECK-NOT:   symbolic.diff
+  // CHECK:       symbolic.div
+  // CHECK:       symbolic.eval
+  func.func @test_diff_quotient_f64(%x_val: f64) -> f64 {
+    %one = arith.constant 1.0 : f64
+    %x = symbolic.sym "x" : !symbolic.expr<f64, isSigned = true>
+    %q = symbolic.div %one, %x : (f64, !symbolic.expr<f64, isSigned = true>) -> !symbolic.expr<f64, isSigned = true>
+    %d = symbolic.diff %q, "x" : !symbolic.expr<f64, isSigned = true>
+    %res = symbolic.eval %d, ["x"], %x_val : (!symbolic.expr<f64, isSigned = true>, f64) -> f64
+    return %res : f64
+  }
+
+  // CHECK-LABEL: func @test_diff_nested
+  // CHECK-NOT:   symbolic.diff
+  // CHECK:       symbolic.eval
+  func.func @test_diff_nested(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %xx = symbolic.mul %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %xxx = symbolic.mul %xx, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %d = symbolic.diff %xxx, "x" : !symbolic.expr<i32, isSigned = true>
+    %d2 = symbolic.diff %d, "x" : !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %d2, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+}
@@ -0,0 +1,75 @@
+// RUN: dialect-driver --symbolic-expand %s | FileCheck %s
+
+module {
+  // CHECK-LABEL: func @test_subs_partial
+  // CHECK:       %[[C:.*]] = arith.constant 2 : i32
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK:       %[[Y:.*]] = symbolic.sym "y"
+  // CHECK-NOT:   symbolic.subs
+  // CHECK:       %[[M:.*]] = symbolic.mul %[[C]], %[[Y]]
+  // CHECK:       symbolic.eval %[[M]], ["y"]
+  func.func @test_subs_partial(%y_val: i32) -> i32 {
+    %c2 = arith.constant 2 : i32
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %xy = symbolic.mul %x, %y : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %p = symbolic.subs %xy, ["x"], %c2 : (!symbolic.expr<i32, isSigned = true>, i32)
+    %res = symbolic.eval %p, ["y"], %y_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_subs_expression
+  // CHECK:       %[[Y:.*]] = symbolic.sym "y"
+  // CHECK:       %[[YP1:.*]] = symbolic.add %[[Y]], %{{.*}}
+  // CHECK-NOT:   symbolic.subs
+  // CHECK:       %[[M:.*]] = symbolic.mul %[[YP1]], %[[YP1]]
+  // CHECK:       symbolic.eval %[[M]], ["y"]
+  func.func @test_subs_expression(%y_val: i32) -> i32 {
+    %c1 = arith.constant 1 : i32
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %yp1 = symbolic.add %y, %c1 : (!symbolic.expr<i32, isSigned = true>, i32) -> !symbolic.expr<i32, isSigned = true>
+    %xx = symbolic.mul %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %p = symbolic.subs %xx, ["x"], %yp1 : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>)
+    %res = symbolic.eval %p, ["y"], %y_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_subs_untouched
+  // CHECK:       %[[Y:.*]] = symbolic.sym "y"
+  // CHECK-NOT:   symbolic.subs
+  // CHECK:       symbolic.eval %[[Y]], ["y"]
+  func.func @test_subs_untouched(%y_val: i32) -> i32 {
+    %c3 = arith.constant 3 : i32
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %p = symbolic.subs %y, ["x"], %c3 : (!symbolic.expr<i32, isSigned = true>, i32)
+    %res = symbolic.eval %p, ["y"], %y_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_subs_all_symbols
+  // CHECK-NOT:   symbolic.subs
+  // CHECK:       %[[R:.*]] = symbolic.constant 4 : i32 : !symbolic.expr<i32, isSigned = true>
+  // CHECK:       return %[[R]]
+  func.func @test_subs_all_symbols() -> !symbolic.expr<i32, isSigned = true> {
+    %c4 = arith.constant 4 : i32
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %p = symbolic.subs %x, ["x"], %c4 : (!symbolic.expr<i32, isSigned = true>, i32)
+    return %p : !symbolic.expr<i32, isSigned = true>
+  }
+
+  // CHECK-LABEL: func @test_subs_after_diff
+  // CHECK-NOT:   symbolic.diff
+  // CHECK-NOT:   symbolic.subs
+  // CHECK:       symbolic.eval
+  func.func @test_subs_after_diff(%y_val: i32) -> i32 {
+    %c5 = arith.constant 5 : i32
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %xy = symbolic.mul %x, %y : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %d = symbolic.diff %xy, "y" : !symbolic.expr<i32, isSigned = true>
+    %p = symbolic.subs %d, ["x"], %c5 : (!symbolic.expr<i32, isSigned = true>, i32)
+    %res = symbolic.eval %p, ["y"], %y_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+}
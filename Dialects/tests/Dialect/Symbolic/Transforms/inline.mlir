@@ -0,0 +1,85 @@
+// RUN: dialect-driver --symbolic-inline %s | FileCheck %s
+
+module {
+  // CHECK-NOT:   func.func private @square
+  func.func private @square(%e: !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true> {
+    %r = symbolic.mul %e, %e : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    return %r : !symbolic.expr<i32, isSigned = true>
+  }
+
+  // CHECK-NOT:   func.func private @fourth
+  func.func private @fourth(%e: !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true> {
+    %sq = call @square(%e) : (!symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %r = call @square(%sq) : (!symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    return %r : !symbolic.expr<i32, isSigned = true>
+  }
+
+  // CHECK-NOT:   func.func private @model
+  func.func private @model() -> !symbolic.expr<i32, isSigned = true> {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %c1 = arith.constant 1 : i32
+    %r = symbolic.add %x, %c1 : (!symbolic.expr<i32, isSigned = true>, i32) -> !symbolic.expr<i32, isSigned = true>
+    return %r : !symbolic.expr<i32, isSigned = true>
+  }
+
+  // CHECK-LABEL: func @test_inline_param
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK-NOT:   call
+  // CHECK:       %[[SQ:.*]] = symbolic.mul %[[X]], %[[X]]
+  // CHECK:       symbolic.eval %[[SQ]], ["x"]
+  func.func @test_inline_param(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %sq = call @square(%x) : (!symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %sq, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_inline_nested
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK-NOT:   call
+  // CHECK:       %[[SQ:.*]] = symbolic.mul %[[X]], %[[X]]
+  // CHECK:       %[[FOURTH:.*]] = symbolic.mul %[[SQ]], %[[SQ]]
+  // CHECK:       symbolic.eval %[[FOURTH]], ["x"]
+  func.func @test_inline_nested(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %r = call @fourth(%x) : (!symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %r, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_inline_return
+  // CHECK-NOT:   call
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK:       %[[R:.*]] = symbolic.add %[[X]], %{{.*}}
+  // CHECK:       symbolic.eval %[[R]], ["x"]
+  func.func @test_inline_return(%x_val: i32) -> i32 {
+    %r = call @model() : () -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %r, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-NOT:   func.func private @curve
+  func.func private @curve() -> !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)> {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %sq = symbolic.mul %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %c2 = arith.constant 2 : i32
+    %u = llvm.mlir.undef : !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+    %s0 = llvm.insertvalue %sq, %u[0] : !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+    %s1 = llvm.insertvalue %c2, %s0[1] : !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+    return %s1 : !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+  }
+
+  // CHECK-LABEL: func @test_inline_struct
+  // CHECK-NOT:   call
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK:       %[[SQ:.*]] = symbolic.mul %[[X]], %[[X]]
+  // CHECK-NOT:   llvm.insertvalue
+  // CHECK-NOT:   llvm.extractvalue
+  // CHECK:       symbolic.eval %[[SQ]], ["x"]
+  func.func @test_inline_struct(%x_val: i32) -> i32 {
+    %s = call @curve() : () -> !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+    %e = llvm.extractvalue %s[0] : !llvm.struct<(!symbolic.expr<i32, isSigned = true>, i32)>
+    %res = symbolic.eval %e, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+}
@@ -0,0 +1,81 @@
+// RUN: dialect-driver --symbolic-simplify %s | FileCheck %s
+
+module {
+  // CHECK-LABEL: func @test_merge_like_terms
+  // CHECK-DAG:   %[[X:.*]] = symbolic.sym "x"
+  // CHECK-DAG:   %[[C:.*]] = arith.constant 4 : i32
+  // CHECK:       %[[R:.*]] = symbolic.mul %[[C]], %[[X]]
+  // CHECK-NOT:   symbolic.add
+  // CHECK:       symbolic.eval %[[R]], ["x"]
+  func.func @test_merge_like_terms(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %0 = symbolic.add %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %1 = symbolic.add %0, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %2 = symbolic.add %1, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %2, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_sub_self
+  // CHECK:       %[[R:.*]] = symbolic.constant 0 : i32 : !symbolic.expr<i32, isSigned = true>
+  // CHECK-NOT:   symbolic.sub
+  // CHECK:       symbolic.eval %[[R]], ["x"]
+  func.func @test_sub_self(%x_val: i32) -> i32 {
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %0 = symbolic.sub %x, %x : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %0, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_mul_zero_and_one
+  // CHECK:       %[[Y:.*]] = symbolic.sym "y"
+  // CHECK-NOT:   symbolic.mul
+  // CHECK-NOT:   symbolic.add
+  // CHECK:       symbolic.eval %[[Y]], ["x", "y"]
+  func.func @test_mul_zero_and_one(%x_val: i32, %y_val: i32) -> i32 {
+    %zero = arith.constant 0 : i32
+    %one = arith.constant 1 : i32
+    %x = symbolic.sym "x" : !symbolic.expr<i32, isSigned = true>
+    %y = symbolic.sym "y" : !symbolic.expr<i32, isSigned = true>
+    %0 = symbolic.mul %x, %zero : (!symbolic.expr<i32, isSigned = true>, i32) -> !symbolic.expr<i32, isSigned = true>
+    %1 = symbolic.mul %y, %one : (!symbolic.expr<i32, isSigned = true>, i32) -> !symbolic.expr<i32, isSigned = true>
+    %2 = symbolic.add %0, %1 : (!symbolic.expr<i32, isSigned = true>, !symbolic.expr<i32, isSigned = true>) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %2, ["x", "y"], %x_val, %y_val : (!symbolic.expr<i32, isSigned = true>, i32, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_fold_constants
+  // CHECK:       %[[R:.*]] = symbolic.constant 5 : i32 : !symbolic.expr<i32, isSigned = true>
+  // CHECK:       symbolic.eval %[[R]], ["x"]
+  func.func @test_fold_constants(%x_val: i32) -> i32 {
+    %two = arith.constant 2 : i32
+    %three = arith.constant 3 : i32
+    %0 = symbolic.add %two, %three : (i32, i32) -> !symbolic.expr<i32, isSigned = true>
+    %res = symbolic.eval %0, ["x"], %x_val : (!symbolic.expr<i32, isSigned = true>, i32) -> i32
+    return %res : i32
+  }
+
+  // CHECK-LABEL: func @test_float_mul_one
+  // CHECK:       %[[X:.*]] = symbolic.sym "x"
+  // CHECK-NOT:   symbolic.mul
+  // CHECK:       symbolic.eval %[[X]], ["x"]
+  func.func @test_float_mul_one(%x_val: f64) -> f64 {
+    %one = arith.constant 1.0 : f64
+    %x = symbolic.sym "x" : !symbolic.expr<f64, isSigned = true>
+    %0 = symbolic.mul %x, %one : (!symbolic.expr<f64, isSigned = true>, f64) -> !symbolic.expr<f64, isSigned = true>
+    %res = symbolic.eval %0, ["x"], %x_val : (!symbolic.expr<f64, isSigned = true>, f64) -> f64
+    return %res : f64
+  }
+
+  // Floats are not reassociated nor multiplied by zero, since that would
+  // change the result for NaN and infinities.
+  // CHECK-LABEL: func @test_float_mul_zero
+  // CHECK:       symbolic.mul
+  func.func @test_float_mul_zero(%x_val: f64) -> f64 {
+    %zero = arith.constant 0.0 : f64
+    %x = symbolic.sym "x" : !symbolic.expr<f64, isSigned = true>
+    %0 = symbolic.mul %x, %zero : (!symbolic.expr<f64, isSigned = true>, f64) -> !symbolic.expr<f64, isSigned = true>
+    %res = symbolic.eval %0, ["x"], %x_val : (!symbolic.expr<f64, isSigned = true>, f64) -> f64
+    return %res : f64
+  }
+}
@@ -1,7 +1,11 @@
 #include "Dialect/Symbolic/Conversions/SymbolicToArith/SymbolicToArith.h"
 #include "Dialect/Symbolic/IR/SymbolicDialect.h"
+#include "Dialect/Symbolic/Transforms/SymbolicExpand.h"
 #include "Dialect/Symbolic/Transforms/SymbolicExtractEval.h"
+#include "Dialect/Symbolic/Transforms/SymbolicInline.h"
+#include "Dialect/Symbolic/Transforms/SymbolicSimplify.h"
 #include <mlir/InitAllDialects.h>
+#include <mlir/InitAllExtensions.h>
 #include <mlir/InitAllPasses.h>
 #include <mlir/Pass/PassManager.h>
 #include <mlir/Pass/PassRegistry.h>
@@ -10,11 +14,26 @@
 
 namespace
 {
+void symbolicInlinePipeline(mlir::OpPassManager &manager)
+{
+    manager.addPass(mlir::symbolic::createSymbolicInline());
+}
+
+void symbolicExpandPipeline(mlir::OpPassManager &manager)
+{
+    manager.addPass(mlir::symbolic::createSymbolicExpand());
+}
+
 void symbolicExtractEvalPipeline(mlir::OpPassManager &manager)
 {
     manager.addPass(mlir::symbolic::createSymbolicExtractEval());
 }
 
+void symbolicSimplifyPipeline(mlir::OpPassManager &manager)
+{
+    manager.addPass(mlir::symbolic::createSymbolicSimplify());
+}
+
 void symbolicToArithPipeline(mlir::OpPassManager &manager)
 {
     manager.addPass(mlir::symbolic::createSymbolicToArith());
@@ -28,12 +47,20 @@ int main(int argc, char **argv)
     registry.insert<mlir::symbolic::SymbolicDialect>();
 
     mlir::registerAllDialects(registry);
+    mlir::registerAllExtensions(registry);
 
     mlir::registerAllPasses();
 
+    mlir::PassPipelineRegistration<>("symbolic-inline",
+                                     "Run pass to inline functions with symbolic expressions in their signature",
+                                     symbolicInlinePipeline);
+    mlir::PassPipelineRegistration<>("symbolic-expand", "Run pass to expand diff and subs operations",
+                                     symbolicExpandPipeline);
     mlir::PassPipelineRegistration<>("symbolic-extract-eval",
                                      "Run pass to pass to convert eval operations in to function calls",
                                      symbolicExtractEvalPipeline);
+    mlir::PassPipelineRegistration<>("symbolic-simplify", "Run pass to simplify symbolic expressions",
+                                     symbolicSimplifyPipeline);
     mlir::PassPipelineRegistration<>("symbolic-to-arith", "Run pass to pass to convert symbolic dialect to arith",
                                      symbolicToArithPipeline);
     return mlir::asMainReturnCode(mlir::MlirOptMain(argc, argv, "Dialect Driver", registry));
@@ -27,8 +27,8 @@ fn bench_mathic(c: &mut Criterion) {
                 opt_lvl: opt,
                 ..Default::default()
             };
-            let module = compiler.compile_source(src, None, comp_opts).unwrap();
-            let executor = MathicJITExecutor::new(vec![module], comp_opts).unwrap();
+            let module = compiler.compile_source(src, None, &comp_opts).unwrap();
+            let executor = MathicJITExecutor::new(vec![module], &comp_opts).unwrap();
 
             group.bench_function(name, |b| {
                 b.iter(|| {
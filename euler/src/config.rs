@@ -1,17 +1,61 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::EulerError;
+
+/// The module whose `main` function a program runs, unless the package
+/// says otherwise.
+pub const MAIN_ENTRY: &str = "main.mth";
+/// The module of a library.
+pub const LIB_ENTRY: &str = "lib.mth";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ConfigToml {
     package: Package,
+    #[serde(default)]
     dependencies: HashMap<String, Dependency>,
+    #[serde(default, rename = "bin", skip_serializing_if = "Vec::is_empty")]
+    bins: Vec<Bin>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Package {
     name: String,
     version: String,
+    /// The module of the package's executable, relative to `src`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<PathBuf>,
+}
+
+/// An executable of the project.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Bin {
+    pub name: String,
+    /// The module whose `main` function the executable runs, relative to
+    /// `src`.
+    pub path: PathBuf,
+}
+
+impl Bin {
+    /// Returns the executable's `main` function, e.g. `bin::solver::main`.
+    pub fn entry_function(&self) -> String {
+        format!("{}::main", mathic::module_name(&self.path))
+    }
+}
+
+/// The dependencies of a project, where each package only sees its own.
+#[derive(Debug, Default, PartialEq)]
+pub struct DependencyRoots {
+    /// Source roots of the project's dependencies, by name.
+    pub project: HashMap<String, PathBuf>,
+    /// Source roots of the dependencies of each dependency, by its source
+    /// root.
+    pub packages: HashMap<PathBuf, HashMap<String, PathBuf>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -19,8 +63,13 @@ pub struct Package {
 enum Dependency {
     // Matches: <dep> = <version>
     Simple(String),
-    // Matches: <dep> = { version = <version> }
-    Detailed { version: String },
+    // Matches: <dep> = { version = <version> } or <dep> = { path = <path> }
+    Detailed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
 }
 
 impl ConfigToml {
@@ -29,8 +78,294 @@ impl ConfigToml {
             package: Package {
                 name: project_name.to_owned(),
                 version: String::from("0.1.0"),
+                entry: None,
             },
             dependencies: HashMap::new(),
+            bins: Vec::new(),
         }
     }
+
+    pub fn load(path: &Path) -> Result<Self, EulerError> {
+        if !fs::exists(path)? {
+            return Err(EulerError::ConfigNotFound);
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Returns the executables of the project: the ones declared with
+    /// `[[bin]]` or else one named after the package, running `entry`, or
+    /// `main.mth` if there's one.
+    pub fn binaries(&self, src_root: &Path) -> Vec<Bin> {
+        if !self.bins.is_empty() {
+            return self.bins.clone();
+        }
+
+        match &self.package.entry {
+            Some(entry) => vec![Bin {
+                name: self.package.name.clone(),
+                path: entry.clone(),
+            }],
+            None if src_root.join(MAIN_ENTRY).is_file() => vec![Bin {
+                name: self.package.name.clone(),
+                path: PathBuf::from(MAIN_ENTRY),
+            }],
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the source roots of the project's dependencies, and of
+    /// theirs. `project_dir` is where dependency paths are relative to.
+    pub fn dependency_roots(&self, project_dir: &Path) -> Result<DependencyRoots, EulerError> {
+        let mut roots = DependencyRoots::default();
+        roots.project = self.package_dependency_roots(project_dir, &mut roots.packages)?;
+
+        Ok(roots)
+    }
+
+    /// Returns the source roots of the package's dependencies, by name, and
+    /// collects the ones of theirs into `packages`. `package_dir` is where
+    /// dependency paths are relative to.
+    ///
+    /// Dependencies without a path are skipped, only path dependencies can be
+    /// loaded. Importing one of them fails to resolve.
+    fn package_dependency_roots(
+        &self,
+        package_dir: &Path,
+        packages: &mut HashMap<PathBuf, HashMap<String, PathBuf>>,
+    ) -> Result<HashMap<String, PathBuf>, EulerError> {
+        let mut roots = HashMap::new();
+
+        for (name, dependency) in &self.dependencies {
+            let Dependency::Detailed {
+                path: Some(path), ..
+            } = dependency
+            else {
+                continue;
+            };
+
+            let dependency_dir = fs::canonicalize(package_dir.join(path))
+                .map_err(|_| EulerError::DependencyNotFound(name.clone(), path.clone()))?;
+            let src_root = dependency_dir.join("src");
+
+            if !src_root.is_dir() {
+                return Err(EulerError::DependencyNotFound(name.clone(), path.clone()));
+            }
+
+            roots.insert(name.clone(), src_root.clone());
+
+            // Already collected, through another package. Inserting it before
+            // its dependencies are collected stops cycles.
+            if packages.insert(src_root.clone(), HashMap::new()).is_some() {
+                continue;
+            }
+
+            let config_path = dependency_dir.join("Mathic.toml");
+
+            if fs::exists(&config_path)? {
+                let dependencies = Self::load(&config_path)?
+                    .package_dependency_roots(&dependency_dir, packages)?;
+
+                packages.insert(src_root, dependencies);
+            }
+        }
+
+        Ok(roots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use crate::error::EulerError;
+
+    use super::{Bin, ConfigToml, DependencyRoots, MAIN_ENTRY};
+
+    fn fixtures() -> PathBuf {
+        fs::canonicalize(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/scoped_dependencies"),
+        )
+        .unwrap()
+    }
+
+    /// Returns the name and module of each executable of the project in
+    /// `euler/tests/fixtures`, declared by `config` instead of its own
+    /// Mathic.toml.
+    fn binaries(project: &str, config: &str) -> Vec<(String, PathBuf)> {
+        let src_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(project)
+            .join("src");
+        let config: ConfigToml = toml::from_str(config).unwrap();
+
+        config
+            .binaries(&src_root)
+            .into_iter()
+            .map(|bin| (bin.name, bin.path))
+            .collect()
+    }
+
+    fn dependency_roots(package: &str) -> Result<DependencyRoots, EulerError> {
+        let package_dir = fixtures().join(package);
+
+        ConfigToml::load(&package_dir.join("Mathic.toml"))?.dependency_roots(&package_dir)
+    }
+
+    #[test]
+    fn resolves_each_package_dependencies() {
+        let src = |package: &str| fixtures().join(package).join("src");
+
+        let roots = dependency_roots("app").unwrap();
+
+        // `registry` has no path, it's only an error to import it.
+        assert_eq!(
+            roots.project,
+            HashMap::from([
+                (String::from("left"), src("left")),
+                (String::from("right"), src("right")),
+            ])
+        );
+        // Both `left` and `right` depend on a different `util`.
+        assert_eq!(
+            roots.packages,
+            HashMap::from([
+                (
+                    src("left"),
+                    HashMap::from([(String::from("util"), src("util_left"))])
+                ),
+                (
+                    src("right"),
+                    HashMap::from([(String::from("util"), src("util_right"))])
+                ),
+                (src("util_left"), HashMap::new()),
+                (src("util_right"), HashMap::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn does_not_expose_transitive_dependencies() {
+        let roots = dependency_roots("transitive").unwrap();
+
+        assert_eq!(roots.project.keys().collect::<Vec<_>>(), ["left"]);
+        assert!(
+            roots
+                .packages
+                .contains_key(&fixtures().join("util_left/src"))
+        );
+    }
+
+    #[test]
+    fn rejects_missing_dependency_path() {
+        let config: ConfigToml = toml::from_str(
+            r#"
+            [package]
+            name = "app"
+            version = "0.1.0"
+
+            [dependencies]
+            missing = { path = "../missing" }
+            "#,
+        )
+        .unwrap();
+
+        let err = config
+            .dependency_roots(&fixtures().join("app"))
+            .unwrap_err();
+
+        assert!(matches!(&err, EulerError::DependencyNotFound(name, path)
+                if name == "missing" && *path == PathBuf::from("../missing")));
+    }
+
+    #[test]
+    fn declared_binaries_come_first() {
+        let bins = binaries(
+            "binaries",
+            r#"
+            [package]
+            name = "binaries"
+            version = "0.1.0"
+            entry = "app.mth"
+
+            [[bin]]
+            name = "solver"
+            path = "bin/solver.mth"
+
+            [[bin]]
+            name = "app"
+            path = "app.mth"
+            "#,
+        );
+
+        assert_eq!(
+            bins,
+            [
+                (String::from("solver"), PathBuf::from("bin/solver.mth")),
+                (String::from("app"), PathBuf::from("app.mth")),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_entry_comes_before_main() {
+        let bins = binaries(
+            "package_entry",
+            r#"
+            [package]
+            name = "package_entry"
+            version = "0.1.0"
+            entry = "app/start.mth"
+            "#,
+        );
+
+        assert_eq!(
+            bins,
+            [(
+                String::from("package_entry"),
+                PathBuf::from("app/start.mth")
+            )]
+        );
+    }
+
+    #[test]
+    fn main_is_the_default_binary() {
+        let bins = binaries(
+            "package_entry",
+            r#"
+            [package]
+            name = "package_entry"
+            version = "0.1.0"
+            "#,
+        );
+
+        assert_eq!(
+            bins,
+            [(String::from("package_entry"), PathBuf::from(MAIN_ENTRY))]
+        );
+    }
+
+    #[test]
+    fn library_has_no_binaries() {
+        let bins = binaries(
+            "library",
+            r#"
+            [package]
+            name = "library"
+            version = "0.1.0"
+            "#,
+        );
+
+        assert!(bins.is_empty());
+    }
+
+    #[test]
+    fn entry_function_is_named_after_the_module() {
+        let bin = Bin {
+            name: String::from("solver"),
+            path: PathBuf::from("bin/solver.mth"),
+        };
+
+        assert_eq!(bin.entry_function(), "bin::solver::main");
+    }
 }
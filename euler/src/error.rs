@@ -1,12 +1,24 @@
-use std::io;
+use std::{io, path::PathBuf};
 
 use mathic::{MathicError, diagnostics::CodegenError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum EulerError {
-    #[error("main.mth was not found")]
-    MainFileNotFound,
+    #[error("src/{} was not found", .0.display())]
+    EntryNotFound(PathBuf),
+    #[error("the project has no executable, add src/main.mth or a [[bin]] to Mathic.toml")]
+    NoBinaries,
+    #[error("the project has no executable named '{0}'")]
+    BinNotFound(String),
+    #[error("the project has several executables, choose one with --bin: {}", .0.join(", "))]
+    SeveralBinaries(Vec<String>),
+    #[error("Mathic.toml was not found")]
+    ConfigNotFound,
+    #[error("src directory was not found")]
+    SrcDirNotFound,
+    #[error("dependency '{0}' was not found, {} should hold a src directory", .1.display())]
+    DependencyNotFound(String, PathBuf),
     #[error(transparent)]
     MathicError(#[from] MathicError),
     #[error(transparent)]
@@ -14,5 +26,7 @@ pub enum EulerError {
     #[error(transparent)]
     Toml(#[from] toml::ser::Error),
     #[error(transparent)]
+    TomlParse(#[from] toml::de::Error),
+    #[error(transparent)]
     CodegenError(#[from] CodegenError),
 }
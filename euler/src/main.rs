@@ -1,14 +1,27 @@
-use std::{env, fs};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufRead, Read, Write},
+    os::{fd::FromRawFd, unix::process::ExitStatusExt},
+    path::{Path, PathBuf},
+    process::{self, ExitStatus},
+};
 
 use clap::{self, Args, Parser, Subcommand, ValueEnum};
 use mathic::{
-    MathicError,
-    compiler::{CompilerOpts, MathicCompiler, OptLvl},
+    MathicError, MathicModule, aot,
+    compiler::{CompilerOpts, EmitKind, MathicCompiler, OptLvl},
+    diagnostics::{self, CompilationError, Lint, LintLevel, LintLevels},
     executor::{MathicExecutor, jit::MathicJITExecutor},
+    formatter,
+    repl::{MathicRepl, ReplOutput},
 };
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
-use crate::{config::ConfigToml, error::EulerError};
+use crate::{
+    config::{Bin, ConfigToml, LIB_ENTRY, MAIN_ENTRY},
+    error::EulerError,
+};
 
 mod config;
 mod error;
@@ -21,20 +34,104 @@ struct MathiCLI {
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    New { project_name: String },
-    Run(CompilerOptionsArgs),
+    New {
+        project_name: String,
+        /// Creates a library, starting at `src/lib.mth` instead of
+        /// `src/main.mth`.
+        #[clap(long)]
+        lib: bool,
+    },
+    Run(RunArgs),
+    Build(BuildArgs),
+    /// Starts an interactive session.
+    Repl(CompilerOptionsArgs),
+    /// Formats the source files of the project.
+    Fmt(FmtArgs),
+    /// Runs the functions declared with `test df` in every module.
+    Test(TestArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct RunArgs {
+    #[clap(flatten)]
+    compiler_opts: CompilerOptionsArgs,
+    /// How errors and warnings are printed.
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+    /// Executable to run, needed when the project has several.
+    #[clap(long, value_name = "NAME")]
+    bin: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct BuildArgs {
+    #[clap(flatten)]
+    compiler_opts: CompilerOptionsArgs,
+    /// How errors and warnings are printed.
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+    /// Path of the executable, `target/<executable name>` by default. Only
+    /// allowed when building a single executable.
+    #[clap(long)]
+    output: Option<PathBuf>,
+    /// Only builds this executable, instead of every one of the project.
+    #[clap(long, value_name = "NAME")]
+    bin: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct FmtArgs {
+    /// Lists the files which aren't formatted instead of formatting them, and
+    /// fails if there's any.
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct TestArgs {
+    #[clap(flatten)]
+    compiler_opts: CompilerOptionsArgs,
+    /// Only runs the tests whose name contains this string.
+    filter: Option<String>,
 }
 
 #[derive(Debug, Clone, Args)]
 struct CompilerOptionsArgs {
     #[clap(short, long, value_enum, default_value_t = OptLvlArg::O2)]
     opt_lvl: OptLvlArg,
-    #[clap(long)]
-    dump_mathir: bool,
-    #[clap(long)]
-    dump_mlir: bool,
-    #[clap(long)]
-    dump_llvmir: bool,
+    /// Artifacts to write for every module, separated by commas.
+    #[clap(long, value_enum, value_delimiter = ',')]
+    emit: Vec<EmitArg>,
+    /// Directory where the emitted artifacts are written.
+    #[clap(long, default_value = "target/emit")]
+    emit_dir: PathBuf,
+    /// Turns a lint into an error, `warnings` for every lint.
+    #[clap(short = 'D', value_name = "LINT", value_parser = parse_lint_arg)]
+    deny: Vec<LintArg>,
+    /// Silences a lint, `warnings` for every lint.
+    #[clap(short = 'A', value_name = "LINT", value_parser = parse_lint_arg)]
+    allow: Vec<LintArg>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LintArg {
+    Warnings,
+    Lint(Lint),
+}
+
+fn parse_lint_arg(arg: &str) -> Result<LintArg, String> {
+    match arg {
+        "warnings" => Ok(LintArg::Warnings),
+        lint => lint.parse().map(LintArg::Lint),
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum MessageFormat {
+    /// Reports for the terminal.
+    Human,
+    /// A JSON array with every diagnostic, printed on a single line to stdout.
+    Json,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -45,6 +142,18 @@ enum OptLvlArg {
     O3,
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+enum EmitArg {
+    Mathir,
+    Mlir,
+    #[value(name = "mlirbc")]
+    MlirBc,
+    LlvmIr,
+    LlvmBc,
+    Asm,
+    Obj,
+}
+
 impl From<CompilerOptionsArgs> for CompilerOpts {
     fn from(args: CompilerOptionsArgs) -> Self {
         CompilerOpts {
@@ -54,13 +163,49 @@ impl From<CompilerOptionsArgs> for CompilerOpts {
                 OptLvlArg::O2 => OptLvl::O2,
                 OptLvlArg::O3 => OptLvl::O3,
             },
-            dump_mathir: args.dump_mathir,
-            dump_mlir: args.dump_mlir,
-            dump_llvmir: args.dump_llvmir,
+            emit: args
+                .emit
+                .into_iter()
+                .map(|kind| match kind {
+                    EmitArg::Mathir => EmitKind::Mathir,
+                    EmitArg::Mlir => EmitKind::Mlir,
+                    EmitArg::MlirBc => EmitKind::MlirBc,
+                    EmitArg::LlvmIr => EmitKind::LlvmIr,
+                    EmitArg::LlvmBc => EmitKind::LlvmBc,
+                    EmitArg::Asm => EmitKind::Asm,
+                    EmitArg::Obj => EmitKind::Obj,
+                })
+                .collect(),
+            emit_dir: args.emit_dir,
+            lints: lint_levels(&args.deny, &args.allow),
+            ..Default::default()
         }
     }
 }
 
+/// Allowing a lint takes precedence over denying it.
+fn lint_levels(deny: &[LintArg], allow: &[LintArg]) -> LintLevels {
+    let mut levels = LintLevels::default();
+
+    for arg in deny {
+        match arg {
+            LintArg::Warnings => levels.deny_warnings(),
+            LintArg::Lint(lint) => levels.set(*lint, LintLevel::Deny),
+        }
+    }
+
+    for arg in allow {
+        match arg {
+            LintArg::Warnings => Lint::ALL
+                .into_iter()
+                .for_each(|lint| levels.set(lint, LintLevel::Allow)),
+            LintArg::Lint(lint) => levels.set(*lint, LintLevel::Allow),
+        }
+    }
+
+    levels
+}
+
 fn main() -> Result<(), EulerError> {
     tracing::subscriber::set_global_default(
         FmtSubscriber::builder()
@@ -70,16 +215,29 @@ fn main() -> Result<(), EulerError> {
     .expect("Failed to set global suscriber");
 
     match MathiCLI::parse().command {
-        Command::New { project_name } => create_project(project_name)?,
-        Command::Run(compiler_opts) => {
-            compile_project(compiler_opts.into())?;
+        Command::New { project_name, lib } => create_project(project_name, lib)?,
+        Command::Run(run_args) => run_project(
+            run_args.compiler_opts.into(),
+            run_args.message_format,
+            run_args.bin.as_deref(),
+        )?,
+        Command::Build(build_args) => build_project(
+            build_args.compiler_opts.into(),
+            build_args.message_format,
+            build_args.output,
+            build_args.bin.as_deref(),
+        )?,
+        Command::Repl(compiler_opts) => run_repl(compiler_opts.into())?,
+        Command::Fmt(fmt_args) => format_project(fmt_args.check)?,
+        Command::Test(test_args) => {
+            run_tests(test_args.compiler_opts.into(), test_args.filter.as_deref())?
         }
     };
 
     Ok(())
 }
 
-fn create_project(project_name: String) -> Result<(), EulerError> {
+fn create_project(project_name: String, lib: bool) -> Result<(), EulerError> {
     let curr_dir = env::current_dir()?;
     let project_path = curr_dir.join(&project_name);
     std::fs::create_dir_all(project_path.join("src"))?;
@@ -87,49 +245,458 @@ fn create_project(project_name: String) -> Result<(), EulerError> {
     let config_toml = ConfigToml::new(&project_name);
     let mathic_toml_path = project_path.join("Mathic.toml");
 
-    let main_file_path = project_path.join("src/main.mth");
-    let main_file_content = r#"df main() i32 {
-    sym x:expr<i32> ;
+    let (entry_file_path, entry_file_content) = if lib {
+        (
+            project_path.join("src").join(LIB_ENTRY),
+            r#"df add(x: i64, y: i64) i64 {
+    return x + y;
+}
+
+test df adds() {
+    assert_eq(add(2, 3), 5);
+}
+"#,
+        )
+    } else {
+        (
+            project_path.join("src").join(MAIN_ENTRY),
+            r#"df main() i32 {
+    sym x: expr<i32>;
     let y: i32 = 5;
 
-    return (x+y)[x=10];
-}"#;
+    return (x + y)[x=10];
+}
+"#,
+        )
+    };
 
     std::fs::write(mathic_toml_path, toml::to_string(&config_toml)?)?;
-    std::fs::write(&main_file_path, main_file_content)?;
+    std::fs::write(&entry_file_path, entry_file_content)?;
 
     println!("Project '{}' created successfully!", project_name);
 
     Ok(())
 }
 
-fn compile_project(compiler_opts: CompilerOpts) -> Result<(), EulerError> {
-    if !fs::exists(env::current_dir()?.join("src/main.mth"))? {
-        return Err(EulerError::MainFileNotFound);
+/// Compiles the project starting at `entry`, relative to `src`, and prints
+/// its diagnostics.
+fn compile_project<'c>(
+    compiler: &'c MathicCompiler,
+    entry: &Path,
+    compiler_opts: &CompilerOpts,
+    message_format: MessageFormat,
+) -> Result<Vec<MathicModule<'c>>, EulerError> {
+    let src_root = env::current_dir()?.join("src");
+
+    if !fs::exists(src_root.join(entry))? {
+        return Err(EulerError::EntryNotFound(entry.to_path_buf()));
     }
 
-    let compiler = MathicCompiler::new()?;
+    let compiler_opts = with_dependencies(compiler_opts)?;
 
-    let src_root = env::current_dir()?.join("src");
+    let result = compiler.compile_project_entry(&src_root, entry, &compiler_opts);
 
-    let modules = match compiler.compile_project(&src_root, compiler_opts) {
-        Ok(modules) => modules,
-        Err(MathicError::CompilationFailed) => {
-            compiler.diagnostics().print_all()?;
-            std::process::exit(1);
+    // On success, only warnings are left.
+    if matches!(result, Ok(_) | Err(MathicError::CompilationFailed)) {
+        match message_format {
+            MessageFormat::Human => compiler.diagnostics().print_all()?,
+            MessageFormat::Json => println!("{}", compiler.diagnostics().to_json()?),
         }
-        Err(e) => {
-            return Err(EulerError::from(e));
-        }
-    };
+    }
+
+    match result {
+        Ok(modules) => Ok(modules),
+        Err(MathicError::CompilationFailed) => std::process::exit(1),
+        Err(e) => Err(EulerError::from(e)),
+    }
+}
+
+/// Loads the project's Mathic.toml, if there's one.
+fn load_config() -> Result<Option<ConfigToml>, EulerError> {
+    let config_path = env::current_dir()?.join("Mathic.toml");
+
+    if !fs::exists(&config_path)? {
+        return Ok(None);
+    }
+
+    Ok(Some(ConfigToml::load(&config_path)?))
+}
+
+/// Adds the dependencies declared in Mathic.toml, if there's one, to the
+/// compiler options.
+fn with_dependencies(compiler_opts: &CompilerOpts) -> Result<CompilerOpts, EulerError> {
+    let mut compiler_opts = compiler_opts.clone();
+
+    if let Some(config_toml) = load_config()? {
+        let roots = config_toml.dependency_roots(&env::current_dir()?)?;
+
+        compiler_opts.dependencies = roots.project;
+        compiler_opts.package_dependencies = roots.packages;
+    }
+
+    Ok(compiler_opts)
+}
+
+/// Returns the executables of the project. Without Mathic.toml, there's a
+/// single one, `main`, if `src/main.mth` exists.
+fn project_binaries() -> Result<Vec<Bin>, EulerError> {
+    let src_root = env::current_dir()?.join("src");
+
+    Ok(match load_config()? {
+        Some(config_toml) => config_toml.binaries(&src_root),
+        None if src_root.join(MAIN_ENTRY).is_file() => vec![Bin {
+            name: String::from("main"),
+            path: PathBuf::from(MAIN_ENTRY),
+        }],
+        None => Vec::new(),
+    })
+}
+
+/// Returns the executable named `name`, or the only one of the project.
+fn select_binary(name: Option<&str>) -> Result<Bin, EulerError> {
+    let mut bins = project_binaries()?;
+
+    match name {
+        Some(name) => bins
+            .into_iter()
+            .find(|bin| bin.name == name)
+            .ok_or_else(|| EulerError::BinNotFound(name.to_string())),
+        None if bins.is_empty() => Err(EulerError::NoBinaries),
+        None if bins.len() == 1 => Ok(bins.remove(0)),
+        None => Err(EulerError::SeveralBinaries(
+            bins.into_iter().map(|bin| bin.name).collect(),
+        )),
+    }
+}
+
+fn run_project(
+    compiler_opts: CompilerOpts,
+    message_format: MessageFormat,
+    bin: Option<&str>,
+) -> Result<(), EulerError> {
+    let bin = select_binary(bin)?;
 
-    let executor = MathicJITExecutor::new(modules, compiler_opts)?;
+    let compiler = MathicCompiler::new()?;
+    let modules = compile_project(&compiler, &bin.path, &compiler_opts, message_format)?;
+
+    let executor = MathicJITExecutor::new(modules, &compiler_opts)?;
 
     tracing::debug!("Executor Created");
-    let result = executor.call_function("main::main");
+    let result = executor.call_function(&bin.entry_function());
 
     tracing::debug!("Execution Done");
     println!("RESULT: {:?}", result);
 
     Ok(())
 }
+
+fn build_project(
+    compiler_opts: CompilerOpts,
+    message_format: MessageFormat,
+    output: Option<PathBuf>,
+    bin: Option<&str>,
+) -> Result<(), EulerError> {
+    let bins = match bin {
+        Some(_) => vec![select_binary(bin)?],
+        None => project_binaries()?,
+    };
+
+    if bins.is_empty() {
+        return Err(EulerError::NoBinaries);
+    }
+
+    if output.is_some() && bins.len() > 1 {
+        return Err(EulerError::SeveralBinaries(
+            bins.into_iter().map(|bin| bin.name).collect(),
+        ));
+    }
+
+    let target_dir = env::current_dir()?.join("target");
+    let compiler = MathicCompiler::new()?;
+
+    for bin in bins {
+        let modules = compile_project(&compiler, &bin.path, &compiler_opts, message_format)?;
+
+        let output = output.clone().unwrap_or_else(|| target_dir.join(&bin.name));
+
+        // Executables may share modules, so each gets its own objects.
+        aot::build_executable(
+            &modules,
+            &compiler_opts,
+            &bin.entry_function(),
+            &target_dir.join("obj").join(&bin.name),
+            &output,
+        )?;
+
+        println!("Executable '{}' built successfully!", output.display());
+    }
+
+    Ok(())
+}
+
+/// Returns the modules the project is compiled from: the one of each
+/// executable, and `lib.mth` for libraries.
+fn project_entries() -> Result<Vec<PathBuf>, EulerError> {
+    let mut entries = Vec::new();
+
+    for bin in project_binaries()? {
+        if !entries.contains(&bin.path) {
+            entries.push(bin.path);
+        }
+    }
+
+    if env::current_dir()?.join("src").join(LIB_ENTRY).is_file() {
+        entries.push(PathBuf::from(LIB_ENTRY));
+    }
+
+    if entries.is_empty() {
+        return Err(EulerError::NoBinaries);
+    }
+
+    Ok(entries)
+}
+
+/// Runs every test whose name contains `filter`. Each entry of the project is
+/// compiled once, and each test runs in a process forked from this one, so
+/// that a failed assertion only ends its test.
+fn run_tests(compiler_opts: CompilerOpts, filter: Option<&str>) -> Result<(), EulerError> {
+    let compiler = MathicCompiler::new()?;
+
+    // The entries may share modules, whose tests are only run once.
+    let mut executors = Vec::new();
+    let mut tests = Vec::<(String, usize)>::new();
+
+    for entry in project_entries()? {
+        let modules = compile_project(&compiler, &entry, &compiler_opts, MessageFormat::Human)?;
+        let test_count = tests.len();
+
+        for test_name in modules.iter().flat_map(|module| module.tests()) {
+            if filter.is_none_or(|filter| test_name.contains(filter))
+                && !tests.iter().any(|(name, _)| name == test_name)
+            {
+                tests.push((test_name.clone(), executors.len()));
+            }
+        }
+
+        if tests.len() > test_count {
+            executors.push(MathicJITExecutor::new(modules, &compiler_opts)?);
+        }
+    }
+
+    tests.sort();
+
+    println!("running {} test(s)", tests.len());
+
+    let mut failures = Vec::new();
+
+    for (test_name, executor_idx) in &tests {
+        let (status, output) = run_forked_test(&executors[*executor_idx], test_name)?;
+
+        if status.success() {
+            println!("test {test_name} ... ok");
+        } else {
+            println!("test {test_name} ... FAILED");
+            failures.push((test_name, format!("{output}{status}\n")));
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+
+        for (test_name, report) in &failures {
+            println!("\n---- {test_name} ----\n{report}");
+        }
+    }
+
+    println!(
+        "\ntest result: {}. {} passed; {} failed",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        tests.len() - failures.len(),
+        failures.len()
+    );
+
+    if !failures.is_empty() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Runs a test in a child process, returning how it exited and what it
+/// wrote to stdout and stderr.
+fn run_forked_test(
+    executor: &MathicJITExecutor,
+    test_name: &str,
+) -> Result<(ExitStatus, String), EulerError> {
+    let mut fds = [0; 2];
+
+    // SAFETY: `fds` holds the two descriptors `pipe` writes.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    let [read_fd, write_fd] = fds;
+
+    // Otherwise the child would write what's buffered again.
+    io::stdout().flush()?;
+
+    // SAFETY: the child only runs the test, writing to the pipe, and exits
+    // without returning to the caller.
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error().into()),
+        0 => {
+            unsafe {
+                libc::close(read_fd);
+                libc::dup2(write_fd, libc::STDOUT_FILENO);
+                libc::dup2(write_fd, libc::STDERR_FILENO);
+                libc::close(write_fd);
+            }
+
+            let code = match executor.run_test(test_name) {
+                Ok(()) => 0,
+                Err(e) => {
+                    eprintln!("{e}");
+                    1
+                }
+            };
+
+            let _ = io::stdout().flush();
+
+            unsafe { libc::_exit(code) }
+        }
+        pid => {
+            // SAFETY: the write end is only used by the child, and the read
+            // end is owned by the file from now on.
+            unsafe { libc::close(write_fd) };
+            let mut pipe = unsafe { File::from_raw_fd(read_fd) };
+
+            let mut output = Vec::new();
+            pipe.read_to_end(&mut output)?;
+
+            let mut status = 0;
+
+            // SAFETY: `pid` is the child forked above.
+            if unsafe { libc::waitpid(pid, &mut status, 0) } == -1 {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            Ok((
+                ExitStatus::from_raw(status),
+                String::from_utf8_lossy(&output).into_owned(),
+            ))
+        }
+    }
+}
+
+fn run_repl(compiler_opts: CompilerOpts) -> Result<(), EulerError> {
+    let compiler = MathicCompiler::new()?;
+    let mut repl = MathicRepl::new(&compiler, compiler_opts)?;
+
+    let mut lines = io::stdin().lock().lines();
+
+    loop {
+        let mut input = String::new();
+        let mut prompt = "> ";
+
+        // Inputs spanning several lines are read until the parser no longer
+        // runs out of tokens. An empty line evaluates the input as it is.
+        loop {
+            print!("{prompt}");
+            io::stdout().flush()?;
+
+            let Some(line) = lines.next().transpose()? else {
+                return Ok(());
+            };
+
+            input.push_str(&line);
+            input.push('\n');
+            prompt = ". ";
+
+            if line.trim().is_empty() || !MathicRepl::is_incomplete(&input) {
+                break;
+            }
+        }
+
+        match input.trim() {
+            "" => continue,
+            ":quit" | ":q" => return Ok(()),
+            _ => {}
+        }
+
+        match repl.eval(&input) {
+            Ok(ReplOutput::None) => {}
+            Ok(ReplOutput::Value(value)) => println!("{value}"),
+            Ok(ReplOutput::Opaque(ty)) => println!("<{ty}>"),
+            Err(MathicError::CompilationFailed) => {
+                compiler.diagnostics().print_all_with_source(&input)?
+            }
+            Err(e) => return Err(EulerError::from(e)),
+        }
+    }
+}
+
+fn format_project(check: bool) -> Result<(), EulerError> {
+    let curr_dir = env::current_dir()?;
+    let src_root = curr_dir.join("src");
+
+    if !src_root.is_dir() {
+        return Err(EulerError::SrcDirNotFound);
+    }
+
+    let mut files = Vec::new();
+    source_files(&src_root, &mut files)?;
+    files.sort();
+
+    let mut failed = false;
+
+    for file in files {
+        let source = fs::read_to_string(&file)?;
+
+        let formatted = match formatter::format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                for e in errors {
+                    diagnostics::format_error(&file, &CompilationError::from(e));
+                }
+
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!(
+                "{}",
+                file.strip_prefix(&curr_dir).unwrap_or(&file).display()
+            );
+            failed = true;
+        } else {
+            fs::write(&file, formatted)?;
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Collects the Mathic files within a directory and its subdirectories.
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), EulerError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            source_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "mth") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
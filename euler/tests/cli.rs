@@ -0,0 +1,146 @@
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+use rstest::rstest;
+
+fn euler(project: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_euler"))
+        .args(args)
+        .current_dir(project)
+        .output()
+        .expect("failed to run euler")
+}
+
+#[rstest]
+#[case(
+    "tests/fixtures/passing_tests",
+    true,
+    &[
+        "test main::squares ... ok",
+        "test main::squares_negative ... ok",
+        "test result: ok. 2 passed; 0 failed",
+    ]
+)]
+#[case(
+    "tests/fixtures/failing_tests",
+    false,
+    &[
+        "test main::squares ... ok",
+        "test main::squares_wrong ... FAILED",
+        "src/main.mth:16:5: assertion failed: assert(square(3) == 10)",
+        "test result: FAILED. 1 passed; 1 failed",
+    ]
+)]
+fn test_euler_test(#[case] project: &Path, #[case] success: bool, #[case] expected: &[&str]) {
+    let output = euler(project, &["test"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.success(), success, "{stdout}");
+
+    for line in expected {
+        assert!(stdout.contains(line), "'{line}' not found in:\n{stdout}");
+    }
+}
+
+#[test]
+fn test_euler_test_filter() {
+    let output = euler(
+        Path::new("tests/fixtures/failing_tests"),
+        &["test", "squares_wrong"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("running 1 test(s)"), "{stdout}");
+    assert!(!stdout.contains("test main::squares ... ok"), "{stdout}");
+}
+
+#[test]
+fn test_euler_test_denied_lint() {
+    let output = euler(
+        Path::new("tests/fixtures/failing_tests"),
+        &["test", "-D", "warnings"],
+    );
+
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("running"));
+}
+
+#[rstest]
+#[case(&["test", "-D", "warnings"], false)]
+#[case(&["test", "-D", "warnings", "-A", "unreachable_code"], true)]
+fn test_euler_test_unreachable_code(#[case] args: &[&str], #[case] success: bool) {
+    let output = euler(Path::new("tests/fixtures/unreachable_code"), args);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.success(), success, "{stdout}");
+}
+
+#[rstest]
+#[case("new_bin", &[])]
+#[case("new_lib", &["--lib"])]
+fn test_euler_new_is_formatted(#[case] project_name: &str, #[case] flags: &[&str]) {
+    let tmp_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let project = tmp_dir.join(project_name);
+    let _ = fs::remove_dir_all(&project);
+
+    let output = euler(tmp_dir, &[&["new", project_name], flags].concat());
+    assert!(output.status.success());
+
+    let output = euler(&project, &["fmt", "--check"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[rstest]
+#[case("tests/fixtures/binaries", "solver", 3)]
+#[case("tests/fixtures/package_entry", "package_entry", 5)]
+fn test_euler_build(#[case] project: &Path, #[case] bin: &str, #[case] exit_code: i32) {
+    let executable = Path::new(env!("CARGO_TARGET_TMPDIR")).join(bin);
+
+    let output = euler(
+        project,
+        &[
+            "build",
+            "--bin",
+            bin,
+            "--output",
+            executable.to_str().unwrap(),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let status = Command::new(&executable)
+        .status()
+        .expect("failed to run the executable");
+
+    assert_eq!(status.code(), Some(exit_code));
+}
+
+#[test]
+fn test_euler_build_library() {
+    let tmp_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let project = tmp_dir.join("build_lib");
+    let _ = fs::remove_dir_all(&project);
+
+    assert!(
+        euler(tmp_dir, &["new", "build_lib", "--lib"])
+            .status
+            .success()
+    );
+
+    let output = euler(&project, &["build"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("NoBinaries"));
+}
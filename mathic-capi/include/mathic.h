@@ -0,0 +1,309 @@
+/* Generated with cbindgen from mathic-capi. Run `make capi-header` after changing the API. */
+
+#ifndef MATHIC_H
+#define MATHIC_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of a function which may fail.
+ */
+typedef enum MathicStatus {
+  MATHIC_STATUS_OK = 0,
+  /**
+   * The program has errors, which are listed by the compiler's
+   * diagnostics.
+   */
+  MATHIC_STATUS_COMPILATION_FAILED,
+  /**
+   * A pointer was null, a string wasn't valid UTF-8, or a value doesn't
+   * fit the function called. See `mathic_last_error`.
+   */
+  MATHIC_STATUS_INVALID_ARGUMENT,
+  /**
+   * Any other failure. See `mathic_last_error`.
+   */
+  MATHIC_STATUS_ERROR,
+} MathicStatus;
+
+typedef enum MathicSeverity {
+  MATHIC_SEVERITY_WARNING,
+  MATHIC_SEVERITY_ERROR,
+} MathicSeverity;
+
+/**
+ * The type of a `MathicValue`, stored in its `kind`.
+ */
+typedef enum MathicValueKind {
+  MATHIC_VALUE_KIND_I8,
+  MATHIC_VALUE_KIND_I16,
+  MATHIC_VALUE_KIND_I32,
+  MATHIC_VALUE_KIND_I64,
+  MATHIC_VALUE_KIND_ISIZE,
+  MATHIC_VALUE_KIND_U8,
+  MATHIC_VALUE_KIND_U16,
+  MATHIC_VALUE_KIND_U32,
+  MATHIC_VALUE_KIND_U64,
+  MATHIC_VALUE_KIND_USIZE,
+  MATHIC_VALUE_KIND_F32,
+  MATHIC_VALUE_KIND_F64,
+  MATHIC_VALUE_KIND_BOOL,
+} MathicValueKind;
+
+/**
+ * A compiler, along with the diagnostics of its last compilation.
+ */
+typedef struct MathicCompiler MathicCompiler;
+
+/**
+ * A JIT holding the modules of a program.
+ */
+typedef struct MathicExecutor MathicExecutor;
+
+/**
+ * The modules of a compiled program, waiting to be loaded into a JIT by
+ * `mathic_executor_new`.
+ */
+typedef struct MathicProgram MathicProgram;
+
+/**
+ * A warning or error of the last compilation. Its strings belong to the
+ * compiler and are valid until its next compilation.
+ *
+ * Lines and columns start at 1, columns count characters. They are 0 when
+ * the diagnostic has no span or its file can't be read.
+ */
+typedef struct MathicDiagnostic {
+  MathicSeverity severity;
+  /**
+   * The diagnostic's code, e.g. `S007`, or null if it has none.
+   */
+  const char *code;
+  const char *title;
+  const char *message;
+  const char *help;
+  const char *file;
+  /**
+   * Whether the diagnostic points to a section of the file. The offsets
+   * below are 0 otherwise.
+   */
+  bool has_span;
+  size_t start;
+  size_t end;
+  size_t line;
+  size_t column;
+  size_t end_line;
+  size_t end_column;
+} MathicDiagnostic;
+
+typedef union MathicValueData {
+  int8_t i8;
+  int16_t i16;
+  int32_t i32;
+  int64_t i64;
+  intptr_t isize;
+  uint8_t u8;
+  uint16_t u16;
+  uint32_t u32;
+  uint64_t u64;
+  size_t usize;
+  float f32;
+  double f64;
+  /**
+   * Named so it doesn't clash with C's `bool`. It is 0 for false and 1
+   * for true.
+   */
+  uint8_t boolean;
+} MathicValueData;
+
+/**
+ * A number or boolean passed to or returned from `mathic_executor_call`.
+ * The field of `data` which is set is the one named by `kind`.
+ */
+typedef struct MathicValue {
+  /**
+   * A `MathicValueKind`. It is a plain integer so a value coming from C
+   * can be checked before being read.
+   */
+  uint32_t kind;
+  MathicValueData data;
+} MathicValue;
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+/**
+ * Returns the message of the last failure in the calling thread, or null if
+ * there was none. It is valid until the next failing call.
+ */
+const char *mathic_last_error(void);
+
+/**
+ * Creates a compiler, or returns null if it fails. Free it with
+ * `mathic_compiler_free`.
+ */
+MathicCompiler *mathic_compiler_new(void);
+
+/**
+ * Frees a compiler. Every program and executor built with it must be freed
+ * before.
+ *
+ * # Safety
+ *
+ * `compiler` must be null or returned by `mathic_compiler_new`, and not
+ * freed yet.
+ */
+void mathic_compiler_free(MathicCompiler *compiler);
+
+/**
+ * Compiles a program made of a single source, whose functions are in the
+ * `program` module. On success, `*program` is set to the compiled program.
+ * Either way, the compiler's diagnostics are replaced by the ones of this
+ * compilation.
+ *
+ * # Safety
+ *
+ * `compiler` must be a live compiler, `source` a NUL-terminated string and
+ * `program` a valid pointer.
+ */
+MathicStatus mathic_compile_source(MathicCompiler *compiler,
+                                   const char *source,
+                                   uint8_t opt_level,
+                                   MathicProgram **program);
+
+/**
+ * Compiles a project, given its source root, which holds `main.mth`. It
+ * behaves like `mathic_compile_source` otherwise.
+ *
+ * # Safety
+ *
+ * `compiler` must be a live compiler, `src_root` a NUL-terminated string and
+ * `program` a valid pointer.
+ */
+MathicStatus mathic_compile_project(MathicCompiler *compiler,
+                                    const char *src_root,
+                                    uint8_t opt_level,
+                                    MathicProgram **program);
+
+/**
+ * Frees a program which wasn't loaded into an executor.
+ *
+ * # Safety
+ *
+ * `program` must be null or returned by a compilation, and neither freed
+ * nor loaded yet.
+ */
+void mathic_program_free(MathicProgram *program);
+
+/**
+ * Returns the number of diagnostics of the compiler's last compilation.
+ *
+ * # Safety
+ *
+ * `compiler` must be null or a live compiler.
+ */
+size_t mathic_diagnostics_count(const MathicCompiler *compiler);
+
+/**
+ * Writes the `index`th diagnostic of the compiler's last compilation to
+ * `*diagnostic`.
+ *
+ * # Safety
+ *
+ * `compiler` must be a live compiler and `diagnostic` a valid pointer.
+ */
+MathicStatus mathic_diagnostics_get(const MathicCompiler *compiler,
+                                    size_t index,
+                                    MathicDiagnostic *diagnostic);
+
+/**
+ * Loads a program into a new JIT. The program is consumed, even if this
+ * fails. On success, `*executor` is set to the JIT, free it with
+ * `mathic_executor_free`.
+ *
+ * # Safety
+ *
+ * `program` must be returned by a compilation, and neither freed nor loaded
+ * yet. `executor` must be a valid pointer.
+ */
+MathicStatus mathic_executor_new(MathicProgram *program, MathicExecutor **executor);
+
+/**
+ * Frees an executor.
+ *
+ * # Safety
+ *
+ * `executor` must be null or returned by `mathic_executor_new`, and not
+ * freed yet.
+ */
+void mathic_executor_free(MathicExecutor *executor);
+
+/**
+ * Returns the address of a function, named after its module, e.g.
+ * `program::f`, or null if there's no such function. It is valid while the
+ * executor lives, and must be cast to the function's exact C signature.
+ *
+ * # Safety
+ *
+ * `executor` must be a live executor and `function_name` a NUL-terminated
+ * string.
+ */
+void *mathic_executor_lookup_function(const MathicExecutor *executor, const char *function_name);
+
+/**
+ * Returns the address of a symbol of the JIT, or null if it isn't defined.
+ *
+ * # Safety
+ *
+ * `executor` must be a live executor and `symbol` a NUL-terminated string.
+ */
+void *mathic_executor_lookup_symbol(const MathicExecutor *executor, const char *symbol);
+
+/**
+ * Calls a function with the given arguments, checked against its
+ * signature, and writes its result to `*result`. Only functions taking and
+ * returning numbers and booleans can be called this way.
+ *
+ * # Safety
+ *
+ * `executor` must be a live executor, `function_name` a NUL-terminated
+ * string, `args` point to `arg_count` values (or be null if there are none)
+ * and `result` be a valid pointer.
+ */
+MathicStatus mathic_executor_call(const MathicExecutor *executor,
+                                  const char *function_name,
+                                  const MathicValue *args,
+                                  size_t arg_count,
+                                  MathicValue *result);
+
+/**
+ * Provides the implementation of a function declared with `extern df`,
+ * named after its module, e.g. `host::log`. `param_kinds` and
+ * `return_kind` are `MathicValueKind`s, which are checked against the
+ * declaration. A host function must be registered before calling any
+ * function which may call it.
+ *
+ * # Safety
+ *
+ * `executor` must be a live executor, `function_name` a NUL-terminated
+ * string, `param_kinds` point to `param_count` kinds (or be null if there
+ * are none), and `host_fn` be a function with the C signature given by the
+ * kinds, e.g. `int64_t log(int64_t)`. Booleans are passed as `bool`.
+ */
+MathicStatus mathic_executor_register_host_fn(const MathicExecutor *executor,
+                                              const char *function_name,
+                                              const uint32_t *param_kinds,
+                                              size_t param_count,
+                                              uint32_t return_kind,
+                                              const void *host_fn);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MATHIC_H */
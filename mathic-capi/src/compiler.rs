@@ -0,0 +1,171 @@
+use std::{ffi::c_char, mem, path::Path, ptr};
+
+use mathic::{
+    MathicError, MathicModule, MathicResult,
+    compiler::{CompilerOpts, MathicCompiler as Compiler},
+};
+
+use crate::{MathicStatus, diagnostics::OwnedDiagnostic, fail, str_arg};
+
+/// A compiler, along with the diagnostics of its last compilation.
+pub struct MathicCompiler {
+    inner: Compiler,
+    pub(crate) diagnostics: Vec<OwnedDiagnostic>,
+}
+
+/// The modules of a compiled program, waiting to be loaded into a JIT by
+/// `mathic_executor_new`.
+pub struct MathicProgram {
+    // The modules borrow the context of the compiler which built them. The
+    // API requires the compiler to outlive them.
+    pub(crate) modules: Vec<MathicModule<'static>>,
+    pub(crate) options: CompilerOpts,
+}
+
+/// Creates a compiler, or returns null if it fails. Free it with
+/// `mathic_compiler_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn mathic_compiler_new() -> *mut MathicCompiler {
+    match Compiler::new() {
+        Ok(inner) => Box::into_raw(Box::new(MathicCompiler {
+            inner,
+            diagnostics: Vec::new(),
+        })),
+        Err(e) => {
+            fail(MathicStatus::Error, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a compiler. Every program and executor built with it must be freed
+/// before.
+///
+/// # Safety
+///
+/// `compiler` must be null or returned by `mathic_compiler_new`, and not
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_compiler_free(compiler: *mut MathicCompiler) {
+    if !compiler.is_null() {
+        drop(unsafe { Box::from_raw(compiler) });
+    }
+}
+
+/// Compiles a program made of a single source, whose functions are in the
+/// `program` module. On success, `*program` is set to the compiled program.
+/// Either way, the compiler's diagnostics are replaced by the ones of this
+/// compilation.
+///
+/// # Safety
+///
+/// `compiler` must be a live compiler, `source` a NUL-terminated string and
+/// `program` a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_compile_source(
+    compiler: *mut MathicCompiler,
+    source: *const c_char,
+    opt_level: u8,
+    program: *mut *mut MathicProgram,
+) -> MathicStatus {
+    let source = match unsafe { str_arg(source, "source") } {
+        Ok(source) => source,
+        Err(status) => return status,
+    };
+
+    unsafe {
+        compile(
+            compiler,
+            opt_level,
+            Some(source),
+            program,
+            |compiler, opts| Ok(vec![compiler.compile_source(source, None, opts)?]),
+        )
+    }
+}
+
+/// Compiles a project, given its source root, which holds `main.mth`. It
+/// behaves like `mathic_compile_source` otherwise.
+///
+/// # Safety
+///
+/// `compiler` must be a live compiler, `src_root` a NUL-terminated string and
+/// `program` a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_compile_project(
+    compiler: *mut MathicCompiler,
+    src_root: *const c_char,
+    opt_level: u8,
+    program: *mut *mut MathicProgram,
+) -> MathicStatus {
+    let src_root = match unsafe { str_arg(src_root, "src_root") } {
+        Ok(src_root) => Path::new(src_root),
+        Err(status) => return status,
+    };
+
+    unsafe {
+        compile(compiler, opt_level, None, program, |compiler, opts| {
+            compiler.compile_project(src_root, opts)
+        })
+    }
+}
+
+/// Frees a program which wasn't loaded into an executor.
+///
+/// # Safety
+///
+/// `program` must be null or returned by a compilation, and neither freed
+/// nor loaded yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_program_free(program: *mut MathicProgram) {
+    if !program.is_null() {
+        drop(unsafe { Box::from_raw(program) });
+    }
+}
+
+/// Runs a compilation and records its diagnostics. `source` is the
+/// program's source when it isn't in a file.
+///
+/// # Safety
+///
+/// `compiler` must be null or a live compiler, and `program` null or a
+/// valid pointer.
+unsafe fn compile(
+    compiler: *mut MathicCompiler,
+    opt_level: u8,
+    source: Option<&str>,
+    program: *mut *mut MathicProgram,
+    compile: impl for<'c> FnOnce(&'c Compiler, &CompilerOpts) -> MathicResult<Vec<MathicModule<'c>>>,
+) -> MathicStatus {
+    let Some(compiler) = (unsafe { compiler.as_mut() }) else {
+        return fail(MathicStatus::InvalidArgument, "compiler is null");
+    };
+
+    if program.is_null() {
+        return fail(MathicStatus::InvalidArgument, "program is null");
+    }
+
+    let options = CompilerOpts {
+        opt_lvl: opt_level.into(),
+        ..Default::default()
+    };
+
+    let result = compile(&compiler.inner, &options);
+    compiler.diagnostics = OwnedDiagnostic::collect(compiler.inner.diagnostics(), source);
+
+    match result {
+        Ok(modules) => {
+            let modules = unsafe {
+                mem::transmute::<Vec<MathicModule<'_>>, Vec<MathicModule<'static>>>(modules)
+            };
+
+            unsafe {
+                *program = Box::into_raw(Box::new(MathicProgram { modules, options }));
+            }
+
+            MathicStatus::Ok
+        }
+        Err(MathicError::CompilationFailed) => MathicStatus::CompilationFailed,
+        Err(e) => fail(MathicStatus::Error, e),
+    }
+}
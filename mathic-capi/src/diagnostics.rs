@@ -0,0 +1,176 @@
+use std::{
+    ffi::{CString, c_char},
+    ptr,
+};
+
+use mathic::diagnostics::{
+    DiagnosticsManager, JsonDiagnostic, Severity, SourceFiles, json::JsonSpan,
+};
+
+use crate::{MathicStatus, c_string, compiler::MathicCompiler, fail};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathicSeverity {
+    Warning,
+    Error,
+}
+
+/// A warning or error of the last compilation. Its strings belong to the
+/// compiler and are valid until its next compilation.
+///
+/// Lines and columns start at 1, columns count characters. They are 0 when
+/// the diagnostic has no span or its file can't be read.
+#[repr(C)]
+pub struct MathicDiagnostic {
+    pub severity: MathicSeverity,
+    /// The diagnostic's code, e.g. `S007`, or null if it has none.
+    pub code: *const c_char,
+    pub title: *const c_char,
+    pub message: *const c_char,
+    pub help: *const c_char,
+    pub file: *const c_char,
+    /// Whether the diagnostic points to a section of the file. The offsets
+    /// below are 0 otherwise.
+    pub has_span: bool,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// A diagnostic whose strings are kept for C.
+pub(crate) struct OwnedDiagnostic {
+    severity: MathicSeverity,
+    code: Option<CString>,
+    title: CString,
+    message: CString,
+    help: CString,
+    file: CString,
+    span: Option<JsonSpan>,
+}
+
+impl OwnedDiagnostic {
+    /// Collects every warning and then every error, like
+    /// `DiagnosticsManager::to_json`.
+    pub(crate) fn collect(diagnostics: &DiagnosticsManager, source: Option<&str>) -> Vec<Self> {
+        let mut sources = SourceFiles::new();
+
+        let warnings = diagnostics.with_warnings(|warnings| {
+            warnings
+                .iter()
+                .map(|(file_path, warning)| {
+                    let source = match source {
+                        Some(source) => Some(source),
+                        None => sources.get(file_path),
+                    };
+
+                    Self::new(JsonDiagnostic::new(file_path, warning.diagnostic(), source))
+                })
+                .collect::<Vec<_>>()
+        });
+        let errors = diagnostics.with_errors(|errors| {
+            errors
+                .iter()
+                .map(|(file_path, error)| {
+                    let source = match source {
+                        Some(source) => Some(source),
+                        None => sources.get(file_path),
+                    };
+
+                    Self::new(JsonDiagnostic::new(file_path, error.diagnostic(), source))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        warnings
+            .unwrap_or_default()
+            .into_iter()
+            .chain(errors.unwrap_or_default())
+            .collect()
+    }
+
+    fn new(diagnostic: JsonDiagnostic) -> Self {
+        Self {
+            severity: match diagnostic.severity {
+                Severity::Warning => MathicSeverity::Warning,
+                Severity::Error => MathicSeverity::Error,
+            },
+            code: diagnostic.code.map(c_string),
+            title: c_string(diagnostic.title),
+            message: c_string(&diagnostic.message),
+            help: c_string(&diagnostic.help),
+            file: c_string(&diagnostic.file),
+            span: diagnostic.span,
+        }
+    }
+
+    fn as_c(&self) -> MathicDiagnostic {
+        let span = self.span.as_ref();
+
+        MathicDiagnostic {
+            severity: self.severity,
+            code: self.code.as_ref().map_or(ptr::null(), |code| code.as_ptr()),
+            title: self.title.as_ptr(),
+            message: self.message.as_ptr(),
+            help: self.help.as_ptr(),
+            file: self.file.as_ptr(),
+            has_span: span.is_some(),
+            start: span.map_or(0, |span| span.start),
+            end: span.map_or(0, |span| span.end),
+            line: span.and_then(|span| span.line).unwrap_or(0),
+            column: span.and_then(|span| span.column).unwrap_or(0),
+            end_line: span.and_then(|span| span.end_line).unwrap_or(0),
+            end_column: span.and_then(|span| span.end_column).unwrap_or(0),
+        }
+    }
+}
+
+/// Returns the number of diagnostics of the compiler's last compilation.
+///
+/// # Safety
+///
+/// `compiler` must be null or a live compiler.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_diagnostics_count(compiler: *const MathicCompiler) -> usize {
+    unsafe { compiler.as_ref() }.map_or(0, |compiler| compiler.diagnostics.len())
+}
+
+/// Writes the `index`th diagnostic of the compiler's last compilation to
+/// `*diagnostic`.
+///
+/// # Safety
+///
+/// `compiler` must be a live compiler and `diagnostic` a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_diagnostics_get(
+    compiler: *const MathicCompiler,
+    index: usize,
+    diagnostic: *mut MathicDiagnostic,
+) -> MathicStatus {
+    let Some(compiler) = (unsafe { compiler.as_ref() }) else {
+        return fail(MathicStatus::InvalidArgument, "compiler is null");
+    };
+
+    if diagnostic.is_null() {
+        return fail(MathicStatus::InvalidArgument, "diagnostic is null");
+    }
+
+    let Some(owned) = compiler.diagnostics.get(index) else {
+        return fail(
+            MathicStatus::InvalidArgument,
+            format!(
+                "diagnostic {index} is out of bounds, there are {}",
+                compiler.diagnostics.len()
+            ),
+        );
+    };
+
+    unsafe {
+        *diagnostic = owned.as_c();
+    }
+
+    MathicStatus::Ok
+}
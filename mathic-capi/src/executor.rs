@@ -0,0 +1,434 @@
+use std::{
+    ffi::{c_char, c_void},
+    ptr, slice,
+};
+
+use mathic::{
+    diagnostics::CodegenError,
+    executor::{FunctionSignature, MathicExecutor as _, ScalarValue, jit::MathicJITExecutor},
+};
+
+use crate::{MathicStatus, compiler::MathicProgram, fail, str_arg};
+
+/// A JIT holding the modules of a program.
+pub struct MathicExecutor {
+    inner: MathicJITExecutor,
+}
+
+/// The type of a `MathicValue`, stored in its `kind`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathicValueKind {
+    I8,
+    I16,
+    I32,
+    I64,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+    F32,
+    F64,
+    Bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union MathicValueData {
+    pub i8: i8,
+    pub i16: i16,
+    pub i32: i32,
+    pub i64: i64,
+    pub isize: isize,
+    pub u8: u8,
+    pub u16: u16,
+    pub u32: u32,
+    pub u64: u64,
+    pub usize: usize,
+    pub f32: f32,
+    pub f64: f64,
+    /// Named so it doesn't clash with C's `bool`. It is 0 for false and 1
+    /// for true.
+    pub boolean: u8,
+}
+
+/// A number or boolean passed to or returned from `mathic_executor_call`.
+/// The field of `data` which is set is the one named by `kind`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MathicValue {
+    /// A `MathicValueKind`. It is a plain integer so a value coming from C
+    /// can be checked before being read.
+    pub kind: u32,
+    pub data: MathicValueData,
+}
+
+const VALUE_KINDS: [MathicValueKind; 13] = [
+    MathicValueKind::I8,
+    MathicValueKind::I16,
+    MathicValueKind::I32,
+    MathicValueKind::I64,
+    MathicValueKind::Isize,
+    MathicValueKind::U8,
+    MathicValueKind::U16,
+    MathicValueKind::U32,
+    MathicValueKind::U64,
+    MathicValueKind::Usize,
+    MathicValueKind::F32,
+    MathicValueKind::F64,
+    MathicValueKind::Bool,
+];
+
+impl TryFrom<u32> for MathicValueKind {
+    type Error = String;
+
+    fn try_from(kind: u32) -> Result<Self, Self::Error> {
+        VALUE_KINDS
+            .iter()
+            .copied()
+            .find(|known| *known as u32 == kind)
+            .ok_or_else(|| format!("{kind} isn't a MathicValueKind"))
+    }
+}
+
+impl MathicValueKind {
+    /// Returns the zero of the kind, which tells its Mathic type.
+    fn zero(self) -> ScalarValue {
+        match self {
+            MathicValueKind::I8 => ScalarValue::I8(0),
+            MathicValueKind::I16 => ScalarValue::I16(0),
+            MathicValueKind::I32 => ScalarValue::I32(0),
+            MathicValueKind::I64 => ScalarValue::I64(0),
+            MathicValueKind::Isize => ScalarValue::Isize(0),
+            MathicValueKind::U8 => ScalarValue::U8(0),
+            MathicValueKind::U16 => ScalarValue::U16(0),
+            MathicValueKind::U32 => ScalarValue::U32(0),
+            MathicValueKind::U64 => ScalarValue::U64(0),
+            MathicValueKind::Usize => ScalarValue::Usize(0),
+            MathicValueKind::F32 => ScalarValue::F32(0.0),
+            MathicValueKind::F64 => ScalarValue::F64(0.0),
+            MathicValueKind::Bool => ScalarValue::Bool(false),
+        }
+    }
+}
+
+impl TryFrom<MathicValue> for ScalarValue {
+    type Error = String;
+
+    /// Fails if the kind is unknown, or the boolean is neither 0 nor 1.
+    fn try_from(value: MathicValue) -> Result<Self, Self::Error> {
+        let kind = MathicValueKind::try_from(value.kind)?;
+
+        // The field read is the one named by the kind.
+        Ok(unsafe {
+            match kind {
+                MathicValueKind::I8 => ScalarValue::I8(value.data.i8),
+                MathicValueKind::I16 => ScalarValue::I16(value.data.i16),
+                MathicValueKind::I32 => ScalarValue::I32(value.data.i32),
+                MathicValueKind::I64 => ScalarValue::I64(value.data.i64),
+                MathicValueKind::Isize => ScalarValue::Isize(value.data.isize),
+                MathicValueKind::U8 => ScalarValue::U8(value.data.u8),
+                MathicValueKind::U16 => ScalarValue::U16(value.data.u16),
+                MathicValueKind::U32 => ScalarValue::U32(value.data.u32),
+                MathicValueKind::U64 => ScalarValue::U64(value.data.u64),
+                MathicValueKind::Usize => ScalarValue::Usize(value.data.usize),
+                MathicValueKind::F32 => ScalarValue::F32(value.data.f32),
+                MathicValueKind::F64 => ScalarValue::F64(value.data.f64),
+                MathicValueKind::Bool => match value.data.boolean {
+                    0 => ScalarValue::Bool(false),
+                    1 => ScalarValue::Bool(true),
+                    other => return Err(format!("{other} isn't a boolean, use 0 or 1")),
+                },
+            }
+        })
+    }
+}
+
+impl TryFrom<ScalarValue> for MathicValue {
+    type Error = ScalarValue;
+
+    /// Fails for 128-bit integers, which C has no standard type for.
+    fn try_from(value: ScalarValue) -> Result<Self, Self::Error> {
+        let (kind, data) = match value {
+            ScalarValue::I8(i8) => (MathicValueKind::I8, MathicValueData { i8 }),
+            ScalarValue::I16(i16) => (MathicValueKind::I16, MathicValueData { i16 }),
+            ScalarValue::I32(i32) => (MathicValueKind::I32, MathicValueData { i32 }),
+            ScalarValue::I64(i64) => (MathicValueKind::I64, MathicValueData { i64 }),
+            ScalarValue::Isize(isize) => (MathicValueKind::Isize, MathicValueData { isize }),
+            ScalarValue::U8(u8) => (MathicValueKind::U8, MathicValueData { u8 }),
+            ScalarValue::U16(u16) => (MathicValueKind::U16, MathicValueData { u16 }),
+            ScalarValue::U32(u32) => (MathicValueKind::U32, MathicValueData { u32 }),
+            ScalarValue::U64(u64) => (MathicValueKind::U64, MathicValueData { u64 }),
+            ScalarValue::Usize(usize) => (MathicValueKind::Usize, MathicValueData { usize }),
+            ScalarValue::F32(f32) => (MathicValueKind::F32, MathicValueData { f32 }),
+            ScalarValue::F64(f64) => (MathicValueKind::F64, MathicValueData { f64 }),
+            ScalarValue::Bool(boolean) => (
+                MathicValueKind::Bool,
+                MathicValueData {
+                    boolean: boolean.into(),
+                },
+            ),
+            ScalarValue::I128(_) | ScalarValue::U128(_) => return Err(value),
+        };
+
+        Ok(Self {
+            kind: kind as u32,
+            data,
+        })
+    }
+}
+
+/// Loads a program into a new JIT. The program is consumed, even if this
+/// fails. On success, `*executor` is set to the JIT, free it with
+/// `mathic_executor_free`.
+///
+/// # Safety
+///
+/// `program` must be returned by a compilation, and neither freed nor loaded
+/// yet. `executor` must be a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_new(
+    program: *mut MathicProgram,
+    executor: *mut *mut MathicExecutor,
+) -> MathicStatus {
+    if program.is_null() {
+        return fail(MathicStatus::InvalidArgument, "program is null");
+    }
+
+    let program = unsafe { *Box::from_raw(program) };
+
+    if executor.is_null() {
+        return fail(MathicStatus::InvalidArgument, "executor is null");
+    }
+
+    match MathicJITExecutor::new(program.modules, &program.options) {
+        Ok(inner) => {
+            unsafe {
+                *executor = Box::into_raw(Box::new(MathicExecutor { inner }));
+            }
+
+            MathicStatus::Ok
+        }
+        Err(e) => fail(MathicStatus::Error, e),
+    }
+}
+
+/// Frees an executor.
+///
+/// # Safety
+///
+/// `executor` must be null or returned by `mathic_executor_new`, and not
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_free(executor: *mut MathicExecutor) {
+    if !executor.is_null() {
+        drop(unsafe { Box::from_raw(executor) });
+    }
+}
+
+/// Returns the address of a function, named after its module, e.g.
+/// `program::f`, or null if there's no such function. It is valid while the
+/// executor lives, and must be cast to the function's exact C signature.
+///
+/// # Safety
+///
+/// `executor` must be a live executor and `function_name` a NUL-terminated
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_lookup_function(
+    executor: *const MathicExecutor,
+    function_name: *const c_char,
+) -> *mut c_void {
+    match unsafe { str_arg(function_name, "function_name") } {
+        Ok(function_name) => unsafe {
+            lookup_symbol(executor, &format!("mathic__{function_name}"))
+        },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Returns the address of a symbol of the JIT, or null if it isn't defined.
+///
+/// # Safety
+///
+/// `executor` must be a live executor and `symbol` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_lookup_symbol(
+    executor: *const MathicExecutor,
+    symbol: *const c_char,
+) -> *mut c_void {
+    match unsafe { str_arg(symbol, "symbol") } {
+        Ok(symbol) => unsafe { lookup_symbol(executor, symbol) },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Calls a function with the given arguments, checked against its
+/// signature, and writes its result to `*result`. Only functions taking and
+/// returning numbers and booleans can be called this way.
+///
+/// # Safety
+///
+/// `executor` must be a live executor, `function_name` a NUL-terminated
+/// string, `args` point to `arg_count` values (or be null if there are none)
+/// and `result` be a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_call(
+    executor: *const MathicExecutor,
+    function_name: *const c_char,
+    args: *const MathicValue,
+    arg_count: usize,
+    result: *mut MathicValue,
+) -> MathicStatus {
+    let Some(executor) = (unsafe { executor.as_ref() }) else {
+        return fail(MathicStatus::InvalidArgument, "executor is null");
+    };
+    let function_name = match unsafe { str_arg(function_name, "function_name") } {
+        Ok(function_name) => function_name,
+        Err(status) => return status,
+    };
+
+    if result.is_null() {
+        return fail(MathicStatus::InvalidArgument, "result is null");
+    }
+
+    let args = if arg_count == 0 {
+        Vec::new()
+    } else if args.is_null() {
+        return fail(MathicStatus::InvalidArgument, "args is null");
+    } else {
+        let args = unsafe { slice::from_raw_parts(args, arg_count) }
+            .iter()
+            .enumerate()
+            .map(|(idx, arg)| {
+                ScalarValue::try_from(*arg).map_err(|e| format!("argument {idx}: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>();
+
+        match args {
+            Ok(args) => args,
+            Err(e) => return fail(MathicStatus::InvalidArgument, e),
+        }
+    };
+
+    // The result must fit a `MathicValue`, which is checked before running
+    // anything.
+    if let Some(signature) = executor.inner.signature(function_name)
+        && let Some(Err(_)) = ScalarValue::zero(signature.return_ty).map(MathicValue::try_from)
+    {
+        return fail(
+            MathicStatus::InvalidArgument,
+            format!(
+                "function '{function_name}' returns {}, which C has no type for",
+                signature.return_ty
+            ),
+        );
+    }
+
+    let value = match executor.inner.call_dynamic(function_name, &args) {
+        Ok(value) => value,
+        Err(e @ (CodegenError::FunctionNotFound(_) | CodegenError::SignatureMismatch { .. })) => {
+            return fail(MathicStatus::InvalidArgument, e);
+        }
+        Err(e) => return fail(MathicStatus::Error, e),
+    };
+
+    match MathicValue::try_from(value) {
+        Ok(value) => {
+            unsafe {
+                *result = value;
+            }
+
+            MathicStatus::Ok
+        }
+        Err(value) => fail(
+            MathicStatus::Error,
+            format!("a {} can't be returned to C", value.mathic_type()),
+        ),
+    }
+}
+
+/// Provides the implementation of a function declared with `extern df`,
+/// named after its module, e.g. `host::log`. `param_kinds` and
+/// `return_kind` are `MathicValueKind`s, which are checked against the
+/// declaration. A host function must be registered before calling any
+/// function which may call it.
+///
+/// # Safety
+///
+/// `executor` must be a live executor, `function_name` a NUL-terminated
+/// string, `param_kinds` point to `param_count` kinds (or be null if there
+/// are none), and `host_fn` be a function with the C signature given by the
+/// kinds, e.g. `int64_t log(int64_t)`. Booleans are passed as `bool`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mathic_executor_register_host_fn(
+    executor: *const MathicExecutor,
+    function_name: *const c_char,
+    param_kinds: *const u32,
+    param_count: usize,
+    return_kind: u32,
+    host_fn: *const c_void,
+) -> MathicStatus {
+    let Some(executor) = (unsafe { executor.as_ref() }) else {
+        return fail(MathicStatus::InvalidArgument, "executor is null");
+    };
+    let function_name = match unsafe { str_arg(function_name, "function_name") } {
+        Ok(function_name) => function_name,
+        Err(status) => return status,
+    };
+
+    if host_fn.is_null() {
+        return fail(MathicStatus::InvalidArgument, "host_fn is null");
+    }
+
+    let param_kinds = if param_count == 0 {
+        &[]
+    } else if param_kinds.is_null() {
+        return fail(MathicStatus::InvalidArgument, "param_kinds is null");
+    } else {
+        unsafe { slice::from_raw_parts(param_kinds, param_count) }
+    };
+
+    let kind_type =
+        |kind: u32| MathicValueKind::try_from(kind).map(|kind| kind.zero().mathic_type());
+    let signature = param_kinds
+        .iter()
+        .map(|kind| kind_type(*kind))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|params| {
+            Ok(FunctionSignature {
+                params,
+                return_ty: kind_type(return_kind)?,
+            })
+        });
+    let signature = match signature {
+        Ok(signature) => signature,
+        Err(e) => return fail(MathicStatus::InvalidArgument, e),
+    };
+
+    // The caller vouches for the function having the signature given.
+    match unsafe {
+        executor
+            .inner
+            .register_host_fn_dynamic(function_name, signature, host_fn.cast())
+    } {
+        Ok(()) => MathicStatus::Ok,
+        Err(e @ (CodegenError::FunctionNotFound(_) | CodegenError::SignatureMismatch { .. })) => {
+            fail(MathicStatus::InvalidArgument, e)
+        }
+        Err(e) => fail(MathicStatus::Error, e),
+    }
+}
+
+/// Looks up a symbol of the executor's JIT.
+///
+/// # Safety
+///
+/// `executor` must be null or a live executor.
+unsafe fn lookup_symbol(executor: *const MathicExecutor, symbol: &str) -> *mut c_void {
+    unsafe { executor.as_ref() }
+        .and_then(|executor| executor.inner.lookup_symbol(symbol))
+        .map_or(ptr::null_mut(), |ptr| ptr.cast())
+}
@@ -0,0 +1,69 @@
+//! The C API of the Mathic compiler.
+//!
+//! `include/mathic.h` is generated from this crate with cbindgen, run `make
+//! capi-header` after changing any exported item.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char},
+    ptr,
+};
+
+mod compiler;
+mod diagnostics;
+mod executor;
+
+/// The result of a function which may fail.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathicStatus {
+    Ok = 0,
+    /// The program has errors, which are listed by the compiler's
+    /// diagnostics.
+    CompilationFailed,
+    /// A pointer was null, a string wasn't valid UTF-8, or a value doesn't
+    /// fit the function called. See `mathic_last_error`.
+    InvalidArgument,
+    /// Any other failure. See `mathic_last_error`.
+    Error,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Returns the message of the last failure in the calling thread, or null if
+/// there was none. It is valid until the next failing call.
+#[unsafe(no_mangle)]
+pub extern "C" fn mathic_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| error.as_ref().map_or(ptr::null(), |error| error.as_ptr()))
+}
+
+/// Records the message of a failure and returns its status.
+fn fail(status: MathicStatus, message: impl ToString) -> MathicStatus {
+    LAST_ERROR.set(Some(c_string(&message.to_string())));
+    status
+}
+
+/// Converts a string for C, dropping any NUL in it.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+/// Reads a string argument.
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, MathicStatus> {
+    if s.is_null() {
+        return Err(fail(
+            MathicStatus::InvalidArgument,
+            format!("{name} is null"),
+        ));
+    }
+
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|_| fail(MathicStatus::InvalidArgument, format!("{name} isn't UTF-8")))
+}
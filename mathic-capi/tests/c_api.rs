@@ -0,0 +1,40 @@
+use std::{env, path::PathBuf, process::Command};
+
+/// Returns the directory holding the crate's libraries, which is the parent
+/// of the `deps` directory holding this test.
+fn library_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+
+    deps.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn test_c_smoke() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let library_dir = library_dir();
+    let binary = env::temp_dir().join(format!("mathic-capi-smoke-{}", std::process::id()));
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests/smoke.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-lmathic_capi")
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "smoke.c didn't compile");
+
+    let output = Command::new(&binary).output().unwrap();
+    let _ = std::fs::remove_file(&binary);
+
+    assert!(
+        output.status.success(),
+        "smoke test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,74 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "mathic.h"
+
+static const char *SOURCE =
+    "extern df scale(x: f64) f64;\n"
+    "\n"
+    "df add(a: i64, b: i64) i64 {\n"
+    "    return a + b;\n"
+    "}\n"
+    "\n"
+    "df compute(x: f64) f64 {\n"
+    "    return scale(x) + 1.0;\n"
+    "}\n";
+
+static double scale(double x) { return x * 2.0; }
+
+#define CHECK(cond)                                                       \
+  do {                                                                    \
+    if (!(cond)) {                                                        \
+      const char *error = mathic_last_error();                            \
+      fprintf(stderr, "%s:%d: %s: %s\n", __FILE__, __LINE__, #cond,       \
+              error ? error : "no error");                                \
+      return 1;                                                           \
+    }                                                                     \
+  } while (0)
+
+int main(void) {
+  MathicCompiler *compiler = mathic_compiler_new();
+  CHECK(compiler != NULL);
+
+  MathicProgram *program = NULL;
+  CHECK(mathic_compile_source(compiler, SOURCE, 0, &program) == MATHIC_STATUS_OK);
+
+  MathicExecutor *executor = NULL;
+  CHECK(mathic_executor_new(program, &executor) == MATHIC_STATUS_OK);
+
+  uint32_t scale_params[] = {MATHIC_VALUE_KIND_F64};
+  CHECK(mathic_executor_register_host_fn(executor, "program::scale", scale_params, 1,
+                                         MATHIC_VALUE_KIND_F64,
+                                         (const void *)scale) == MATHIC_STATUS_OK);
+
+  MathicValue args[2];
+  MathicValue result;
+
+  args[0].kind = MATHIC_VALUE_KIND_I64;
+  args[0].data.i64 = 2;
+  args[1].kind = MATHIC_VALUE_KIND_I64;
+  args[1].data.i64 = 40;
+  CHECK(mathic_executor_call(executor, "program::add", args, 2, &result) == MATHIC_STATUS_OK);
+  CHECK(result.kind == MATHIC_VALUE_KIND_I64 && result.data.i64 == 42);
+
+  args[0].kind = MATHIC_VALUE_KIND_F64;
+  args[0].data.f64 = 1.5;
+  CHECK(mathic_executor_call(executor, "program::compute", args, 1, &result) == MATHIC_STATUS_OK);
+  CHECK(result.kind == MATHIC_VALUE_KIND_F64 && result.data.f64 == 4.0);
+
+  /* Values which don't fit the signature are rejected before the call. */
+  args[0].kind = MATHIC_VALUE_KIND_I64;
+  args[0].data.i64 = 1;
+  CHECK(mathic_executor_call(executor, "program::compute", args, 1, &result) ==
+        MATHIC_STATUS_INVALID_ARGUMENT);
+
+  args[0].kind = 1000;
+  CHECK(mathic_executor_call(executor, "program::compute", args, 1, &result) ==
+        MATHIC_STATUS_INVALID_ARGUMENT);
+  CHECK(strstr(mathic_last_error(), "argument 0") != NULL);
+
+  mathic_executor_free(executor);
+  mathic_compiler_free(compiler);
+
+  return 0;
+}
@@ -0,0 +1,19 @@
+use std::io;
+
+use lsp_server::{Message, ProtocolError};
+use mathic::MathicError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LspError {
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("failed to send a message to the client: {0}")]
+    Send(#[from] crossbeam_channel::SendError<Message>),
+    #[error(transparent)]
+    MathicError(#[from] MathicError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
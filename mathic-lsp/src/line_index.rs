@@ -0,0 +1,60 @@
+use lsp_types::{Position, Range};
+use mathic::Span;
+
+/// Converts byte offsets of a source into LSP positions, whose columns are
+/// counted in UTF-16 code units, and back.
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = self
+            .line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        let line_start = self.line_starts[line];
+
+        let character = self.source[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return self.source.len();
+        };
+
+        let mut character = 0;
+
+        for (idx, c) in self.source[line_start..].char_indices() {
+            if character >= position.character as usize || c == '\n' {
+                return line_start + idx;
+            }
+
+            character += c.len_utf16();
+        }
+
+        self.source.len()
+    }
+}
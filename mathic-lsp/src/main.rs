@@ -0,0 +1,45 @@
+use lsp_server::Connection;
+use lsp_types::{
+    CompletionOptions, HoverProviderCapability, OneOf, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+use crate::{error::LspError, server::Server};
+
+mod error;
+mod line_index;
+mod server;
+
+fn main() -> Result<(), LspError> {
+    // Stdout is used to talk to the client.
+    tracing::subscriber::set_global_default(
+        FmtSubscriber::builder()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_writer(std::io::stderr)
+            .finish(),
+    )
+    .expect("Failed to set global suscriber");
+
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    tracing::info!("Server initialized");
+    Server::new(connection).run()?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,374 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionResponse, Diagnostic, DiagnosticSeverity,
+    GotoDefinitionResponse, Hover, HoverContents, Location, MarkupContent, MarkupKind,
+    NumberOrString, Position, PublishDiagnosticsParams, Url,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as LspRequest},
+};
+use mathic::{
+    Span,
+    analysis::{self, Analysis, CompletionKind},
+    diagnostics::{Diagnostic as MathicDiagnostic, Severity},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{error::LspError, line_index::LineIndex};
+
+/// An open source file.
+struct Document {
+    text: String,
+    analysis: Analysis,
+    /// The latest analysis of the document which parsed. Code being typed
+    /// often doesn't, so completions are taken from here.
+    parsed_analysis: Option<Analysis>,
+}
+
+pub struct Server {
+    connection: Connection,
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            documents: HashMap::new(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<(), LspError> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), LspError> {
+        let id = request.id.clone();
+
+        match request.method.as_str() {
+            HoverRequest::METHOD => {
+                let Some(params) = self
+                    .request_params::<<HoverRequest as LspRequest>::Params>(&id, request.params)?
+                else {
+                    return Ok(());
+                };
+                let position = params.text_document_position_params;
+
+                let hover = self.hover(&position.text_document.uri, position.position);
+                self.respond(id, hover)
+            }
+            GotoDefinition::METHOD => {
+                let Some(params) = self.request_params::<<GotoDefinition as LspRequest>::Params>(
+                    &id,
+                    request.params,
+                )?
+                else {
+                    return Ok(());
+                };
+                let position = params.text_document_position_params;
+
+                let definition = self.definition(&position.text_document.uri, position.position);
+                self.respond(id, definition)
+            }
+            Completion::METHOD => {
+                let Some(params) =
+                    self.request_params::<<Completion as LspRequest>::Params>(&id, request.params)?
+                else {
+                    return Ok(());
+                };
+                let position = params.text_document_position;
+
+                let completions = self.completions(&position.text_document.uri, position.position);
+                self.respond(id, completions)
+            }
+            _ => {
+                let response = Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unhandled request: {}", request.method),
+                );
+
+                self.connection.sender.send(Message::Response(response))?;
+
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), LspError> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = notification_params::<
+                    <DidOpenTextDocument as LspNotification>::Params,
+                >(&notification.method, notification.params) else {
+                    return Ok(());
+                };
+
+                self.update(params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(mut params) = notification_params::<
+                    <DidChangeTextDocument as LspNotification>::Params,
+                >(&notification.method, notification.params) else {
+                    return Ok(());
+                };
+
+                // Documents are synchronized in full, the last change holds
+                // the whole text.
+                match params.content_changes.pop() {
+                    Some(change) => self.update(params.text_document.uri, change.text),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = notification_params::<
+                    <DidCloseTextDocument as LspNotification>::Params,
+                >(&notification.method, notification.params) else {
+                    return Ok(());
+                };
+
+                self.documents.remove(&params.text_document.uri);
+                self.publish_diagnostics(params.text_document.uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Analyzes the new text of a document and publishes its diagnostics.
+    fn update(&mut self, uri: Url, text: String) -> Result<(), LspError> {
+        let Ok(file_path) = uri.to_file_path() else {
+            tracing::debug!("Ignoring non-file document: {uri}");
+            return Ok(());
+        };
+
+        // Imports being typed may point to files which can't be read, which
+        // is reported on the document instead of stopping the server.
+        let analysis = match analysis::analyze(&src_root(&file_path), &file_path, &text) {
+            Ok(analysis) => analysis,
+            Err(e) => {
+                tracing::warn!("Failed to analyze {uri}: {e}");
+
+                self.documents.remove(&uri);
+
+                let diagnostic = Diagnostic {
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    source: Some("mathic".to_string()),
+                    message: format!("failed to analyze the document: {e}"),
+                    ..Default::default()
+                };
+
+                return self.publish_diagnostics(uri, vec![diagnostic]);
+            }
+        };
+
+        let line_index = LineIndex::new(&text);
+        let diagnostics = analysis
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| to_lsp_diagnostic(&line_index, diagnostic))
+            .collect();
+
+        let previous = self.documents.remove(&uri);
+        let parsed_analysis = if analysis.is_parsed() {
+            None
+        } else {
+            previous.and_then(|document| {
+                if document.analysis.is_parsed() {
+                    Some(document.analysis)
+                } else {
+                    document.parsed_analysis
+                }
+            })
+        };
+
+        self.documents.insert(
+            uri.clone(),
+            Document {
+                text,
+                analysis,
+                parsed_analysis,
+            },
+        );
+
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn hover(&self, uri: &Url, position: Position) -> Option<Hover> {
+        let document = self.documents.get(uri)?;
+        let line_index = LineIndex::new(&document.text);
+
+        let hover = document.analysis.hover(line_index.offset(position))?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```mathic\n{}\n```", hover.contents),
+            }),
+            range: Some(line_index.range(hover.span)),
+        })
+    }
+
+    fn definition(&self, uri: &Url, position: Position) -> Option<GotoDefinitionResponse> {
+        let document = self.documents.get(uri)?;
+        let offset = LineIndex::new(&document.text).offset(position);
+
+        let location = document.analysis.definition(offset)?;
+        let target_uri = Url::from_file_path(&location.path).ok()?;
+
+        // The definition may live in a file which isn't open.
+        let target_text = match self.documents.get(&target_uri) {
+            Some(target) => target.text.clone(),
+            None => fs::read_to_string(&location.path).ok()?,
+        };
+
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: target_uri,
+            range: LineIndex::new(&target_text).range(location.span),
+        }))
+    }
+
+    fn completions(&self, uri: &Url, position: Position) -> Option<CompletionResponse> {
+        let document = self.documents.get(uri)?;
+        let analysis = document
+            .parsed_analysis
+            .as_ref()
+            .unwrap_or(&document.analysis);
+
+        let offset = LineIndex::new(&document.text).offset(position);
+
+        let items = analysis
+            .completions(&document.text, offset)
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: Some(match completion.kind {
+                    CompletionKind::Local => CompletionItemKind::VARIABLE,
+                    CompletionKind::Function => CompletionItemKind::FUNCTION,
+                    CompletionKind::Struct => CompletionItemKind::STRUCT,
+                    CompletionKind::Field => CompletionItemKind::FIELD,
+                }),
+                detail: Some(completion.detail),
+                ..Default::default()
+            })
+            .collect();
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> Result<(), LspError> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+
+        Ok(())
+    }
+
+    /// Parses the parameters of a request. Malformed ones are answered with
+    /// an `InvalidParams` error, and `None` is returned.
+    fn request_params<P: DeserializeOwned>(
+        &self,
+        id: &RequestId,
+        params: serde_json::Value,
+    ) -> Result<Option<P>, LspError> {
+        match serde_json::from_value(params) {
+            Ok(params) => Ok(Some(params)),
+            Err(e) => {
+                let response = Response::new_err(
+                    id.clone(),
+                    ErrorCode::InvalidParams as i32,
+                    format!("invalid params: {e}"),
+                );
+
+                self.connection.sender.send(Message::Response(response))?;
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn respond(&self, id: RequestId, result: impl serde::Serialize) -> Result<(), LspError> {
+        self.connection
+            .sender
+            .send(Message::Response(Response::new_ok(id, result)))?;
+
+        Ok(())
+    }
+}
+
+/// Returns the directory imports are resolved from: the `src/` directory of
+/// the project containing the file, or the file's own directory if there's
+/// no project.
+fn src_root(file_path: &Path) -> PathBuf {
+    let parent = file_path.parent().unwrap_or(Path::new("/"));
+
+    parent
+        .ancestors()
+        .find(|dir| dir.join("Mathic.toml").is_file())
+        .map(|project| project.join("src"))
+        .filter(|src| file_path.starts_with(src))
+        .unwrap_or_else(|| parent.to_path_buf())
+}
+
+fn to_lsp_diagnostic(line_index: &LineIndex, diagnostic: &MathicDiagnostic) -> Diagnostic {
+    let message = if diagnostic.help.is_empty() {
+        format!("{}: {}", diagnostic.title, diagnostic.message)
+    } else {
+        format!(
+            "{}: {}\nhelp: {}",
+            diagnostic.title, diagnostic.message, diagnostic.help
+        )
+    };
+
+    Diagnostic {
+        range: line_index.range(diagnostic.span.unwrap_or(Span::from(0..0))),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        code: diagnostic
+            .code
+            .map(|code| NumberOrString::String(code.to_string())),
+        source: Some("mathic".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Parses the parameters of a notification. Malformed ones can't be answered,
+/// so the notification is ignored.
+fn notification_params<P: DeserializeOwned>(method: &str, params: serde_json::Value) -> Option<P> {
+    serde_json::from_value(params)
+        .inspect_err(|e| tracing::debug!("Ignoring malformed '{method}' notification: {e}"))
+        .ok()
+}
@@ -0,0 +1,137 @@
+use std::{
+    fs,
+    io::BufReader,
+    path::Path,
+    process::{ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use lsp_server::{Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    DiagnosticSeverity, DidOpenTextDocumentParams, HoverParams, InitializeParams,
+    InitializedParams, Position, PublishDiagnosticsParams, TextDocumentIdentifier,
+    TextDocumentItem, TextDocumentPositionParams, Url,
+    notification::{
+        DidOpenTextDocument, Exit, Initialized, Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{HoverRequest, Initialize, Request as LspRequest, Shutdown},
+};
+
+fn send(stdin: &mut ChildStdin, message: impl Into<Message>) {
+    message
+        .into()
+        .write(stdin)
+        .expect("failed to send a message to the server");
+}
+
+fn recv(stdout: &mut BufReader<ChildStdout>) -> Message {
+    Message::read(stdout)
+        .expect("failed to read a message from the server")
+        .expect("the server closed the connection")
+}
+
+/// Reads messages until the response to the request `id`.
+fn response(stdout: &mut BufReader<ChildStdout>, id: i32) -> Response {
+    loop {
+        if let Message::Response(response) = recv(stdout)
+            && response.id == RequestId::from(id)
+        {
+            return response;
+        }
+    }
+}
+
+#[test]
+fn test_unreadable_import() {
+    let project = Path::new(env!("CARGO_TARGET_TMPDIR")).join("unreadable_import");
+    fs::create_dir_all(&project).unwrap();
+
+    // Not valid UTF-8, so the import can't be read.
+    fs::write(project.join("broken.mth"), [0xff, 0xfe, 0xfd]).unwrap();
+
+    let main_path = project.join("main.mth");
+    let text = "imp broken::value;\n\ndf main() i32 {\n    return 0;\n}\n";
+    fs::write(&main_path, text).unwrap();
+
+    let uri = Url::from_file_path(&main_path).unwrap();
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_mathic-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start the server");
+    let mut stdin = server.stdin.take().unwrap();
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+
+    send(
+        &mut stdin,
+        Request::new(
+            1.into(),
+            Initialize::METHOD.to_string(),
+            InitializeParams::default(),
+        ),
+    );
+    response(&mut stdout, 1);
+    send(
+        &mut stdin,
+        Notification::new(Initialized::METHOD.to_string(), InitializedParams {}),
+    );
+
+    send(
+        &mut stdin,
+        Notification::new(
+            DidOpenTextDocument::METHOD.to_string(),
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "mathic".to_string(),
+                    version: 1,
+                    text: text.to_string(),
+                },
+            },
+        ),
+    );
+
+    let diagnostics = loop {
+        if let Message::Notification(notification) = recv(&mut stdout)
+            && notification.method == PublishDiagnostics::METHOD
+        {
+            let params: PublishDiagnosticsParams =
+                serde_json::from_value(notification.params).unwrap();
+
+            break params.diagnostics;
+        }
+    };
+
+    assert!(
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR)),
+        "{diagnostics:?}"
+    );
+
+    // The server keeps answering.
+    send(
+        &mut stdin,
+        Request::new(
+            2.into(),
+            HoverRequest::METHOD.to_string(),
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: Position::new(2, 4),
+                },
+                work_done_progress_params: Default::default(),
+            },
+        ),
+    );
+    assert!(response(&mut stdout, 2).error.is_none());
+
+    send(
+        &mut stdin,
+        Request::new(3.into(), Shutdown::METHOD.to_string(), ()),
+    );
+    response(&mut stdout, 3);
+    send(&mut stdin, Notification::new(Exit::METHOD.to_string(), ()));
+
+    assert!(server.wait().unwrap().success());
+}
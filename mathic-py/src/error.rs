@@ -0,0 +1,71 @@
+use mathic::{
+    MathicError,
+    diagnostics::{CodegenError, DiagnosticsManager, JsonDiagnostic, SourceFiles},
+};
+use pyo3::{
+    PyErr, create_exception,
+    exceptions::{PyException, PyKeyError, PyRuntimeError, PyTypeError},
+};
+
+create_exception!(
+    mathic,
+    CompileError,
+    PyException,
+    "Raised when a program fails to compile. The message lists every error."
+);
+
+/// Converts an error of the compiler. The errors it reported are read from
+/// its diagnostics, against `source` when the program isn't in a file.
+pub fn compile_error(
+    e: MathicError,
+    diagnostics: &DiagnosticsManager,
+    source: Option<&str>,
+) -> PyErr {
+    match e {
+        MathicError::CompilationFailed => {
+            let mut sources = SourceFiles::new();
+
+            let messages = diagnostics.with_errors(|errors| {
+                errors
+                    .iter()
+                    .map(|(file_path, error)| {
+                        let source = match source {
+                            Some(source) => Some(source),
+                            None => sources.get(file_path),
+                        };
+
+                        error_message(JsonDiagnostic::new(file_path, error.diagnostic(), source))
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            match messages {
+                Ok(messages) if !messages.is_empty() => CompileError::new_err(messages.join("\n")),
+                Ok(_) => CompileError::new_err(e.to_string()),
+                Err(e) => PyRuntimeError::new_err(e.to_string()),
+            }
+        }
+        MathicError::Io(e) => e.into(),
+        e => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+/// Converts an error of a call.
+pub fn call_error(e: CodegenError) -> PyErr {
+    match e {
+        CodegenError::FunctionNotFound(_) => PyKeyError::new_err(e.to_string()),
+        CodegenError::SignatureMismatch { .. } => PyTypeError::new_err(e.to_string()),
+        e => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+/// Formats an error as `file:line:column: title: message`.
+fn error_message(diagnostic: JsonDiagnostic) -> String {
+    let location = diagnostic
+        .span
+        .and_then(|span| span.line.zip(span.column))
+        .map(|(line, column)| format!("{}:{line}:{column}", diagnostic.file))
+        .unwrap_or(diagnostic.file);
+
+    format!("{location}: {}: {}", diagnostic.title, diagnostic.message)
+}
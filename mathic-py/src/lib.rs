@@ -0,0 +1,30 @@
+use pyo3::prelude::*;
+
+use crate::{
+    error::CompileError,
+    program::Program,
+    symbolic::{Expr, Kernel},
+};
+
+mod error;
+mod program;
+mod symbolic;
+
+/// The `mathic` Python module.
+#[pymodule]
+#[pyo3(name = "mathic")]
+fn mathic_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("CompileError", m.py().get_type::<CompileError>())?;
+
+    m.add_class::<Program>()?;
+    m.add_function(wrap_pyfunction!(program::compile_source, m)?)?;
+    m.add_function(wrap_pyfunction!(program::compile_project, m)?)?;
+
+    m.add_class::<Expr>()?;
+    m.add_class::<Kernel>()?;
+    m.add_function(wrap_pyfunction!(symbolic::symbol, m)?)?;
+    m.add_function(wrap_pyfunction!(symbolic::symbols, m)?)?;
+    m.add_function(wrap_pyfunction!(symbolic::lambdify, m)?)?;
+
+    Ok(())
+}
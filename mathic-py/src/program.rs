@@ -0,0 +1,178 @@
+use std::path::PathBuf;
+
+use mathic::{
+    MathicModule, MathicResult,
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::CodegenError,
+    executor::{MathicExecutor, ScalarValue, jit::MathicJITExecutor},
+};
+use pyo3::{
+    IntoPyObjectExt,
+    exceptions::{PyRuntimeError, PyTypeError},
+    prelude::*,
+    types::PyTuple,
+};
+
+use crate::error::{call_error, compile_error};
+
+/// A compiled program, whose functions are called through the JIT.
+///
+/// Functions are named after their module, e.g. `program::f` for sources
+/// compiled with `compile_source`.
+#[pyclass(module = "mathic", unsendable)]
+pub struct Program {
+    // Dropped before the compiler, whose context it was built with.
+    executor: MathicJITExecutor,
+    _compiler: MathicCompiler,
+}
+
+impl Program {
+    /// Compiles a program and loads its modules into a JIT. `source` is the
+    /// program's source when it isn't in a file, errors are reported against
+    /// it.
+    pub fn compile(
+        opt_level: u8,
+        source: Option<&str>,
+        compile: impl for<'c> FnOnce(
+            &'c MathicCompiler,
+            &CompilerOpts,
+        ) -> MathicResult<Vec<MathicModule<'c>>>,
+    ) -> PyResult<Self> {
+        let compiler = MathicCompiler::new().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        let opts = CompilerOpts {
+            opt_lvl: opt_level.into(),
+            ..Default::default()
+        };
+
+        let modules = compile(&compiler, &opts)
+            .map_err(|e| compile_error(e, compiler.diagnostics(), source))?;
+        let executor = MathicJITExecutor::new(modules, &opts).map_err(call_error)?;
+
+        Ok(Self {
+            executor,
+            _compiler: compiler,
+        })
+    }
+
+    /// Calls a function, converting each argument to the type of its
+    /// parameter.
+    pub fn call_with(
+        &self,
+        py: Python<'_>,
+        name: &str,
+        args: &Bound<'_, PyTuple>,
+    ) -> PyResult<PyObject> {
+        let signature = self
+            .executor
+            .signature(name)
+            .ok_or_else(|| call_error(CodegenError::FunctionNotFound(name.to_string())))?;
+
+        if args.len() != signature.params.len() {
+            return Err(PyTypeError::new_err(format!(
+                "'{name}' takes {} arguments but {} were given",
+                signature.params.len(),
+                args.len()
+            )));
+        }
+
+        let args = signature
+            .params
+            .iter()
+            .zip(args.iter())
+            .map(|(ty, arg)| {
+                let zero = ScalarValue::zero(*ty).ok_or_else(|| {
+                    PyTypeError::new_err(format!(
+                        "'{name}' is {signature}, only numbers and booleans can be passed"
+                    ))
+                })?;
+
+                extract_scalar(&arg, zero)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let result = self
+            .executor
+            .call_dynamic(name, &args)
+            .map_err(call_error)?;
+
+        scalar_into_py(py, result)
+    }
+}
+
+#[pymethods]
+impl Program {
+    /// Calls a function with the given arguments, e.g.
+    /// `program.call("program::f", 1, 2.5)`.
+    #[pyo3(signature = (name, *args))]
+    fn call(&self, py: Python<'_>, name: &str, args: &Bound<'_, PyTuple>) -> PyResult<PyObject> {
+        self.call_with(py, name, args)
+    }
+
+    /// Returns the signature of a function, e.g. `df(i32, f64) f64`, or
+    /// `None` if there's no such function.
+    fn signature(&self, name: &str) -> Option<String> {
+        self.executor
+            .signature(name)
+            .map(|signature| signature.to_string())
+    }
+}
+
+/// Compiles a program made of a single source. Its functions are in the
+/// `program` module.
+#[pyfunction]
+#[pyo3(signature = (source, opt_level = 2))]
+pub fn compile_source(source: &str, opt_level: u8) -> PyResult<Program> {
+    Program::compile(opt_level, Some(source), |compiler, opts| {
+        Ok(vec![compiler.compile_source(source, None, opts)?])
+    })
+}
+
+/// Compiles a project, given its source root, which holds `main.mth`.
+#[pyfunction]
+#[pyo3(signature = (src_root, opt_level = 2))]
+pub fn compile_project(src_root: PathBuf, opt_level: u8) -> PyResult<Program> {
+    Program::compile(opt_level, None, |compiler, opts| {
+        compiler.compile_project(&src_root, opts)
+    })
+}
+
+/// Converts a Python value to the type of `zero`.
+fn extract_scalar(value: &Bound<'_, PyAny>, zero: ScalarValue) -> PyResult<ScalarValue> {
+    Ok(match zero {
+        ScalarValue::Isize(_) => ScalarValue::Isize(value.extract()?),
+        ScalarValue::I8(_) => ScalarValue::I8(value.extract()?),
+        ScalarValue::I16(_) => ScalarValue::I16(value.extract()?),
+        ScalarValue::I32(_) => ScalarValue::I32(value.extract()?),
+        ScalarValue::I64(_) => ScalarValue::I64(value.extract()?),
+        ScalarValue::I128(_) => ScalarValue::I128(value.extract()?),
+        ScalarValue::Usize(_) => ScalarValue::Usize(value.extract()?),
+        ScalarValue::U8(_) => ScalarValue::U8(value.extract()?),
+        ScalarValue::U16(_) => ScalarValue::U16(value.extract()?),
+        ScalarValue::U32(_) => ScalarValue::U32(value.extract()?),
+        ScalarValue::U64(_) => ScalarValue::U64(value.extract()?),
+        ScalarValue::U128(_) => ScalarValue::U128(value.extract()?),
+        ScalarValue::F32(_) => ScalarValue::F32(value.extract()?),
+        ScalarValue::F64(_) => ScalarValue::F64(value.extract()?),
+        ScalarValue::Bool(_) => ScalarValue::Bool(value.extract()?),
+    })
+}
+
+fn scalar_into_py(py: Python<'_>, value: ScalarValue) -> PyResult<PyObject> {
+    match value {
+        ScalarValue::Isize(value) => value.into_py_any(py),
+        ScalarValue::I8(value) => value.into_py_any(py),
+        ScalarValue::I16(value) => value.into_py_any(py),
+        ScalarValue::I32(value) => value.into_py_any(py),
+        ScalarValue::I64(value) => value.into_py_any(py),
+        ScalarValue::I128(value) => value.into_py_any(py),
+        ScalarValue::Usize(value) => value.into_py_any(py),
+        ScalarValue::U8(value) => value.into_py_any(py),
+        ScalarValue::U16(value) => value.into_py_any(py),
+        ScalarValue::U32(value) => value.into_py_any(py),
+        ScalarValue::U64(value) => value.into_py_any(py),
+        ScalarValue::U128(value) => value.into_py_any(py),
+        ScalarValue::F32(value) => value.into_py_any(py),
+        ScalarValue::F64(value) => value.into_py_any(py),
+        ScalarValue::Bool(value) => value.into_py_any(py),
+    }
+}
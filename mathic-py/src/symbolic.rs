@@ -0,0 +1,429 @@
+use std::sync::Arc;
+
+use pyo3::{
+    exceptions::{PyOverflowError, PyTypeError, PyValueError},
+    prelude::*,
+    types::PyTuple,
+};
+
+use crate::program::Program;
+
+/// Name of the function compiled by `lambdify`.
+const KERNEL_FUNCTION: &str = "kernel";
+
+/// Types a kernel may take and return.
+const DTYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128", "f32", "f64",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Symbol(String),
+    Int(i64),
+    Float(f64),
+    Binary(BinOp, Arc<Node>, Arc<Node>),
+    Diff(Arc<Node>, String),
+}
+
+impl Node {
+    /// Renders the node as a Mathic expression. Binary operations are always
+    /// parenthesized, and literals are written as floats when `floats` is
+    /// set.
+    fn render(&self, floats: bool) -> String {
+        match self {
+            Node::Symbol(name) => name.clone(),
+            Node::Int(value) => {
+                let literal = if floats {
+                    format!("{}.0", value.unsigned_abs())
+                } else {
+                    value.unsigned_abs().to_string()
+                };
+
+                negate_if(*value < 0, literal)
+            }
+            Node::Float(value) => negate_if(value.is_sign_negative(), float_literal(value.abs())),
+            Node::Binary(op, lhs, rhs) => format!(
+                "({} {} {})",
+                lhs.render(floats),
+                op.as_str(),
+                rhs.render(floats)
+            ),
+            Node::Diff(expr, symbol) => format!("diff({}, {symbol})", expr.render(floats)),
+        }
+    }
+
+    /// Pushes the symbols of the node which aren't in `symbols` yet, in the
+    /// order they appear.
+    fn collect_symbols(&self, symbols: &mut Vec<String>) {
+        match self {
+            Node::Symbol(name) => {
+                if !symbols.contains(name) {
+                    symbols.push(name.clone());
+                }
+            }
+            Node::Int(_) | Node::Float(_) => {}
+            Node::Binary(_, lhs, rhs) => {
+                lhs.collect_symbols(symbols);
+                rhs.collect_symbols(symbols);
+            }
+            Node::Diff(expr, _) => expr.collect_symbols(symbols),
+        }
+    }
+
+    /// Checks that the integers of the node fit in `dtype`. Negative ones
+    /// are subtracted from zero, so their absolute value must fit too.
+    fn check_int_range(&self, dtype: &str) -> PyResult<()> {
+        match self {
+            Node::Int(value) => match int_range(dtype) {
+                Some((min, max)) if !(min..=max).contains(&i128::from(*value)) => {
+                    Err(PyOverflowError::new_err(format!(
+                        "{value} is out of range for dtype '{dtype}'"
+                    )))
+                }
+                _ => Ok(()),
+            },
+            Node::Symbol(_) | Node::Float(_) => Ok(()),
+            Node::Binary(_, lhs, rhs) => {
+                lhs.check_int_range(dtype)?;
+                rhs.check_int_range(dtype)
+            }
+            Node::Diff(expr, _) => expr.check_int_range(dtype),
+        }
+    }
+}
+
+/// The integers an expression of an integer `dtype` may hold, `None` for
+/// floats.
+fn int_range(dtype: &str) -> Option<(i128, i128)> {
+    let max = match dtype {
+        "i8" => i8::MAX.into(),
+        "i16" => i16::MAX.into(),
+        "i32" => i32::MAX.into(),
+        "i64" => i64::MAX.into(),
+        "i128" => i128::MAX,
+        "u8" => return Some((0, u8::MAX.into())),
+        "u16" => return Some((0, u16::MAX.into())),
+        "u32" => return Some((0, u32::MAX.into())),
+        "u64" => return Some((0, u64::MAX.into())),
+        "u128" => return Some((0, i128::MAX)),
+        _ => return None,
+    };
+
+    Some((-max, max))
+}
+
+/// Mathic has no negative literals, they are subtracted from zero.
+fn negate_if(negative: bool, literal: String) -> String {
+    if negative {
+        format!("(0 - {literal})")
+    } else {
+        literal
+    }
+}
+
+/// Writes a non-negative float as a Mathic literal. Those need a digit other
+/// than zero before the point, so smaller values are written as a division
+/// by a power of ten.
+fn float_literal(value: f64) -> String {
+    // Display never uses an exponent.
+    let literal = value.to_string();
+
+    match literal.split_once('.') {
+        None => format!("{literal}.0"),
+        Some(("0", decimals)) => format!(
+            "({}.0 / 1{}.0)",
+            decimals.trim_start_matches('0'),
+            "0".repeat(decimals.len())
+        ),
+        Some(_) => literal,
+    }
+}
+
+/// A symbolic expression, built from symbols and numbers with `+`, `-`,
+/// `*`, `/` and `**`.
+#[pyclass(module = "mathic", frozen)]
+#[derive(Clone)]
+pub struct Expr {
+    node: Arc<Node>,
+}
+
+/// A value expressions are built from.
+#[derive(FromPyObject)]
+pub enum Operand {
+    Expr(Expr),
+    Int(i64),
+    Float(f64),
+}
+
+impl Operand {
+    fn into_node(self) -> PyResult<Arc<Node>> {
+        Ok(match self {
+            Operand::Expr(expr) => expr.node,
+            Operand::Int(value) => Arc::new(Node::Int(value)),
+            Operand::Float(value) if value.is_finite() => Arc::new(Node::Float(value)),
+            Operand::Float(value) => {
+                return Err(PyValueError::new_err(format!(
+                    "{value} can't be used in an expression"
+                )));
+            }
+        })
+    }
+}
+
+impl Expr {
+    fn binary(op: BinOp, lhs: Arc<Node>, rhs: Operand) -> PyResult<Self> {
+        Ok(Self {
+            node: Arc::new(Node::Binary(op, lhs, rhs.into_node()?)),
+        })
+    }
+
+    fn binary_rev(op: BinOp, lhs: Operand, rhs: Arc<Node>) -> PyResult<Self> {
+        Ok(Self {
+            node: Arc::new(Node::Binary(op, lhs.into_node()?, rhs)),
+        })
+    }
+
+    fn symbol_name(&self) -> Option<&str> {
+        match self.node.as_ref() {
+            Node::Symbol(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+#[pymethods]
+impl Expr {
+    fn __add__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary(BinOp::Add, self.node.clone(), other)
+    }
+
+    fn __radd__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary_rev(BinOp::Add, other, self.node.clone())
+    }
+
+    fn __sub__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary(BinOp::Sub, self.node.clone(), other)
+    }
+
+    fn __rsub__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary_rev(BinOp::Sub, other, self.node.clone())
+    }
+
+    fn __mul__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary(BinOp::Mul, self.node.clone(), other)
+    }
+
+    fn __rmul__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary_rev(BinOp::Mul, other, self.node.clone())
+    }
+
+    fn __truediv__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary(BinOp::Div, self.node.clone(), other)
+    }
+
+    fn __rtruediv__(&self, other: Operand) -> PyResult<Self> {
+        Self::binary_rev(BinOp::Div, other, self.node.clone())
+    }
+
+    fn __neg__(&self) -> PyResult<Self> {
+        Self::binary_rev(BinOp::Sub, Operand::Int(0), self.node.clone())
+    }
+
+    fn __pos__(&self) -> Self {
+        self.clone()
+    }
+
+    /// Only natural exponents are supported, the power is expanded into
+    /// products.
+    #[pyo3(signature = (exponent, modulo = None))]
+    fn __pow__(&self, exponent: u32, modulo: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        if modulo.is_some_and(|modulo| !modulo.is_none()) {
+            return Err(PyTypeError::new_err("pow() with a modulo isn't supported"));
+        }
+
+        let node = if exponent == 0 {
+            Arc::new(Node::Int(1))
+        } else {
+            (1..exponent).fold(self.node.clone(), |node, _| {
+                Arc::new(Node::Binary(BinOp::Mul, node, self.node.clone()))
+            })
+        };
+
+        Ok(Self { node })
+    }
+
+    /// Differentiates the expression with respect to a symbol.
+    fn diff(&self, symbol: &Expr) -> PyResult<Self> {
+        let name = symbol
+            .symbol_name()
+            .ok_or_else(|| PyTypeError::new_err("expressions are differentiated by a symbol"))?;
+
+        Ok(Self {
+            node: Arc::new(Node::Diff(self.node.clone(), name.to_string())),
+        })
+    }
+
+    fn __str__(&self) -> String {
+        self.node.render(false)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Expr({})", self.node.render(false))
+    }
+}
+
+/// Creates a symbol.
+#[pyfunction]
+pub fn symbol(name: &str) -> PyResult<Expr> {
+    // Names starting with `__` are used by the generated kernels.
+    if !mathic::is_identifier(name) || name.starts_with("__") {
+        return Err(PyValueError::new_err(format!(
+            "'{name}' isn't a valid symbol name"
+        )));
+    }
+
+    Ok(Expr {
+        node: Arc::new(Node::Symbol(name.to_string())),
+    })
+}
+
+/// Creates a symbol for each name in a string separated by spaces or commas,
+/// e.g. `x, y = symbols("x y")`.
+#[pyfunction]
+pub fn symbols(names: &str) -> PyResult<Vec<Expr>> {
+    names
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|name| !name.is_empty())
+        .map(symbol)
+        .collect()
+}
+
+/// An expression compiled by `lambdify`, called with a value for each of its
+/// parameters.
+#[pyclass(module = "mathic", unsendable)]
+pub struct Kernel {
+    program: Program,
+    source: String,
+}
+
+#[pymethods]
+impl Kernel {
+    #[pyo3(signature = (*args))]
+    fn __call__(&self, py: Python<'_>, args: &Bound<'_, PyTuple>) -> PyResult<PyObject> {
+        self.program
+            .call_with(py, &format!("program::{KERNEL_FUNCTION}"), args)
+    }
+
+    /// The Mathic source the kernel was compiled from.
+    #[getter]
+    fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Compiles an expression into a kernel taking the given symbols, in order,
+/// and returning the expression's value. The expression goes through the
+/// symbolic dialect, where it is simplified before being evaluated.
+#[pyfunction]
+#[pyo3(signature = (params, expr, dtype = "f64", opt_level = 2))]
+pub fn lambdify(params: Vec<Expr>, expr: Operand, dtype: &str, opt_level: u8) -> PyResult<Kernel> {
+    if !DTYPES.contains(&dtype) {
+        return Err(PyValueError::new_err(format!(
+            "unsupported dtype '{dtype}', expected one of: {}",
+            DTYPES.join(", ")
+        )));
+    }
+
+    let mut param_names = Vec::with_capacity(params.len());
+
+    for param in &params {
+        let name = param
+            .symbol_name()
+            .ok_or_else(|| PyTypeError::new_err("the parameters of a kernel must be symbols"))?;
+
+        if param_names.contains(&name) {
+            return Err(PyValueError::new_err(format!(
+                "symbol '{name}' is a parameter more than once"
+            )));
+        }
+
+        param_names.push(name);
+    }
+
+    let node = expr.into_node()?;
+    node.check_int_range(dtype)?;
+
+    let mut symbols = Vec::new();
+    node.collect_symbols(&mut symbols);
+
+    let mut substitutions = Vec::with_capacity(symbols.len());
+
+    for symbol in &symbols {
+        let idx = param_names
+            .iter()
+            .position(|name| name == symbol)
+            .ok_or_else(|| PyValueError::new_err(format!("symbol '{symbol}' isn't a parameter")))?;
+
+        substitutions.push(format!("{symbol}=__p{idx}"));
+    }
+
+    let source = kernel_source(dtype, params.len(), &symbols, &substitutions, &node);
+    let program = Program::compile(opt_level, Some(&source), |compiler, opts| {
+        Ok(vec![compiler.compile_source(&source, None, opts)?])
+    })?;
+
+    Ok(Kernel { program, source })
+}
+
+/// Writes the function evaluating an expression. Its symbols are declared
+/// and replaced by the parameters they stand for.
+fn kernel_source(
+    dtype: &str,
+    param_count: usize,
+    symbols: &[String],
+    substitutions: &[String],
+    node: &Node,
+) -> String {
+    let params = (0..param_count)
+        .map(|idx| format!("__p{idx}: {dtype}"))
+        .collect::<Vec<_>>();
+    let body = node.render(dtype.starts_with('f'));
+
+    let mut source = format!("df {KERNEL_FUNCTION}({}) {dtype} {{\n", params.join(", "));
+
+    for symbol in symbols {
+        source.push_str(&format!("    sym {symbol}: expr<{dtype}>;\n"));
+    }
+
+    if symbols.is_empty() {
+        source.push_str(&format!("    return {body};\n"));
+    } else {
+        source.push_str(&format!("    let __expr: expr<{dtype}> = {body};\n"));
+        source.push_str(&format!(
+            "    return __expr[{}];\n",
+            substitutions.join(", ")
+        ));
+    }
+
+    source.push_str("}\n");
+    source
+}
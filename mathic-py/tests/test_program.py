@@ -0,0 +1,79 @@
+import pytest
+
+import mathic
+
+AREA = "df area(w: f64, h: f64) f64 { return w * h; }"
+
+
+def test_compile_source_and_call():
+    program = mathic.compile_source(AREA)
+
+    assert program.call("program::area", 2.0, 3.0) == 6.0
+
+
+def test_compile_project(tmp_path):
+    (tmp_path / "main.mth").write_text("df twice(x: i64) i64 { return x * 2; }\n")
+    program = mathic.compile_project(tmp_path)
+
+    assert program.call("main::twice", 21) == 42
+
+
+@pytest.mark.parametrize(
+    "source, name, args, expected",
+    [
+        ("df f(x: i32) i32 { return x + 1; }", "program::f", (41,), 42),
+        ("df f(x: u8) u8 { return x; }", "program::f", (200,), 200),
+        ("df f(x: f32) f32 { return x * 2.0; }", "program::f", (1.5,), 3.0),
+        ("df f(x: i64) bool { return x > 0; }", "program::f", (3,), True),
+    ],
+)
+def test_call_converts_arguments(source, name, args, expected):
+    program = mathic.compile_source(source)
+
+    assert program.call(name, *args) == expected
+
+
+def test_signature():
+    program = mathic.compile_source(AREA)
+
+    assert program.signature("program::area") == "df(f64, f64) f64"
+    assert program.signature("program::missing") is None
+
+
+def test_compile_error():
+    with pytest.raises(mathic.CompileError, match="Undeclared variable"):
+        mathic.compile_source("df f() i32 { return y; }")
+
+
+def test_literal_out_of_range():
+    with pytest.raises(mathic.CompileError, match="doesn't fit in type 'u8'"):
+        mathic.compile_source("df f() u8 { let x: u8 = 300; return x; }")
+
+
+def test_unknown_function():
+    program = mathic.compile_source(AREA)
+
+    with pytest.raises(KeyError):
+        program.call("program::missing")
+
+
+@pytest.mark.parametrize("args", [(1.0,), (1.0, 2.0, 3.0)])
+def test_wrong_argument_count(args):
+    program = mathic.compile_source(AREA)
+
+    with pytest.raises(TypeError):
+        program.call("program::area", *args)
+
+
+def test_wrong_argument_type():
+    program = mathic.compile_source("df f(x: i32) i32 { return x; }")
+
+    with pytest.raises(TypeError):
+        program.call("program::f", "one")
+
+
+def test_argument_out_of_range():
+    program = mathic.compile_source("df f(x: u8) u8 { return x; }")
+
+    with pytest.raises(OverflowError):
+        program.call("program::f", 256)
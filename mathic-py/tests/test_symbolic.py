@@ -0,0 +1,110 @@
+import pytest
+
+import mathic
+
+
+def test_lambdify():
+    x, y = mathic.symbols("x y")
+    f = mathic.lambdify([x, y], x**2 + 3 * y)
+
+    assert f(3.0, 4.0) == 21.0
+
+
+@pytest.mark.parametrize("dtype", ["i32", "i64", "u8", "f32", "f64"])
+def test_lambdify_dtype(dtype):
+    x = mathic.symbol("x")
+    f = mathic.lambdify([x], 2 * x + 1, dtype=dtype)
+
+    assert f(5) == 11
+    assert f"__p0: {dtype}" in f.source
+
+
+def test_lambdify_diff():
+    x = mathic.symbol("x")
+    f = mathic.lambdify([x], (x**3).diff(x))
+
+    assert f(2.0) == 12.0
+
+
+def test_lambdify_constant():
+    f = mathic.lambdify([], 4)
+
+    assert f() == 4.0
+
+
+def test_lambdify_negative_literals():
+    x = mathic.symbol("x")
+    f = mathic.lambdify([x], x - 0.25, dtype="f64")
+
+    assert f(1.0) == 0.75
+    assert mathic.lambdify([x], -3 * x, dtype="i32")(2) == -6
+
+
+def test_expr_str():
+    x, y = mathic.symbols("x, y")
+
+    assert str(x * y + 1) == "((x * y) + 1)"
+
+
+@pytest.mark.parametrize("name", ["1x", "x y", "", "__p0", "let", "df", "sym", "in"])
+def test_invalid_symbol(name):
+    with pytest.raises(ValueError):
+        mathic.symbol(name)
+
+
+@pytest.mark.parametrize("name", ["x", "_x", "x1", "inner", "θ"])
+def test_valid_symbol(name):
+    assert str(mathic.symbol(name)) == name
+
+
+@pytest.mark.parametrize(
+    "dtype, value", [("u8", 256), ("u8", -1), ("i8", 128), ("i32", 2**31), ("u32", -5)]
+)
+def test_int_out_of_range(dtype, value):
+    x = mathic.symbol("x")
+
+    with pytest.raises(OverflowError):
+        mathic.lambdify([x], x + value, dtype=dtype)
+
+
+def test_unsupported_dtype():
+    x = mathic.symbol("x")
+
+    with pytest.raises(ValueError, match="unsupported dtype"):
+        mathic.lambdify([x], x, dtype="bool")
+
+
+def test_parameters_must_be_symbols():
+    x, y = mathic.symbols("x y")
+
+    with pytest.raises(TypeError):
+        mathic.lambdify([x + y], x)
+
+
+def test_duplicate_parameter():
+    x = mathic.symbol("x")
+
+    with pytest.raises(ValueError):
+        mathic.lambdify([x, x], x)
+
+
+def test_symbol_not_a_parameter():
+    x, y = mathic.symbols("x y")
+
+    with pytest.raises(ValueError, match="isn't a parameter"):
+        mathic.lambdify([x], x + y)
+
+
+def test_wrong_argument_count():
+    x = mathic.symbol("x")
+    f = mathic.lambdify([x], x)
+
+    with pytest.raises(TypeError):
+        f(1.0, 2.0)
+
+
+def test_pow_with_modulo():
+    x = mathic.symbol("x")
+
+    with pytest.raises(TypeError):
+        pow(x, 2, 3)
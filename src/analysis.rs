@@ -0,0 +1,615 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    MathicResult,
+    analysis::cursor::{Cursor, Node},
+    diagnostics::{CompilationError, Diagnostic, DiagnosticsManager, LintLevels},
+    loader::ModuleLoader,
+    lowering::{
+        self,
+        ir::{
+            Ir,
+            adts::{Adt, StructField},
+            function::{Function, LocalKind},
+            symbols::{DeclTable, TypeIndex},
+            types::MathicType,
+        },
+        lint::lint_program,
+        lower_program,
+    },
+    parser::{
+        Span,
+        ast::{
+            IrModule,
+            declaration::{AstType, FuncDecl, StructDecl},
+            expression::{ExprStmt, ExprStmtKind, PrimaryExpr},
+        },
+    },
+};
+
+mod cursor;
+
+/// A place in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub span: Span,
+}
+
+/// Information about what's under a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hover {
+    /// The hovered node.
+    pub span: Span,
+    /// Mathic code describing the node, e.g. a declaration.
+    pub contents: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Local,
+    Function,
+    Struct,
+    Field,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The type or signature of the completed item.
+    pub detail: String,
+}
+
+/// Semantic information about a source file, meant for editors.
+///
+/// The file is parsed and lowered the same way `compile_project` does, but
+/// nothing is generated. Queries take byte offsets into the analyzed source.
+pub struct Analysis {
+    file_path: PathBuf,
+    /// The AST of the file, without the statements and items which have
+    /// syntax errors.
+    program: Option<Arc<IrModule>>,
+    /// Whether the file has no syntax errors.
+    parsed: bool,
+    /// Paths of the modules the file imports, directly or not.
+    module_paths: HashMap<String, PathBuf>,
+    decl_table: DeclTable,
+    /// The MATHIR of the file, if it lowers.
+    ir: Option<Ir>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Analyzes the source of the file at `file_path`, resolving imports from
+/// `src_root`. Imported modules are read from disk.
+pub fn analyze(src_root: &Path, file_path: &Path, source: &str) -> MathicResult<Analysis> {
+    let diagnostics = DiagnosticsManager::new();
+
+    let relative_path = file_path
+        .strip_prefix(src_root)
+        .unwrap_or(file_path)
+        .to_path_buf();
+    let file_path = src_root.join(&relative_path);
+
+    let modules = ModuleLoader::new(&diagnostics).load_source(src_root, relative_path, source)?;
+
+    let program = modules.get(&file_path).cloned();
+    let module_paths = modules
+        .iter()
+        .map(|(path, module)| (module.module_name.clone(), path.clone()))
+        .collect();
+
+    let parsed = !diagnostics.with_errors(|errors| {
+        errors
+            .iter()
+            .any(|(path, error)| *path == file_path && matches!(error, CompilationError::Parse(_)))
+    })?;
+
+    let (decl_table, ir) = match &program {
+        Some(program) => {
+            // Queries work on the partial AST, but only a complete one is
+            // worth lowering.
+            let ir = if parsed {
+                match lower_program(program) {
+                    Ok(ir) => {
+                        for warning in lint_program(&ir) {
+                            diagnostics.report_lint(
+                                file_path.clone(),
+                                warning,
+                                &LintLevels::default(),
+                            )?;
+                        }
+
+                        Some(ir)
+                    }
+                    Err(errors) => {
+                        for e in errors {
+                            diagnostics.report(file_path.clone(), CompilationError::Lowering(e))?;
+                        }
+
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            (lowering::declarations(program), ir)
+        }
+        None => (DeclTable::default(), None),
+    };
+
+    // Errors of imported modules are shown once those are analyzed.
+    let mut diagnostics_of_file = diagnostics.with_errors(|errors| {
+        errors
+            .iter()
+            .filter(|(path, _)| *path == file_path)
+            .map(|(_, error)| error.diagnostic())
+            .collect::<Vec<_>>()
+    })?;
+
+    diagnostics_of_file.extend(diagnostics.with_warnings(|warnings| {
+        warnings
+            .iter()
+            .filter(|(path, _)| *path == file_path)
+            .map(|(_, warning)| warning.diagnostic())
+            .collect::<Vec<_>>()
+    })?);
+
+    Ok(Analysis {
+        file_path,
+        program,
+        parsed,
+        module_paths,
+        decl_table,
+        ir,
+        diagnostics: diagnostics_of_file,
+    })
+}
+
+impl Analysis {
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Whether the source parsed without errors. Otherwise, queries only see
+    /// the parts of it which did.
+    pub fn is_parsed(&self) -> bool {
+        self.parsed
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn hover(&self, offset: usize) -> Option<Hover> {
+        let program = self.program.as_ref()?;
+        let cursor = Cursor::new(program, offset);
+
+        let (span, contents) = match cursor.node? {
+            Node::Local { name, span } => {
+                let contents = match self.local_type(&cursor, name) {
+                    Some((kind, ty)) => match kind {
+                        LocalKind::Temp => format!("let {name}: {ty}"),
+                        LocalKind::Sym => format!("sym {name}: {ty}"),
+                        LocalKind::Param => format!("{name}: {ty}"),
+                    },
+                    None => {
+                        let binding = cursor.binding(name)?;
+
+                        match binding.ty {
+                            Some(ty) => format!("{name}: {ty}"),
+                            None => name.to_string(),
+                        }
+                    }
+                };
+
+                (span, contents)
+            }
+            Node::Function { name, span } => (span, signature(self.function(&cursor, name)?.0)),
+            Node::Struct { name, span } => (span, struct_decl(self.strct(&cursor, name)?.0)),
+            Node::Field { base, name, span } => {
+                let (func, base_ty) = self.expr_type(&cursor, base)?;
+                let (adt, field) = self.field(func, base_ty, name)?;
+                let field_ty = self.resolve_type(func, field.ty)?;
+
+                (
+                    span,
+                    format!(
+                        "{}.{name}: {}",
+                        adt_name(adt),
+                        self.type_name(func, field_ty)
+                    ),
+                )
+            }
+            Node::Import(path) => {
+                let name = path.idents.last()?;
+
+                let contents = if let Some((func, _)) = self.decl_table.get_function_decl(name) {
+                    signature(func)
+                } else if let Some((strct, _)) = self.decl_table.get_struct_decl(name) {
+                    struct_decl(strct)
+                } else {
+                    format!("module {}", path.join("::"))
+                };
+
+                (path.span, contents)
+            }
+        };
+
+        Some(Hover { span, contents })
+    }
+
+    pub fn definition(&self, offset: usize) -> Option<Location> {
+        let program = self.program.as_ref()?;
+        let cursor = Cursor::new(program, offset);
+
+        match cursor.node? {
+            Node::Local { name, .. } => Some(self.location(None, cursor.binding(name)?.span)),
+            Node::Function { name, .. } => {
+                let (func, module_idx) = self.function(&cursor, name)?;
+
+                Some(self.location(module_idx, func.span))
+            }
+            Node::Struct { name, .. } => {
+                let (strct, module_idx) = self.strct(&cursor, name)?;
+
+                Some(self.location(module_idx, strct.span))
+            }
+            Node::Field { base, name, .. } => {
+                let (func, base_ty) = self.expr_type(&cursor, base)?;
+                let (adt, _) = self.field(func, base_ty, name)?;
+                let (strct, module_idx) = self.strct(&cursor, adt_name(adt))?;
+                let field = strct.fields.iter().find(|field| field.name == name)?;
+
+                Some(self.location(module_idx, field.span))
+            }
+            Node::Import(path) => {
+                let name = path.idents.last()?;
+
+                if let Some((func, module_idx)) = self.decl_table.get_function_decl(name) {
+                    Some(self.location(*module_idx, func.span))
+                } else if let Some((strct, module_idx)) = self.decl_table.get_struct_decl(name) {
+                    Some(self.location(*module_idx, strct.span))
+                } else {
+                    Some(Location {
+                        path: self.module_paths.get(&path.join("::"))?.clone(),
+                        span: Span::from(0..0),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Returns the completions at the offset of `source`.
+    ///
+    /// `source` may be newer than the analyzed one, since code being typed
+    /// often doesn't parse. Right after a `.`, the fields of the value before
+    /// it are completed. Otherwise, the visible variables, functions and
+    /// structs are.
+    pub fn completions(&self, source: &str, offset: usize) -> Vec<Completion> {
+        let Some(program) = self.program.as_ref() else {
+            return Vec::new();
+        };
+        let cursor = Cursor::new(program, offset);
+
+        let receiver = receiver_chain(source, offset);
+
+        if !receiver.is_empty() {
+            return self
+                .field_completions(&cursor, &receiver)
+                .unwrap_or_default();
+        }
+
+        let mut completions = Vec::new();
+
+        for binding in cursor.bindings.iter().rev() {
+            if completions
+                .iter()
+                .any(|c: &Completion| c.label == binding.name)
+            {
+                continue;
+            }
+
+            let detail = match self.local_type(&cursor, binding.name) {
+                Some((_, ty)) => ty,
+                None => binding.ty.map(AstType::to_string).unwrap_or_default(),
+            };
+
+            completions.push(Completion {
+                label: binding.name.to_string(),
+                kind: CompletionKind::Local,
+                detail,
+            });
+        }
+
+        let functions = cursor
+            .inner_functions
+            .iter()
+            .copied()
+            .chain(self.decl_table.functions().map(|(func, _)| func));
+
+        for func in functions {
+            completions.push(Completion {
+                label: func.name.clone(),
+                kind: CompletionKind::Function,
+                detail: signature(func),
+            });
+        }
+
+        let structs = cursor
+            .inner_structs
+            .iter()
+            .copied()
+            .chain(self.decl_table.structs().map(|(strct, _)| strct));
+
+        for strct in structs {
+            completions.push(Completion {
+                label: strct.name.clone(),
+                kind: CompletionKind::Struct,
+                detail: struct_decl(strct),
+            });
+        }
+
+        completions
+    }
+
+    fn field_completions(&self, cursor: &Cursor, receiver: &[&str]) -> Option<Vec<Completion>> {
+        let (first, fields) = receiver.split_first()?;
+
+        let func = self.lowered_function(cursor)?;
+        let local = func
+            .get_locals()
+            .iter()
+            .find(|local| local.debug_name.as_deref() == Some(*first))?;
+        let mut ty = self.resolve_type(func, local.ty)?;
+
+        for name in fields {
+            let (_, field) = self.field(func, ty, name)?;
+            ty = self.resolve_type(func, field.ty)?;
+        }
+
+        let Adt::Struct(strct) = self.adt(func, ty)?;
+
+        strct
+            .fields
+            .iter()
+            .map(|field| {
+                Some(Completion {
+                    label: field.name.clone(),
+                    kind: CompletionKind::Field,
+                    detail: self.type_name(func, self.resolve_type(func, field.ty)?),
+                })
+            })
+            .collect()
+    }
+
+    fn location(&self, module_idx: Option<usize>, span: Span) -> Location {
+        let path = module_idx
+            .and_then(|idx| self.decl_table.get_module(idx))
+            .and_then(|module| self.module_paths.get(&module.module_name))
+            .unwrap_or(&self.file_path);
+
+        Location {
+            path: path.clone(),
+            span,
+        }
+    }
+
+    fn function<'a>(
+        &'a self,
+        cursor: &Cursor<'a>,
+        name: &str,
+    ) -> Option<(&'a FuncDecl, Option<usize>)> {
+        match cursor.inner_function(name) {
+            Some(func) => Some((func, None)),
+            None => self
+                .decl_table
+                .get_function_decl(name)
+                .map(|(func, module_idx)| (func, *module_idx)),
+        }
+    }
+
+    fn strct<'a>(
+        &'a self,
+        cursor: &Cursor<'a>,
+        name: &str,
+    ) -> Option<(&'a StructDecl, Option<usize>)> {
+        match cursor.inner_struct(name) {
+            Some(strct) => Some((strct, None)),
+            None => self
+                .decl_table
+                .get_struct_decl(name)
+                .map(|(strct, module_idx)| (strct, *module_idx)),
+        }
+    }
+
+    /// Returns the lowered function containing the cursor.
+    fn lowered_function(&self, cursor: &Cursor) -> Option<&Function> {
+        let ir = self.ir.as_ref()?;
+        let module_name = &self.program.as_ref()?.module_name;
+
+        let (outermost, inner) = cursor.functions.split_first()?;
+        let mangled_name = |name: &str| format!("{module_name}::{name}");
+
+        let mut func = ir
+            .get_functions()
+            .iter()
+            .find(|f| f.name == mangled_name(&outermost.name))?;
+
+        for decl in inner {
+            func = func
+                .get_inner_functions()
+                .iter()
+                .find(|f| f.name == mangled_name(&decl.name))?;
+        }
+
+        Some(func)
+    }
+
+    /// Returns the kind and the type of a variable of the function
+    /// containing the cursor.
+    fn local_type(&self, cursor: &Cursor, name: &str) -> Option<(LocalKind, String)> {
+        let func = self.lowered_function(cursor)?;
+        let local = func
+            .get_locals()
+            .iter()
+            .find(|local| local.debug_name.as_deref() == Some(name))?;
+
+        Some((
+            local.kind,
+            self.type_name(func, self.resolve_type(func, local.ty)?),
+        ))
+    }
+
+    /// Returns the type of a variable or a field access.
+    fn expr_type<'a>(
+        &'a self,
+        cursor: &Cursor,
+        expr: &ExprStmt,
+    ) -> Option<(&'a Function, MathicType)> {
+        match &expr.kind {
+            ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
+                let func = self.lowered_function(cursor)?;
+                let local = func
+                    .get_locals()
+                    .iter()
+                    .find(|local| local.debug_name.as_deref() == Some(name))?;
+
+                Some((func, self.resolve_type(func, local.ty)?))
+            }
+            ExprStmtKind::StructGet { expr, field_name } => {
+                let (func, base_ty) = self.expr_type(cursor, expr)?;
+                let (_, field) = self.field(func, base_ty, field_name)?;
+
+                Some((func, self.resolve_type(func, field.ty)?))
+            }
+            ExprStmtKind::Group(inner) => self.expr_type(cursor, inner),
+            _ => None,
+        }
+    }
+
+    fn field<'a>(
+        &'a self,
+        func: &'a Function,
+        ty: MathicType,
+        name: &str,
+    ) -> Option<(&'a Adt, &'a StructField)> {
+        let adt = self.adt(func, ty)?;
+        let Adt::Struct(strct) = adt;
+
+        Some((adt, strct.fields.iter().find(|field| field.name == name)?))
+    }
+
+    fn resolve_type(&self, func: &Function, idx: TypeIndex) -> Option<MathicType> {
+        if idx.is_local {
+            func.get_type(idx.idx)
+        } else {
+            self.ir.as_ref()?.get_type(idx.idx)
+        }
+    }
+
+    fn adt<'a>(&'a self, func: &'a Function, ty: MathicType) -> Option<&'a Adt> {
+        match ty {
+            MathicType::Adt {
+                index,
+                is_local: true,
+            } => func.get_adt(index),
+            MathicType::Adt {
+                index,
+                is_local: false,
+            } => self.ir.as_ref()?.get_adt(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of a type as written in the source.
+    fn type_name(&self, func: &Function, ty: MathicType) -> String {
+        match ty {
+            MathicType::Adt { .. } => self
+                .adt(func, ty)
+                .map(|adt| adt_name(adt).to_string())
+                .unwrap_or_else(|| ty.to_string()),
+            MathicType::Array { elem, len } => match self.resolve_type(func, elem) {
+                Some(elem) => format!("[{}; {len}]", self.type_name(func, elem)),
+                None => ty.to_string(),
+            },
+            _ => ty.to_string(),
+        }
+    }
+}
+
+fn adt_name(adt: &Adt) -> &str {
+    match adt {
+        Adt::Struct(strct) => &strct.name,
+    }
+}
+
+fn signature(func: &FuncDecl) -> String {
+    let params = func
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, param.ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let keyword = if func.is_extern {
+        "extern df"
+    } else if func.is_test {
+        "test df"
+    } else {
+        "df"
+    };
+
+    match &func.return_ty {
+        Some(ty) => format!("{keyword} {}({params}) {ty}", func.name),
+        None => format!("{keyword} {}({params})", func.name),
+    }
+}
+
+fn struct_decl(strct: &StructDecl) -> String {
+    let fields = strct
+        .fields
+        .iter()
+        .map(|field| {
+            let visibility = if field.is_pub { "pub " } else { "" };
+
+            format!("    {visibility}{}: {}", field.name, field.ty)
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("struct {} {{\n{fields}\n}}", strct.name)
+}
+
+/// Returns the names before the `.` preceding the word at the offset, like
+/// `["a", "b"]` for `a.b.c|`. Empty if there's no `.`.
+fn receiver_chain(source: &str, offset: usize) -> Vec<&str> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let Some(before) = source.get(..offset) else {
+        return Vec::new();
+    };
+
+    // Skip the word being completed.
+    let mut rest = before.trim_end_matches(is_ident);
+    let mut chain = Vec::new();
+
+    while let Some(stripped) = rest.strip_suffix('.') {
+        let name_start = stripped.trim_end_matches(is_ident).len();
+        let name = &stripped[name_start..];
+
+        if name.is_empty() {
+            break;
+        }
+
+        chain.push(name);
+        rest = &stripped[..name_start];
+    }
+
+    chain.reverse();
+    chain
+}
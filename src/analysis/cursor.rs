@@ -0,0 +1,324 @@
+use crate::parser::{
+    Span,
+    ast::{
+        IrModule,
+        control_flow::ForIterable,
+        declaration::{AstType, DeclStmt, FuncDecl, Path, StructDecl, TopLevelItem},
+        expression::{ExprStmt, ExprStmtKind, PrimaryExpr},
+        statement::{BlockStmt, Stmt, StmtKind},
+    },
+};
+
+/// A name visible from a position of a function's body.
+#[derive(Debug, Clone, Copy)]
+pub struct Binding<'a> {
+    pub name: &'a str,
+    /// The declared type, loop variables don't have one.
+    pub ty: Option<&'a AstType>,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+/// The node of the AST found at a position.
+#[derive(Debug, Clone, Copy)]
+pub enum Node<'a> {
+    /// A variable.
+    Local {
+        name: &'a str,
+        span: Span,
+    },
+    /// The callee of a call.
+    Function {
+        name: &'a str,
+        span: Span,
+    },
+    /// The name of a struct being initialized.
+    Struct {
+        name: &'a str,
+        span: Span,
+    },
+    /// A field access, `span` covers the whole access.
+    Field {
+        base: &'a ExprStmt,
+        name: &'a str,
+        span: Span,
+    },
+    Import(&'a Path),
+}
+
+/// Everything known about a position of the source.
+#[derive(Debug, Default)]
+pub struct Cursor<'a> {
+    /// The functions containing the position, outermost first.
+    pub functions: Vec<&'a FuncDecl>,
+    /// Functions and structs declared within those functions.
+    pub inner_functions: Vec<&'a FuncDecl>,
+    pub inner_structs: Vec<&'a StructDecl>,
+    /// Names visible from the position, in order of declaration.
+    pub bindings: Vec<Binding<'a>>,
+    pub node: Option<Node<'a>>,
+}
+
+impl<'a> Cursor<'a> {
+    /// Walks the program down to the given offset.
+    pub fn new(program: &'a IrModule, offset: usize) -> Self {
+        let mut cursor = Self::default();
+
+        for item in &program.items {
+            match item {
+                TopLevelItem::Func(func) if contains(func.span, offset) => {
+                    cursor.walk_function(func, offset);
+                }
+                TopLevelItem::Import(path) if contains(path.span, offset) => {
+                    cursor.node = Some(Node::Import(innermost_path(path, offset)));
+                }
+                _ => {}
+            }
+        }
+
+        cursor
+    }
+
+    pub fn binding(&self, name: &str) -> Option<&Binding<'a>> {
+        self.bindings.iter().rev().find(|b| b.name == name)
+    }
+
+    pub fn inner_function(&self, name: &str) -> Option<&'a FuncDecl> {
+        self.inner_functions
+            .iter()
+            .rev()
+            .find(|f| f.name == name)
+            .copied()
+    }
+
+    pub fn inner_struct(&self, name: &str) -> Option<&'a StructDecl> {
+        self.inner_structs
+            .iter()
+            .rev()
+            .find(|s| s.name == name)
+            .copied()
+    }
+
+    fn walk_function(&mut self, func: &'a FuncDecl, offset: usize) {
+        self.functions.push(func);
+
+        // Bindings of the enclosing function aren't visible from this one.
+        self.bindings = func
+            .params
+            .iter()
+            .map(|param| Binding {
+                name: &param.name,
+                ty: Some(&param.ty),
+                span: param.span,
+            })
+            .collect();
+
+        // Functions and structs can be referenced before their declaration.
+        for stmt in &func.body {
+            match &stmt.kind {
+                StmtKind::Decl(DeclStmt::Func(f)) => self.inner_functions.push(f),
+                StmtKind::Decl(DeclStmt::Struct(s)) => self.inner_structs.push(s),
+                _ => {}
+            }
+        }
+
+        self.walk_stmts(&func.body, offset);
+    }
+
+    fn walk_block(&mut self, block: &'a BlockStmt, offset: usize) {
+        self.walk_stmts(&block.stmts, offset);
+    }
+
+    fn walk_stmts(&mut self, stmts: &'a [Stmt], offset: usize) {
+        for stmt in stmts {
+            if stmt.span.start > offset {
+                break;
+            }
+
+            match &stmt.kind {
+                StmtKind::Decl(DeclStmt::Var(var)) => self.bindings.push(Binding {
+                    name: &var.name,
+                    ty: Some(&var.ty),
+                    span: stmt.span,
+                }),
+                StmtKind::Decl(DeclStmt::Sym(sym)) => self.bindings.push(Binding {
+                    name: &sym.name,
+                    ty: Some(&sym.ty),
+                    span: stmt.span,
+                }),
+                _ => {}
+            }
+
+            if contains(stmt.span, offset) {
+                self.walk_stmt(stmt, offset);
+            }
+        }
+    }
+
+    fn walk_stmt(&mut self, stmt: &'a Stmt, offset: usize) {
+        match &stmt.kind {
+            StmtKind::Decl(DeclStmt::Var(var)) => self.walk_expr(&var.expr, offset),
+            StmtKind::Decl(DeclStmt::Func(func)) => self.walk_function(func, offset),
+            StmtKind::Decl(DeclStmt::Sym(_) | DeclStmt::Struct(_)) => {}
+            StmtKind::Block(block) => self.walk_block(block, offset),
+            StmtKind::If(if_stmt) => {
+                self.walk_expr(&if_stmt.condition, offset);
+                self.walk_block(&if_stmt.then_block, offset);
+
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.walk_block(else_block, offset);
+                }
+            }
+            StmtKind::While(while_stmt) => {
+                self.walk_expr(&while_stmt.condition, offset);
+                self.walk_block(&while_stmt.body, offset);
+            }
+            StmtKind::For(for_stmt) => {
+                match &for_stmt.iterable {
+                    ForIterable::Range { start, end } => {
+                        self.walk_expr(start, offset);
+                        self.walk_expr(end, offset);
+                    }
+                    ForIterable::Array(array) => self.walk_expr(array, offset),
+                }
+
+                if contains(for_stmt.body.span, offset) {
+                    self.bindings.push(Binding {
+                        name: &for_stmt.variable,
+                        ty: None,
+                        span: stmt.span,
+                    });
+                    self.walk_block(&for_stmt.body, offset);
+                }
+            }
+            StmtKind::Return(expr) | StmtKind::Expr(expr) => self.walk_expr(expr, offset),
+        }
+    }
+
+    fn walk_expr(&mut self, expr: &'a ExprStmt, offset: usize) {
+        if !contains(expr.extent(), offset) {
+            return;
+        }
+
+        match &expr.kind {
+            ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
+                self.node = Some(Node::Local {
+                    name,
+                    span: expr.span,
+                });
+            }
+            ExprStmtKind::Primary(_) => {}
+            ExprStmtKind::Call { callee, args } => {
+                if let ExprStmtKind::Primary(PrimaryExpr::Ident(name)) = &callee.kind
+                    && contains(callee.span, offset)
+                {
+                    self.node = Some(Node::Function {
+                        name,
+                        span: callee.span,
+                    });
+                } else {
+                    self.walk_expr(callee, offset);
+                    self.walk_exprs(args, offset);
+                }
+            }
+            ExprStmtKind::StructInit {
+                expr: strct,
+                fields,
+            } => {
+                if let ExprStmtKind::Primary(PrimaryExpr::Ident(name)) = &strct.kind
+                    && contains(strct.span, offset)
+                {
+                    self.node = Some(Node::Struct {
+                        name,
+                        span: expr.span,
+                    });
+                } else {
+                    self.walk_exprs(fields.values(), offset);
+                }
+            }
+            ExprStmtKind::StructGet {
+                expr: base,
+                field_name,
+            } => {
+                if contains(base.extent(), offset) {
+                    self.walk_expr(base, offset);
+                } else {
+                    self.node = Some(Node::Field {
+                        base,
+                        name: field_name,
+                        span: expr.span,
+                    });
+                }
+            }
+            ExprStmtKind::StructSet {
+                lhs,
+                field_name,
+                rhs,
+            } => {
+                if contains(lhs.extent(), offset) {
+                    self.walk_expr(lhs, offset);
+                } else if contains(rhs.extent(), offset) {
+                    self.walk_expr(rhs, offset);
+                } else {
+                    self.node = Some(Node::Field {
+                        base: lhs,
+                        name: field_name,
+                        span: expr.span,
+                    });
+                }
+            }
+            ExprStmtKind::Assign { name, expr: value } => {
+                if contains(value.extent(), offset) {
+                    self.walk_expr(value, offset);
+                } else {
+                    self.node = Some(Node::Local {
+                        name,
+                        span: expr.span,
+                    });
+                }
+            }
+            ExprStmtKind::Binary { lhs, rhs, .. } | ExprStmtKind::Logical { lhs, rhs, .. } => {
+                self.walk_expr(lhs, offset);
+                self.walk_expr(rhs, offset);
+            }
+            ExprStmtKind::Group(inner) | ExprStmtKind::Unary { rhs: inner, .. } => {
+                self.walk_expr(inner, offset)
+            }
+            ExprStmtKind::ArrayInit { elements } => self.walk_exprs(elements, offset),
+            ExprStmtKind::Index { expr: array, index } => {
+                self.walk_expr(array, offset);
+                self.walk_expr(index, offset);
+            }
+            ExprStmtKind::IndexSet { lhs, index, rhs } => {
+                self.walk_expr(lhs, offset);
+                self.walk_expr(index, offset);
+                self.walk_expr(rhs, offset);
+            }
+            ExprStmtKind::Substitution { callee, args } => {
+                self.walk_expr(callee, offset);
+                self.walk_exprs(args.iter().map(|(_, value)| value), offset);
+            }
+        }
+    }
+
+    fn walk_exprs(&mut self, exprs: impl IntoIterator<Item = &'a ExprStmt>, offset: usize) {
+        for expr in exprs {
+            self.walk_expr(expr, offset);
+        }
+    }
+}
+
+/// Returns the member of a group import containing the offset.
+fn innermost_path(path: &Path, offset: usize) -> &Path {
+    path.group_paths
+        .iter()
+        .find(|member| contains(member.span, offset))
+        .map(|member| innermost_path(member, offset))
+        .unwrap_or(path)
+}
+
+/// Whether the span contains the offset. The end is included so that a
+/// position right after a name still refers to it.
+fn contains(span: Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
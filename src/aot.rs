@@ -0,0 +1,114 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    codegen::module::MathicModule,
+    compiler::CompilerOpts,
+    diagnostics::CodegenError,
+    ffi,
+    lowering::ir::types::{MathicType, NumericTy},
+};
+
+/// Compiles the modules into native object files, one per module, inside
+/// `obj_dir`.
+///
+/// The module defining `entry` (e.g. `main::main`) also gets a C-ABI `main`
+/// calling it, so the objects can be linked into an executable. The entry
+/// must take no parameters and return an integer or a boolean.
+pub fn emit_objects(
+    modules: &[MathicModule],
+    compiler_options: &CompilerOpts,
+    entry: &str,
+    obj_dir: &Path,
+) -> Result<Vec<PathBuf>, CodegenError> {
+    // Nothing provides the host functions to an executable.
+    if let Some(name) = modules.iter().flat_map(|m| m.host_functions().keys()).min() {
+        return Err(CodegenError::HostFunctionNotAvailable(name.clone()));
+    }
+
+    let signature = modules
+        .iter()
+        .find_map(|m| m.signatures().get(entry))
+        .ok_or_else(|| CodegenError::EntryNotFound(entry.to_string()))?;
+
+    if !signature.params.is_empty()
+        || !matches!(
+            signature.return_ty,
+            MathicType::Numeric(NumericTy::Sint(_) | NumericTy::Uint(_)) | MathicType::Bool
+        )
+    {
+        return Err(CodegenError::InvalidEntry {
+            function_name: entry.to_string(),
+            signature: signature.to_string(),
+        });
+    }
+
+    // The exit code is the entry's result, which is only sign extended when
+    // it's signed.
+    let signed_result = matches!(signature.return_ty, MathicType::Numeric(NumericTy::Sint(_)));
+
+    fs::create_dir_all(obj_dir)?;
+
+    let entry_symbol = format!("mathic__{entry}");
+    let mut objects = Vec::with_capacity(modules.len());
+
+    for (idx, module) in modules.iter().enumerate() {
+        let obj_path = obj_dir.join(format!("module_{idx}.o"));
+
+        ffi::llvm::emit_object_file(
+            module.as_inner(),
+            compiler_options.opt_lvl.into(),
+            &entry_symbol,
+            signed_result,
+            &obj_path,
+        )?;
+
+        objects.push(obj_path);
+    }
+
+    Ok(objects)
+}
+
+/// Links object files into a native executable.
+///
+/// The system C compiler drives the linker, which can be overridden through
+/// the `CC` environment variable. The resulting executable only depends on
+/// the C runtime.
+pub fn link_executable(objects: &[PathBuf], output: &Path) -> Result<(), CodegenError> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let cc = env::var_os("CC").unwrap_or_else(|| "cc".into());
+    let result = Command::new(&cc)
+        .args(objects)
+        .arg("-lm")
+        .arg("-o")
+        .arg(output)
+        .output()?;
+
+    if !result.status.success() {
+        return Err(CodegenError::LinkerError(
+            String::from_utf8_lossy(&result.stderr).into_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Compiles the modules into a native executable at `output`, keeping the
+/// intermediate object files in `obj_dir`.
+pub fn build_executable(
+    modules: &[MathicModule],
+    compiler_options: &CompilerOpts,
+    entry: &str,
+    obj_dir: &Path,
+    output: &Path,
+) -> Result<(), CodegenError> {
+    let objects = emit_objects(modules, compiler_options, entry, obj_dir)?;
+
+    link_executable(&objects, output)
+}
@@ -1,4 +1,8 @@
-use std::{fs, path::PathBuf};
+use std::{
+    cell::OnceCell,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use ariadne::Source;
 use melior::{
@@ -23,10 +27,13 @@ use tracing::instrument;
 pub mod compiler_helper;
 mod dialect_integration;
 pub mod function_ctx;
+pub mod host;
 pub mod lvalue;
 pub mod module;
+pub mod packed;
 pub mod rvalue;
 pub mod terminator;
+pub mod trap;
 
 /// Struct that holds global infomation to the code generation.
 ///
@@ -35,11 +42,13 @@ pub mod terminator;
 /// **ctx**: MLIR Context, global to the whole compilation.
 /// **module**: MLIR Module, where we store the generated mlir code.
 /// **file_path**: the path to file being compiled.
+/// **source**: the file's source, read the first time a position is needed.
 pub struct MathicCodeGen<'ctx> {
     ctx: &'ctx Context,
     module: &'ctx Module<'ctx>,
     ir: &'ctx Ir,
     file_path: Option<PathBuf>,
+    source: OnceCell<Source>,
 }
 
 impl<'ctx> MathicCodeGen<'ctx> {
@@ -54,6 +63,7 @@ impl<'ctx> MathicCodeGen<'ctx> {
             module,
             ir,
             file_path,
+            source: OnceCell::new(),
         }
     }
 
@@ -63,10 +73,7 @@ impl<'ctx> MathicCodeGen<'ctx> {
     pub fn get_location(&self, span: Option<Span>) -> Result<Location<'ctx>, CodegenError> {
         Ok(
             if let (Some(path), Some(span)) = (self.file_path.as_ref(), span) {
-                let (_, line, column) = {
-                    let source = fs::read_to_string(path)?;
-                    Source::from(source).get_offset_line(span.start).unwrap()
-                };
+                let (_, line, column) = self.source(path)?.get_offset_line(span.start).unwrap();
                 Location::new(
                     self.ctx,
                     path.file_name().unwrap().to_str().unwrap(),
@@ -79,6 +86,17 @@ impl<'ctx> MathicCodeGen<'ctx> {
         )
     }
 
+    /// Returns the source of the file being compiled, reading it only once.
+    fn source(&self, path: &Path) -> Result<&Source, CodegenError> {
+        if let Some(source) = self.source.get() {
+            return Ok(source);
+        }
+
+        let source = Source::from(fs::read_to_string(path)?);
+
+        Ok(self.source.get_or_init(|| source))
+    }
+
     /// Code generation entrypoint.
     ///
     /// Populates the module for a compile unit.
@@ -102,6 +120,10 @@ impl<'ctx> MathicCodeGen<'ctx> {
                 )],
                 helper,
             )?;
+
+            if !func.is_external {
+                self.compile_packed_function(func)?;
+            }
         }
 
         tracing::info!("Code generation complete: {:?}", start.elapsed());
@@ -126,8 +148,8 @@ impl<'ctx> MathicCodeGen<'ctx> {
         let ty = self.get_type(func, ty_idx)?;
 
         Ok(match ty {
-            MathicType::Numeric(NumericTy::Sint(_) | NumericTy::Uint(_)) => {
-                IntegerType::new(self.ctx, ty.bit_width()).into()
+            MathicType::Numeric(int_ty @ (NumericTy::Sint(_) | NumericTy::Uint(_))) => {
+                IntegerType::new(self.ctx, int_ty.bit_width()).into()
             }
             MathicType::Numeric(NumericTy::Float(float_ty)) => match float_ty {
                 FloatTy::F32 => Type::float32(self.ctx),
@@ -147,6 +169,9 @@ impl<'ctx> MathicCodeGen<'ctx> {
                 symbolic::sym_expr_type(self.ctx, inner_type, inner_ty.is_signed())
             }
             MathicType::Void => Type::none(self.ctx),
+            MathicType::Array { elem, len } => {
+                llvm::r#type::array(self.get_compiled_type(func, elem)?, len as u32)
+            }
             MathicType::Adt { index, is_local } => {
                 let adt = if is_local {
                     func.get_adt(index)
@@ -4,7 +4,7 @@ use std::collections::HashSet;
 
 use melior::{
     Context,
-    dialect::{llvm, ods},
+    dialect::{arith::CmpiPredicate, llvm, ods, scf},
     helpers::{ArithBlockExt, BuiltinBlockExt, LlvmBlockExt},
     ir::{
         Attribute, Block, BlockLike, Location, Module, Region, Value,
@@ -93,6 +93,40 @@ impl DebugUtils {
         Ok(block.load(ctx, location, sym_addr, ptr_ty)?)
     }
 
+    /// Calls a function of the debug runtime. Only the JIT provides it, the
+    /// slots of an executable stay null, so the call is skipped then.
+    fn call_binding<'ctx, 'func>(
+        &mut self,
+        ctx: &'ctx Context,
+        module: &Module,
+        block: &'func Block<'ctx>,
+        binding: DebugBinding,
+        args: &[Value<'ctx, 'func>],
+    ) -> Result<(), CodegenError> {
+        let location = Location::unknown(ctx);
+        let func_ptr = self.build_function(ctx, module, block, binding)?;
+
+        let func_addr = block.append_op_result(
+            ods::llvm::ptrtoint(ctx, IntegerType::new(ctx, 64).into(), func_ptr, location).into(),
+        )?;
+        let null = block.const_int(ctx, location, 0, 64)?;
+        let is_set = block.cmpi(ctx, CmpiPredicate::Ne, func_addr, null, location)?;
+
+        let then_region = Region::new();
+        let then_block = then_region.append_block(Block::new(&[]));
+        let call_args = [func_ptr]
+            .into_iter()
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>();
+
+        then_block.append_operation(build_llvm_indirect_call(ctx, &call_args, &[])?);
+        then_block.append_operation(scf::r#yield(&[], location));
+
+        block.append_operation(scf::r#if(is_set, &[], then_region, Region::new(), location));
+
+        Ok(())
+    }
+
     pub fn debug_breakpoint_trap(
         &self,
         block: &Block,
@@ -140,11 +174,7 @@ impl DebugUtils {
         ptr: Value,
         len: Value,
     ) -> Result<(), CodegenError> {
-        let func_ptr = self.build_function(ctx, module, block, DebugBinding::Str)?;
-
-        block.append_operation(build_llvm_indirect_call(ctx, &[func_ptr, ptr, len], &[])?);
-
-        Ok(())
+        self.call_binding(ctx, module, block, DebugBinding::Str, &[ptr, len])
     }
 
     pub fn print_ptr(
@@ -154,11 +184,7 @@ impl DebugUtils {
         block: &Block,
         ptr: Value,
     ) -> Result<(), CodegenError> {
-        let func_ptr = self.build_function(ctx, module, block, DebugBinding::Ptr)?;
-
-        block.append_operation(build_llvm_indirect_call(ctx, &[func_ptr, ptr], &[])?);
-
-        Ok(())
+        self.call_binding(ctx, module, block, DebugBinding::Ptr, &[ptr])
     }
 
     pub fn print_number(
@@ -168,11 +194,7 @@ impl DebugUtils {
         block: &Block,
         val: Value,
     ) -> Result<(), CodegenError> {
-        let func_ptr = self.build_function(ctx, module, block, DebugBinding::Number)?;
-
-        block.append_operation(build_llvm_indirect_call(ctx, &[func_ptr, val], &[])?);
-
-        Ok(())
+        self.call_binding(ctx, module, block, DebugBinding::Number, &[val])
     }
 }
 
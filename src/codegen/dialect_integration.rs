@@ -99,6 +99,40 @@ pub mod symbolic {
                 .expect("valid operation")
         }
 
+        pub fn diff<'ctx>(
+            ctx: &'ctx Context,
+            location: Location<'ctx>,
+            expr: Value<'ctx, '_>,
+            sym_name: &str,
+        ) -> Operation<'ctx> {
+            let result_type = expr.r#type();
+            OperationBuilder::new("symbolic.diff", location)
+                .add_operands(&[expr])
+                .add_attributes(&[(
+                    Identifier::new(ctx, "sym"),
+                    StringAttribute::new(ctx, sym_name).into(),
+                )])
+                .add_results(&[result_type])
+                .build()
+                .expect("valid operation")
+        }
+
+        pub fn subs<'ctx>(
+            ctx: &'ctx Context,
+            location: Location<'ctx>,
+            expr: Value<'ctx, '_>,
+            sym_names: ArrayAttribute<'ctx>,
+            values: &[Value<'ctx, '_>],
+        ) -> Operation<'ctx> {
+            let result_type = expr.r#type();
+            OperationBuilder::new("symbolic.subs", location)
+                .add_operands(&[&[expr], values].concat())
+                .add_attributes(&[(Identifier::new(ctx, "syms"), sym_names.into())])
+                .add_results(&[result_type])
+                .build()
+                .expect("valid operation")
+        }
+
         pub fn eval<'ctx>(
             ctx: &'ctx Context,
             location: Location<'ctx>,
@@ -19,7 +19,8 @@ use crate::{
 /// ## Fields
 ///
 /// **locals**: variables defined within the function context. Stores either
-/// symbols or pointers to stack allocated variables.
+/// values holding symbolic expressions or pointers to stack allocated
+/// variables.
 /// **mlir_blocks**: the MLIR Blocks that the function will use.
 #[derive(Debug)]
 pub struct FunctionCtx<'this> {
@@ -136,8 +137,19 @@ impl MathicCodeGen<'_> {
 
         {
             // Allocate space for params and make them visible to the function.
+            // Params holding symbolic expressions are SSA values, they are
+            // used directly.
             for (i, _) in function_params.enumerate() {
                 let value = entry_block.arg(i)?;
+
+                if self
+                    .get_type(ir_func, ir_func.params_tys[i])?
+                    .holds_symbolic(self.ir, ir_func)
+                {
+                    fn_ctx.define_local(value, ir_func.params_tys[i]);
+                    continue;
+                }
+
                 let ptr = entry_block.alloca1(self.ctx, location, entry_block_params[i].0, 8)?;
 
                 entry_block.store(self.ctx, location, ptr, value)?;
@@ -0,0 +1,114 @@
+use melior::{
+    dialect::{arith, func, llvm, ods},
+    helpers::{BuiltinBlockExt, LlvmBlockExt},
+    ir::{
+        Attribute, Block, BlockLike, Location, Region, RegionLike, Type,
+        attribute::{FlatSymbolRefAttribute, StringAttribute, TypeAttribute},
+        r#type::IntegerType,
+    },
+};
+
+use crate::{
+    codegen::{MathicCodeGen, compiler_helper::build_llvm_indirect_call},
+    diagnostics::CodegenError,
+    lowering::ir::{function::Function, types::MathicType},
+};
+
+/// Returns the symbol of the global holding the pointer to a host function,
+/// which is written when the host registers it.
+pub fn host_fn_slot_symbol(function_name: &str) -> String {
+    format!("mathic__host__{function_name}")
+}
+
+impl MathicCodeGen<'_> {
+    /// Generates the body of a function declared with `extern df`, which calls
+    /// the pointer held by its slot.
+    ///
+    /// Booleans are passed to and returned from the host as bytes, since only
+    /// the lowest bit of an `i1` is defined. A function returning nothing
+    /// returns nothing from the host either.
+    pub fn compile_host_function<'ctx, 'func>(
+        &'func self,
+        region: &Region<'ctx>,
+        location: Location<'ctx>,
+        ir_func: &Function,
+        entry_block_params: &[(Type<'ctx>, Location<'ctx>)],
+    ) -> Result<(), CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let ptr_ty = llvm::r#type::pointer(self.ctx, 0);
+        let byte_ty: Type = IntegerType::new(self.ctx, 8).into();
+        let slot_symbol = host_fn_slot_symbol(&ir_func.name);
+
+        self.module.body().append_operation(
+            ods::llvm::mlir_global(
+                self.ctx,
+                Region::new(),
+                TypeAttribute::new(ptr_ty),
+                StringAttribute::new(self.ctx, &slot_symbol),
+                Attribute::parse(self.ctx, "#llvm.linkage<weak>").ok_or(
+                    melior::Error::AttributeParse("could not parse linkage attribute".to_string()),
+                )?,
+                location,
+            )
+            .into(),
+        );
+
+        let block = region.append_block(Block::new(entry_block_params));
+
+        let slot_addr = block.append_op_result(
+            ods::llvm::mlir_addressof(
+                self.ctx,
+                ptr_ty,
+                FlatSymbolRefAttribute::new(self.ctx, &slot_symbol),
+                location,
+            )
+            .into(),
+        )?;
+        let func_ptr = block.load(self.ctx, location, slot_addr, ptr_ty)?;
+
+        let mut args = vec![func_ptr];
+
+        for (idx, param_ty) in ir_func.params_tys.iter().enumerate() {
+            let arg = block.arg(idx)?;
+
+            args.push(if self.get_type(ir_func, *param_ty)?.is_bool() {
+                block.append_op_result(arith::extui(arg, byte_ty, location))?
+            } else {
+                arg
+            });
+        }
+
+        let return_ty = self.get_type(ir_func, ir_func.return_ty)?;
+
+        if return_ty == MathicType::Void {
+            block.append_operation(build_llvm_indirect_call(self.ctx, &args, &[])?);
+            block.append_operation(func::r#return(&[], location));
+
+            return Ok(());
+        }
+
+        let returns_bool = return_ty.is_bool();
+        let result_ty = if returns_bool {
+            byte_ty
+        } else {
+            self.get_compiled_type(ir_func, ir_func.return_ty)?
+        };
+
+        let mut result =
+            block.append_op_result(build_llvm_indirect_call(self.ctx, &args, &[result_ty])?)?;
+
+        if returns_bool {
+            result = block.append_op_result(arith::trunci(
+                result,
+                IntegerType::new(self.ctx, 1).into(),
+                location,
+            ))?;
+        }
+
+        block.append_operation(func::r#return(&[result], location));
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use melior::{
-    dialect::func,
-    helpers::{BuiltinBlockExt, GepIndex, LlvmBlockExt},
+    dialect::{func, llvm, ods},
+    helpers::{BuiltinBlockExt, LlvmBlockExt},
     ir::{
         Attribute, Block, BlockLike, Identifier, Region,
-        attribute::{StringAttribute, TypeAttribute},
+        attribute::{FlatSymbolRefAttribute, StringAttribute, TypeAttribute},
         r#type::FunctionType,
     },
 };
@@ -14,10 +14,7 @@ use crate::{
         function_ctx::FunctionCtx,
     },
     diagnostics::CodegenError,
-    lowering::ir::{
-        adts::Adt, function::Function, instruction::LValInstruct, types::MathicType,
-        value::ValueModifier,
-    },
+    lowering::ir::{function::Function, instruction::LValInstruct, types::MathicType},
 };
 
 impl MathicCodeGen<'_> {
@@ -42,7 +39,7 @@ impl MathicCodeGen<'_> {
                 let init_val = self.compile_rvalue(fn_ctx, block, init, helper)?;
                 let init_ty = self.get_type(fn_ctx.get_ir_func(), init.ty)?;
 
-                if init_ty.is_symbolic() {
+                if init_ty.holds_symbolic(self.ir, fn_ctx.get_ir_func()) {
                     fn_ctx.define_local(init_val, init.ty);
                     return Ok(());
                 }
@@ -68,47 +65,23 @@ impl MathicCodeGen<'_> {
                 let location = self.get_location(*span)?;
 
                 let val = self.compile_rvalue(fn_ctx, block, value, helper)?;
-                let val_ty = self.get_type(fn_ctx.get_ir_func(), value.ty)?;
+                let (local, ty_idx) = fn_ctx.get_local(*local_idx).expect("invalid local idx");
+                let local_ty = self.get_type(fn_ctx.get_ir_func(), ty_idx)?;
+
+                // Values holding symbolic expressions are SSA, assigning them
+                // or one of their fields replaces the local's value.
+                if local_ty.holds_symbolic(self.ir, fn_ctx.get_ir_func()) {
+                    let val = self.compile_modifiers_insert(
+                        fn_ctx, block, local, ty_idx, modifier, val, helper,
+                    )?;
 
-                if val_ty.is_symbolic() {
                     fn_ctx.assign_local(*local_idx, val);
                     return Ok(());
                 }
 
-                let (mut ptr, mut ty_idx) =
-                    fn_ctx.get_local(*local_idx).expect("invalid local idx");
-
-                for m in modifier {
-                    ptr = match m {
-                        ValueModifier::Field(idx) => match self
-                            .get_type(fn_ctx.get_ir_func(), ty_idx)?
-                        {
-                            MathicType::Adt { index, is_local } => {
-                                let adt = if is_local {
-                                    fn_ctx.get_ir_func().get_adt(index)
-                                } else {
-                                    self.ir.get_adt(index)
-                                }
-                                .ok_or(CodegenError::InvalidAdtIndex(index))?;
-
-                                match adt {
-                                    Adt::Struct(struct_adt) => {
-                                        let field_ty = struct_adt.fields[*idx].ty;
-                                        ty_idx = field_ty;
-                                        block.gep(
-                                            self.ctx,
-                                            location,
-                                            ptr,
-                                            &[GepIndex::Const(*idx as i32)],
-                                            self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?,
-                                        )?
-                                    }
-                                }
-                            }
-                            _ => unreachable!(),
-                        },
-                    };
-                }
+                let ptr = local;
+                let (ptr, _) =
+                    self.compile_modifiers_ptr(fn_ctx, block, ptr, ty_idx, modifier, helper)?;
 
                 block.store(self.ctx, location, ptr, val)?;
             }
@@ -130,6 +103,57 @@ impl MathicCodeGen<'_> {
 
                 fn_ctx.define_local(sym, *ty);
             }
+            LValInstruct::Global {
+                local_idx: _,
+                symbol,
+                ty,
+                is_definition,
+                span,
+            } => {
+                let location = self.get_location(*span)?;
+                let ptr_ty = llvm::r#type::pointer(self.ctx, 0);
+                let global_ty = self.get_compiled_type(fn_ctx.get_ir_func(), *ty)?;
+
+                // Without an initializer, the global is only declared.
+                let initializer = Region::new();
+
+                if *is_definition {
+                    let init_block = initializer.append_block(Block::new(&[]));
+                    let zero = init_block.append_op_result(
+                        ods::llvm::mlir_zero(self.ctx, global_ty, location).into(),
+                    )?;
+
+                    init_block.append_operation(llvm::r#return(Some(zero), location));
+                }
+
+                self.module.body().append_operation(
+                    ods::llvm::mlir_global(
+                        self.ctx,
+                        initializer,
+                        TypeAttribute::new(global_ty),
+                        StringAttribute::new(self.ctx, symbol),
+                        Attribute::parse(self.ctx, "#llvm.linkage<external>").ok_or(
+                            melior::Error::AttributeParse(
+                                "could not parse linkage attribute".to_string(),
+                            ),
+                        )?,
+                        location,
+                    )
+                    .into(),
+                );
+
+                let ptr = block.append_op_result(
+                    ods::llvm::mlir_addressof(
+                        self.ctx,
+                        ptr_ty,
+                        FlatSymbolRefAttribute::new(self.ctx, symbol),
+                        location,
+                    )
+                    .into(),
+                )?;
+
+                fn_ctx.define_local(ptr, *ty);
+            }
         }
 
         Ok(())
@@ -146,7 +170,11 @@ impl MathicCodeGen<'_> {
     {
         let location = self.get_location(None)?;
 
-        let return_ty = self.get_compiled_type(ir_func, ir_func.return_ty)?;
+        // Functions returning nothing have no results.
+        let return_tys = match self.get_type(ir_func, ir_func.return_ty)? {
+            MathicType::Void => Vec::new(),
+            _ => vec![self.get_compiled_type(ir_func, ir_func.return_ty)?],
+        };
         let mut params_types = Vec::with_capacity(ir_func.params_tys.len());
         let mut entry_block_params = Vec::with_capacity(ir_func.params_tys.len());
 
@@ -164,8 +192,11 @@ impl MathicCodeGen<'_> {
         // External functions have no body. MLIR needs every function that is
         // called to be declared in the module. Since the actual body is in an
         // external module, they have no body. Later, the linker will handle
-        // the resolution.
-        if !ir_func.is_external {
+        // the resolution. Host functions get a body forwarding the call to
+        // the host.
+        if ir_func.is_host {
+            self.compile_host_function(&region, location, ir_func, &entry_block_params)?;
+        } else if !ir_func.is_external {
             let (mut fn_ctx, mlir_blocks) =
                 self.create_fn_ctx(&region, location, ir_func, &entry_block_params)?;
 
@@ -188,7 +219,10 @@ impl MathicCodeGen<'_> {
                 // Block args must always refer to a valid local value.
                 for (idx, arg) in block.args.iter().enumerate() {
                     let block_arg = mlir_block.arg(idx)?;
-                    if self.get_type(ir_func, arg.ty)?.is_symbolic() {
+                    if self
+                        .get_type(ir_func, arg.ty)?
+                        .holds_symbolic(self.ir, ir_func)
+                    {
                         fn_ctx.assign_local(arg.local_idx, block_arg);
                     } else {
                         mlir_block.store(
@@ -216,7 +250,7 @@ impl MathicCodeGen<'_> {
         self.module.body().append_operation(func::func(
             self.ctx,
             StringAttribute::new(self.ctx, &format!("mathic__{}", ir_func.name)),
-            TypeAttribute::new(FunctionType::new(self.ctx, &params_types, &[return_ty]).into()),
+            TypeAttribute::new(FunctionType::new(self.ctx, &params_types, &return_tys).into()),
             region,
             attributes,
             location,
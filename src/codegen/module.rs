@@ -1,4 +1,7 @@
-use std::sync::OnceLock;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use melior::{
     Context,
@@ -11,11 +14,21 @@ use melior::{
 use crate::{
     compiler::OptLvl,
     diagnostics::CodegenError,
+    executor::FunctionSignature,
     ffi::llvm::{get_data_layout_rep, get_target_triple, initialize_llvm},
+    lowering::ir::{Ir, basic_block::Terminator, function::Function},
 };
 
 pub struct MathicModule<'ctx> {
     inner: Module<'ctx>,
+    /// Signatures of the functions the module defines, by name.
+    signatures: HashMap<String, FunctionSignature>,
+    /// Signatures of the functions declared with `extern df`, by name.
+    host_functions: HashMap<String, FunctionSignature>,
+    /// Names of the functions declared with `test df`, in order.
+    tests: Vec<String>,
+    /// Names of the functions each function calls, inner ones included.
+    callees: HashMap<String, HashSet<String>>,
 }
 
 unsafe impl Sync for MathicModule<'_> {}
@@ -51,6 +64,10 @@ impl<'ctx> MathicModule<'ctx> {
         Ok(Self {
             inner: Module::from_operation(op)
                 .ok_or(CodegenError::Custom("Could not create module".to_string()))?,
+            signatures: HashMap::new(),
+            host_functions: HashMap::new(),
+            tests: Vec::new(),
+            callees: HashMap::new(),
         })
     }
 
@@ -69,4 +86,71 @@ impl<'ctx> MathicModule<'ctx> {
     pub fn inner_owned(self) -> Module<'ctx> {
         self.inner
     }
+
+    pub fn signatures(&self) -> &HashMap<String, FunctionSignature> {
+        &self.signatures
+    }
+
+    pub fn host_functions(&self) -> &HashMap<String, FunctionSignature> {
+        &self.host_functions
+    }
+
+    pub fn tests(&self) -> &[String] {
+        &self.tests
+    }
+
+    pub fn callees(&self) -> &HashMap<String, HashSet<String>> {
+        &self.callees
+    }
+
+    /// Records the signatures of the functions defined by a Mathir, which of
+    /// them are tests, and the functions they call. Inner functions are
+    /// private, so they only appear as callees.
+    pub(crate) fn add_signatures(&mut self, ir: &Ir) {
+        for func in ir.get_functions().iter().filter(|f| !f.is_external) {
+            self.add_callees(func);
+
+            if let Some(signature) = FunctionSignature::of(ir, func) {
+                if func.is_host {
+                    self.host_functions
+                        .insert(func.name.clone(), signature.clone());
+                }
+
+                if func.is_test {
+                    self.tests.push(func.name.clone());
+                }
+
+                self.signatures.insert(func.name.clone(), signature);
+            }
+        }
+    }
+
+    fn add_callees(&mut self, func: &Function) {
+        let callees = func
+            .basic_blocks
+            .iter()
+            .filter_map(|block| match &block.terminator {
+                Terminator::Call { callee, .. } => Some(callee.clone()),
+                _ => None,
+            })
+            .collect();
+
+        self.callees.insert(func.name.clone(), callees);
+
+        for inner_func in func.get_inner_functions() {
+            self.add_callees(inner_func);
+        }
+    }
+
+    /// Returns a deep copy of the module.
+    pub fn try_clone(&self) -> Result<Self, CodegenError> {
+        Ok(Self {
+            inner: Module::from_operation((*self.inner.as_operation()).clone())
+                .ok_or(CodegenError::Custom("Could not clone module".to_string()))?,
+            signatures: self.signatures.clone(),
+            host_functions: self.host_functions.clone(),
+            tests: self.tests.clone(),
+            callees: self.callees.clone(),
+        })
+    }
 }
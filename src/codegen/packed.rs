@@ -0,0 +1,109 @@
+use melior::{
+    dialect::{arith, func, llvm},
+    helpers::{BuiltinBlockExt, GepIndex, LlvmBlockExt},
+    ir::{
+        Block, BlockLike, Identifier, Region, RegionLike, Type,
+        attribute::{FlatSymbolRefAttribute, StringAttribute, TypeAttribute},
+        r#type::{FunctionType, IntegerType},
+    },
+};
+
+use crate::{
+    codegen::MathicCodeGen,
+    diagnostics::CodegenError,
+    lowering::ir::{function::Function, types::MathicType},
+};
+
+/// Returns the symbol of the packed wrapper of a function.
+pub fn packed_fn_symbol(function_name: &str) -> String {
+    format!("mathic__packed__{function_name}")
+}
+
+impl MathicCodeGen<'_> {
+    /// Generates a wrapper of a function taking a single pointer to an array
+    /// of pointers, one to each argument followed by one to the result. It
+    /// lets the host call functions whose signature is only known at runtime.
+    ///
+    /// Functions taking or returning anything but numbers and booleans have
+    /// no wrapper. Like with host functions, booleans are read and written as
+    /// bytes.
+    pub fn compile_packed_function(&self, ir_func: &Function) -> Result<(), CodegenError> {
+        let mut tys = Vec::with_capacity(ir_func.params_tys.len() + 1);
+
+        for ty in ir_func.params_tys.iter().chain([&ir_func.return_ty]) {
+            let mathic_ty = self.get_type(ir_func, *ty)?;
+
+            if !matches!(mathic_ty, MathicType::Numeric(_) | MathicType::Bool) {
+                return Ok(());
+            }
+
+            tys.push((mathic_ty.is_bool(), self.get_compiled_type(ir_func, *ty)?));
+        }
+
+        let location = self.get_location(None)?;
+        let ptr_ty = llvm::r#type::pointer(self.ctx, 0);
+        let byte_ty: Type = IntegerType::new(self.ctx, 8).into();
+
+        let region = Region::new();
+        let block = region.append_block(Block::new(&[(ptr_ty, location)]));
+        let packed_args = block.arg(0)?;
+
+        // Returns the pointer held by the `idx`th slot of the array.
+        let slot = |idx: usize| {
+            let slot_addr = block.gep(
+                self.ctx,
+                location,
+                packed_args,
+                &[GepIndex::Const(idx as i32)],
+                ptr_ty,
+            )?;
+
+            block.load(self.ctx, location, slot_addr, ptr_ty)
+        };
+
+        let (return_ty, params_tys) = tys.split_last().expect("the return type was pushed");
+        let mut args = Vec::with_capacity(params_tys.len());
+
+        for (idx, (is_bool, ty)) in params_tys.iter().enumerate() {
+            let arg_ptr = slot(idx)?;
+
+            args.push(if *is_bool {
+                let byte = block.load(self.ctx, location, arg_ptr, byte_ty)?;
+                block.append_op_result(arith::trunci(byte, *ty, location))?
+            } else {
+                block.load(self.ctx, location, arg_ptr, *ty)?
+            });
+        }
+
+        let (returns_bool, result_ty) = *return_ty;
+        let mut result = block.append_op_result(func::call(
+            self.ctx,
+            FlatSymbolRefAttribute::new(self.ctx, &format!("mathic__{}", ir_func.name)),
+            &args,
+            &[result_ty],
+            location,
+        ))?;
+
+        if returns_bool {
+            result = block.append_op_result(arith::extui(result, byte_ty, location))?;
+        }
+
+        let result_ptr = slot(params_tys.len())?;
+        block.store(self.ctx, location, result_ptr, result)?;
+        block.append_operation(func::r#return(&[], location));
+
+        self.module.body().append_operation(func::func(
+            self.ctx,
+            StringAttribute::new(self.ctx, &packed_fn_symbol(&ir_func.name)),
+            TypeAttribute::new(FunctionType::new(self.ctx, &[ptr_ty], &[]).into()),
+            region,
+            &[(
+                Identifier::new(self.ctx, "sym_visibility"),
+                StringAttribute::new(self.ctx, "private").into(),
+            )],
+            location,
+        ));
+
+        Ok(())
+    }
+}
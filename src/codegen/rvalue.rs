@@ -1,7 +1,14 @@
 use melior::{
-    dialect::{arith::CmpiPredicate, llvm, ods},
-    helpers::{ArithBlockExt, BuiltinBlockExt, LlvmBlockExt},
-    ir::{Block, Value, ValueLike, attribute::StringAttribute, r#type::IntegerType},
+    dialect::{
+        arith::{self, CmpfPredicate, CmpiPredicate},
+        llvm, ods,
+    },
+    helpers::{ArithBlockExt, BuiltinBlockExt, GepIndex, LlvmBlockExt},
+    ir::{
+        Block, Location, Type, Value, ValueLike,
+        attribute::{ArrayAttribute, FloatAttribute, StringAttribute},
+        r#type::IntegerType,
+    },
 };
 
 use crate::{
@@ -44,6 +51,19 @@ impl MathicCodeGen<'_> {
             } => {
                 self.compile_symbolic_binop(fn_ctx, block, lhs, *op, rhs, *span, rvalue.ty, helper)
             }
+            RValueKind::SymbolicDiff {
+                expr,
+                sym_name,
+                span,
+                ..
+            } => self.compile_symbolic_diff(fn_ctx, block, expr, sym_name, *span, helper),
+            RValueKind::SymbolicSubs {
+                expr,
+                syms,
+                values,
+                span,
+                ..
+            } => self.compile_symbolic_subs(fn_ctx, block, expr, syms, values, *span, helper),
             RValueKind::Unary { op, rhs, span, .. } => {
                 self.compile_unary(fn_ctx, block, *op, rhs, *span, helper)
             }
@@ -61,7 +81,7 @@ impl MathicCodeGen<'_> {
         fn_ctx: &mut FunctionCtx<'func>,
         block: &'func Block<'ctx>,
         init_inst: &InitInstruct,
-        adt_ty_idx: TypeIndex,
+        ty_idx: TypeIndex,
         span: Span,
         helper: &mut CompilerHelper,
     ) -> Result<Value<'ctx, 'func>, CodegenError>
@@ -69,18 +89,19 @@ impl MathicCodeGen<'_> {
         'func: 'ctx,
     {
         let location = self.get_location(Some(span))?;
-        match init_inst {
-            InitInstruct::StructInit { fields } => {
-                let struct_ty = self.get_compiled_type(fn_ctx.get_ir_func(), adt_ty_idx)?;
-                let empty_struct = block.append_op_result(llvm::undef(struct_ty, location))?;
-                let fields_values = fields
-                    .iter()
-                    .map(|rv| self.compile_rvalue(fn_ctx, block, rv, helper))
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                Ok(block.insert_values(self.ctx, location, empty_struct, &fields_values)?)
-            }
-        }
+        let (InitInstruct::StructInit { fields: values }
+        | InitInstruct::ArrayInit { elements: values }) = init_inst;
+
+        // Both structs and arrays are aggregates, they are initialized by
+        // inserting each value in order.
+        let aggregate_ty = self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?;
+        let empty_aggregate = block.append_op_result(llvm::undef(aggregate_ty, location))?;
+        let values = values
+            .iter()
+            .map(|rv| self.compile_rvalue(fn_ctx, block, rv, helper))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(block.insert_values(self.ctx, location, empty_aggregate, &values)?)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -129,6 +150,10 @@ impl MathicCodeGen<'_> {
 
         let lhs_ty = self.get_type(fn_ctx.get_ir_func(), lhs.ty)?;
 
+        if lhs_ty.is_float() {
+            return self.compile_float_binop(block, lhs_val, op, rhs_val, location);
+        }
+
         Ok(match op {
             BinaryOp::Compare(cmp) => match cmp {
                 CmpOp::Eq => block.cmpi(self.ctx, CmpiPredicate::Eq, lhs_val, rhs_val, location)?,
@@ -194,6 +219,44 @@ impl MathicCodeGen<'_> {
         })
     }
 
+    fn compile_float_binop<'ctx, 'func>(
+        &'func self,
+        block: &'func Block<'ctx>,
+        lhs_val: Value<'ctx, 'func>,
+        op: BinaryOp,
+        rhs_val: Value<'ctx, 'func>,
+        location: Location<'ctx>,
+    ) -> Result<Value<'ctx, 'func>, CodegenError>
+    where
+        'func: 'ctx,
+    {
+        // Comparisons use ordered predicates, meaning they yield false if
+        // either of the operands is NaN.
+        let op = match op {
+            BinaryOp::Compare(cmp) => {
+                let predicate = match cmp {
+                    CmpOp::Eq => CmpfPredicate::Oeq,
+                    CmpOp::Ne => CmpfPredicate::One,
+                    CmpOp::Lt => CmpfPredicate::Olt,
+                    CmpOp::Le => CmpfPredicate::Ole,
+                    CmpOp::Gt => CmpfPredicate::Ogt,
+                    CmpOp::Ge => CmpfPredicate::Oge,
+                };
+
+                arith::cmpf(self.ctx, predicate, lhs_val, rhs_val, location)
+            }
+            BinaryOp::Arithmetic(arith_op) => match arith_op {
+                ArithOp::Add => arith::addf(lhs_val, rhs_val, location),
+                ArithOp::Sub => arith::subf(lhs_val, rhs_val, location),
+                ArithOp::Mul => arith::mulf(lhs_val, rhs_val, location),
+                ArithOp::Div => arith::divf(lhs_val, rhs_val, location),
+                ArithOp::Mod => arith::remf(lhs_val, rhs_val, location),
+            },
+        };
+
+        Ok(block.append_op_result(op)?)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn compile_symbolic_binop<'ctx, 'func>(
         &'func self,
@@ -226,6 +289,61 @@ impl MathicCodeGen<'_> {
         Ok(block.append_op_result(op)?)
     }
 
+    fn compile_symbolic_diff<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &mut FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        expr: &RValInstruct,
+        sym_name: &str,
+        span: Span,
+        helper: &mut CompilerHelper,
+    ) -> Result<Value<'ctx, 'func>, CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(Some(span))?;
+        let expr_val = self.compile_rvalue(fn_ctx, block, expr, helper)?;
+
+        Ok(block.append_op_result(symbolic::operation::diff(
+            self.ctx, location, expr_val, sym_name,
+        ))?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compile_symbolic_subs<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &mut FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        expr: &RValInstruct,
+        syms: &[String],
+        values: &[RValInstruct],
+        span: Span,
+        helper: &mut CompilerHelper,
+    ) -> Result<Value<'ctx, 'func>, CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(Some(span))?;
+        let expr_val = self.compile_rvalue(fn_ctx, block, expr, helper)?;
+
+        let syms = syms
+            .iter()
+            .map(|s| StringAttribute::new(self.ctx, s).into())
+            .collect::<Vec<_>>();
+        let values = values
+            .iter()
+            .map(|value| self.compile_rvalue(fn_ctx, block, value, helper))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(block.append_op_result(symbolic::operation::subs(
+            self.ctx,
+            location,
+            expr_val,
+            ArrayAttribute::new(self.ctx, &syms),
+            &values,
+        ))?)
+    }
+
     fn compile_unary<'func, 'ctx>(
         &'func self,
         fn_ctx: &mut FunctionCtx<'func>,
@@ -246,6 +364,9 @@ impl MathicCodeGen<'_> {
                 let k0 = block.const_int_from_type(self.ctx, location, 0, rhs_val.r#type())?;
                 block.andi(k0, rhs_val, location)?
             }
+            UnaryOp::Neg if self.get_type(fn_ctx.get_ir_func(), rhs.ty)?.is_float() => {
+                block.append_op_result(arith::negf(rhs_val, location))?
+            }
             UnaryOp::Neg => {
                 let k_neg_1 =
                     block.const_int_from_type(self.ctx, location, -1, rhs_val.r#type())?;
@@ -259,7 +380,7 @@ impl MathicCodeGen<'_> {
         fn_ctx: &mut FunctionCtx<'func>,
         block: &'func Block<'ctx>,
         value: &IRValue,
-        _helper: &mut CompilerHelper,
+        helper: &mut CompilerHelper,
     ) -> Result<Value<'ctx, 'func>, CodegenError>
     where
         'func: 'ctx,
@@ -272,45 +393,26 @@ impl MathicCodeGen<'_> {
                 local_idx,
                 modifier,
             } => {
-                let (ptr, mut ty_idx) = fn_ctx.get_local(*local_idx)?;
+                let (ptr, ty_idx) = fn_ctx.get_local(*local_idx)?;
+                let func = fn_ctx.get_ir_func();
+
+                // Structs holding symbolic expressions are SSA values as well.
+                if self.get_type(func, ty_idx)?.holds_symbolic(self.ir, func) {
+                    let (value, _) = self
+                        .compile_modifiers_extract(fn_ctx, block, ptr, ty_idx, modifier, helper)?;
+
+                    return Ok(value);
+                }
 
-                let mut val = block.load(
+                let (ptr, ty_idx) =
+                    self.compile_modifiers_ptr(fn_ctx, block, ptr, ty_idx, modifier, helper)?;
+
+                block.load(
                     self.ctx,
                     location,
                     ptr,
                     self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?,
-                )?;
-
-                for m in modifier {
-                    val = match m {
-                        ValueModifier::Field(idx) => match self
-                            .get_type(fn_ctx.get_ir_func(), ty_idx)?
-                        {
-                            MathicType::Adt { index, is_local } => {
-                                let adt = if is_local {
-                                    fn_ctx.get_ir_func().get_adt(index)
-                                } else {
-                                    self.ir.get_adt(index)
-                                }
-                                .ok_or(CodegenError::InvalidAdtIndex(index))?;
-
-                                match adt {
-                                    Adt::Struct(struct_adt) => {
-                                        let field_ty_idx = struct_adt.fields[*idx].ty;
-                                        ty_idx = field_ty_idx;
-                                        let mlir_ty =
-                                            self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?;
-                                        block
-                                            .extract_value(self.ctx, location, val, mlir_ty, *idx)?
-                                    }
-                                }
-                            }
-                            other => unreachable!("{}", other),
-                        },
-                    }
-                }
-
-                val
+                )?
             }
             IRValue::Const(const_expr) => match const_expr {
                 ConstExpr::Numeric(num_const) => match num_const {
@@ -330,8 +432,16 @@ impl MathicCodeGen<'_> {
                     NumericConst::U32(val) => block.const_int(self.ctx, location, val, 32)?,
                     NumericConst::U64(val) => block.const_int(self.ctx, location, val, 64)?,
                     NumericConst::U128(val) => block.const_int(self.ctx, location, val, 128)?,
-                    NumericConst::F32(_) => todo!(),
-                    NumericConst::F64(_) => todo!(),
+                    NumericConst::F32(val) => block.append_op_result(arith::constant(
+                        self.ctx,
+                        FloatAttribute::new(self.ctx, Type::float32(self.ctx), *val as f64).into(),
+                        location,
+                    ))?,
+                    NumericConst::F64(val) => block.append_op_result(arith::constant(
+                        self.ctx,
+                        FloatAttribute::new(self.ctx, Type::float64(self.ctx), *val).into(),
+                        location,
+                    ))?,
                 },
                 ConstExpr::Str(s) => {
                     // Str is a fixed size, null terminated array of bytes
@@ -367,4 +477,224 @@ impl MathicCodeGen<'_> {
             },
         })
     }
+
+    /// Computes the address of the value a chain of modifiers points to,
+    /// starting from the pointer of a local.
+    ///
+    /// Returns the pointer along with the type of the pointed value.
+    pub fn compile_modifiers_ptr<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &mut FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        mut ptr: Value<'ctx, 'func>,
+        mut ty_idx: TypeIndex,
+        modifier: &[ValueModifier],
+        helper: &mut CompilerHelper,
+    ) -> Result<(Value<'ctx, 'func>, TypeIndex), CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(None)?;
+
+        for m in modifier {
+            let aggregate_ty = self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?;
+
+            ptr = match (m, self.get_type(fn_ctx.get_ir_func(), ty_idx)?) {
+                (ValueModifier::Field(idx), MathicType::Adt { index, is_local }) => {
+                    ty_idx = self.get_field_ty(fn_ctx, index, is_local, *idx)?;
+
+                    block.gep(
+                        self.ctx,
+                        location,
+                        ptr,
+                        &[GepIndex::Const(0), GepIndex::Const(*idx as i32)],
+                        aggregate_ty,
+                    )?
+                }
+                (ValueModifier::Index(index), MathicType::Array { elem, .. }) => {
+                    let index_val = self.compile_value_use(fn_ctx, block, index, helper)?;
+
+                    ty_idx = elem;
+
+                    block.gep(
+                        self.ctx,
+                        location,
+                        ptr,
+                        &[GepIndex::Const(0), GepIndex::Value(index_val)],
+                        aggregate_ty,
+                    )?
+                }
+                (_, other) => unreachable!("{}", other),
+            };
+        }
+
+        Ok((ptr, ty_idx))
+    }
+
+    /// Reads the value a chain of modifiers points to, starting from a struct
+    /// held as an SSA value, i.e. one holding symbolic expressions.
+    ///
+    /// Returns the value along with its type.
+    pub fn compile_modifiers_extract<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &mut FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        mut value: Value<'ctx, 'func>,
+        mut ty_idx: TypeIndex,
+        modifier: &[ValueModifier],
+        helper: &mut CompilerHelper,
+    ) -> Result<(Value<'ctx, 'func>, TypeIndex), CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(None)?;
+
+        for (i, m) in modifier.iter().enumerate() {
+            match (m, self.get_type(fn_ctx.get_ir_func(), ty_idx)?) {
+                (ValueModifier::Field(idx), MathicType::Adt { index, is_local }) => {
+                    ty_idx = self.get_field_ty(fn_ctx, index, is_local, *idx)?;
+
+                    value = block.extract_value(
+                        self.ctx,
+                        location,
+                        value,
+                        self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?,
+                        *idx,
+                    )?;
+                }
+                // Arrays are indexed in memory, as they are everywhere else.
+                (ValueModifier::Index(_), _) => {
+                    let ptr = self.spill(fn_ctx, block, value, ty_idx)?;
+                    let (ptr, ty_idx) = self.compile_modifiers_ptr(
+                        fn_ctx,
+                        block,
+                        ptr,
+                        ty_idx,
+                        &modifier[i..],
+                        helper,
+                    )?;
+                    let value = block.load(
+                        self.ctx,
+                        location,
+                        ptr,
+                        self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?,
+                    )?;
+
+                    return Ok((value, ty_idx));
+                }
+                (_, other) => unreachable!("{}", other),
+            }
+        }
+
+        Ok((value, ty_idx))
+    }
+
+    /// Replaces the value a chain of modifiers points to, starting from a
+    /// struct held as an SSA value. Returns the new struct.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compile_modifiers_insert<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &mut FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        container: Value<'ctx, 'func>,
+        ty_idx: TypeIndex,
+        modifier: &[ValueModifier],
+        value: Value<'ctx, 'func>,
+        helper: &mut CompilerHelper,
+    ) -> Result<Value<'ctx, 'func>, CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(None)?;
+
+        let Some((m, rest)) = modifier.split_first() else {
+            return Ok(value);
+        };
+
+        match (m, self.get_type(fn_ctx.get_ir_func(), ty_idx)?) {
+            (ValueModifier::Field(idx), MathicType::Adt { index, is_local }) => {
+                let field_ty_idx = self.get_field_ty(fn_ctx, index, is_local, *idx)?;
+                let field = block.extract_value(
+                    self.ctx,
+                    location,
+                    container,
+                    self.get_compiled_type(fn_ctx.get_ir_func(), field_ty_idx)?,
+                    *idx,
+                )?;
+                let field = self.compile_modifiers_insert(
+                    fn_ctx,
+                    block,
+                    field,
+                    field_ty_idx,
+                    rest,
+                    value,
+                    helper,
+                )?;
+
+                Ok(block.insert_value(self.ctx, location, container, field, *idx)?)
+            }
+            // See `compile_modifiers_extract`.
+            (ValueModifier::Index(_), _) => {
+                let ptr = self.spill(fn_ctx, block, container, ty_idx)?;
+                let (elem_ptr, _) =
+                    self.compile_modifiers_ptr(fn_ctx, block, ptr, ty_idx, modifier, helper)?;
+
+                block.store(self.ctx, location, elem_ptr, value)?;
+
+                Ok(block.load(
+                    self.ctx,
+                    location,
+                    ptr,
+                    self.get_compiled_type(fn_ctx.get_ir_func(), ty_idx)?,
+                )?)
+            }
+            (_, other) => unreachable!("{}", other),
+        }
+    }
+
+    /// Stores a value in a new stack slot, returning its pointer.
+    fn spill<'ctx, 'func>(
+        &'func self,
+        fn_ctx: &FunctionCtx<'func>,
+        block: &'func Block<'ctx>,
+        value: Value<'ctx, 'func>,
+        ty_idx: TypeIndex,
+    ) -> Result<Value<'ctx, 'func>, CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(None)?;
+        let func = fn_ctx.get_ir_func();
+
+        let ptr = block.alloca1(
+            self.ctx,
+            location,
+            self.get_compiled_type(func, ty_idx)?,
+            self.get_type(func, ty_idx)?.align(self.ir, func),
+        )?;
+
+        block.store(self.ctx, location, ptr, value)?;
+
+        Ok(ptr)
+    }
+
+    /// Returns the type of a struct's field.
+    fn get_field_ty(
+        &self,
+        fn_ctx: &FunctionCtx<'_>,
+        index: usize,
+        is_local: bool,
+        field_idx: usize,
+    ) -> Result<TypeIndex, CodegenError> {
+        let adt = if is_local {
+            fn_ctx.get_ir_func().get_adt(index)
+        } else {
+            self.ir.get_adt(index)
+        }
+        .ok_or(CodegenError::InvalidAdtIndex(index))?;
+
+        match adt {
+            Adt::Struct(struct_adt) => Ok(struct_adt.fields[field_idx].ty),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use melior::{
-    dialect::{cf, func, llvm},
+    dialect::{cf, func, llvm, ods},
     helpers::{BuiltinBlockExt, LlvmBlockExt},
     ir::{
         Block, BlockLike, BlockRef, Location,
@@ -13,7 +13,7 @@ use crate::{
         function_ctx::FunctionCtx,
     },
     diagnostics::CodegenError,
-    lowering::ir::basic_block::Terminator,
+    lowering::ir::{basic_block::Terminator, types::MathicType},
 };
 
 impl MathicCodeGen<'_> {
@@ -85,6 +85,9 @@ impl MathicCodeGen<'_> {
             Terminator::Unreachable(span) => {
                 block.append_operation(llvm::unreachable(self.get_location(*span)?))
             }
+            Terminator::Trap { message, span } => {
+                return self.compile_trap(block, message, *span, helper);
+            }
             Terminator::Call {
                 callee,
                 args,
@@ -100,26 +103,56 @@ impl MathicCodeGen<'_> {
                     args_vals.push(self.compile_rvalue(fn_ctx, block, arg, helper)?);
                 }
 
-                let mlir_return_ty =
-                    self.get_compiled_type(fn_ctx.get_ir_func(), *return_ty_idx)?;
                 let return_ty = self.get_type(fn_ctx.get_ir_func(), *return_ty_idx)?;
-                let return_ptr = block.alloca1(
-                    self.ctx,
-                    unknown_location,
-                    mlir_return_ty,
-                    return_ty.align(self.ir, fn_ctx.get_ir_func()),
-                )?;
-                let return_value = block.append_op_result(func::call(
-                    self.ctx,
-                    FlatSymbolRefAttribute::new(self.ctx, &format!("mathic__{}", callee)),
-                    &args_vals,
-                    &[mlir_return_ty],
-                    self.get_location(*span)?,
-                ))?;
-
-                block.store(self.ctx, unknown_location, return_ptr, return_value)?;
-
-                fn_ctx.define_local(return_ptr, *return_ty_idx);
+                let callee = FlatSymbolRefAttribute::new(self.ctx, &format!("mathic__{}", callee));
+
+                if return_ty == MathicType::Void {
+                    block.append_operation(func::call(
+                        self.ctx,
+                        callee,
+                        &args_vals,
+                        &[],
+                        self.get_location(*span)?,
+                    ));
+
+                    // The call still defines its local, which is never read.
+                    let placeholder = block.append_op_result(
+                        ods::llvm::mlir_zero(
+                            self.ctx,
+                            llvm::r#type::pointer(self.ctx, 0),
+                            unknown_location,
+                        )
+                        .into(),
+                    )?;
+                    fn_ctx.define_local(placeholder, *return_ty_idx);
+                } else {
+                    let mlir_return_ty =
+                        self.get_compiled_type(fn_ctx.get_ir_func(), *return_ty_idx)?;
+                    let return_value = block.append_op_result(func::call(
+                        self.ctx,
+                        callee,
+                        &args_vals,
+                        &[mlir_return_ty],
+                        self.get_location(*span)?,
+                    ))?;
+
+                    // Values holding symbolic expressions are SSA, the call is
+                    // inlined later on by the symbolic-inline pass.
+                    if return_ty.holds_symbolic(self.ir, fn_ctx.get_ir_func()) {
+                        fn_ctx.define_local(return_value, *return_ty_idx);
+                    } else {
+                        let return_ptr = block.alloca1(
+                            self.ctx,
+                            unknown_location,
+                            mlir_return_ty,
+                            return_ty.align(self.ir, fn_ctx.get_ir_func()),
+                        )?;
+
+                        block.store(self.ctx, unknown_location, return_ptr, return_value)?;
+
+                        fn_ctx.define_local(return_ptr, *return_ty_idx);
+                    }
+                }
 
                 block.append_operation(cf::br(
                     &mlir_blocks[*dest_block],
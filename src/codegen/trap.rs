@@ -0,0 +1,115 @@
+use melior::{
+    dialect::{func, llvm, ods},
+    helpers::{ArithBlockExt, BuiltinBlockExt, LlvmBlockExt},
+    ir::{
+        Block, BlockLike, Identifier, Region,
+        attribute::{FlatSymbolRefAttribute, StringAttribute, TypeAttribute},
+        operation::OperationBuilder,
+        r#type::{FunctionType, IntegerType},
+    },
+};
+
+use crate::{
+    codegen::{MathicCodeGen, compiler_helper::CompilerHelper},
+    diagnostics::{CodegenError, json::line_column},
+    parser::Span,
+};
+
+/// The C library function writing the message of a trap, which both the
+/// JIT and executables resolve.
+const WRITE_SYMBOL: &str = "write";
+const STDERR_FD: i64 = 2;
+
+/// Whether the module declares [`WRITE_SYMBOL`] already.
+#[derive(Default)]
+struct TrapRuntime {
+    declared: bool,
+}
+
+impl MathicCodeGen<'_> {
+    /// Writes a message to stderr, prefixed with the position of `span`, and
+    /// traps, ending the program.
+    pub fn compile_trap<'ctx, 'func>(
+        &'func self,
+        block: &'func Block<'ctx>,
+        message: &str,
+        span: Option<Span>,
+        helper: &mut CompilerHelper,
+    ) -> Result<(), CodegenError>
+    where
+        'func: 'ctx,
+    {
+        let location = self.get_location(span)?;
+        let ptr_ty = llvm::r#type::pointer(self.ctx, 0);
+        let i32_ty = IntegerType::new(self.ctx, 32).into();
+        let i64_ty = IntegerType::new(self.ctx, 64).into();
+
+        let runtime = helper.get_or_insert(TrapRuntime::default);
+
+        if !runtime.declared {
+            runtime.declared = true;
+
+            self.module.body().append_operation(func::func(
+                self.ctx,
+                StringAttribute::new(self.ctx, WRITE_SYMBOL),
+                TypeAttribute::new(
+                    FunctionType::new(self.ctx, &[i32_ty, ptr_ty, i64_ty], &[i64_ty]).into(),
+                ),
+                Region::new(),
+                &[(
+                    Identifier::new(self.ctx, "sym_visibility"),
+                    StringAttribute::new(self.ctx, "private").into(),
+                )],
+                location,
+            ));
+        }
+
+        let message = match span {
+            Some(span) => format!("{}: {message}\n", self.source_position(span)?),
+            None => format!("{message}\n"),
+        };
+        let message_ty =
+            llvm::r#type::array(IntegerType::new(self.ctx, 8).into(), message.len() as u32);
+
+        let message_ptr = block.alloca1(self.ctx, location, message_ty, 1)?;
+        let message_val = block.append_op_result(
+            ods::llvm::mlir_constant(
+                self.ctx,
+                message_ty,
+                StringAttribute::new(self.ctx, &message).into(),
+                location,
+            )
+            .into(),
+        )?;
+
+        block.store(self.ctx, location, message_ptr, message_val)?;
+
+        let fd = block.const_int(self.ctx, location, STDERR_FD, 32)?;
+        let len = block.const_int(self.ctx, location, message.len(), 64)?;
+
+        block.append_operation(func::call(
+            self.ctx,
+            FlatSymbolRefAttribute::new(self.ctx, WRITE_SYMBOL),
+            &[fd, message_ptr, len],
+            &[i64_ty],
+            location,
+        ));
+        block.append_operation(OperationBuilder::new("llvm.intr.trap", location).build()?);
+        block.append_operation(llvm::unreachable(location));
+
+        Ok(())
+    }
+
+    /// Returns the `file:line:column` a span starts at, or its byte offset
+    /// when the source isn't in a file.
+    fn source_position(&self, span: Span) -> Result<String, CodegenError> {
+        Ok(match self.file_path.as_ref() {
+            Some(path) => {
+                let (line, column) = line_column(self.source(path)?.text(), span.start);
+
+                format!("{}:{line}:{column}", path.display())
+            }
+            None => format!("offset {}", span.start),
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use std::{io::Write, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf};
 
 use melior::{
     Context,
@@ -16,24 +16,62 @@ use std::{fs, path::Path};
 use crate::{
     MathicError, MathicResult,
     codegen::{MathicCodeGen, compiler_helper::CompilerHelper, module::MathicModule},
-    diagnostics::{CodegenError, CompilationError, DiagnosticsManager},
+    diagnostics::{CodegenError, CompilationError, DiagnosticsManager, LintLevels},
     ffi::{
         self,
         dialect_integration::symbolic_dialect::{
-            create_symbolic_extract_eval, create_symbolic_to_arith,
+            create_symbolic_expand, create_symbolic_extract_eval, create_symbolic_inline,
+            create_symbolic_simplify, create_symbolic_to_arith, import_symbolic_functions,
         },
+        llvm::LLVMArtifact,
     },
     loader::ModuleLoader,
-    lowering::{self, ir::Ir, lower_program},
+    lowering::{self, ir::Ir, lint::lint_program, lower_program},
     parser::MathicParser,
 };
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct CompilerOpts {
     pub opt_lvl: OptLvl,
-    pub dump_mathir: bool,
-    pub dump_mlir: bool,
-    pub dump_llvmir: bool,
+    /// Artifacts to write for every compiled module.
+    pub emit: Vec<EmitKind>,
+    /// Directory where artifacts are written. Each module gets its own files,
+    /// named after its path relative to the source root.
+    pub emit_dir: PathBuf,
+    /// Whether each lint is allowed, warns or fails the compilation.
+    pub lints: LintLevels,
+    /// Source roots of the project's dependencies, by the name their modules
+    /// are imported with.
+    pub dependencies: HashMap<String, PathBuf>,
+    /// Dependencies of each dependency, like `dependencies`, by its source
+    /// root. Each package only sees its own.
+    pub package_dependencies: HashMap<PathBuf, HashMap<String, PathBuf>>,
+}
+
+impl CompilerOpts {
+    fn emits(&self, kind: EmitKind) -> bool {
+        self.emit.contains(&kind)
+    }
+}
+
+/// An artifact the compiler can write for each module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitKind {
+    /// The module's MATHIR (`.mathir`).
+    Mathir,
+    /// The MLIR module as generated (`.mlir`) and after running the passes
+    /// (`.lowered.mlir`).
+    Mlir,
+    /// Same as [`EmitKind::Mlir`], in MLIR's bytecode format.
+    MlirBc,
+    /// LLVM IR (`.ll`).
+    LlvmIr,
+    /// LLVM bitcode (`.bc`).
+    LlvmBc,
+    /// Native assembly (`.s`).
+    Asm,
+    /// Native object file (`.o`).
+    Obj,
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -106,30 +144,84 @@ impl MathicCompiler {
     pub fn compile_project(
         &self,
         src_root: &Path,
-        compiler_options: CompilerOpts,
+        compiler_options: &CompilerOpts,
+    ) -> MathicResult<Vec<MathicModule<'_>>> {
+        self.compile_project_entry(src_root, Path::new("main.mth"), compiler_options)
+    }
+
+    /// Like [`MathicCompiler::compile_project`], starting at the module at
+    /// `entry`, relative to `src_root`, instead of `main.mth`. Used for
+    /// libraries and projects with several executables.
+    pub fn compile_project_entry(
+        &self,
+        src_root: &Path,
+        entry: &Path,
+        compiler_options: &CompilerOpts,
     ) -> MathicResult<Vec<MathicModule<'_>>> {
         self.diagnostics.clear()?;
 
         let mut loader = ModuleLoader::new(&self.diagnostics);
-        let compilation_unit = loader.load(src_root, PathBuf::from("main.mth"))?;
+
+        for (name, dependency_root) in &compiler_options.dependencies {
+            loader.add_dependency(name.clone(), dependency_root.clone());
+        }
+
+        for (package_root, dependencies) in &compiler_options.package_dependencies {
+            for (name, dependency_root) in dependencies {
+                loader.add_package_dependency(
+                    package_root.clone(),
+                    name.clone(),
+                    dependency_root.clone(),
+                );
+            }
+        }
+
+        let compilation_unit = loader.load(src_root, entry.to_path_buf())?;
 
         if self.diagnostics.has_errors()? {
             return Err(MathicError::CompilationFailed);
         }
 
+        // Artifacts are named after the module's path, which for modules of
+        // dependencies isn't under `src_root`.
+        let module_paths = compilation_unit
+            .keys()
+            .map(|path| (path.clone(), loader.module_path(src_root, path)))
+            .collect::<HashMap<_, _>>();
+
         let irs = compilation_unit
             .into_par_iter()
-            .map(|(path, p)| (path, lower_program(&p)))
+            .map(|(path, p)| {
+                let ir = lower_program(&p).map(|ir| {
+                    let warnings = lint_program(&ir);
+                    (ir, warnings)
+                });
+
+                (path, ir)
+            })
             .collect::<Vec<_>>();
 
         let mut lowered = Vec::with_capacity(irs.len());
 
         for (path, ir) in irs {
             match ir {
-                Ok(ir) => lowered.push((path, ir)),
-                Err(e) => self
-                    .diagnostics
-                    .report(path, CompilationError::Lowering(e))?,
+                Ok((ir, warnings)) => {
+                    for warning in warnings {
+                        self.diagnostics.report_lint(
+                            path.clone(),
+                            warning,
+                            &compiler_options.lints,
+                        )?;
+                    }
+
+                    lowered.push((path, ir));
+                }
+                Err(errors) => {
+                    for e in errors {
+                        self.diagnostics
+                            .report(path.clone(), CompilationError::Lowering(e))?;
+                    }
+                }
             }
         }
 
@@ -142,14 +234,35 @@ impl MathicCompiler {
             .map(|(path, ir)| {
                 (
                     path,
-                    self.compile_module(ir, src_root, path, compiler_options),
+                    self.generate_module(ir, Some(path), &module_paths[path], compiler_options),
                 )
             })
             .collect::<Vec<_>>();
 
+        let mut generated_modules = Vec::with_capacity(modules.len());
+
+        for (path, module) in modules {
+            if let Ok(module) = module {
+                generated_modules.push((path, module));
+            }
+        }
+
+        if self.diagnostics.has_errors()? {
+            return Err(MathicError::CompilationFailed);
+        }
+
+        Self::import_symbolic_functions(&generated_modules);
+
+        let modules = generated_modules
+            .into_par_iter()
+            .map(|(path, module)| {
+                self.compile_module(module, Some(path), &module_paths[path], compiler_options)
+            })
+            .collect::<Vec<_>>();
+
         let mut compiled_modules = Vec::with_capacity(modules.len());
 
-        for (_, module) in modules {
+        for module in modules {
             if let Ok(module) = module {
                 compiled_modules.push(module);
             }
@@ -162,29 +275,45 @@ impl MathicCompiler {
         Ok(compiled_modules)
     }
 
+    /// Makes the bodies of functions taking or returning symbolic expressions
+    /// available to every module calling them.
+    ///
+    /// Those functions are inlined into their callers by the `symbolic-inline`
+    /// pass. Importing a body may declare functions from a third module, so we
+    /// keep going until nothing else is imported.
+    fn import_symbolic_functions(modules: &[(&PathBuf, MathicModule<'_>)]) {
+        let mut imported = true;
+
+        while imported {
+            imported = false;
+
+            for (i, (_, dest)) in modules.iter().enumerate() {
+                for (j, (_, src)) in modules.iter().enumerate() {
+                    if i != j {
+                        imported |= import_symbolic_functions(dest.as_inner(), src.as_inner());
+                    }
+                }
+            }
+        }
+    }
+
     /// Handles the code generation of a Mathir.
-    fn compile_module<'func>(
+    ///
+    /// `file_path` is the source file the Mathir comes from, if any, while
+    /// `module_path` names the module's artifacts.
+    pub(crate) fn generate_module<'func>(
         &'func self,
         ir: &Ir,
-        src_root: &Path,
-        file_path: &Path,
-        compiler_options: CompilerOpts,
+        file_path: Option<&Path>,
+        module_path: &Path,
+        compiler_options: &CompilerOpts,
     ) -> MathicResult<MathicModule<'func>> {
-        let relative_path = file_path
-            .strip_prefix(src_root)
-            .unwrap_or(file_path)
-            .to_owned();
-
-        if compiler_options.dump_mathir {
-            let mathir_path = PathBuf::from("mathir_dumps")
-                .join(&relative_path)
-                .with_extension("mathir");
-
-            fs::create_dir_all(mathir_path.parent().unwrap())?;
+        let report_path = file_path.unwrap_or(module_path).to_path_buf();
 
-            let mut f_mathir = fs::File::create(mathir_path)?;
+        if compiler_options.emits(EmitKind::Mathir) {
+            let mathir_path = Self::artifact_path(compiler_options, module_path, "mathir")?;
 
-            write!(f_mathir, "{}", ir)?;
+            fs::write(mathir_path, ir.to_string())?;
         }
 
         // Generate Module.
@@ -193,59 +322,61 @@ impl MathicCompiler {
             Err(e) => {
                 return Err(self
                     .diagnostics
-                    .report_and_fail(file_path.to_path_buf(), CompilationError::Codegen(e)));
+                    .report_and_fail(report_path, CompilationError::Codegen(e)));
             }
         };
 
+        module.add_signatures(ir);
+
         {
             let codegen = MathicCodeGen::new(
                 &self.ctx,
                 ir,
                 module.as_inner(),
-                Some(file_path.to_path_buf()),
+                file_path.map(Path::to_path_buf),
             );
             let mut helper = CompilerHelper::new();
 
             if let Err(e) = codegen.generate_module(&mut helper) {
                 return Err(self
                     .diagnostics
-                    .report_and_fail(file_path.to_path_buf(), CompilationError::Codegen(e)));
+                    .report_and_fail(report_path, CompilationError::Codegen(e)));
             }
         }
 
-        if compiler_options.dump_mlir {
-            let mlir_path = PathBuf::from("mlir_dumps")
-                .join(format!("{}-dump-prepass", relative_path.display()))
-                .with_extension("mlir");
-
-            fs::create_dir_all(mlir_path.parent().unwrap())?;
-
-            let mut f_prepass_program = fs::File::create(mlir_path)?;
-
-            write!(f_prepass_program, "{}", module.inner_to_operation())?;
-        }
+        Self::emit_mlir(&module, compiler_options, module_path, "")?;
 
         debug_assert!(module.inner_to_operation().verify());
         tracing::debug!("Module crated successfully");
 
+        Ok(module)
+    }
+
+    /// Runs the passes over a generated module.
+    pub(crate) fn compile_module<'func>(
+        &'func self,
+        mut module: MathicModule<'func>,
+        file_path: Option<&Path>,
+        module_path: &Path,
+        compiler_options: &CompilerOpts,
+    ) -> MathicResult<MathicModule<'func>> {
+        let report_path = file_path.unwrap_or(module_path).to_path_buf();
+
         // Run Passes to the generated module.
         if let Err(e) = Self::run_passes(&self.ctx, module.as_inner_mut()) {
             return Err(self
                 .diagnostics
-                .report_and_fail(file_path.to_path_buf(), CompilationError::Codegen(e)));
+                .report_and_fail(report_path, CompilationError::Codegen(e)));
         }
 
         tracing::debug!("Passes ran successfully");
 
-        if compiler_options.dump_mlir {
-            let mlir_path = PathBuf::from("mlir_dumps")
-                .join(format!("{}-dump-postpass", relative_path.display()))
-                .with_extension("mlir");
-
-            fs::create_dir_all(mlir_path.parent().unwrap())?;
+        Self::emit_mlir(&module, compiler_options, module_path, "lowered.")?;
 
-            let mut f = fs::File::create(mlir_path).unwrap();
-            write!(f, "{}", module.inner_to_operation())?;
+        if let Err(e) = Self::emit_llvm(&module, compiler_options, module_path) {
+            return Err(self
+                .diagnostics
+                .report_and_fail(report_path, CompilationError::Codegen(e)));
         }
 
         Ok(module)
@@ -255,7 +386,7 @@ impl MathicCompiler {
     pub fn compile_path<'func>(
         &'func self,
         file_path: &Path,
-        compiler_options: CompilerOpts,
+        compiler_options: &CompilerOpts,
     ) -> MathicResult<MathicModule<'func>> {
         let source = fs::read_to_string(file_path)?;
 
@@ -267,92 +398,136 @@ impl MathicCompiler {
         &'func self,
         source: &str,
         file_path: Option<PathBuf>,
-        compiler_options: CompilerOpts,
+        compiler_options: &CompilerOpts,
     ) -> MathicResult<MathicModule<'func>> {
         self.diagnostics.clear()?;
 
         let path = file_path
             .clone()
             .unwrap_or_else(|| PathBuf::from("program"));
+        let module_path = PathBuf::from(path.file_name().unwrap_or("program".as_ref()));
 
         // Source code parsing.
         let ast = {
             let parser = MathicParser::new(source);
-            match parser.parse("program".to_string()) {
-                Ok(ast) => ast,
-                Err(e) => {
-                    self.diagnostics.report(path, CompilationError::Parse(e))?;
-                    return Err(MathicError::CompilationFailed);
+            let (ast, errors) = parser.parse("program".to_string());
+
+            if !errors.is_empty() {
+                for e in errors {
+                    self.diagnostics
+                        .report(path.clone(), CompilationError::Parse(e))?;
                 }
+
+                return Err(MathicError::CompilationFailed);
             }
+
+            ast
         };
 
         // AST lowering and semantic checks.
         let ir = match lowering::lower_program(&ast) {
             Ok(ir) => ir,
-            Err(e) => {
-                self.diagnostics
-                    .report(path, CompilationError::Lowering(e))?;
+            Err(errors) => {
+                for e in errors {
+                    self.diagnostics
+                        .report(path.clone(), CompilationError::Lowering(e))?;
+                }
+
                 return Err(MathicError::CompilationFailed);
             }
         };
 
-        if compiler_options.dump_mathir {
-            let mathir_path = PathBuf::from("program.mathir");
-
-            let mut f_mathir = fs::File::create(mathir_path)?;
+        for warning in lint_program(&ir) {
+            self.diagnostics
+                .report_lint(path.clone(), warning, &compiler_options.lints)?;
+        }
 
-            write!(f_mathir, "{}", ir)?;
+        if self.diagnostics.has_errors()? {
+            return Err(MathicError::CompilationFailed);
         }
 
-        // Generate Module.
-        let mut module = match MathicModule::new(&self.ctx, compiler_options.opt_lvl) {
-            Ok(module) => module,
-            Err(e) => {
-                self.diagnostics
-                    .report(path.clone(), CompilationError::Codegen(e))?;
-                return Err(MathicError::CompilationFailed);
-            }
-        };
+        let module =
+            self.generate_module(&ir, file_path.as_deref(), &module_path, compiler_options)?;
 
-        {
-            let codegen = MathicCodeGen::new(&self.ctx, &ir, module.as_inner(), file_path);
-            let mut helper = CompilerHelper::new();
+        self.compile_module(module, file_path.as_deref(), &module_path, compiler_options)
+    }
 
-            if let Err(e) = codegen.generate_module(&mut helper) {
-                self.diagnostics
-                    .report(path, CompilationError::Codegen(e))?;
-                return Err(MathicError::CompilationFailed);
-            }
+    /// Returns the path of a module's artifact, creating its parent
+    /// directories.
+    fn artifact_path(
+        compiler_options: &CompilerOpts,
+        module_path: &Path,
+        extension: &str,
+    ) -> std::io::Result<PathBuf> {
+        let path = compiler_options
+            .emit_dir
+            .join(module_path)
+            .with_extension(extension);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
 
-        if compiler_options.dump_mlir {
-            let file_path = PathBuf::from("dump-prepass.mlir");
-
-            let mut f_prepass_program = fs::File::create(file_path)?;
+        Ok(path)
+    }
 
-            write!(f_prepass_program, "{}", module.inner_to_operation())?;
+    /// Writes the requested MLIR artifacts of a module. The `stage` prefixes
+    /// the extension, telling apart the module before and after the passes.
+    fn emit_mlir(
+        module: &MathicModule,
+        compiler_options: &CompilerOpts,
+        module_path: &Path,
+        stage: &str,
+    ) -> MathicResult<()> {
+        if compiler_options.emits(EmitKind::Mlir) {
+            let mlir_path =
+                Self::artifact_path(compiler_options, module_path, &format!("{stage}mlir"))?;
+
+            fs::write(mlir_path, module.inner_to_operation().to_string())?;
         }
 
-        debug_assert!(module.inner_to_operation().verify());
-        tracing::debug!("Module crated successfully");
+        if compiler_options.emits(EmitKind::MlirBc) {
+            let mlirbc_path =
+                Self::artifact_path(compiler_options, module_path, &format!("{stage}mlirbc"))?;
 
-        // Run Passes to the generated module.
-        if let Err(e) = Self::run_passes(&self.ctx, module.as_inner_mut()) {
-            self.diagnostics
-                .report(path, CompilationError::Codegen(e))?;
-            return Err(MathicError::CompilationFailed);
+            fs::write(mlirbc_path, ffi::write_bytecode(module.as_inner()))?;
         }
 
-        tracing::debug!("Passes ran successfully");
+        Ok(())
+    }
 
-        if compiler_options.dump_mlir {
-            let file_path = PathBuf::from("dump.mlir");
-            let mut f = fs::File::create(file_path).unwrap();
-            write!(f, "{}", module.inner_to_operation()).unwrap();
+    /// Writes the requested LLVM artifacts of a lowered module.
+    fn emit_llvm(
+        module: &MathicModule,
+        compiler_options: &CompilerOpts,
+        module_path: &Path,
+    ) -> Result<(), CodegenError> {
+        // Native code is emitted last, since it may modify the LLVM module.
+        let artifacts = [
+            (EmitKind::LlvmIr, LLVMArtifact::Ir, "ll"),
+            (EmitKind::LlvmBc, LLVMArtifact::Bitcode, "bc"),
+            (EmitKind::Asm, LLVMArtifact::Assembly, "s"),
+            (EmitKind::Obj, LLVMArtifact::Object, "o"),
+        ]
+        .into_iter()
+        .filter(|(kind, ..)| compiler_options.emits(*kind))
+        .map(|(_, artifact, extension)| {
+            Ok((
+                artifact,
+                Self::artifact_path(compiler_options, module_path, extension)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, CodegenError>>()?;
+
+        if artifacts.is_empty() {
+            return Ok(());
         }
 
-        Ok(module)
+        ffi::llvm::emit_llvm_artifacts(
+            module.as_inner(),
+            compiler_options.opt_lvl.into(),
+            &artifacts,
+        )
     }
 
     fn run_passes(ctx: &Context, module: &mut Module) -> Result<(), CodegenError> {
@@ -361,6 +536,9 @@ impl MathicCompiler {
         pass_manager.enable_verifier(true);
         pass_manager.add_pass(create_canonicalizer());
         pass_manager.add_pass(create_scf_to_control_flow()); // needed because to_llvm doesn't include it.
+        pass_manager.add_pass(create_symbolic_inline());
+        pass_manager.add_pass(create_symbolic_expand());
+        pass_manager.add_pass(create_symbolic_simplify());
         pass_manager.add_pass(create_symbolic_extract_eval());
         pass_manager.add_pass(create_symbolic_to_arith());
         pass_manager.add_pass(create_to_llvm());
@@ -4,20 +4,27 @@ use std::{
     sync::Mutex,
 };
 
-use ariadne::{FnCache, ReportBuilder};
+use ariadne::{FnCache, Report, ReportBuilder, ReportKind};
+use serde::Serialize;
 use thiserror::Error;
 
 pub mod codegen;
+pub mod json;
+pub mod lint;
 pub mod lowering;
 pub mod parse;
 
 pub use codegen::CodegenError;
+pub use json::{JsonDiagnostic, SourceFiles};
+pub use lint::{Lint, LintLevel, LintLevels, LintWarning};
 pub use lowering::LoweringError;
 pub use parse::{LexError, ParseError, SyntaxError};
 
 use crate::{
     MathicError, MathicResult,
-    diagnostics::{lowering::format_lowering_error, parse::format_parse_error},
+    diagnostics::{
+        lint::format_lint_warning, lowering::format_lowering_error, parse::format_parse_error,
+    },
     parser::Span,
 };
 
@@ -30,12 +37,87 @@ pub enum CompilationError {
     Parse(#[from] ParseError),
     #[error(transparent)]
     Lowering(#[from] LoweringError),
+    /// A lint denied with `-D`.
+    #[error(transparent)]
+    Lint(#[from] LintWarning),
+}
+
+impl CompilationError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            CompilationError::Parse(e) => e.diagnostic(),
+            CompilationError::Lowering(e) => e.diagnostic(),
+            CompilationError::Lint(e) => Diagnostic {
+                severity: Severity::Error,
+                ..e.diagnostic()
+            },
+            CompilationError::Codegen(e) => Diagnostic {
+                severity: Severity::Error,
+                code: None,
+                title: "Codegen Error",
+                message: e.to_string(),
+                help: String::new(),
+                span: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The contents of a compilation error or warning, independent of how it is
+/// shown.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub title: &'static str,
+    pub message: String,
+    pub help: String,
+    /// Section of the source the error points to, if any.
+    pub span: Option<Span>,
 }
 
-/// Accumulates compilation errors.
+impl Diagnostic {
+    /// Builds a report of a diagnostic pointing to a source.
+    fn report<'a>(&self, file_path: &Path) -> ReportBuilder<'a, ReportSpan> {
+        let report_span = ReportSpan {
+            path: file_path.display().to_string(),
+            span: self.span.unwrap_or(Span::from(0..0)),
+        };
+
+        let (kind, color) = match self.severity {
+            Severity::Error => (ReportKind::Error, ariadne::Color::Red),
+            Severity::Warning => (ReportKind::Warning, ariadne::Color::Yellow),
+        };
+
+        let mut report = Report::build(kind, report_span.clone())
+            .with_message(self.title)
+            .with_label(
+                ariadne::Label::new(report_span)
+                    .with_color(color)
+                    .with_message(&self.message),
+            )
+            .with_help(&self.help);
+
+        if let Some(code) = self.code {
+            report = report.with_code(code);
+        }
+
+        report
+    }
+}
+
+/// Accumulates compilation errors and warnings.
 #[derive(Debug, Default)]
 pub struct DiagnosticsManager {
     errors: Mutex<Vec<(PathBuf, CompilationError)>>,
+    warnings: Mutex<Vec<(PathBuf, LintWarning)>>,
 }
 
 impl DiagnosticsManager {
@@ -65,6 +147,28 @@ impl DiagnosticsManager {
         MathicError::CompilationFailed
     }
 
+    /// Adds the finding of a lint, as a warning or as an error depending on
+    /// the lint's level.
+    pub fn report_lint(
+        &self,
+        file_path: PathBuf,
+        warning: LintWarning,
+        levels: &LintLevels,
+    ) -> MathicResult<()> {
+        match levels.level(warning.lint()) {
+            LintLevel::Allow => Ok(()),
+            LintLevel::Warn => {
+                self.warnings
+                    .lock()
+                    .map_err(|e| MathicError::LockPoisoned(e.to_string()))?
+                    .push((file_path, warning));
+
+                Ok(())
+            }
+            LintLevel::Deny => self.report(file_path, CompilationError::Lint(warning)),
+        }
+    }
+
     pub fn has_errors(&self) -> MathicResult<bool> {
         self.errors
             .lock()
@@ -79,11 +183,22 @@ impl DiagnosticsManager {
             .map(|errors| errors.len() as u32)
     }
 
+    pub fn warning_count(&self) -> MathicResult<u32> {
+        self.warnings
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))
+            .map(|warnings| warnings.len() as u32)
+    }
+
     pub fn clear(&self) -> MathicResult<()> {
         self.errors
             .lock()
             .map_err(|e| MathicError::LockPoisoned(e.to_string()))?
             .clear();
+        self.warnings
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))?
+            .clear();
 
         Ok(())
     }
@@ -101,14 +216,78 @@ impl DiagnosticsManager {
         Ok(f(&errors))
     }
 
+    /// Runs a function over the reported warnings.
+    pub fn with_warnings<R>(
+        &self,
+        f: impl FnOnce(&[(PathBuf, LintWarning)]) -> R,
+    ) -> MathicResult<R> {
+        let warnings = self
+            .warnings
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))?;
+
+        Ok(f(&warnings))
+    }
+
+    /// Serializes every warning and then every error as a JSON array, for
+    /// tools which can't parse the terminal reports.
+    pub fn to_json(&self) -> MathicResult<String> {
+        let warnings = self
+            .warnings
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))?;
+        let errors = self
+            .errors
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))?;
+
+        let mut sources = SourceFiles::new();
+
+        let diagnostics = warnings
+            .iter()
+            .map(|(file_path, warning)| (file_path, warning.diagnostic()))
+            .chain(
+                errors
+                    .iter()
+                    .map(|(file_path, error)| (file_path, error.diagnostic())),
+            )
+            .map(|(file_path, diagnostic)| {
+                JsonDiagnostic::new(file_path, diagnostic, sources.get(file_path))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::to_string(&diagnostics)?)
+    }
+
+    /// Prints every warning and then every error.
     pub fn print_all(&self) -> MathicResult<()> {
+        self.print(None)
+    }
+
+    /// Prints every warning and error against the given source, instead of
+    /// reading it from the reported file. Used for code which doesn't live in
+    /// a file, like REPL inputs.
+    pub fn print_all_with_source(&self, source: &str) -> MathicResult<()> {
+        self.print(Some(source))
+    }
+
+    fn print(&self, source: Option<&str>) -> MathicResult<()> {
+        let warnings = self
+            .warnings
+            .lock()
+            .map_err(|e| MathicError::LockPoisoned(e.to_string()))?;
+
+        for (file_path, warning) in warnings.iter() {
+            eprint_report(format_lint_warning(file_path, warning), source);
+        }
+
         let errors = self
             .errors
             .lock()
             .map_err(|e| MathicError::LockPoisoned(e.to_string()))?;
 
         for (file_path, error) in errors.iter() {
-            format_error(file_path, error);
+            print_error(file_path, error, source);
         }
 
         Ok(())
@@ -137,21 +316,31 @@ impl ariadne::Span for ReportSpan {
     }
 }
 
-fn eprint_report(report: ReportBuilder<ReportSpan>) {
-    report
-        .finish()
-        .eprint(FnCache::new(|p: &String| fs::read_to_string(p)))
-        .unwrap();
+fn eprint_report(report: ReportBuilder<ReportSpan>, source: Option<&str>) {
+    let report = report.finish();
+
+    match source {
+        Some(source) => report.eprint(FnCache::new(|_: &String| {
+            Ok::<_, std::io::Error>(source.to_string())
+        })),
+        None => report.eprint(FnCache::new(|p: &String| fs::read_to_string(p))),
+    }
+    .unwrap();
 }
 
 pub fn format_error(file_path: &Path, error: &CompilationError) {
+    print_error(file_path, error, None);
+}
+
+fn print_error(file_path: &Path, error: &CompilationError, source: Option<&str>) {
     match error {
         CompilationError::Parse(parse_error) => {
-            eprint_report(format_parse_error(file_path, parse_error))
+            eprint_report(format_parse_error(file_path, parse_error), source)
         }
         CompilationError::Lowering(lowering_error) => {
-            eprint_report(format_lowering_error(file_path, lowering_error))
+            eprint_report(format_lowering_error(file_path, lowering_error), source)
         }
+        CompilationError::Lint(_) => eprint_report(error.diagnostic().report(file_path), source),
         CompilationError::Codegen(e) => eprintln!("{}: error: {}", file_path.display(), e),
     }
 }
@@ -8,6 +8,8 @@ pub enum CodegenError {
     MeliorError(#[from] melior::Error),
     #[error("LLVM error: {0}")]
     LLVMError(String),
+    #[error("linker error: {0}")]
+    LinkerError(String),
     #[error(transparent)]
     ParseIntError(#[from] ParseIntError),
     #[error("Couldn't not parse attribute")]
@@ -18,6 +20,33 @@ pub enum CodegenError {
     #[error("Invalid ADT index")]
     InvalidAdtIndex(usize),
 
+    #[error("function '{0}' not found")]
+    FunctionNotFound(String),
+    #[error("host function '{0}' was not registered")]
+    HostFunctionNotRegistered(String),
+    #[error("host function '{0}' is only available through the JIT")]
+    HostFunctionNotAvailable(String),
+    #[error("no module defines the entry function '{0}'")]
+    EntryNotFound(String),
+    #[error(
+        "entry function '{function_name}' is {signature}, but must take no parameters and return an integer or a boolean"
+    )]
+    InvalidEntry {
+        function_name: String,
+        signature: String,
+    },
+    #[error("function '{function_name}' is {expected}, but was called as {found}")]
+    SignatureMismatch {
+        function_name: String,
+        expected: String,
+        found: String,
+    },
+    #[error("function '{function_name}' returned {value}, which doesn't fit in an i64")]
+    ReturnOutOfRange {
+        function_name: String,
+        value: String,
+    },
+
     #[error("{0}")]
     Custom(String),
 
@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::diagnostics::{Diagnostic, Severity};
+
+/// A diagnostic as serialized by `DiagnosticsManager::to_json`.
+///
+/// Lines and columns start at 1, columns count characters. They are missing
+/// when the diagnostic has no span or its source isn't known.
+#[derive(Debug, Serialize)]
+pub struct JsonDiagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub title: &'static str,
+    pub message: String,
+    pub help: String,
+    pub file: String,
+    pub span: Option<JsonSpan>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+}
+
+/// The sources of the files diagnostics are reported in, each one read once
+/// however many diagnostics it has.
+#[derive(Debug, Default)]
+pub struct SourceFiles {
+    sources: HashMap<PathBuf, Option<String>>,
+}
+
+impl SourceFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the source of a file, or `None` if it can't be read.
+    pub fn get(&mut self, file_path: &Path) -> Option<&str> {
+        self.sources
+            .entry(file_path.to_path_buf())
+            .or_insert_with(|| fs::read_to_string(file_path).ok())
+            .as_deref()
+    }
+}
+
+impl JsonDiagnostic {
+    /// Creates the diagnostic reported in `file_path`, whose source is
+    /// `source`, see [`SourceFiles`].
+    pub fn new(file_path: &Path, diagnostic: Diagnostic, source: Option<&str>) -> Self {
+        let span = diagnostic.span.map(|span| {
+            let start = source.map(|s| line_column(s, span.start));
+            let end = source.map(|s| line_column(s, span.end));
+
+            JsonSpan {
+                start: span.start,
+                end: span.end,
+                line: start.map(|(line, _)| line),
+                column: start.map(|(_, column)| column),
+                end_line: end.map(|(line, _)| line),
+                end_column: end.map(|(_, column)| column),
+            }
+        });
+
+        Self {
+            severity: diagnostic.severity,
+            code: diagnostic.code,
+            title: diagnostic.title,
+            message: diagnostic.message,
+            help: diagnostic.help,
+            file: file_path.display().to_string(),
+            span,
+        }
+    }
+}
+
+/// Returns the line and column of a byte offset, both starting at 1.
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let offset = (0..=offset.min(source.len()))
+        .rev()
+        .find(|i| source.is_char_boundary(*i))
+        .unwrap_or(0);
+    let before = &source[..offset];
+
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, fmt, path::Path, str::FromStr};
+
+use ariadne::ReportBuilder;
+use thiserror::Error;
+
+use crate::{
+    diagnostics::{Diagnostic, ReportSpan, Severity},
+    parser::Span,
+};
+
+/// A check of the lint pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedSymbols,
+    UnsubstitutedSymbols,
+    UnreachableCode,
+    UnusedAssignments,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariables,
+        Lint::UnusedSymbols,
+        Lint::UnsubstitutedSymbols,
+        Lint::UnreachableCode,
+        Lint::UnusedAssignments,
+    ];
+
+    /// The name used to refer to the lint from the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedSymbols => "unused_symbols",
+            Lint::UnsubstitutedSymbols => "unsubstituted_symbols",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::UnusedAssignments => "unused_assignments",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lint::ALL
+            .into_iter()
+            .find(|lint| lint.name() == s)
+            .ok_or_else(|| format!("unknown lint '{s}'"))
+    }
+}
+
+/// How the findings of a lint are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LintLevel {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+/// The level of every lint. Lints warn unless told otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    deny_warnings: bool,
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        match self.levels.get(&lint) {
+            Some(level) => *level,
+            None if self.deny_warnings => LintLevel::Deny,
+            None => LintLevel::Warn,
+        }
+    }
+
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    /// Turns every lint which wasn't given a level into an error.
+    pub fn deny_warnings(&mut self) {
+        self.deny_warnings = true;
+    }
+}
+
+/// A finding of the lint pass.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum LintWarning {
+    #[error("Unused variable '{name}'")]
+    UnusedVariable { name: String, span: Span },
+
+    #[error("Unused symbol '{name}'")]
+    UnusedSymbol { name: String, span: Span },
+
+    #[error("Symbol '{name}' is never substituted")]
+    UnsubstitutedSymbol { name: String, span: Span },
+
+    #[error("Unreachable code")]
+    UnreachableCode { span: Span },
+
+    #[error("Value assigned to '{name}' is never read")]
+    UnusedAssignment { name: String, span: Span },
+}
+
+impl LintWarning {
+    pub fn lint(&self) -> Lint {
+        match self {
+            LintWarning::UnusedVariable { .. } => Lint::UnusedVariables,
+            LintWarning::UnusedSymbol { .. } => Lint::UnusedSymbols,
+            LintWarning::UnsubstitutedSymbol { .. } => Lint::UnsubstitutedSymbols,
+            LintWarning::UnreachableCode { .. } => Lint::UnreachableCode,
+            LintWarning::UnusedAssignment { .. } => Lint::UnusedAssignments,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            LintWarning::UnusedVariable { span, .. }
+            | LintWarning::UnusedSymbol { span, .. }
+            | LintWarning::UnsubstitutedSymbol { span, .. }
+            | LintWarning::UnreachableCode { span }
+            | LintWarning::UnusedAssignment { span, .. } => *span,
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (code, help) = match self {
+            LintWarning::UnusedVariable { name, .. } => (
+                "W001",
+                format!("if this is intentional, prefix it with an underscore: '_{name}'"),
+            ),
+            LintWarning::UnusedSymbol { name, .. } => (
+                "W002",
+                format!("remove the declaration of '{name}' or prefix it with an underscore"),
+            ),
+            LintWarning::UnsubstitutedSymbol { name, .. } => (
+                "W003",
+                format!("give '{name}' a value with a substitution, e.g. 'e[{name}=1]'"),
+            ),
+            LintWarning::UnreachableCode { .. } => (
+                "W004",
+                "no path of the function reaches this code".to_string(),
+            ),
+            LintWarning::UnusedAssignment { name, .. } => (
+                "W005",
+                format!("'{name}' is overwritten or goes out of scope before being read"),
+            ),
+        };
+
+        Diagnostic {
+            severity: Severity::Warning,
+            code: Some(code),
+            title: "Warning",
+            message: format!("{self} ({})", self.lint()),
+            help,
+            span: Some(self.span()),
+        }
+    }
+}
+
+pub fn format_lint_warning<'w>(
+    file_path: &'w Path,
+    warning: &LintWarning,
+) -> ReportBuilder<'w, ReportSpan> {
+    warning.diagnostic().report(file_path)
+}
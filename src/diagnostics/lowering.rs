@@ -1,11 +1,15 @@
 use std::path::Path;
 
-use ariadne::{Report, ReportBuilder, ReportKind};
+use ariadne::ReportBuilder;
 use thiserror::Error;
 
-use crate::{diagnostics::ReportSpan, lowering::ir::types::MathicType, parser::Span};
+use crate::{
+    diagnostics::{Diagnostic, ReportSpan, Severity},
+    lowering::ir::types::MathicType,
+    parser::Span,
+};
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 pub enum LoweringError {
     #[error("Undeclared variable '{name}'")]
     UndeclaredVariable { name: String, span: Span },
@@ -61,95 +65,208 @@ pub enum LoweringError {
 
     #[error("The substitution is missing some symbols")]
     MissingSymbols { missing: String, span: Span },
+
+    #[error("Index {index} is out of bounds for an array of length {len}")]
+    IndexOutOfBounds {
+        index: usize,
+        len: usize,
+        span: Span,
+    },
+
+    #[error("Type '{ty}' cannot be indexed")]
+    NotIndexable { ty: MathicType, span: Span },
+
+    #[error("Type '{ty}' cannot be iterated")]
+    NotIterable { ty: MathicType, span: Span },
+
+    #[error("Expected a symbolic variable")]
+    NotASymbol { span: Span },
+
+    #[error("Symbol '{name}' returned by '{function}' conflicts with a variable")]
+    ConflictingSymbol {
+        name: String,
+        function: String,
+        span: Span,
+    },
+
+    #[error("Test function '{name}' can't take parameters or return a value")]
+    InvalidTestFunction { name: String, span: Span },
+
+    #[error("'{name}' is both a dependency and a module of the project")]
+    AmbiguousImport { name: String, span: Span },
+
+    #[error("Circular import: {}", cycle.join(" -> "))]
+    CircularImport {
+        /// The modules of the cycle, starting and ending with the same one.
+        cycle: Vec<String>,
+        /// Where each module of the cycle imports the next one.
+        imports: Vec<String>,
+        span: Span,
+    },
+
+    #[error("Literal '{literal}' doesn't fit in type '{ty}'")]
+    LiteralOutOfRange {
+        literal: String,
+        ty: MathicType,
+        span: Span,
+    },
+
+    #[error("Function '{name}' has the name of a builtin")]
+    BuiltinRedefinition { name: String, span: Span },
+
+    #[error("Mismatched array length")]
+    MismatchedArrayLength {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+}
+
+impl LoweringError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (code, help, span) = match self {
+            LoweringError::UndeclaredVariable { name, span } => (
+                "S001",
+                format!("declare '{}' with 'let' before using it", name),
+                span,
+            ),
+            LoweringError::DuplicateDeclaration { name, span } => (
+                "S002",
+                format!("'{}' is already declared in this scope", name),
+                span,
+            ),
+            LoweringError::WrongArgumentCount { expected, span, .. } => {
+                ("S003", format!("expected {} argument(s)", expected), span)
+            }
+            LoweringError::UndeclaredFunction { span, .. } => (
+                "S004",
+                "declare the function before calling it".to_string(),
+                span,
+            ),
+            LoweringError::UndeclaredType { span, .. } => {
+                ("S005", "declare it using it".to_string(), span)
+            }
+            LoweringError::UnsupportedFeature { span, feature } => {
+                ("S006", format!("{} is not yet implemented", feature), span)
+            }
+            LoweringError::MismatchedType {
+                span,
+                found,
+                expected,
+            } => ("S007", format!("expected: {expected}, got {found}"), span),
+            LoweringError::MismatchedReturnType {
+                expected,
+                found,
+                span,
+            } => (
+                "S008",
+                format!(
+                    "function expects return type '{}', found '{}'",
+                    expected, found
+                ),
+                span,
+            ),
+            LoweringError::UndeclaredStructField { span, .. } => {
+                ("S009", "check struct declaration".to_string(), span)
+            }
+            LoweringError::MissingStructFields { missing, span } => (
+                "S010",
+                format!("initialize the missing fields: {missing}"),
+                span,
+            ),
+            LoweringError::TypeRequiresTypeParameter { name, span } => (
+                "S011",
+                format!(
+                    "provide a type parameter if '{0}' requires one, otherwise remove the angle brackets",
+                    name
+                ),
+                span,
+            ),
+            LoweringError::UnResolvedPath { span, .. } => ("S012", "".to_string(), span),
+            LoweringError::SymbolNotInExpression { name, span } => (
+                "S013",
+                format!("'{name}' is not a symbol of the expression being substituted"),
+                span,
+            ),
+            LoweringError::MissingSymbols { missing, span } => (
+                "S014",
+                format!("provide a value for the missing symbols: {missing}"),
+                span,
+            ),
+            LoweringError::IndexOutOfBounds { len, span, .. } => {
+                ("S015", format!("indices must be lower than {len}"), span)
+            }
+            LoweringError::NotIndexable { span, .. } => {
+                ("S016", "only arrays can be indexed".to_string(), span)
+            }
+            LoweringError::NotIterable { span, .. } => (
+                "S022",
+                "loop over an array or a range such as '0..n'".to_string(),
+                span,
+            ),
+            LoweringError::MismatchedArrayLength {
+                expected,
+                found,
+                span,
+            } => (
+                "S017",
+                format!("expected {expected} element(s), got {found}"),
+                span,
+            ),
+            LoweringError::NotASymbol { span } => (
+                "S018",
+                "differentiate with respect to a variable declared with 'sym'".to_string(),
+                span,
+            ),
+            LoweringError::ConflictingSymbol { name, span, .. } => (
+                "S024",
+                format!("'{name}' must be a symbol of the same type, or not be declared at all"),
+                span,
+            ),
+            LoweringError::LiteralOutOfRange { ty, span, .. } => (
+                "S025",
+                format!("use a value within the range of '{ty}', or a wider type"),
+                span,
+            ),
+            LoweringError::BuiltinRedefinition { name, span } => (
+                "S026",
+                format!("rename the function, '{name}' always calls the builtin"),
+                span,
+            ),
+            LoweringError::InvalidTestFunction { span, .. } => (
+                "S019",
+                "tests are declared as 'test df name() { ... }'".to_string(),
+                span,
+            ),
+            LoweringError::AmbiguousImport { span, .. } => (
+                "S020",
+                "rename the module or the dependency".to_string(),
+                span,
+            ),
+            LoweringError::CircularImport { imports, span, .. } => (
+                "S021",
+                format!(
+                    "{}; move what the modules share to one they all import",
+                    imports.join(", ")
+                ),
+                span,
+            ),
+        };
+
+        Diagnostic {
+            severity: Severity::Error,
+            code: Some(code),
+            title: "Semantic Error",
+            message: self.to_string(),
+            help,
+            span: Some(*span),
+        }
+    }
 }
 
 pub fn format_lowering_error<'err>(
     file_path: &'err Path,
     error: &LoweringError,
 ) -> ReportBuilder<'err, ReportSpan> {
-    let path = file_path.display().to_string();
-
-    let msg = error.to_string();
-    let (code, help, span) = match error {
-        LoweringError::UndeclaredVariable { name, span } => (
-            "S001",
-            format!("declare '{}' with 'let' before using it", name),
-            span,
-        ),
-        LoweringError::DuplicateDeclaration { name, span } => (
-            "S002",
-            format!("'{}' is already declared in this scope", name),
-            span,
-        ),
-        LoweringError::WrongArgumentCount { expected, span, .. } => {
-            ("S003", format!("expected {} argument(s)", expected), span)
-        }
-        LoweringError::UndeclaredFunction { span, .. } => (
-            "S004",
-            "declare the function before calling it".to_string(),
-            span,
-        ),
-        LoweringError::UndeclaredType { span, .. } => {
-            ("S005", "declare it using it".to_string(), span)
-        }
-        LoweringError::UnsupportedFeature { span, feature } => {
-            ("S006", format!("{} is not yet implemented", feature), span)
-        }
-        LoweringError::MismatchedType {
-            span,
-            found,
-            expected,
-        } => ("S007", format!("expected: {expected}, got {found}"), span),
-        LoweringError::MismatchedReturnType {
-            expected,
-            found,
-            span,
-        } => (
-            "S008",
-            format!(
-                "function expects return type '{}', found '{}'",
-                expected, found
-            ),
-            span,
-        ),
-        LoweringError::UndeclaredStructField { span, .. } => {
-            ("S009", "check struct declaration".to_string(), span)
-        }
-        LoweringError::MissingStructFields { missing, span } => (
-            "S010",
-            format!("initialize the missing fields: {missing}"),
-            span,
-        ),
-        LoweringError::TypeRequiresTypeParameter { name, span } => (
-            "S011",
-            format!(
-                "provide a type parameter if '{0}' requires one, otherwise remove the angle brackets",
-                name
-            ),
-            span,
-        ),
-        LoweringError::UnResolvedPath { span, .. } => ("S012", "".to_string(), span),
-        LoweringError::SymbolNotInExpression { name, span } => (
-            "S013",
-            format!("'{name}' is not a symbol of the expression being substituted"),
-            span,
-        ),
-        LoweringError::MissingSymbols { missing, span } => (
-            "S014",
-            format!("provide a value for the missing symbols: {missing}"),
-            span,
-        ),
-    };
-
-    let report_span = ReportSpan { path, span: *span };
-
-    Report::build(ReportKind::Error, report_span.clone())
-        .with_code(code)
-        .with_message("Semantic Error")
-        .with_label(
-            ariadne::Label::new(report_span)
-                .with_color(ariadne::Color::Red)
-                .with_message(msg),
-        )
-        .with_help(help)
+    error.diagnostic().report(file_path)
 }
@@ -3,11 +3,11 @@ use std::{
     path::Path,
 };
 
-use ariadne::{Report, ReportBuilder, ReportKind};
+use ariadne::ReportBuilder;
 use thiserror::Error;
 
 use crate::{
-    diagnostics::ReportSpan,
+    diagnostics::{Diagnostic, ReportSpan, Severity},
     parser::{Span, lexer::SpannedToken, token::Token},
 };
 
@@ -119,54 +119,57 @@ impl LexError {
     }
 }
 
+impl ParseError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (code, message, span, help) = match self {
+            ParseError::Lexical(lex_error, span) => {
+                let (code, msg) = match lex_error {
+                    LexError::TokenError => ("L001", "unknown token".to_string()),
+                    LexError::InvalidCharacter(c) => {
+                        ("L002", format!("invalid character: '{}'", c))
+                    }
+                    LexError::UnterminatedString => ("L003", "unterminated string".to_string()),
+                    LexError::UnterminatedComment => ("L004", "unterminated comment".to_string()),
+                    LexError::InvalidNumber(n) => ("L005", format!("invalid number: {}", n)),
+                };
+                (code, msg, *span, "Lexical Error".to_string())
+            }
+            ParseError::Syntax(syntax_error) => match syntax_error {
+                SyntaxError::UnexpectedToken { found, expected } => (
+                    "E001",
+                    format!("expected {}, found '{}'", expected, found.lexeme),
+                    found.span,
+                    expected.help().to_string(),
+                ),
+                SyntaxError::UnexpectedEnd { span } => (
+                    "E002",
+                    "found an unexpected end of file".to_string(),
+                    *span,
+                    String::new(),
+                ),
+                SyntaxError::MissingToken { expected, span } => (
+                    "E003",
+                    format!("expected '{}'", expected),
+                    *span,
+                    format!("add '{}' here to complete the syntax", expected),
+                ),
+            },
+        };
+
+        Diagnostic {
+            severity: Severity::Error,
+            code: Some(code),
+            title: "Syntax Error",
+            message,
+            help,
+            span: Some(span),
+        }
+    }
+}
+
 pub fn format_parse_error<'err>(
     file_path: &Path,
     error: &ParseError,
 ) -> ReportBuilder<'err, ReportSpan> {
-    let path = file_path.display().to_string();
-
-    let (code, msg, span, help) = match error {
-        ParseError::Lexical(lex_error, span) => {
-            let (code, msg) = match lex_error {
-                LexError::TokenError => ("L001", "unknown token".to_string()),
-                LexError::InvalidCharacter(c) => ("L002", format!("invalid character: '{}'", c)),
-                LexError::UnterminatedString => ("L003", "unterminated string".to_string()),
-                LexError::UnterminatedComment => ("L004", "unterminated comment".to_string()),
-                LexError::InvalidNumber(n) => ("L005", format!("invalid number: {}", n)),
-            };
-            (code, msg, *span, "Lexical Error".to_string())
-        }
-        ParseError::Syntax(syntax_error) => match syntax_error {
-            SyntaxError::UnexpectedToken { found, expected } => (
-                "E001",
-                format!("expected {}, found '{}'", expected, found.lexeme),
-                found.span,
-                expected.help().to_string(),
-            ),
-            SyntaxError::UnexpectedEnd { span } => (
-                "E002",
-                "found an unexpected end of file".to_string(),
-                *span,
-                String::new(),
-            ),
-            SyntaxError::MissingToken { expected, span } => (
-                "E003",
-                format!("expected '{}'", expected),
-                *span,
-                format!("add '{}' here to complete the syntax", expected),
-            ),
-        },
-    };
-
-    let report_span = ReportSpan { path, span };
-
-    Report::build(ReportKind::Error, report_span.clone())
-        .with_code(code)
-        .with_message("Syntax Error")
-        .with_label(
-            ariadne::Label::new(report_span)
-                .with_color(ariadne::Color::Red)
-                .with_message(msg),
-        )
-        .with_help(help)
+    error.diagnostic().report(file_path)
 }
@@ -1,14 +1,42 @@
 use crate::diagnostics::CodegenError;
 
 pub mod jit;
+pub mod signature;
+
+pub use signature::{AbiBool, FunctionSignature, HostFn, MathicArgs, MathicValue, ScalarValue};
 
 pub trait MathicExecutor {
     /// Executes a function.
     ///
     /// Given a symbol_name (the name of the function to execute) the engine looks
-    /// for the associated function and executes it.
+    /// for the associated function and executes it. The function must take no
+    /// arguments and return a boolean or an integer which fits in an `i64`.
     fn call_function(&self, symbol_name: &str) -> Result<i64, CodegenError>;
 
+    /// Calls a function with the given arguments.
+    ///
+    /// The arguments and return type are checked against the function's
+    /// signature, e.g. `df f(a: i32, b: f64) i64` is called with
+    /// `call::<(i32, f64), i64>("mod::f", (1, 2.0))`.
+    fn call<A: MathicArgs, R: MathicValue>(
+        &self,
+        function_name: &str,
+        args: A,
+    ) -> Result<R, CodegenError>;
+
+    /// Calls a function with arguments whose types are only known at
+    /// runtime, e.g. `call_dynamic("mod::f", &[ScalarValue::I32(1),
+    /// ScalarValue::F64(2.0)])`. Only functions taking and returning numbers
+    /// and booleans can be called this way.
+    fn call_dynamic(
+        &self,
+        function_name: &str,
+        args: &[ScalarValue],
+    ) -> Result<ScalarValue, CodegenError>;
+
+    /// Returns the signature of a function, if it was registered.
+    fn signature(&self, function_name: &str) -> Option<FunctionSignature>;
+
     /// Returns a pointer associated to the given symbol name.
     ///
     /// if the symbol was registered, the engine will find it and return the
@@ -5,60 +5,361 @@ use llvm_sys::{
         lljit::{LLVMOrcDisposeLLJIT, LLVMOrcLLJITLookup, LLVMOrcLLJITRef},
     },
 };
-use std::mem;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    mem,
+};
 
 use crate::{
-    codegen::{compiler_helper::debugging, module::MathicModule},
+    codegen::{
+        compiler_helper::debugging, host::host_fn_slot_symbol, module::MathicModule,
+        packed::packed_fn_symbol,
+    },
     compiler::CompilerOpts,
     diagnostics::CodegenError,
-    executor::MathicExecutor,
+    executor::{FunctionSignature, HostFn, MathicArgs, MathicExecutor, MathicValue, ScalarValue},
     ffi,
+    lowering::ir::types::{MathicType, NumericTy, SintTy, UintTy},
 };
 
 /// A wrapper over melior's ExecutionEngine.
 pub struct MathicJITExecutor {
     engine: LLVMOrcLLJITRef,
+    /// Signatures of the functions of every module added, by name.
+    signatures: RefCell<HashMap<String, FunctionSignature>>,
+    /// Signatures of the functions declared with `extern df`, by name.
+    host_functions: RefCell<HashMap<String, FunctionSignature>>,
+    /// Host functions which weren't registered yet.
+    unregistered: RefCell<HashSet<String>>,
+    /// Names of the functions each function calls.
+    callees: RefCell<HashMap<String, HashSet<String>>>,
 }
 
 impl MathicJITExecutor {
     // Creates the LLJIT
     pub fn new(
         modules: Vec<MathicModule>,
-        compiler_options: CompilerOpts,
+        compiler_options: &CompilerOpts,
     ) -> Result<Self, CodegenError> {
+        let signatures = collect_signatures(&modules, MathicModule::signatures);
+        let host_functions = collect_signatures(&modules, MathicModule::host_functions);
+        let callees = collect_callees(&modules);
         let modules = modules
             .into_iter()
             .map(|m| m.inner_owned())
             .collect::<Vec<_>>();
-        let engine = ffi::llvm::create_llvm_jit(
-            &modules,
-            compiler_options.opt_lvl.into(),
-            compiler_options.dump_llvmir,
-        )?;
-        let executor = Self { engine };
+        let engine = ffi::llvm::create_llvm_jit(&modules, compiler_options.opt_lvl.into())?;
+        let executor = Self {
+            engine,
+            signatures: RefCell::new(signatures),
+            unregistered: RefCell::new(host_functions.keys().cloned().collect()),
+            host_functions: RefCell::new(host_functions),
+            callees: RefCell::new(callees),
+        };
 
         debugging::debug_utils_runtime::setup(|sym| executor.lookup_symbol(sym));
 
         Ok(executor)
     }
+
+    /// Adds modules to the JIT. They may call the functions of the modules
+    /// added before them.
+    pub fn add_modules(
+        &self,
+        modules: Vec<MathicModule>,
+        compiler_options: &CompilerOpts,
+    ) -> Result<(), CodegenError> {
+        let signatures = collect_signatures(&modules, MathicModule::signatures);
+        let host_functions = collect_signatures(&modules, MathicModule::host_functions);
+        let callees = collect_callees(&modules);
+        let modules = modules
+            .into_iter()
+            .map(|m| m.inner_owned())
+            .collect::<Vec<_>>();
+
+        ffi::llvm::add_modules_to_llvm_jit(self.engine, &modules, compiler_options.opt_lvl.into())?;
+
+        self.signatures.borrow_mut().extend(signatures);
+        self.unregistered
+            .borrow_mut()
+            .extend(host_functions.keys().cloned());
+        self.host_functions.borrow_mut().extend(host_functions);
+        self.callees.borrow_mut().extend(callees);
+
+        debugging::debug_utils_runtime::setup(|sym| self.lookup_symbol(sym));
+
+        Ok(())
+    }
+
+    /// Provides the implementation of a function declared with `extern df`.
+    ///
+    /// The function is named after the module declaring it, e.g. `extern df
+    /// log(x: i64) i64;` in `host.mth` is registered with
+    /// `register_host_fn("host::log", log as extern "C" fn(i64) -> i64)`.
+    /// Its signature is checked against the declaration. A host function
+    /// must be registered before calling any function which may call it.
+    pub fn register_host_fn<F: HostFn>(
+        &self,
+        function_name: &str,
+        host_fn: F,
+    ) -> Result<(), CodegenError> {
+        // `F` has the signature it claims.
+        unsafe { self.register_host_fn_dynamic(function_name, F::signature(), host_fn.as_ptr()) }
+    }
+
+    /// Provides the implementation of a function declared with `extern df`,
+    /// whose signature is only known at runtime. See `register_host_fn`.
+    ///
+    /// # Safety
+    ///
+    /// `host_fn` must point to an `extern "C"` function with the given
+    /// signature, living as long as the executor.
+    pub unsafe fn register_host_fn_dynamic(
+        &self,
+        function_name: &str,
+        found: FunctionSignature,
+        host_fn: *const (),
+    ) -> Result<(), CodegenError> {
+        let expected = self
+            .host_functions
+            .borrow()
+            .get(function_name)
+            .cloned()
+            .ok_or_else(|| CodegenError::FunctionNotFound(function_name.to_string()))?;
+
+        if expected != found {
+            return Err(CodegenError::SignatureMismatch {
+                function_name: function_name.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+        }
+
+        let slot_symbol = host_fn_slot_symbol(function_name);
+        let slot = self
+            .lookup_symbol(&slot_symbol)
+            .ok_or(CodegenError::LLVMError(format!(
+                "symbol '{slot_symbol}' not found"
+            )))?;
+
+        unsafe {
+            *slot.cast::<*const ()>() = host_fn;
+        }
+
+        self.unregistered.borrow_mut().remove(function_name);
+
+        Ok(())
+    }
+
+    /// Runs a test, or any function taking and returning nothing. A failed
+    /// assertion traps, ending the process, so tests are best run in a
+    /// process of their own.
+    pub fn run_test(&self, test_name: &str) -> Result<(), CodegenError> {
+        let expected = self
+            .signature(test_name)
+            .ok_or_else(|| CodegenError::FunctionNotFound(test_name.to_string()))?;
+        let found = FunctionSignature {
+            params: Vec::new(),
+            return_ty: MathicType::Void,
+        };
+
+        if expected != found {
+            return Err(CodegenError::SignatureMismatch {
+                function_name: test_name.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+        }
+
+        let func_ptr = self.lookup_callable(test_name, &format!("mathic__{test_name}"))?;
+
+        // The signature was checked above.
+        let func: extern "C" fn() = unsafe { mem::transmute(func_ptr) };
+        func();
+
+        Ok(())
+    }
+
+    /// Looks up a function which is about to be called, through the symbol
+    /// `symbol_name`.
+    fn lookup_callable(
+        &self,
+        function_name: &str,
+        symbol_name: &str,
+    ) -> Result<*mut (), CodegenError> {
+        // A host function which wasn't registered would be a null pointer.
+        if let Some(name) = self.unregistered_reachable(function_name) {
+            return Err(CodegenError::HostFunctionNotRegistered(name));
+        }
+
+        self.lookup_symbol(symbol_name)
+            .ok_or(CodegenError::LLVMError(format!(
+                "symbol '{symbol_name}' not found"
+            )))
+    }
+
+    /// Returns the first host function, by name, which wasn't registered and
+    /// may be called by `function_name`, either directly or through other
+    /// functions.
+    fn unregistered_reachable(&self, function_name: &str) -> Option<String> {
+        let unregistered = self.unregistered.borrow();
+
+        if unregistered.is_empty() {
+            return None;
+        }
+
+        let callees = self.callees.borrow();
+        let mut visited = HashSet::from([function_name]);
+        let mut pending = vec![function_name];
+
+        while let Some(name) = pending.pop() {
+            for callee in callees.get(name).into_iter().flatten() {
+                if visited.insert(callee) {
+                    pending.push(callee);
+                }
+            }
+        }
+
+        visited
+            .into_iter()
+            .filter(|name| unregistered.contains(*name))
+            .min()
+            .map(str::to_string)
+    }
 }
 
 impl MathicExecutor for MathicJITExecutor {
     /// Executes a function.
     ///
     /// Given a symbol_name (the name of the function to execute) the engine looks
-    /// for the associated function and executes it.
+    /// for the associated function and executes it. The function must take no
+    /// arguments and return a boolean or an integer which fits in an `i64`.
     fn call_function(&self, symbol_name: &str) -> Result<i64, CodegenError> {
-        let func: fn() -> i64 = unsafe {
-            mem::transmute(
-                self.lookup_symbol(&format!("mathic__{}", symbol_name))
-                    .ok_or(CodegenError::LLVMError(format!(
-                        "symbol 'mathic__{symbol_name}' not found"
-                    )))?,
-            )
+        let signature = self
+            .signature(symbol_name)
+            .ok_or_else(|| CodegenError::FunctionNotFound(symbol_name.to_string()))?;
+
+        macro_rules! call_as {
+            ($ty:ty) => {
+                i64::from(self.call::<(), $ty>(symbol_name, ())?)
+            };
+            // For types which may not fit in an `i64`.
+            (checked $ty:ty) => {{
+                let value = self.call::<(), $ty>(symbol_name, ())?;
+
+                i64::try_from(value).map_err(|_| CodegenError::ReturnOutOfRange {
+                    function_name: symbol_name.to_string(),
+                    value: value.to_string(),
+                })?
+            }};
+        }
+
+        Ok(match signature.return_ty {
+            MathicType::Bool => call_as!(bool),
+            MathicType::Numeric(NumericTy::Sint(sint)) => match sint {
+                SintTy::Isize => call_as!(checked isize),
+                SintTy::I8 => call_as!(i8),
+                SintTy::I16 => call_as!(i16),
+                SintTy::I32 => call_as!(i32),
+                SintTy::I64 => self.call::<(), i64>(symbol_name, ())?,
+                SintTy::I128 => call_as!(checked i128),
+            },
+            MathicType::Numeric(NumericTy::Uint(uint)) => match uint {
+                UintTy::Usize => call_as!(checked usize),
+                UintTy::U8 => call_as!(u8),
+                UintTy::U16 => call_as!(u16),
+                UintTy::U32 => call_as!(u32),
+                UintTy::U64 => call_as!(checked u64),
+                UintTy::U128 => call_as!(checked u128),
+            },
+            // Other values aren't integers, so the call reports the mismatch.
+            _ => self.call::<(), i64>(symbol_name, ())?,
+        })
+    }
+
+    /// Calls a function with the given arguments.
+    ///
+    /// The arguments and return type are checked against the function's
+    /// signature, e.g. `df f(a: i32, b: f64) i64` is called with
+    /// `call::<(i32, f64), i64>("mod::f", (1, 2.0))`.
+    fn call<A: MathicArgs, R: MathicValue>(
+        &self,
+        function_name: &str,
+        args: A,
+    ) -> Result<R, CodegenError> {
+        let expected = self
+            .signature(function_name)
+            .ok_or_else(|| CodegenError::FunctionNotFound(function_name.to_string()))?;
+        let found = FunctionSignature {
+            params: A::mathic_types(),
+            return_ty: R::mathic_type(),
         };
 
-        Ok(func())
+        if expected != found {
+            return Err(CodegenError::SignatureMismatch {
+                function_name: function_name.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+        }
+
+        let func_ptr = self.lookup_callable(function_name, &format!("mathic__{function_name}"))?;
+
+        // The signature was checked above.
+        Ok(unsafe { args.call(func_ptr) })
+    }
+
+    /// Calls a function with arguments whose types are only known at
+    /// runtime. They are checked against the function's signature, which
+    /// must only have numbers and booleans.
+    fn call_dynamic(
+        &self,
+        function_name: &str,
+        args: &[ScalarValue],
+    ) -> Result<ScalarValue, CodegenError> {
+        let expected = self
+            .signature(function_name)
+            .ok_or_else(|| CodegenError::FunctionNotFound(function_name.to_string()))?;
+        let found = FunctionSignature {
+            params: args.iter().map(ScalarValue::mathic_type).collect(),
+            return_ty: expected.return_ty,
+        };
+
+        if expected != found {
+            return Err(CodegenError::SignatureMismatch {
+                function_name: function_name.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+        }
+
+        let mut result = ScalarValue::zero(expected.return_ty).ok_or_else(|| {
+            CodegenError::Custom(format!(
+                "function '{function_name}' doesn't return a number or a boolean"
+            ))
+        })?;
+
+        let func_ptr = self.lookup_callable(function_name, &packed_fn_symbol(function_name))?;
+
+        // The packed wrapper reads each argument and writes the result
+        // through the array of pointers.
+        let mut args = args.to_vec();
+        let mut packed_args = args
+            .iter_mut()
+            .map(ScalarValue::as_mut_ptr)
+            .chain([result.as_mut_ptr()])
+            .collect::<Vec<_>>();
+
+        let func: extern "C" fn(*mut *mut ()) = unsafe { mem::transmute(func_ptr) };
+        func(packed_args.as_mut_ptr());
+
+        Ok(result)
+    }
+
+    /// Returns the signature of a function, if it was registered.
+    fn signature(&self, function_name: &str) -> Option<FunctionSignature> {
+        self.signatures.borrow().get(function_name).cloned()
     }
 
     /// Returns a pointer associated to the given symbol name.
@@ -81,6 +382,17 @@ impl MathicExecutor for MathicJITExecutor {
     }
 }
 
+fn collect_signatures(
+    modules: &[MathicModule],
+    signatures: impl Fn(&MathicModule) -> &HashMap<String, FunctionSignature>,
+) -> HashMap<String, FunctionSignature> {
+    modules.iter().flat_map(|m| signatures(m).clone()).collect()
+}
+
+fn collect_callees(modules: &[MathicModule]) -> HashMap<String, HashSet<String>> {
+    modules.iter().flat_map(|m| m.callees().clone()).collect()
+}
+
 impl Drop for MathicJITExecutor {
     fn drop(&mut self) {
         unsafe {
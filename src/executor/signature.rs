@@ -0,0 +1,347 @@
+use std::{fmt, mem};
+
+use crate::lowering::ir::{
+    Ir,
+    function::Function,
+    symbols::TypeIndex,
+    types::{FloatTy, MathicType, NumericTy, SintTy, UintTy},
+};
+
+/// The parameter and return types of a function, as recorded in MATHIR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    pub params: Vec<MathicType>,
+    pub return_ty: MathicType,
+}
+
+impl FunctionSignature {
+    /// Returns the signature of a function, if all of its types are known.
+    pub(crate) fn of(ir: &Ir, func: &Function) -> Option<Self> {
+        let resolve = |ty: TypeIndex| {
+            if ty.is_local {
+                func.get_type(ty.idx)
+            } else {
+                ir.get_type(ty.idx)
+            }
+        };
+
+        Some(Self {
+            params: func
+                .params_tys
+                .iter()
+                .map(|ty| resolve(*ty))
+                .collect::<Option<_>>()?,
+            return_ty: resolve(func.return_ty)?,
+        })
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "df({}) {}", params.join(", "), self.return_ty)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A Rust type which can be passed to or returned from a Mathic function.
+///
+/// Implemented for the integer and float types, and for `bool` and `AbiBool`.
+pub trait MathicValue: sealed::Sealed + Copy {
+    /// How the value is passed across the C ABI.
+    type Abi: Copy;
+
+    fn mathic_type() -> MathicType;
+
+    fn into_abi(self) -> Self::Abi;
+
+    fn from_abi(abi: Self::Abi) -> Self;
+}
+
+macro_rules! impl_numeric_value {
+    ($($ty:ty => $mathic_ty:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl MathicValue for $ty {
+                type Abi = $ty;
+
+                fn mathic_type() -> MathicType {
+                    MathicType::Numeric($mathic_ty)
+                }
+
+                fn into_abi(self) -> Self::Abi {
+                    self
+                }
+
+                fn from_abi(abi: Self::Abi) -> Self {
+                    abi
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_value! {
+    isize => NumericTy::Sint(SintTy::Isize),
+    i8 => NumericTy::Sint(SintTy::I8),
+    i16 => NumericTy::Sint(SintTy::I16),
+    i32 => NumericTy::Sint(SintTy::I32),
+    i64 => NumericTy::Sint(SintTy::I64),
+    i128 => NumericTy::Sint(SintTy::I128),
+    usize => NumericTy::Uint(UintTy::Usize),
+    u8 => NumericTy::Uint(UintTy::U8),
+    u16 => NumericTy::Uint(UintTy::U16),
+    u32 => NumericTy::Uint(UintTy::U32),
+    u64 => NumericTy::Uint(UintTy::U64),
+    u128 => NumericTy::Uint(UintTy::U128),
+    f32 => NumericTy::Float(FloatTy::F32),
+    f64 => NumericTy::Float(FloatTy::F64),
+}
+
+impl sealed::Sealed for bool {}
+
+impl MathicValue for bool {
+    type Abi = AbiBool;
+
+    fn mathic_type() -> MathicType {
+        MathicType::Bool
+    }
+
+    fn into_abi(self) -> Self::Abi {
+        self.into()
+    }
+
+    fn from_abi(abi: Self::Abi) -> Self {
+        abi.into()
+    }
+}
+
+/// A boolean as it's passed across the C ABI. Booleans are `i1`s, of which
+/// only the lowest bit is defined, so the byte holding one may not be a valid
+/// Rust `bool`. Host functions take and return booleans as `AbiBool`s.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbiBool(u8);
+
+impl From<bool> for AbiBool {
+    fn from(value: bool) -> Self {
+        Self(value as u8)
+    }
+}
+
+impl From<AbiBool> for bool {
+    fn from(value: AbiBool) -> Self {
+        value.0 & 1 != 0
+    }
+}
+
+impl sealed::Sealed for AbiBool {}
+
+impl MathicValue for AbiBool {
+    type Abi = AbiBool;
+
+    fn mathic_type() -> MathicType {
+        MathicType::Bool
+    }
+
+    fn into_abi(self) -> Self::Abi {
+        self
+    }
+
+    fn from_abi(abi: Self::Abi) -> Self {
+        abi
+    }
+}
+
+macro_rules! scalar_values {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        /// A number or boolean whose type is only known at runtime, e.g. when
+        /// called from a dynamic language.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum ScalarValue {
+            $($variant($ty)),*
+        }
+
+        impl ScalarValue {
+            pub fn mathic_type(&self) -> MathicType {
+                match self {
+                    $(Self::$variant(_) => <$ty>::mathic_type()),*
+                }
+            }
+
+            /// Returns the zero of a type, if it's a number or a boolean.
+            pub fn zero(ty: MathicType) -> Option<Self> {
+                $(
+                    if ty == <$ty>::mathic_type() {
+                        return Some(Self::$variant(Default::default()));
+                    }
+                )*
+
+                None
+            }
+
+            pub(crate) fn as_mut_ptr(&mut self) -> *mut () {
+                match self {
+                    $(Self::$variant(value) => (value as *mut $ty).cast()),*
+                }
+            }
+        }
+
+        $(
+            impl From<$ty> for ScalarValue {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+scalar_values! {
+    Isize(isize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Usize(usize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for ScalarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Isize(value) => value.fmt(f),
+            Self::I8(value) => value.fmt(f),
+            Self::I16(value) => value.fmt(f),
+            Self::I32(value) => value.fmt(f),
+            Self::I64(value) => value.fmt(f),
+            Self::I128(value) => value.fmt(f),
+            Self::Usize(value) => value.fmt(f),
+            Self::U8(value) => value.fmt(f),
+            Self::U16(value) => value.fmt(f),
+            Self::U32(value) => value.fmt(f),
+            Self::U64(value) => value.fmt(f),
+            Self::U128(value) => value.fmt(f),
+            Self::F32(value) => value.fmt(f),
+            Self::F64(value) => value.fmt(f),
+            Self::Bool(value) => value.fmt(f),
+        }
+    }
+}
+
+/// The arguments of a Mathic function, as a tuple of `MathicValue`s.
+pub trait MathicArgs: sealed::Sealed {
+    fn mathic_types() -> Vec<MathicType>;
+
+    /// Calls a function with these arguments.
+    ///
+    /// # Safety
+    ///
+    /// `func_ptr` must point to a function whose signature matches the
+    /// arguments and `R`.
+    unsafe fn call<R: MathicValue>(self, func_ptr: *mut ()) -> R;
+}
+
+macro_rules! impl_args {
+    ($($arg:ident),*) => {
+        impl<$($arg: MathicValue),*> sealed::Sealed for ($($arg,)*) {}
+
+        impl<$($arg: MathicValue),*> MathicArgs for ($($arg,)*) {
+            fn mathic_types() -> Vec<MathicType> {
+                vec![$($arg::mathic_type()),*]
+            }
+
+            #[allow(non_snake_case)]
+            unsafe fn call<R: MathicValue>(self, func_ptr: *mut ()) -> R {
+                let ($($arg,)*) = self;
+                let func: extern "C" fn($($arg::Abi),*) -> R::Abi =
+                    unsafe { mem::transmute(func_ptr) };
+
+                R::from_abi(func($($arg.into_abi()),*))
+            }
+        }
+    };
+}
+
+impl_args!();
+impl_args!(A);
+impl_args!(A, B);
+impl_args!(A, B, C);
+impl_args!(A, B, C, D);
+impl_args!(A, B, C, D, E);
+impl_args!(A, B, C, D, E, F);
+impl_args!(A, B, C, D, E, F, G);
+impl_args!(A, B, C, D, E, F, G, H);
+
+/// A Rust function implementing a function declared with `extern df`.
+///
+/// Implemented for `extern "C"` function pointers taking `MathicValue`s and
+/// returning one or nothing, e.g. `log as extern "C" fn(i64) -> i64`. The
+/// values are taken and returned as they cross the C ABI, so booleans are
+/// `AbiBool`s.
+pub trait HostFn: sealed::Sealed + Copy {
+    fn signature() -> FunctionSignature;
+
+    fn as_ptr(self) -> *const ();
+}
+
+macro_rules! impl_host_fn {
+    ($($arg:ident),*) => {
+        impl<R: MathicValue<Abi = R>, $($arg: MathicValue<Abi = $arg>),*> sealed::Sealed for extern "C" fn($($arg),*) -> R {}
+
+        impl<R: MathicValue<Abi = R>, $($arg: MathicValue<Abi = $arg>),*> HostFn for extern "C" fn($($arg),*) -> R {
+            fn signature() -> FunctionSignature {
+                FunctionSignature {
+                    params: vec![$($arg::mathic_type()),*],
+                    return_ty: R::mathic_type(),
+                }
+            }
+
+            fn as_ptr(self) -> *const () {
+                self as *const ()
+            }
+        }
+
+        impl<$($arg: MathicValue<Abi = $arg>),*> sealed::Sealed for extern "C" fn($($arg),*) {}
+
+        impl<$($arg: MathicValue<Abi = $arg>),*> HostFn for extern "C" fn($($arg),*) {
+            fn signature() -> FunctionSignature {
+                FunctionSignature {
+                    params: vec![$($arg::mathic_type()),*],
+                    return_ty: MathicType::Void,
+                }
+            }
+
+            fn as_ptr(self) -> *const () {
+                self as *const ()
+            }
+        }
+    };
+}
+
+impl_host_fn!();
+impl_host_fn!(A);
+impl_host_fn!(A, B);
+impl_host_fn!(A, B, C);
+impl_host_fn!(A, B, C, D);
+impl_host_fn!(A, B, C, D, E);
+impl_host_fn!(A, B, C, D, E, F);
+impl_host_fn!(A, B, C, D, E, F, G);
+impl_host_fn!(A, B, C, D, E, F, G, H);
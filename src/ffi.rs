@@ -1,8 +1,12 @@
+use std::ffi::c_void;
+
 use melior::{
     Context,
     dialect::DialectRegistry,
+    ir::Module,
     utility::{register_all_dialects, register_all_llvm_translations, register_all_passes},
 };
+use mlir_sys::{MlirStringRef, mlirOperationWriteBytecode};
 
 use crate::diagnostics::CodegenError;
 
@@ -30,3 +34,26 @@ fn create_dialect_registry() -> DialectRegistry {
 
     registry
 }
+
+/// Serializes a module into MLIR's bytecode format.
+pub fn write_bytecode(module: &Module) -> Vec<u8> {
+    unsafe extern "C" fn append(data: MlirStringRef, user_data: *mut c_void) {
+        let buffer = unsafe { &mut *(user_data as *mut Vec<u8>) };
+
+        buffer.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(data.data as *const u8, data.length)
+        });
+    }
+
+    let mut buffer = Vec::new();
+
+    unsafe {
+        mlirOperationWriteBytecode(
+            module.as_operation().to_raw(),
+            Some(append),
+            &mut buffer as *mut Vec<u8> as *mut c_void,
+        );
+    }
+
+    buffer
+}
@@ -1,12 +1,16 @@
 pub mod symbolic_dialect {
-    use melior::{dialect::DialectRegistry, pass::Pass};
-    use mlir_sys::{MlirContext, MlirDialectRegistry, MlirPass, MlirType};
+    use melior::{dialect::DialectRegistry, ir::Module, pass::Pass};
+    use mlir_sys::{MlirContext, MlirDialectRegistry, MlirModule, MlirPass, MlirType};
 
     #[link(name = "dialect_bindings")]
     unsafe extern "C" {
+        fn mlirCreateSymbolicExpand() -> MlirPass;
         fn mlirCreateSymbolicExtractEval() -> MlirPass;
+        fn mlirCreateSymbolicInline() -> MlirPass;
+        fn mlirCreateSymbolicSimplify() -> MlirPass;
         fn mlirCreateSymbolicToArith() -> MlirPass;
         fn mlirInsertSymbolicDialect(registry: MlirDialectRegistry);
+        fn mlirSymbolicImportFunctions(dest: MlirModule, src: MlirModule) -> bool;
         pub fn getSymExprType(ctx: MlirContext, inner_type: MlirType, is_signed: bool) -> MlirType;
     }
 
@@ -15,10 +19,26 @@ pub mod symbolic_dialect {
             mlirInsertSymbolicDialect(registry.to_raw());
         }
     }
+    pub fn create_symbolic_simplify() -> Pass {
+        unsafe { Pass::from_raw_fn(mlirCreateSymbolicSimplify) }
+    }
     pub fn create_symbolic_to_arith() -> Pass {
         unsafe { Pass::from_raw_fn(mlirCreateSymbolicToArith) }
     }
+    pub fn create_symbolic_expand() -> Pass {
+        unsafe { Pass::from_raw_fn(mlirCreateSymbolicExpand) }
+    }
     pub fn create_symbolic_extract_eval() -> Pass {
         unsafe { Pass::from_raw_fn(mlirCreateSymbolicExtractEval) }
     }
+    pub fn create_symbolic_inline() -> Pass {
+        unsafe { Pass::from_raw_fn(mlirCreateSymbolicInline) }
+    }
+
+    /// Copies into `dest` the bodies of the functions taking or returning
+    /// symbolic expressions that `dest` declares and `src` defines. Returns
+    /// whether any body was imported.
+    pub fn import_symbolic_functions(dest: &Module, src: &Module) -> bool {
+        unsafe { mlirSymbolicImportFunctions(dest.to_raw(), src.to_raw()) }
+    }
 }
@@ -1,12 +1,21 @@
 use std::{
     ffi::{CStr, CString},
     mem::MaybeUninit,
+    path::{Path, PathBuf},
     ptr::{addr_of_mut, null_mut},
 };
 
 use llvm_sys::{
-    LLVMModule,
-    core::{LLVMContextCreate, LLVMDisposeMessage, LLVMDisposeModule},
+    LLVMModule, LLVMTypeKind,
+    bit_writer::LLVMWriteBitcodeToFile,
+    core::{
+        LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMBuildCall2, LLVMBuildIntCast2,
+        LLVMBuildRet, LLVMContextCreate, LLVMContextDispose, LLVMCountParamTypes,
+        LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMDisposeMessage, LLVMDisposeModule,
+        LLVMFunctionType, LLVMGetModuleContext, LLVMGetNamedFunction, LLVMGetReturnType,
+        LLVMGetTypeKind, LLVMGlobalGetValueType, LLVMInt32TypeInContext, LLVMIsDeclaration,
+        LLVMPositionBuilderAtEnd, LLVMPrintModuleToFile,
+    },
     error::{LLVMDisposeErrorMessage, LLVMGetErrorMessage},
     orc2::{
         LLVMOrcCreateNewThreadSafeModule, LLVMOrcDisposeThreadSafeModule,
@@ -49,13 +58,12 @@ fn lower_mlir_to_llvmir(
     llvm_ctx: LLVMContextRef,
     module: &Module,
     opt_lvl: usize,
-    dump_llvmir: bool,
 ) -> Result<*mut LLVMModule, CodegenError> {
     unsafe {
         let llvm_module =
             mlirTranslateModuleToLLVMIR(module.as_operation().to_raw(), llvm_ctx as *mut _)
                 as *mut _;
-        let machine = create_llvm_machine(opt_lvl)?;
+        let machine = create_llvm_machine(opt_lvl, LLVMRelocMode::LLVMRelocDefault)?;
         let pass_builder_opts = LLVMCreatePassBuilderOptions();
 
         let passes = CString::new(format!("default<O{opt_lvl}>")).unwrap();
@@ -75,31 +83,6 @@ fn lower_mlir_to_llvmir(
 
         LLVMDisposePassBuilderOptions(pass_builder_opts);
 
-        if dump_llvmir {
-            let mut null = null_mut();
-            let error_buffer = addr_of_mut!(null);
-            let file_path = CString::new("llvm_dump.ll").unwrap();
-            let ok = LLVMTargetMachineEmitToFile(
-                machine,
-                llvm_module,
-                file_path.as_ptr(),
-                LLVMCodeGenFileType::LLVMAssemblyFile,
-                error_buffer,
-            );
-
-            if ok != 0 {
-                let error = CStr::from_ptr(*error_buffer).to_string_lossy().into_owned();
-
-                LLVMDisposeMessage(*error_buffer);
-                LLVMDisposeTargetMachine(machine);
-                LLVMDisposeModule(llvm_module);
-
-                return Err(CodegenError::LLVMError(error))?;
-            } else if !(*error_buffer).is_null() {
-                LLVMDisposeMessage(*error_buffer);
-            }
-        }
-
         LLVMDisposeTargetMachine(machine);
 
         Ok(llvm_module)
@@ -119,20 +102,8 @@ unsafe extern "C" {
 pub fn create_llvm_jit(
     modules: &[Module],
     opt_lvl: usize,
-    dump_llvm: bool,
 ) -> Result<LLVMOrcLLJITRef, CodegenError> {
     unsafe {
-        let context = LLVMContextCreate();
-        let tsm_context = LLVMOrcCreateNewThreadSafeContextFromLLVMContext(context);
-        let mut tsms = vec![];
-
-        for m in modules {
-            tsms.push(LLVMOrcCreateNewThreadSafeModule(
-                lower_mlir_to_llvmir(context, m, opt_lvl, dump_llvm)?,
-                tsm_context,
-            ));
-        }
-
         let builder = LLVMOrcCreateLLJITBuilder();
         let mut jit: MaybeUninit<LLVMOrcLLJITRef> = MaybeUninit::uninit();
 
@@ -143,15 +114,50 @@ pub fn create_llvm_jit(
             let msg = CStr::from_ptr(error).to_string_lossy().into_owned();
 
             LLVMDisposeErrorMessage(error);
-            for tsm in tsms {
-                LLVMOrcDisposeThreadSafeModule(tsm);
-            }
 
             return Err(CodegenError::LLVMError(msg));
         }
 
         let jit = jit.assume_init();
 
+        if let Err(e) = add_modules_to_llvm_jit(jit, modules, opt_lvl) {
+            LLVMOrcDisposeLLJIT(jit);
+
+            return Err(e);
+        }
+
+        Ok(jit)
+    }
+}
+
+/// Adds modules to an existing LLVM's OrcLLJIT.
+///
+/// Their symbols are linked against the ones of the modules already added.
+pub fn add_modules_to_llvm_jit(
+    jit: LLVMOrcLLJITRef,
+    modules: &[Module],
+    opt_lvl: usize,
+) -> Result<(), CodegenError> {
+    unsafe {
+        let context = LLVMContextCreate();
+        let tsm_context = LLVMOrcCreateNewThreadSafeContextFromLLVMContext(context);
+        let mut tsms = vec![];
+
+        for m in modules {
+            match lower_mlir_to_llvmir(context, m, opt_lvl) {
+                Ok(llvm_module) => {
+                    tsms.push(LLVMOrcCreateNewThreadSafeModule(llvm_module, tsm_context))
+                }
+                Err(e) => {
+                    for tsm in tsms {
+                        LLVMOrcDisposeThreadSafeModule(tsm);
+                    }
+
+                    return Err(e);
+                }
+            }
+        }
+
         let dylib = LLVMOrcLLJITGetMainJITDylib(jit);
 
         for tsm in tsms {
@@ -161,14 +167,207 @@ pub fn create_llvm_jit(
                 let error = LLVMGetErrorMessage(err);
                 let msg = CStr::from_ptr(error).to_string_lossy().into_owned();
 
-                LLVMOrcDisposeLLJIT(jit);
                 LLVMDisposeErrorMessage(error);
 
                 return Err(CodegenError::LLVMError(msg));
             }
         }
 
-        Ok(jit)
+        Ok(())
+    }
+}
+
+/// Formats in which a module translated to LLVM can be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LLVMArtifact {
+    /// Textual LLVM IR.
+    Ir,
+    /// LLVM bitcode.
+    Bitcode,
+    /// Native assembly.
+    Assembly,
+    /// Native object file.
+    Object,
+}
+
+/// Compiles a module into a native object file.
+///
+/// If the module defines `entry`, a C-ABI `main` calling it is added as well,
+/// so the object can be linked into an executable by the system linker.
+/// `signed_result` tells whether the entry's result is sign extended.
+pub fn emit_object_file(
+    module: &Module,
+    opt_lvl: usize,
+    entry: &str,
+    signed_result: bool,
+    path: &Path,
+) -> Result<(), CodegenError> {
+    unsafe {
+        with_llvm_module(module, opt_lvl, |llvm_module| {
+            build_main_shim(llvm_module, entry, signed_result)?;
+            write_llvm_module(llvm_module, opt_lvl, LLVMArtifact::Object, path)
+        })
+    }
+}
+
+/// Translates a module to LLVM and writes it in each of the given formats.
+///
+/// Emitting native code may modify the LLVM module, so IR and bitcode
+/// artifacts should come first.
+pub fn emit_llvm_artifacts(
+    module: &Module,
+    opt_lvl: usize,
+    artifacts: &[(LLVMArtifact, PathBuf)],
+) -> Result<(), CodegenError> {
+    unsafe {
+        with_llvm_module(module, opt_lvl, |llvm_module| {
+            for (artifact, path) in artifacts {
+                write_llvm_module(llvm_module, opt_lvl, *artifact, path)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Translates a module to LLVM in a context of its own and gives it to `f`.
+/// Both the LLVM module and its context are disposed afterwards.
+unsafe fn with_llvm_module<T>(
+    module: &Module,
+    opt_lvl: usize,
+    f: impl FnOnce(*mut LLVMModule) -> Result<T, CodegenError>,
+) -> Result<T, CodegenError> {
+    unsafe {
+        let context = LLVMContextCreate();
+        let llvm_module = match lower_mlir_to_llvmir(context, module, opt_lvl) {
+            Ok(llvm_module) => llvm_module,
+            Err(e) => {
+                LLVMContextDispose(context);
+                return Err(e);
+            }
+        };
+
+        let result = f(llvm_module);
+
+        LLVMDisposeModule(llvm_module);
+        LLVMContextDispose(context);
+
+        result
+    }
+}
+
+/// Writes a LLVM module to `path` in the given format.
+unsafe fn write_llvm_module(
+    llvm_module: *mut LLVMModule,
+    opt_lvl: usize,
+    artifact: LLVMArtifact,
+    path: &Path,
+) -> Result<(), CodegenError> {
+    unsafe {
+        let file_path = CString::new(path.as_os_str().as_encoded_bytes())
+            .map_err(|e| CodegenError::Custom(e.to_string()))?;
+        let mut null = null_mut();
+        let error_buffer = addr_of_mut!(null);
+
+        let failed = match artifact {
+            LLVMArtifact::Ir => {
+                LLVMPrintModuleToFile(llvm_module, file_path.as_ptr(), error_buffer) != 0
+            }
+            LLVMArtifact::Bitcode => {
+                if LLVMWriteBitcodeToFile(llvm_module, file_path.as_ptr()) != 0 {
+                    return Err(CodegenError::LLVMError(format!(
+                        "could not write bitcode to '{}'",
+                        path.display()
+                    )));
+                }
+
+                false
+            }
+            LLVMArtifact::Assembly | LLVMArtifact::Object => {
+                // Executables are position independent by default on most
+                // platforms, so objects must be too.
+                let machine = create_llvm_machine(opt_lvl, LLVMRelocMode::LLVMRelocPIC)?;
+                let failed = LLVMTargetMachineEmitToFile(
+                    machine,
+                    llvm_module,
+                    file_path.as_ptr(),
+                    if artifact == LLVMArtifact::Assembly {
+                        LLVMCodeGenFileType::LLVMAssemblyFile
+                    } else {
+                        LLVMCodeGenFileType::LLVMObjectFile
+                    },
+                    error_buffer,
+                ) != 0;
+
+                LLVMDisposeTargetMachine(machine);
+
+                failed
+            }
+        };
+
+        if failed {
+            let error = CStr::from_ptr(*error_buffer).to_string_lossy().into_owned();
+
+            LLVMDisposeMessage(*error_buffer);
+
+            return Err(CodegenError::LLVMError(error));
+        } else if !(*error_buffer).is_null() {
+            LLVMDisposeMessage(*error_buffer);
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds a C-ABI `main` to the module which calls `entry` and returns its
+/// result as the process exit code. Booleans and unsigned integers are zero
+/// extended, since LLVM integers carry no sign.
+///
+/// Modules not defining `entry` are left untouched.
+unsafe fn build_main_shim(
+    llvm_module: *mut LLVMModule,
+    entry: &str,
+    signed_result: bool,
+) -> Result<(), CodegenError> {
+    unsafe {
+        let entry_name = CString::new(entry).map_err(|e| CodegenError::Custom(e.to_string()))?;
+        let entry_fn = LLVMGetNamedFunction(llvm_module, entry_name.as_ptr());
+
+        if entry_fn.is_null() || LLVMIsDeclaration(entry_fn) != 0 {
+            return Ok(());
+        }
+
+        let entry_ty = LLVMGlobalGetValueType(entry_fn);
+        let return_ty = LLVMGetReturnType(entry_ty);
+
+        if LLVMCountParamTypes(entry_ty) != 0
+            || LLVMGetTypeKind(return_ty) != LLVMTypeKind::LLVMIntegerTypeKind
+        {
+            return Err(CodegenError::Custom(format!(
+                "'{entry}' must take no parameters and return an integer"
+            )));
+        }
+
+        let context = LLVMGetModuleContext(llvm_module);
+        let i32_ty = LLVMInt32TypeInContext(context);
+        let main_fn = LLVMAddFunction(
+            llvm_module,
+            c"main".as_ptr(),
+            LLVMFunctionType(i32_ty, null_mut(), 0, 0),
+        );
+        let block = LLVMAppendBasicBlockInContext(context, main_fn, c"entry".as_ptr());
+        let builder = LLVMCreateBuilderInContext(context);
+
+        LLVMPositionBuilderAtEnd(builder, block);
+
+        let result = LLVMBuildCall2(builder, entry_ty, entry_fn, null_mut(), 0, c"".as_ptr());
+        let exit_code =
+            LLVMBuildIntCast2(builder, result, i32_ty, signed_result.into(), c"".as_ptr());
+
+        LLVMBuildRet(builder, exit_code);
+        LLVMDisposeBuilder(builder);
+
+        Ok(())
     }
 }
 
@@ -189,7 +388,7 @@ pub fn get_target_triple() -> String {
 /// This function gets the data layout of the host target triple.
 pub fn get_data_layout_rep(opt_lvl: usize) -> Result<String, CodegenError> {
     unsafe {
-        let machine = create_llvm_machine(opt_lvl)?;
+        let machine = create_llvm_machine(opt_lvl, LLVMRelocMode::LLVMRelocDefault)?;
         let data_layout = LLVMCreateTargetDataLayout(machine);
         let data_layout_str = CStr::from_ptr(LLVMCopyStringRepOfTargetData(data_layout));
 
@@ -200,7 +399,10 @@ pub fn get_data_layout_rep(opt_lvl: usize) -> Result<String, CodegenError> {
     }
 }
 
-fn create_llvm_machine(opt_lvl: usize) -> Result<*mut LLVMOpaqueTargetMachine, CodegenError> {
+fn create_llvm_machine(
+    opt_lvl: usize,
+    reloc_mode: LLVMRelocMode,
+) -> Result<*mut LLVMOpaqueTargetMachine, CodegenError> {
     unsafe {
         let mut null = null_mut();
         let error_buffer = addr_of_mut!(null);
@@ -238,7 +440,7 @@ fn create_llvm_machine(opt_lvl: usize) -> Result<*mut LLVMOpaqueTargetMachine, C
                 2 => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
                 _ => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
             },
-            LLVMRelocMode::LLVMRelocDefault,
+            reloc_mode,
             LLVMCodeModel::LLVMCodeModelDefault,
         ) as *mut _;
 
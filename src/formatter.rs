@@ -0,0 +1,431 @@
+use std::{iter::Peekable, vec};
+
+use crate::{
+    diagnostics::ParseError,
+    parser::{
+        MathicParser, Span,
+        ast::{
+            control_flow::ForIterable,
+            declaration::{DeclStmt, FuncDecl, Path, StructDecl, TopLevelItem},
+            expression::{
+                ArithOp, BinaryOp, CmpOp, ExprStmt, ExprStmtKind, LogicalOp, PrimaryExpr, UnaryOp,
+            },
+            statement::{Stmt, StmtKind},
+        },
+        lexer::SpannedToken,
+    },
+};
+
+const INDENT: &str = "    ";
+
+/// Formats the source of a module.
+///
+/// The source is printed back from its AST, so the result only depends on
+/// the program and its comments. A comment stays after the code of its
+/// line, or on its own line before the item or statement following it.
+/// Blank lines between statements are kept, runs of them become one.
+///
+/// Sources with syntax errors aren't formatted, all the errors are returned.
+pub fn format_source(source: &str) -> Result<String, Vec<ParseError>> {
+    let parser = MathicParser::new(source);
+    let (program, errors) = parser.parse(String::new());
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut printer = Printer {
+        source,
+        comments: parser.take_comments().into_iter().peekable(),
+        out: String::new(),
+        indent: 0,
+    };
+
+    printer.items(&program.items);
+
+    Ok(printer.out)
+}
+
+struct Printer<'a> {
+    source: &'a str,
+    /// Comments yet to be printed.
+    comments: Peekable<vec::IntoIter<SpannedToken<'a>>>,
+    out: String,
+    indent: usize,
+}
+
+impl Printer<'_> {
+    fn items(&mut self, items: &[TopLevelItem]) {
+        for (i, item) in items.iter().enumerate() {
+            // Items are separated by a blank line, except for imports which
+            // are grouped as they were written.
+            let grouped = matches!(
+                (i.checked_sub(1).map(|prev| &items[prev]), item),
+                (None, _) | (Some(TopLevelItem::Import(_)), TopLevelItem::Import(_))
+            );
+
+            if !grouped {
+                self.blank_line();
+            }
+
+            match item {
+                TopLevelItem::Func(func) => {
+                    self.begin(func.span.start, i == 0);
+                    self.func(func);
+                }
+                TopLevelItem::Import(path) => {
+                    self.begin(path.span.start, i == 0);
+                    self.line(&format!("imp {};", import_path(path, 0)));
+                }
+                TopLevelItem::Struct(strct) => {
+                    self.begin(strct.span.start, i == 0);
+                    self.strct(strct);
+                }
+            }
+        }
+
+        self.comments_before(self.source.len(), items.is_empty());
+    }
+
+    fn func(&mut self, func: &FuncDecl) {
+        let params = func
+            .params
+            .iter()
+            .map(|param| format!("{}: {}", param.name, param.ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let keyword = if func.is_test { "test df" } else { "df" };
+
+        let header = match &func.return_ty {
+            Some(ty) => format!("{keyword} {}({params}) {ty}", func.name),
+            None => format!("{keyword} {}({params})", func.name),
+        };
+
+        if func.is_extern {
+            self.line(&format!("extern {header};"));
+            return;
+        }
+
+        self.block(&header, &func.body, func.span);
+    }
+
+    fn strct(&mut self, strct: &StructDecl) {
+        let header = format!("struct {}", strct.name);
+
+        if strct.fields.is_empty() && !self.has_comments_before(strct.span.end) {
+            self.line(&format!("{header} {{}}"));
+            return;
+        }
+
+        self.open(&header);
+
+        for (i, field) in strct.fields.iter().enumerate() {
+            self.begin(field.span.start, i == 0);
+
+            let visibility = if field.is_pub { "pub " } else { "" };
+            let separator = if i + 1 < strct.fields.len() { "," } else { "" };
+
+            self.line(&format!(
+                "{visibility}{}: {}{separator}",
+                field.name, field.ty
+            ));
+        }
+
+        self.close(strct.span, strct.fields.is_empty());
+        self.line("}");
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            self.begin(stmt_start(stmt), i == 0);
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(DeclStmt::Var(var)) => {
+                self.line(&format!(
+                    "let {}: {} = {};",
+                    var.name,
+                    var.ty,
+                    expr(&var.expr)
+                ));
+            }
+            StmtKind::Decl(DeclStmt::Sym(sym)) => {
+                self.line(&format!("sym {}: {};", sym.name, sym.ty));
+            }
+            StmtKind::Decl(DeclStmt::Struct(strct)) => self.strct(strct),
+            StmtKind::Decl(DeclStmt::Func(func)) => self.func(func),
+            StmtKind::Block(block) => self.block("", &block.stmts, block.span),
+            StmtKind::If(if_stmt) => {
+                let header = format!("if {}", expr(&if_stmt.condition));
+                let then_block = &if_stmt.then_block;
+
+                match &if_stmt.else_block {
+                    Some(else_block) => {
+                        self.open(&header);
+                        self.stmts(&then_block.stmts);
+                        self.close(then_block.span, then_block.stmts.is_empty());
+
+                        self.open("} else");
+                        self.stmts(&else_block.stmts);
+                        self.close(else_block.span, else_block.stmts.is_empty());
+                        self.line("}");
+                    }
+                    None => self.block(&header, &then_block.stmts, then_block.span),
+                }
+            }
+            StmtKind::While(while_stmt) => {
+                let header = format!("while {}", expr(&while_stmt.condition));
+
+                self.block(&header, &while_stmt.body.stmts, while_stmt.body.span);
+            }
+            StmtKind::For(for_stmt) => {
+                let iterable = match &for_stmt.iterable {
+                    ForIterable::Range { start, end } => format!("{}..{}", expr(start), expr(end)),
+                    ForIterable::Array(array) => expr(array),
+                };
+                let header = format!("for {} in {iterable}", for_stmt.variable);
+
+                self.block(&header, &for_stmt.body.stmts, for_stmt.body.span);
+            }
+            StmtKind::Return(value) => self.line(&format!("return {};", expr(value))),
+            StmtKind::Expr(value) => self.line(&format!("{};", expr(value))),
+        }
+    }
+
+    /// Prints `header { ... }`, where `span` ends at the closing brace.
+    fn block(&mut self, header: &str, stmts: &[Stmt], span: Span) {
+        let open = if header.is_empty() {
+            "{".to_string()
+        } else {
+            format!("{header} {{")
+        };
+
+        if stmts.is_empty() && !self.has_comments_before(span.end) {
+            self.line(&format!("{open}}}"));
+            return;
+        }
+
+        self.line(&open);
+        self.indent += 1;
+        self.stmts(stmts);
+        self.close(span, stmts.is_empty());
+        self.line("}");
+    }
+
+    fn open(&mut self, header: &str) {
+        self.line(&format!("{header} {{"));
+        self.indent += 1;
+    }
+
+    /// Prints the comments left in a block, whose `span` ends at the closing
+    /// brace, and leaves it.
+    fn close(&mut self, span: Span, empty: bool) {
+        self.comments_before(span.end, empty);
+        self.indent -= 1;
+    }
+
+    /// Prints the comments before the node at `pos`, then separates the node
+    /// with a blank line if it had one in the source. Nothing is separated
+    /// from the start of its block.
+    fn begin(&mut self, pos: usize, first: bool) {
+        let first = self.comments_before(pos, first);
+
+        if !first && self.follows_blank_line(pos) {
+            self.blank_line();
+        }
+    }
+
+    /// Prints the comments before `pos`. Returns whether the next line is
+    /// still the first one of its block.
+    fn comments_before(&mut self, pos: usize, mut first: bool) -> bool {
+        while let Some(comment) = self.comments.next_if(|c| c.span.start < pos) {
+            let start = comment.span.start;
+
+            // Comments after code stay on the line of that code.
+            if self.follows_code(start) && self.out.ends_with('\n') {
+                self.out.pop();
+                self.out.push(' ');
+                self.out.push_str(comment.lexeme);
+                self.out.push('\n');
+                continue;
+            }
+
+            if !first && self.follows_blank_line(start) {
+                self.blank_line();
+            }
+
+            self.line(comment.lexeme);
+            first = false;
+        }
+
+        first
+    }
+
+    fn has_comments_before(&mut self, pos: usize) -> bool {
+        self.comments.peek().is_some_and(|c| c.span.start < pos)
+    }
+
+    /// Whether there is code before `pos` on its line.
+    fn follows_code(&self, pos: usize) -> bool {
+        !self.source[line_start(self.source, pos)..pos]
+            .trim()
+            .is_empty()
+    }
+
+    /// Whether the line before the one of `pos` is blank.
+    fn follows_blank_line(&self, pos: usize) -> bool {
+        let Some(prev_end) = line_start(self.source, pos).checked_sub(1) else {
+            return false;
+        };
+
+        self.source[line_start(self.source, prev_end)..prev_end]
+            .trim()
+            .is_empty()
+    }
+
+    fn line(&mut self, text: &str) {
+        self.out.push_str(&INDENT.repeat(self.indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+fn line_start(source: &str, pos: usize) -> usize {
+    source[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Returns where the statement starts, comments before it belong to it.
+fn stmt_start(stmt: &Stmt) -> usize {
+    match &stmt.kind {
+        StmtKind::Expr(value) => stmt.span.start.min(value.extent().start),
+        _ => stmt.span.start,
+    }
+}
+
+/// Prints an import path, without the `prefix_len` idents it shares with the
+/// group holding it.
+fn import_path(path: &Path, prefix_len: usize) -> String {
+    let mut text = path.idents[prefix_len..].join("::");
+
+    if path.import_all {
+        text.push_str("::*");
+    }
+
+    if !path.group_paths.is_empty() {
+        let members = path
+            .group_paths
+            .iter()
+            .map(|member| import_path(member, path.idents.len()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        text.push_str(&format!("::{{{members}}}"));
+    }
+
+    text
+}
+
+/// Prints an expression on a single line.
+pub(crate) fn expr(expr_stmt: &ExprStmt) -> String {
+    match &expr_stmt.kind {
+        ExprStmtKind::Primary(primary) => match primary {
+            PrimaryExpr::Path(path) => path.join("::"),
+            PrimaryExpr::Ident(value) | PrimaryExpr::Num(value) | PrimaryExpr::Str(value) => {
+                value.clone()
+            }
+            PrimaryExpr::Char(value) => format!("'{value}'"),
+            PrimaryExpr::Bool(value) => value.to_string(),
+        },
+        ExprStmtKind::Binary { lhs, op, rhs } => {
+            let op = match op {
+                BinaryOp::Arithmetic(ArithOp::Add) => "+",
+                BinaryOp::Arithmetic(ArithOp::Sub) => "-",
+                BinaryOp::Arithmetic(ArithOp::Mul) => "*",
+                BinaryOp::Arithmetic(ArithOp::Div) => "/",
+                BinaryOp::Arithmetic(ArithOp::Mod) => "%",
+                BinaryOp::Compare(CmpOp::Eq) => "==",
+                BinaryOp::Compare(CmpOp::Ne) => "!=",
+                BinaryOp::Compare(CmpOp::Lt) => "<",
+                BinaryOp::Compare(CmpOp::Le) => "<=",
+                BinaryOp::Compare(CmpOp::Gt) => ">",
+                BinaryOp::Compare(CmpOp::Ge) => ">=",
+            };
+
+            format!("{} {op} {}", expr(lhs), expr(rhs))
+        }
+        ExprStmtKind::Logical { lhs, op, rhs } => {
+            let op = match op {
+                LogicalOp::And => "and",
+                LogicalOp::Or => "or",
+            };
+
+            format!("{} {op} {}", expr(lhs), expr(rhs))
+        }
+        ExprStmtKind::Unary { op, rhs } => {
+            let op = match op {
+                UnaryOp::Neg => "-",
+                UnaryOp::Not => "!",
+            };
+
+            format!("{op}{}", expr(rhs))
+        }
+        ExprStmtKind::Group(inner) => format!("({})", expr(inner)),
+        ExprStmtKind::Call { callee, args } => format!("{}({})", expr(callee), exprs(args)),
+        ExprStmtKind::StructInit {
+            expr: strct,
+            fields,
+        } => {
+            // Fields are stored unordered, they keep their order in the source.
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(_, value)| value.extent().start);
+
+            let fields = fields
+                .into_iter()
+                .map(|(name, value)| format!("{name}: {}", expr(value)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            format!("{} {{ {fields} }}", expr(strct))
+        }
+        ExprStmtKind::ArrayInit { elements } => format!("[{}]", exprs(elements)),
+        ExprStmtKind::Index { expr: array, index } => {
+            format!("{}[{}]", expr(array), expr(index))
+        }
+        ExprStmtKind::IndexSet { lhs, index, rhs } => {
+            format!("{}[{}] = {}", expr(lhs), expr(index), expr(rhs))
+        }
+        ExprStmtKind::Assign { name, expr: value } => format!("{name} = {}", expr(value)),
+        ExprStmtKind::Substitution { callee, args } => {
+            let args = args
+                .iter()
+                .map(|(name, value)| format!("{name}={}", expr(value)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            format!("{}[{args}]", expr(callee))
+        }
+        ExprStmtKind::StructGet {
+            expr: base,
+            field_name,
+        } => format!("{}.{field_name}", expr(base)),
+        ExprStmtKind::StructSet {
+            lhs,
+            field_name,
+            rhs,
+        } => format!("{}.{field_name} = {}", expr(lhs), expr(rhs)),
+    }
+}
+
+fn exprs(exprs: &[ExprStmt]) -> String {
+    exprs.iter().map(expr).collect::<Vec<_>>().join(", ")
+}
@@ -1,12 +1,21 @@
+pub mod analysis;
+pub mod aot;
 mod codegen;
 pub mod compiler;
 pub mod diagnostics;
 pub mod executor;
 mod ffi;
+pub mod formatter;
 mod loader;
 mod lowering;
 mod parser;
+pub mod repl;
+pub mod session;
 
+pub use codegen::module::MathicModule;
+pub use loader::module_name;
+pub use parser::{Span, lexer::is_identifier};
+pub use session::{CompiledFn, Session};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -15,6 +24,8 @@ pub enum MathicError {
     CompilationFailed,
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
     #[error("diagnostics lock poisoned: {0}")]
     LockPoisoned(String),
 }
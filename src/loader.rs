@@ -7,9 +7,9 @@ use std::{
 
 use crate::{
     MathicResult,
-    diagnostics::{CompilationError, DiagnosticsManager, LoweringError},
+    diagnostics::{CompilationError, DiagnosticsManager, LoweringError, json::line_column},
     parser::{
-        MathicParser,
+        MathicParser, Span,
         ast::{
             IrModule,
             declaration::{Path as MathicPath, TopLevelItem},
@@ -21,6 +21,29 @@ use crate::{
 pub struct ModuleLoader<'a> {
     diagnostics: &'a DiagnosticsManager,
     parsed: HashMap<PathBuf, Arc<IrModule>>,
+    /// Source roots of the project's dependencies, by the name their modules
+    /// are imported with.
+    dependencies: HashMap<String, PathBuf>,
+    /// Dependencies of each dependency, by its source root. Each package
+    /// only sees its own.
+    package_dependencies: HashMap<PathBuf, HashMap<String, PathBuf>>,
+    /// The path the modules of each reached dependency are under, by its
+    /// source root. The project's dependencies are under their name, and
+    /// the others under the path of the first package reaching them.
+    dependency_paths: HashMap<PathBuf, PathBuf>,
+    /// Modules whose imports are being loaded, each one imported by the
+    /// previous one. Importing any of them again closes a cycle.
+    loading: Vec<LoadingModule>,
+}
+
+/// A module whose imports are being loaded.
+struct LoadingModule {
+    abs_path: PathBuf,
+    /// The module's path relative to the source root.
+    path: PathBuf,
+    source: String,
+    /// The import being loaded.
+    import_span: Option<Span>,
 }
 
 impl<'a> ModuleLoader<'a> {
@@ -28,6 +51,80 @@ impl<'a> ModuleLoader<'a> {
         Self {
             diagnostics,
             parsed: HashMap::new(),
+            dependencies: HashMap::new(),
+            package_dependencies: HashMap::new(),
+            dependency_paths: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    /// Makes the modules under `src_root` importable as `name::<module>`,
+    /// e.g. `imp geometry::shapes::area;` for `<src_root>/shapes.mth`.
+    pub fn add_dependency(&mut self, name: String, src_root: PathBuf) {
+        self.dependency_paths
+            .entry(src_root.clone())
+            .or_insert_with(|| PathBuf::from(&name));
+        self.dependencies.insert(name, src_root);
+    }
+
+    /// Makes the modules under `src_root` importable as `name::<module>` by
+    /// the modules of the dependency at `package_root`.
+    pub fn add_package_dependency(
+        &mut self,
+        package_root: PathBuf,
+        name: String,
+        src_root: PathBuf,
+    ) {
+        self.package_dependencies
+            .entry(package_root)
+            .or_default()
+            .insert(name, src_root);
+    }
+
+    /// Returns the path of a module file relative to the source root. The
+    /// modules of a dependency are under its path, see
+    /// [`ModuleLoader::dependency_paths`]. Roots may be nested, the
+    /// innermost one holding the file is the module's.
+    pub fn module_path(&self, src_root: &Path, file_path: &Path) -> PathBuf {
+        match self.package_root(file_path) {
+            Some(root) => self.dependency_paths[root].join(file_path.strip_prefix(root).unwrap()),
+            None => file_path
+                .strip_prefix(src_root)
+                .unwrap_or(file_path)
+                .to_owned(),
+        }
+    }
+
+    /// Returns the file of a module, given its path relative to the source
+    /// root. It's the inverse of [`ModuleLoader::module_path`].
+    fn file_path(&self, src_root: &Path, path: &Path) -> PathBuf {
+        self.dependency_paths
+            .iter()
+            .filter_map(|(root, dependency_path)| {
+                Some((
+                    dependency_path,
+                    root.join(path.strip_prefix(dependency_path).ok()?),
+                ))
+            })
+            .max_by_key(|(dependency_path, _)| dependency_path.components().count())
+            .map(|(_, file_path)| file_path)
+            .unwrap_or_else(|| src_root.join(path))
+    }
+
+    /// Returns the source root of the reached dependency holding `path`, or
+    /// `None` if it's in the project.
+    fn package_root(&self, path: &Path) -> Option<&PathBuf> {
+        self.dependency_paths
+            .keys()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    }
+
+    /// Returns the dependencies seen by the package holding `path`.
+    fn scope(&self, path: &Path) -> Option<&HashMap<String, PathBuf>> {
+        match self.package_root(path) {
+            Some(root) => self.package_dependencies.get(root),
+            None => Some(&self.dependencies),
         }
     }
 
@@ -39,35 +136,63 @@ impl<'a> ModuleLoader<'a> {
         src_root: &Path,
         path: PathBuf,
     ) -> MathicResult<HashMap<PathBuf, Arc<IrModule>>> {
-        let abs_path = src_root.join(&path);
-        let base_path = abs_path.parent().unwrap();
+        let abs_path = self.file_path(src_root, &path);
 
         if let Some(module) = self.parsed.get(&abs_path) {
             return Ok(HashMap::from([(abs_path, module.clone())]));
         }
 
+        if let Some(idx) = self
+            .loading
+            .iter()
+            .position(|module| module.abs_path == abs_path)
+        {
+            self.report_circular_import(idx)?;
+            return Ok(HashMap::new());
+        }
+
         let source = fs::read_to_string(&abs_path)?;
 
+        self.load_source(src_root, path, &source)
+    }
+
+    /// Like [`ModuleLoader::load`], but takes the source of the module at
+    /// `path` instead of reading it. Imported modules are still read from
+    /// disk.
+    pub fn load_source(
+        &mut self,
+        src_root: &Path,
+        path: PathBuf,
+        source: &str,
+    ) -> MathicResult<HashMap<PathBuf, Arc<IrModule>>> {
+        let abs_path = self.file_path(src_root, &path);
+        let base_path = abs_path.parent().unwrap();
+
         let mut compilation_unit = HashMap::new();
 
         let mut program = {
-            let parser = MathicParser::new(&source);
-            let module_name = path
-                .with_extension("")
-                .to_string_lossy()
-                .replace("/", "::")
-                .to_string();
-
-            match parser.parse(module_name) {
-                Err(e) => {
-                    self.diagnostics
-                        .report(abs_path.clone(), CompilationError::Parse(e))?;
-                    return Ok(compilation_unit);
-                }
-                Ok(module) => module,
+            let parser = MathicParser::new(source);
+            let module_name = module_name(&path);
+
+            // The module is kept even if it has syntax errors, so that its
+            // imports are loaded and checked as well.
+            let (module, errors) = parser.parse(module_name);
+
+            for e in errors {
+                self.diagnostics
+                    .report(abs_path.clone(), CompilationError::Parse(e))?;
             }
+
+            module
         };
 
+        self.loading.push(LoadingModule {
+            abs_path: abs_path.clone(),
+            path,
+            source: source.to_string(),
+            import_span: None,
+        });
+
         for item in &program.items {
             if let TopLevelItem::Import(import_path) = item {
                 if import_path.group_paths.is_empty() {
@@ -92,7 +217,10 @@ impl<'a> ModuleLoader<'a> {
             }
         }
 
+        self.loading.pop();
+
         program.modules = compilation_unit.values().cloned().collect();
+        program.dependency_aliases = self.dependency_aliases(&abs_path);
 
         let module = Arc::new(program);
         self.parsed.insert(abs_path.clone(), module.clone());
@@ -112,7 +240,7 @@ impl<'a> ModuleLoader<'a> {
         import_path: &MathicPath,
         compilation_unit: &mut HashMap<PathBuf, Arc<IrModule>>,
     ) -> MathicResult<()> {
-        let new_base_path = match get_module_path(src_root, base_path, import_path) {
+        let new_base_path = match self.get_module_file(src_root, base_path, import_path) {
             Ok(res) => res,
             Err(err) => {
                 self.diagnostics.report(abs_path.to_path_buf(), err)?;
@@ -121,9 +249,8 @@ impl<'a> ModuleLoader<'a> {
         };
 
         if !new_base_path.exists() {
-            let path = new_base_path
-                .strip_prefix(src_root)
-                .unwrap()
+            let path = self
+                .module_path(src_root, &new_base_path)
                 .to_string_lossy()
                 .replace("/", "::");
 
@@ -137,13 +264,134 @@ impl<'a> ModuleLoader<'a> {
             return Ok(());
         }
 
-        compilation_unit.extend(self.load(
-            src_root,
-            new_base_path.strip_prefix(src_root).unwrap().to_owned(),
-        )?);
+        if let Some(module) = self.loading.last_mut() {
+            module.import_span = Some(import_path.span);
+        }
+
+        compilation_unit.extend(self.load(src_root, self.module_path(src_root, &new_base_path))?);
 
         Ok(())
     }
+
+    /// Reports the import closing a cycle, which goes from the module at
+    /// `start` in [`ModuleLoader::loading`] to the last one, and back.
+    fn report_circular_import(&self, start: usize) -> MathicResult<()> {
+        let cycle = &self.loading[start..];
+
+        let mut module_names = cycle
+            .iter()
+            .map(|module| module_name(&module.path))
+            .collect::<Vec<_>>();
+        module_names.push(module_names[0].clone());
+
+        let imports = cycle
+            .iter()
+            .zip(&module_names[1..])
+            .map(|(module, imported)| {
+                let span = module
+                    .import_span
+                    .expect("modules in a cycle are loading an import");
+                let (line, column) = line_column(&module.source, span.start);
+
+                format!(
+                    "{}:{line}:{column} imports {imported}",
+                    module.path.display()
+                )
+            })
+            .collect();
+
+        let last = cycle.last().expect("a cycle has at least one module");
+
+        self.diagnostics.report(
+            last.abs_path.clone(),
+            CompilationError::Lowering(LoweringError::CircularImport {
+                cycle: module_names,
+                imports,
+                span: last
+                    .import_span
+                    .expect("modules in a cycle are loading an import"),
+            }),
+        )
+    }
+
+    /// Returns the names the package holding `path` imports its reached
+    /// dependencies with, mapped to the names of their modules, when they
+    /// differ.
+    fn dependency_aliases(&self, path: &Path) -> HashMap<String, String> {
+        self.scope(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, root)| {
+                let dependency_name = module_name(self.dependency_paths.get(root)?);
+
+                (*name != dependency_name).then(|| (name.clone(), dependency_name))
+            })
+            .collect()
+    }
+
+    /// Gets the file of the module an import refers to. Imports starting
+    /// with the name of a dependency of the importing module's package are
+    /// resolved from its source root, and the others relative to the
+    /// importing module.
+    fn get_module_file(
+        &mut self,
+        src_root: &Path,
+        base_path: &Path,
+        import_path: &MathicPath,
+    ) -> Result<PathBuf, CompilationError> {
+        let name = &import_path.idents[0];
+
+        let Some(dependency_root) = self
+            .scope(base_path)
+            .and_then(|scope| scope.get(name))
+            .cloned()
+        else {
+            return get_module_path(src_root, base_path, import_path);
+        };
+
+        let local_path = base_path.join(name);
+
+        if local_path.is_dir() || local_path.with_added_extension("mth").is_file() {
+            return Err(CompilationError::Lowering(LoweringError::AmbiguousImport {
+                name: name.clone(),
+                span: import_path.span,
+            }));
+        }
+
+        // A dependency isn't a module itself, only the ones in it are.
+        if import_path.idents.len() == 1 {
+            return Err(CompilationError::Lowering(LoweringError::UnResolvedPath {
+                path: name.clone(),
+                span: import_path.span,
+            }));
+        }
+
+        if !self.dependency_paths.contains_key(&dependency_root) {
+            let package_path = self
+                .package_root(base_path)
+                .map(|root| self.dependency_paths[root].clone())
+                .unwrap_or_default();
+
+            self.dependency_paths
+                .insert(dependency_root.clone(), package_path.join(name));
+        }
+
+        let dependency_path = MathicPath {
+            idents: import_path.idents[1..].to_vec(),
+            ..import_path.clone()
+        };
+
+        get_module_path(&dependency_root, &dependency_root, &dependency_path)
+    }
+}
+
+/// Names a module after its path relative to the source root, e.g.
+/// `geo::distance` for `geo/distance.mth`.
+pub fn module_name(path: &Path) -> String {
+    path.with_extension("")
+        .to_string_lossy()
+        .replace("/", "::")
+        .to_string()
 }
 
 /// Gets the path to a module based on an import path relative to a base path.
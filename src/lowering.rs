@@ -1,25 +1,33 @@
 mod ast_lowering;
 pub mod ir;
+pub mod lint;
 mod utils;
 
 use crate::{
     diagnostics::LoweringError,
     lowering::{
-        ast_lowering::statement,
+        ast_lowering::{
+            declaration,
+            expression::{is_builtin_name, lower_expr},
+            statement,
+        },
         ir::{
             IrBuilder,
             adts::{Adt, StructAdt, StructField},
-            function::FunctionBuilder,
-            symbols::TypeIndex,
-            types::{MathicType, NumericTy, SintTy, UintTy},
+            basic_block::Terminator,
+            function::{FunctionBuilder, LocalKind},
+            instruction::LValInstruct,
+            symbols::{DeclTable, TypeIndex},
+            types::{FloatTy, MathicType, NumericTy, SintTy, UintTy, lower_inner_ast_type},
         },
     },
     parser::{
         Span,
         ast::{
             IrModule,
-            declaration::{AstType, DeclStmt, FuncDecl, Path, StructDecl, TopLevelItem},
-            statement::StmtKind,
+            declaration::{AstType, DeclStmt, FuncDecl, Path, StructDecl, TopLevelItem, VarDecl},
+            expression::ExprStmt,
+            statement::{Stmt, StmtKind},
         },
     },
 };
@@ -30,36 +38,253 @@ use tracing::instrument;
 ///
 /// Given an AST, this function lowers it and returns a MATHIR. In the process,
 /// semantic check are perfomed to verify the correctness of the program.
+///
+/// Lowering goes on after a semantic error, so every error of the program is
+/// returned at once.
 #[instrument(target = "lowering")]
-pub fn lower_program(program: &IrModule) -> Result<Ir, LoweringError> {
+pub fn lower_program(program: &IrModule) -> Result<Ir, Vec<LoweringError>> {
     let start = std::time::Instant::now();
     tracing::info!("Starting lowering phase");
-    let mut ir_builder = IrBuilder::new(program.module_name.clone(), program.modules.clone());
+    let mut ir_builder = IrBuilder::new(program);
+
+    lower_items(&mut ir_builder, program);
+
+    if !ir_builder.errors.is_empty() {
+        return Err(ir_builder.errors);
+    }
+
+    tracing::info!("Lowering complete: {:?}", start.elapsed());
+
+    Ok(ir_builder.build())
+}
+
+/// A variable declared by a previous REPL input, which lives in a global.
+#[derive(Debug, Clone)]
+pub struct ReplGlobal {
+    pub name: String,
+    pub ty: AstType,
+    /// Symbol of the global.
+    pub symbol: String,
+}
+
+/// A lowered REPL input.
+pub struct ReplLowering {
+    pub ir: Ir,
+    /// Type of the input's result, if it has one.
+    pub result_ty: Option<MathicType>,
+    /// Globals holding the variables the input declares.
+    pub globals: Vec<ReplGlobal>,
+}
+
+/// Lowers a REPL input.
+///
+/// Besides the items of `program`, a function named `name` is built running
+/// `body`, which sees the variables of previous inputs through `globals`.
+/// The variables `body` declares at its top level are kept in new globals,
+/// except for symbolic ones: symbolic values only exist while compiling, so
+/// they can't outlive the function.
+///
+/// If there's a `result` expression, it becomes the function's return value
+/// as long as it's a number or a boolean, otherwise it is only evaluated.
+pub fn lower_repl_input(
+    program: &IrModule,
+    name: &str,
+    globals: &[ReplGlobal],
+    body: &[Stmt],
+    result: Option<&ExprStmt>,
+) -> Result<ReplLowering, Vec<LoweringError>> {
+    let mut ir_builder = IrBuilder::new(program);
+
+    lower_items(&mut ir_builder, program);
+
+    let span = Span::from(0..0);
+    let void_ty = ir_builder.get_or_insert_type_idx(MathicType::Void);
+    let mangled_function_name = ir_builder.get_mangled_name(&ir_builder.module_name, name);
+    let mut func_builder = FunctionBuilder::new(
+        mangled_function_name,
+        &[],
+        void_ty,
+        &mut ir_builder,
+        span,
+        false,
+    )
+    .map_err(|e| vec![e])?;
+
+    for global in globals {
+        if let Err(e) = bind_repl_global(&mut func_builder, global, false, span) {
+            func_builder.report(e);
+        }
+    }
+
+    let mut new_globals = Vec::new();
+
+    for stmt in body {
+        let lowered = match &stmt.kind {
+            StmtKind::Decl(DeclStmt::Var(var)) => lower_repl_var(&mut func_builder, var, stmt.span)
+                .map(|global| {
+                    new_globals.extend(global);
+                }),
+            _ => statement::lower_stmt(&mut func_builder, stmt),
+        };
+
+        if let Err(e) = lowered {
+            func_builder.report(e);
+        }
+    }
+
+    let result_ty = match result.map(|expr| lower_repl_result(&mut func_builder, expr)) {
+        Some(Ok(result_ty)) => Some(result_ty),
+        Some(Err(e)) => {
+            func_builder.report(e);
+            None
+        }
+        None => None,
+    };
+
+    let func = func_builder.build();
+
+    ir_builder.add_function(func);
+
+    if !ir_builder.errors.is_empty() {
+        return Err(ir_builder.errors);
+    }
+
+    Ok(ReplLowering {
+        ir: ir_builder.build(),
+        result_ty,
+        globals: new_globals,
+    })
+}
+
+/// Lowers a variable declared at the top level of a REPL input. Unless it
+/// holds symbolic expressions, it is stored in a new global, which is
+/// returned.
+fn lower_repl_var(
+    func_builder: &mut FunctionBuilder,
+    var: &VarDecl,
+    span: Span,
+) -> Result<Option<ReplGlobal>, LoweringError> {
+    let ty_idx = lower_inner_ast_type(func_builder, &var.ty, span)?;
+
+    if func_builder.holds_symbolic(ty_idx, span)? {
+        declaration::lower_var_declaration(func_builder, var, span)?;
+
+        return Ok(None);
+    }
+
+    let (init, _) = declaration::lower_var_init(func_builder, var, span)?;
+
+    let global = ReplGlobal {
+        name: var.name.clone(),
+        ty: var.ty.clone(),
+        symbol: format!(
+            "mathic__repl__{}::{}",
+            func_builder.ir_builder.module_name, var.name
+        ),
+    };
+    let local_idx = bind_repl_global(func_builder, &global, true, span)?;
 
+    func_builder.push_instruction(LValInstruct::Assign {
+        local_idx,
+        value: init,
+        modifier: Vec::new(),
+        span: Some(span),
+    });
+
+    Ok(Some(global))
+}
+
+/// Declares a local standing for a REPL global, returning its index.
+fn bind_repl_global(
+    func_builder: &mut FunctionBuilder,
+    global: &ReplGlobal,
+    is_definition: bool,
+    span: Span,
+) -> Result<usize, LoweringError> {
+    let ty = lower_inner_ast_type(func_builder, &global.ty, span)?;
+    let local_idx = func_builder.sym_table.add_local(
+        Some(global.name.clone()),
+        ty,
+        Some(span),
+        LocalKind::Temp,
+    )?;
+
+    func_builder.push_instruction(LValInstruct::Global {
+        local_idx,
+        symbol: global.symbol.clone(),
+        ty,
+        is_definition,
+        span: Some(span),
+    });
+
+    Ok(local_idx)
+}
+
+/// Lowers the result of a REPL input, returning its type.
+fn lower_repl_result(
+    func_builder: &mut FunctionBuilder,
+    expr: &ExprStmt,
+) -> Result<MathicType, LoweringError> {
+    let (value, value_ty_idx) = lower_expr(func_builder, expr, None)?;
+    let value_ty = func_builder.get_type(value_ty_idx, expr.span)?;
+
+    if matches!(value_ty, MathicType::Numeric(_) | MathicType::Bool) {
+        func_builder.return_ty = value_ty_idx;
+
+        let last_block_idx = func_builder.last_block_idx();
+        func_builder.get_basic_block_mut(last_block_idx).terminator =
+            Terminator::Return(Some(value), Some(expr.span));
+    }
+
+    Ok(value_ty)
+}
+
+/// Returns the declarations visible from a program's top level, which are its
+/// own items and the imported ones.
+///
+/// Items which fail to be declared are skipped, `lower_program` reports them.
+pub fn declarations(program: &IrModule) -> DeclTable {
+    let mut ir_builder = IrBuilder::new(program);
+
+    for item in program.items.iter() {
+        let _ = match item {
+            TopLevelItem::Func(f) => ir_builder.decl_table.add_func_decl(f.clone(), None),
+            TopLevelItem::Import(imp) => lower_import(&mut ir_builder, imp),
+            TopLevelItem::Struct(s) => ir_builder.decl_table.add_struct_decl(s.clone(), None),
+        };
+    }
+
+    ir_builder.decl_table
+}
+
+/// Lowers the items of a program, reporting the errors of each of them.
+fn lower_items(ir_builder: &mut IrBuilder, program: &IrModule) {
     // Save program's items' declarations. This is for on-demand lowering, allowing
     // to reference function no yet declared. For example, a function call
     // of a not yet declared function.
     for item in program.items.iter() {
-        match item {
-            TopLevelItem::Func(f) => ir_builder.decl_table.add_func_decl(f.clone(), None)?,
-            TopLevelItem::Import(imp) => lower_import(&mut ir_builder, imp)?,
-            TopLevelItem::Struct(s) => ir_builder.decl_table.add_struct_decl(s.clone(), None)?,
+        let declared = match item {
+            TopLevelItem::Func(f) => ir_builder.decl_table.add_func_decl(f.clone(), None),
+            TopLevelItem::Import(imp) => lower_import(ir_builder, imp),
+            TopLevelItem::Struct(s) => ir_builder.decl_table.add_struct_decl(s.clone(), None),
+        };
+
+        if let Err(e) = declared {
+            ir_builder.report(e);
         }
     }
 
     for item in program.items.iter() {
-        match item {
-            TopLevelItem::Func(f) => lower_top_level_function(&mut ir_builder, f)?,
-            TopLevelItem::Struct(s) => {
-                let _ = lower_top_level_struct(&mut ir_builder, s)?;
-            }
-            _ => {}
+        let lowered = match item {
+            TopLevelItem::Func(f) => lower_top_level_function(ir_builder, f),
+            TopLevelItem::Struct(s) => lower_top_level_struct(ir_builder, s).map(|_| ()),
+            _ => Ok(()),
+        };
+
+        if let Err(e) = lowered {
+            ir_builder.report(e);
         }
     }
-
-    tracing::info!("Lowering complete: {:?}", start.elapsed());
-
-    Ok(ir_builder.build())
 }
 
 /// Lowering an import statement.
@@ -146,8 +371,25 @@ fn lower_top_level_function(
         body,
         span,
         return_ty,
+        is_extern,
+        is_test,
     } = func_decl;
 
+    if is_builtin_name(name) {
+        return Err(LoweringError::BuiltinRedefinition {
+            name: name.clone(),
+            span: *span,
+        });
+    }
+
+    // Tests are called by the runner, which has nothing to pass them.
+    if *is_test && (!params.is_empty() || return_ty.is_some()) {
+        return Err(LoweringError::InvalidTestFunction {
+            name: name.clone(),
+            span: *span,
+        });
+    }
+
     let return_ty = match return_ty {
         Some(ty) => lower_top_level_ast_type(ir_builder, ty, *span)?,
         None => ir_builder.get_or_insert_type_idx(MathicType::Void),
@@ -163,17 +405,55 @@ fn lower_top_level_function(
         false,
     )?;
 
+    // Host functions have no body, the code generated for them calls the
+    // function the host registers. They may return nothing.
+    if *is_extern {
+        let mut tys = func_builder
+            .params_tys
+            .iter()
+            .map(|ty| func_builder.get_type(*ty, *span))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match func_builder.get_type(func_builder.return_ty, *span)? {
+            MathicType::Void => {}
+            ty => tys.push(ty),
+        }
+
+        if let Some(ty) = tys
+            .iter()
+            .find(|ty| !matches!(ty, MathicType::Numeric(_) | MathicType::Bool))
+        {
+            return Err(LoweringError::UnsupportedFeature {
+                feature: format!("passing {ty} to or from host functions"),
+                span: *span,
+            });
+        }
+
+        func_builder.is_host = true;
+
+        let func = func_builder.build();
+        ir_builder.add_function(func);
+
+        return Ok(());
+    }
+
+    func_builder.is_test = *is_test;
+
     // Save function's declaration. This for on-demand lowering, allowing
     // to reference function no yet declared. For example, a function call
     // of a not yet declared function.
     for stmt in body.iter() {
-        if let StmtKind::Decl(DeclStmt::Func(f)) = &stmt.kind {
-            func_builder.decl_table.add_func_decl(f.clone(), None)?;
+        if let StmtKind::Decl(DeclStmt::Func(f)) = &stmt.kind
+            && let Err(e) = func_builder.decl_table.add_func_decl(f.clone(), None)
+        {
+            func_builder.report(e);
         }
     }
 
     for stmt in body {
-        statement::lower_stmt(&mut func_builder, stmt)?;
+        if let Err(e) = statement::lower_stmt(&mut func_builder, stmt) {
+            func_builder.report(e);
+        }
     }
 
     let func = func_builder.build();
@@ -196,9 +476,11 @@ fn lower_top_level_struct(
     };
 
     for field in fields {
+        let ty = lower_top_level_ast_type(ir_builder, &field.ty, field.span)?;
+
         adt.fields.push(StructField {
             name: field.name.clone(),
-            ty: lower_top_level_ast_type(ir_builder, &field.ty, field.span)?,
+            ty,
             _is_pub: field.is_pub,
         });
     }
@@ -214,6 +496,13 @@ pub fn lower_top_level_ast_type(
     span: Span,
 ) -> Result<TypeIndex, LoweringError> {
     Ok(match ty {
+        AstType::Array { elem, len } => {
+            let elem_ty_idx = lower_top_level_ast_type(ir_builder, elem, span)?;
+            ir_builder.get_or_insert_type_idx(MathicType::Array {
+                elem: elem_ty_idx,
+                len: *len,
+            })
+        }
         AstType::Type { ty, inner } => {
             match ty.as_str() {
                 "isz" => ir_builder
@@ -240,6 +529,10 @@ pub fn lower_top_level_ast_type(
                     .get_or_insert_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::U64))),
                 "u128" => ir_builder
                     .get_or_insert_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::U128))),
+                "f32" => ir_builder
+                    .get_or_insert_type_idx(MathicType::Numeric(NumericTy::Float(FloatTy::F32))),
+                "f64" => ir_builder
+                    .get_or_insert_type_idx(MathicType::Numeric(NumericTy::Float(FloatTy::F64))),
                 "str" => ir_builder.get_or_insert_type_idx(MathicType::Str),
                 "char" => ir_builder.get_or_insert_type_idx(MathicType::Char),
                 "bool" => ir_builder.get_or_insert_type_idx(MathicType::Bool),
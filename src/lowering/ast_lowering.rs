@@ -2,3 +2,4 @@ pub mod control_flow;
 pub mod declaration;
 pub mod expression;
 pub mod statement;
+pub mod symbolic;
@@ -3,23 +3,27 @@ use crate::{
     lowering::{
         ast_lowering::{expression, statement},
         ir::{
-            basic_block::Terminator,
+            basic_block::{BlockId, Terminator},
             function::{FunctionBuilder, LocalKind},
             instruction::{LValInstruct, RValInstruct, RValueKind},
-            types::MathicType,
-            value::Value,
+            types::{MathicType, NumericTy, UintTy},
+            value::{ConstExpr, NumericConst, Value, ValueModifier},
         },
     },
     parser::{
         Span,
         ast::{
-            control_flow::{ForStmt, IfStmt, WhileStmt},
-            expression::{ArithOp, BinaryOp, CmpOp},
+            control_flow::{ForIterable, ForStmt, IfStmt, WhileStmt},
+            expression::{ArithOp, BinaryOp, CmpOp, ExprStmt},
             statement::BlockStmt,
         },
     },
 };
 
+/// Target of the branch ending a body whose following block isn't created
+/// yet, see [`resolve_pending_branch`].
+const PENDING_BLOCK: BlockId = BlockId::MAX;
+
 pub fn lower_if(func: &mut FunctionBuilder, stmt: &IfStmt) -> Result<(), LoweringError> {
     let IfStmt {
         condition,
@@ -27,69 +31,54 @@ pub fn lower_if(func: &mut FunctionBuilder, stmt: &IfStmt) -> Result<(), Lowerin
         else_block,
     } = stmt;
 
-    let (condition_val, condition_ty) = expression::lower_expr(func, condition, None)?;
-
-    if !func.get_type(condition_ty, condition.span)?.is_bool() {
-        return Err(LoweringError::MismatchedType {
-            expected: MathicType::Bool,
-            found: func.get_type(condition_ty, condition.span)?,
-            span: condition.span,
-        });
-    }
+    // The blocks are checked even if the condition is wrong.
+    let condition_val = match lower_condition(func, condition) {
+        Ok(condition) => Some(condition),
+        Err(e) => {
+            func.report(e);
+            None
+        }
+    };
 
-    // Hold the index of the current block to create the condition branch later.
+    // The condition may have added blocks, e.g. for calls.
     let trigger_block_idx = func.last_block_idx();
 
-    let (true_block, false_block) = if let Some(else_block) = else_block {
-        let target_block_idx = func.last_block_idx() + 3;
+    let then_block_idx = statement::lower_block(func, then_block, pending_branch())?;
+    let then_end_idx = func.last_block_idx();
 
-        let then_block_idx = statement::lower_block(
-            func,
-            then_block,
-            Terminator::Branch {
-                target: target_block_idx,
-                block_args: Vec::new(),
-                span: None,
-            },
-        )?;
+    let else_blocks = match else_block {
+        Some(else_block) => {
+            let else_block_idx = statement::lower_block(func, else_block, pending_branch())?;
 
-        let else_block_idx = statement::lower_block(
-            func,
-            else_block,
-            Terminator::Branch {
-                target: target_block_idx,
-                block_args: Vec::new(),
+            Some((else_block_idx, func.last_block_idx()))
+        }
+        None => None,
+    };
+
+    // The code after the `if` goes on in a new block.
+    let next_block_idx =
+        func.split_block(trigger_block_idx, |next_block_idx| match condition_val {
+            Some(condition) => Terminator::CondBranch {
+                condition,
+                true_block: then_block_idx,
+                true_block_args: Vec::new(),
+                false_block_args: Vec::new(),
+                false_block: else_blocks
+                    .map_or(next_block_idx, |(else_block_idx, _)| else_block_idx),
                 span: None,
             },
-        )?;
-
-        (then_block_idx, else_block_idx)
-    } else {
-        let target_block_idx = func.last_block_idx() + 2;
-
-        let then_block_idx = statement::lower_block(
-            func,
-            then_block,
-            Terminator::Branch {
-                target: target_block_idx,
+            None => Terminator::Branch {
+                target: next_block_idx,
                 block_args: Vec::new(),
                 span: None,
             },
-        )?;
-
-        func.add_block(Terminator::Return(None, None), None);
+        });
 
-        (then_block_idx, target_block_idx)
-    };
+    resolve_pending_branch(func, then_end_idx, next_block_idx);
 
-    func.get_basic_block_mut(trigger_block_idx).terminator = Terminator::CondBranch {
-        condition: condition_val,
-        true_block,
-        true_block_args: Vec::new(),
-        false_block_args: Vec::new(),
-        false_block,
-        span: None,
-    };
+    if let Some((_, else_end_idx)) = else_blocks {
+        resolve_pending_branch(func, else_end_idx, next_block_idx);
+    }
 
     Ok(())
 }
@@ -101,7 +90,39 @@ pub fn lower_while(
 ) -> Result<(), LoweringError> {
     let WhileStmt { condition, body } = stmt;
 
-    let (loop_breaker_condition, condition_ty_idx) = expression::lower_expr(func, condition, None)?;
+    // The body is checked even if the condition is wrong, which then never
+    // gets to be generated.
+    let loop_breaker_condition = |func: &mut FunctionBuilder| match lower_condition(func, condition)
+    {
+        Ok(condition) => condition,
+        Err(e) => {
+            func.report(e);
+
+            RValInstruct::new(
+                RValueKind::Use {
+                    value: Value::Const(ConstExpr::Bool(false)),
+                    span: None,
+                },
+                func.get_or_insert_global_type_idx(MathicType::Bool),
+            )
+        }
+    };
+
+    lower_loop(
+        func,
+        body,
+        loop_breaker_condition,
+        None,
+        Vec::with_capacity(0),
+    )
+}
+
+/// Lowers the condition of an `if` or a `while`, which must be a boolean.
+fn lower_condition(
+    func: &mut FunctionBuilder,
+    condition: &ExprStmt,
+) -> Result<RValInstruct, LoweringError> {
+    let (condition_val, condition_ty_idx) = expression::lower_expr(func, condition, None)?;
     let condition_ty = func.get_type(condition_ty_idx, condition.span)?;
 
     if !condition_ty.is_bool() {
@@ -112,7 +133,7 @@ pub fn lower_while(
         });
     }
 
-    lower_loop(func, body, loop_breaker_condition, Vec::with_capacity(0))
+    Ok(condition_val)
 }
 
 pub fn lower_for(
@@ -122,11 +143,15 @@ pub fn lower_for(
 ) -> Result<(), LoweringError> {
     let ForStmt {
         variable,
-        start,
-        end,
+        iterable,
         body,
     } = stmt;
 
+    let (start, end) = match iterable {
+        ForIterable::Range { start, end } => (start, end),
+        ForIterable::Array(array) => return lower_for_array(func, variable, array, body, span),
+    };
+
     let (start_val, start_ty) = expression::lower_expr(func, start, None)?;
     let (end_val, _) = expression::lower_expr(func, end, None)?;
 
@@ -191,7 +216,153 @@ pub fn lower_for(
         span: None,
     });
 
-    lower_loop(func, body, loop_breaker_condition, extra_instructions)
+    lower_loop(
+        func,
+        body,
+        |_| loop_breaker_condition,
+        None,
+        extra_instructions,
+    )
+}
+
+/// Lowers a loop over the elements of an array. A counter goes from 0 to the
+/// array's length, and every iteration starts by reading the element at the
+/// counter into the loop's variable.
+fn lower_for_array(
+    func: &mut FunctionBuilder,
+    variable: &str,
+    array: &ExprStmt,
+    body: &BlockStmt,
+    span: Span,
+) -> Result<(), LoweringError> {
+    let (array_val, array_ty_idx) = expression::lower_expr(func, array, None)?;
+
+    let MathicType::Array { elem, len } = func.get_type(array_ty_idx, array.span)? else {
+        return Err(LoweringError::NotIterable {
+            ty: func.get_type(array_ty_idx, array.span)?,
+            span: array.span,
+        });
+    };
+
+    // Elements are read from memory, so temporary arrays are stored first.
+    let (array_local_idx, array_modifier) = match array_val.kind {
+        RValueKind::Use {
+            value:
+                Value::InMemory {
+                    local_idx,
+                    modifier,
+                },
+            ..
+        } => (local_idx, modifier),
+        _ => {
+            let local_idx = func
+                .sym_table
+                .add_local(None, array_ty_idx, None, LocalKind::Temp)?;
+
+            func.push_instruction(LValInstruct::Let {
+                local_idx,
+                init: array_val,
+                span: Some(array.span),
+            });
+
+            (local_idx, vec![])
+        }
+    };
+
+    let usize_ty_idx =
+        func.get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::Usize)));
+    let usize_const = |value: usize| {
+        RValInstruct::new(
+            RValueKind::Use {
+                value: Value::Const(ConstExpr::Numeric(NumericConst::Usize(value))),
+                span: None,
+            },
+            usize_ty_idx,
+        )
+    };
+
+    let counter_idx = func
+        .sym_table
+        .add_local(None, usize_ty_idx, None, LocalKind::Temp)?;
+    let counter_use = || {
+        RValInstruct::new(
+            RValueKind::Use {
+                value: Value::InMemory {
+                    local_idx: counter_idx,
+                    modifier: vec![],
+                },
+                span: None,
+            },
+            usize_ty_idx,
+        )
+    };
+
+    func.push_instruction(LValInstruct::Let {
+        local_idx: counter_idx,
+        init: usize_const(0),
+        span: None,
+    });
+
+    let element_idx = func.sym_table.add_local(
+        Some(variable.to_string()),
+        elem,
+        Some(span),
+        LocalKind::Temp,
+    )?;
+
+    let mut element_modifier = array_modifier;
+    element_modifier.push(ValueModifier::Index(Value::InMemory {
+        local_idx: counter_idx,
+        modifier: vec![],
+    }));
+
+    let read_element = LValInstruct::Let {
+        local_idx: element_idx,
+        init: RValInstruct::new(
+            RValueKind::Use {
+                value: Value::InMemory {
+                    local_idx: array_local_idx,
+                    modifier: element_modifier,
+                },
+                span: Some(array.span),
+            },
+            elem,
+        ),
+        span: None,
+    };
+
+    let loop_breaker_condition = RValInstruct::new(
+        RValueKind::Binary {
+            op: BinaryOp::Compare(CmpOp::Lt),
+            lhs: Box::new(counter_use()),
+            rhs: Box::new(usize_const(len)),
+            span: array.span,
+        },
+        func.get_or_insert_global_type_idx(MathicType::Bool),
+    );
+
+    let extra_instructions = vec![LValInstruct::Assign {
+        local_idx: counter_idx,
+        value: RValInstruct::new(
+            RValueKind::Binary {
+                op: BinaryOp::Arithmetic(ArithOp::Add),
+                lhs: Box::new(counter_use()),
+                rhs: Box::new(usize_const(1)),
+                span: array.span,
+            },
+            usize_ty_idx,
+        ),
+        modifier: vec![],
+        span: None,
+    }];
+
+    lower_loop(
+        func,
+        body,
+        |_| loop_breaker_condition,
+        Some(read_element),
+        extra_instructions,
+    )
 }
 
 /// Helper function to lower a loop.
@@ -203,13 +374,17 @@ pub fn lower_for(
 ///
 /// `func`: the current function being lowered.
 /// `loop_body`: loop's statements.
-/// `condition`: loop's breaker condition.
+/// `condition`: lowers the loop's breaker condition, which is evaluated
+/// before every iteration.
+/// `prelude`: instruction to execute at the start of every iteration, before
+/// the loop's statements.
 /// `extra_instructions`: set of instructions to execute at the and of every
 /// iteration.
 fn lower_loop(
     func: &mut FunctionBuilder,
     loop_body: &BlockStmt,
-    condition: RValInstruct,
+    condition: impl FnOnce(&mut FunctionBuilder) -> RValInstruct,
+    prelude: Option<LValInstruct>,
     extra_instructions: Vec<LValInstruct>,
 ) -> Result<(), LoweringError> {
     // Loops take three basic blocks:
@@ -217,36 +392,23 @@ fn lower_loop(
     //     looping or not.
     //  2. loop: it is in charge of executing the loop's statements.
     //  3. exit: it next block after the loop finished.
-    let start_block_idx = func.last_block_idx() + 1;
-    let loop_block_idx = start_block_idx + 1;
-    let end_block_idx = start_block_idx + 2;
-
-    // Jump to the start block.
-    func.get_basic_block_mut(func.last_block_idx()).terminator = Terminator::Branch {
-        target: start_block_idx,
-        block_args: Vec::new(),
-        span: None,
-    };
-
-    // The first block to add will be the start block. It will jump to the
-    // loop block (the block to it) until the condition is not matched.
-    // Once this happens, it will jump to exit block (which is two blocks
-    // after it).
-    let start_block_idx = func.add_block(
-        Terminator::CondBranch {
-            condition,
-            true_block: loop_block_idx,
-            true_block_args: Vec::new(),
-            false_block_args: Vec::new(),
-            false_block: end_block_idx,
+    //
+    // Both the condition and the loop's statements may add blocks of their
+    // own, e.g. for calls.
+    let start_block_idx = func.split_block(func.last_block_idx(), |start_block_idx| {
+        Terminator::Branch {
+            target: start_block_idx,
+            block_args: Vec::new(),
             span: None,
-        },
-        None,
-    );
+        }
+    });
+
+    let condition = condition(func);
+    let check_block_idx = func.last_block_idx();
 
     // The loop block executes the loop's statements and jumps back to the
     // start block.
-    let _ = statement::lower_block(
+    let loop_block_idx = statement::lower_block(
         func,
         loop_body,
         Terminator::Branch {
@@ -256,13 +418,44 @@ fn lower_loop(
         },
     )?;
 
+    if let Some(prelude) = prelude {
+        func.get_basic_block_mut(loop_block_idx)
+            .instructions
+            .insert(0, prelude);
+    }
+
     // Instructions to execute before verifying the condition.
     for i in extra_instructions {
         func.push_instruction(i);
     }
 
-    // Prepare the exit block.
-    func.add_block(Terminator::Return(None, None), None);
+    // Once the condition doesn't hold, the code goes on in the exit block.
+    func.split_block(check_block_idx, |end_block_idx| Terminator::CondBranch {
+        condition,
+        true_block: loop_block_idx,
+        true_block_args: Vec::new(),
+        false_block_args: Vec::new(),
+        false_block: end_block_idx,
+        span: None,
+    });
 
     Ok(())
 }
+
+fn pending_branch() -> Terminator {
+    Terminator::Branch {
+        target: PENDING_BLOCK,
+        block_args: Vec::new(),
+        span: None,
+    }
+}
+
+/// Points the branch ending a body to the block which follows it. A return
+/// in the body leaves the branch at the end of the code after it.
+fn resolve_pending_branch(func: &mut FunctionBuilder, block_idx: BlockId, next_block_idx: BlockId) {
+    if let Terminator::Branch { target, .. } = &mut func.get_basic_block_mut(block_idx).terminator
+        && *target == PENDING_BLOCK
+    {
+        *target = next_block_idx;
+    }
+}
@@ -6,10 +6,10 @@ use crate::{
         ast_lowering::{expression, statement},
         ir::{
             adts::{Adt, StructAdt, StructField},
-            function::{FunctionBuilder, LocalKind},
-            instruction::{LValInstruct, RValueKind},
+            function::{FunctionBuilder, LocalKind, symbol_name},
+            instruction::{LValInstruct, RValInstruct},
+            symbols::TypeIndex,
             types::{MathicType, lower_inner_ast_type},
-            value::Value,
         },
     },
     parser::{
@@ -26,10 +26,43 @@ pub fn lower_var_declaration(
     stmt: &VarDecl,
     span: Span,
 ) -> Result<(), LoweringError> {
+    let (init, var_ty_idx) = lower_var_init(func, stmt, span)?;
+
+    let local_idx = func.sym_table.add_local(
+        Some(stmt.name.clone()),
+        var_ty_idx,
+        Some(span),
+        LocalKind::Temp,
+    )?;
+
+    // We need to track the symbols used in the symbolic expressions.
+    if func.holds_symbolic(var_ty_idx, span)? {
+        let symbols = expression::rvalue_symbols(func, &init.kind);
+        let field_symbols = expression::rvalue_field_symbols(func, &init.kind);
+        let local = &mut func.sym_table.locals[local_idx];
+
+        local.symbols = symbols;
+        local.field_symbols = field_symbols;
+    }
+
+    func.push_instruction(LValInstruct::Let {
+        local_idx,
+        init,
+        span: Some(span),
+    });
+
+    Ok(())
+}
+
+/// Lowers the value a variable is declared with, checking it has the
+/// variable's type.
+pub fn lower_var_init(
+    func: &mut FunctionBuilder,
+    stmt: &VarDecl,
+    span: Span,
+) -> Result<(RValInstruct, TypeIndex), LoweringError> {
     let VarDecl {
-        name,
-        expr,
-        ty: var_ty,
+        expr, ty: var_ty, ..
     } = stmt;
     let var_ty_idx = lower_inner_ast_type(func, var_ty, span)?;
     let (init, expr_ty_idx) = expression::lower_expr(func, expr, Some(var_ty_idx))?;
@@ -38,6 +71,10 @@ pub fn lower_var_declaration(
     let expr_ty = func.get_type(expr_ty_idx, span)?;
 
     if expr_ty_idx != var_ty_idx {
+        if let Some(err) = expression::missing_symbols_error(func, &init, span) {
+            return Err(err);
+        }
+
         return Err(LoweringError::MismatchedType {
             expected: var_ty,
             found: expr_ty,
@@ -45,31 +82,7 @@ pub fn lower_var_declaration(
         });
     }
 
-    let local_idx =
-        func.sym_table
-            .add_local(Some(name.clone()), var_ty_idx, Some(span), LocalKind::Temp)?;
-
-    // We need to track the symbols used in the symbolic expression.
-    if func.get_type(var_ty_idx, span)?.is_symbolic() {
-        let symbols = match &init.kind {
-            RValueKind::SymbolicBinary { symbols, .. } => symbols.clone(),
-            RValueKind::Use {
-                value: Value::Symbol { local_idx },
-                ..
-            } => func.sym_table.locals[*local_idx].symbols.clone(),
-            _ => HashSet::with_capacity(0),
-        };
-
-        func.sym_table.locals[local_idx].symbols = symbols;
-    }
-
-    func.push_instruction(LValInstruct::Let {
-        local_idx,
-        init,
-        span: Some(span),
-    });
-
-    Ok(())
+    Ok((init, var_ty_idx))
 }
 
 pub fn lower_sym_decl(
@@ -80,6 +93,14 @@ pub fn lower_sym_decl(
     let SymDecl { name, ty } = sym_decl;
 
     let sym_ty_idx = lower_inner_ast_type(func, ty, span)?;
+
+    // A call may already have declared the symbol, see `declare_call_symbol`.
+    if func.sym_table.implicit_symbols.remove(name)
+        && func.sym_table.get_local_from_name(name, span)?.ty == sym_ty_idx
+    {
+        return Ok(());
+    }
+
     let local_idx =
         func.sym_table
             .add_local(Some(name.clone()), sym_ty_idx, Some(span), LocalKind::Sym)?;
@@ -88,7 +109,7 @@ pub fn lower_sym_decl(
 
     func.push_instruction(LValInstruct::Sym {
         local_idx,
-        sym_name: name.clone(),
+        sym_name: symbol_name(&func.name, name),
         ty: sym_ty_idx,
         span: Some(span),
     });
@@ -109,9 +130,11 @@ pub fn lower_inner_struct(
     };
 
     for field in fields {
+        let ty = lower_inner_ast_type(func, &field.ty, field.span)?;
+
         adt.fields.push(StructField {
             name: field.name.clone(),
-            ty: lower_inner_ast_type(func, &field.ty, field.span)?,
+            ty,
             _is_pub: field.is_pub,
         });
     }
@@ -134,6 +157,13 @@ pub fn lower_inner_function(
         ..
     } = stmt;
 
+    if expression::is_builtin_name(name) {
+        return Err(LoweringError::BuiltinRedefinition {
+            name: name.clone(),
+            span,
+        });
+    }
+
     let mangled_function_name = func
         .ir_builder
         .get_mangled_name(&func.ir_builder.module_name, name);
@@ -153,19 +183,25 @@ pub fn lower_inner_function(
     // to reference function no yet declared. For example, a function call
     // of a not yet declared function.
     for stmt in body.iter() {
-        match &stmt.kind {
+        let declared = match &stmt.kind {
             StmtKind::Decl(DeclStmt::Func(f)) => {
-                inner_func.decl_table.add_func_decl(f.clone(), None)?
+                inner_func.decl_table.add_func_decl(f.clone(), None)
             }
             StmtKind::Decl(DeclStmt::Struct(s)) => {
-                inner_func.decl_table.add_struct_decl(s.clone(), None)?
+                inner_func.decl_table.add_struct_decl(s.clone(), None)
             }
-            _ => {}
+            _ => Ok(()),
+        };
+
+        if let Err(e) = declared {
+            inner_func.report(e);
         }
     }
 
     for stmt in body.iter() {
-        statement::lower_stmt(&mut inner_func, stmt)?;
+        if let Err(e) = statement::lower_stmt(&mut inner_func, stmt) {
+            inner_func.report(e);
+        }
     }
 
     let inner_func = inner_func.build();
@@ -1,16 +1,21 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use crate::{
     diagnostics::LoweringError,
     lowering::{
+        ast_lowering::symbolic::return_symbols,
+        declarations,
         ir::{
             basic_block::Terminator,
-            function::{FunctionBuilder, LocalKind},
+            function::{FunctionBuilder, LocalKind, symbol_name},
             instruction::{InitInstruct, LValInstruct, RValInstruct, RValueKind},
             symbols::TypeIndex,
             types::{
-                FloatTy, MathicType, NumericTy, SintTy, UintTy, lower_inner_ast_type,
-                resolve_struct_type,
+                FloatTy, MathicType, NumericTy, SintTy, UintTy, get_or_insert_array_type,
+                lower_inner_ast_type, resolve_struct_type,
             },
             value::{ConstExpr, NumericConst, Value, ValueModifier},
         },
@@ -18,10 +23,22 @@ use crate::{
     },
     parser::{
         Span,
-        ast::expression::{BinaryOp, ExprStmt, ExprStmtKind, LogicalOp, PrimaryExpr, UnaryOp},
+        ast::{
+            declaration::FuncDecl,
+            expression::{
+                BinaryOp, CmpOp, ExprStmt, ExprStmtKind, LogicalOp, PrimaryExpr, UnaryOp,
+            },
+        },
     },
 };
 
+/// Name of the builtin computing the derivative of a symbolic expression.
+const DIFF_BUILTIN: &str = "diff";
+/// Name of the builtin trapping when a condition doesn't hold.
+const ASSERT_BUILTIN: &str = "assert";
+/// Name of the builtin trapping when two values aren't equal.
+const ASSERT_EQ_BUILTIN: &str = "assert_eq";
+
 pub fn lower_expr(
     func: &mut FunctionBuilder,
     expr: &ExprStmt,
@@ -34,13 +51,28 @@ pub fn lower_expr(
         ExprStmtKind::Group(expr) => {
             return lower_expr(func, expr, ty_hint);
         }
+        ExprStmtKind::Call { callee, args } if is_diff_call(callee) => {
+            return lower_diff(func, args, expr.span);
+        }
+        ExprStmtKind::Call { callee, args } if is_assert_call(callee) => {
+            return lower_assert(func, expr, callee, args);
+        }
         ExprStmtKind::Call { callee, args } => lower_call(func, callee, args, expr.span)?,
         ExprStmtKind::Assign {
             name,
             expr: assign_expr,
         } => lower_assignment(func, name, assign_expr, expr.span)?,
         ExprStmtKind::Logical { lhs, op, rhs } => lower_logical_op(func, lhs, *op, rhs, expr.span)?,
-        ExprStmtKind::Index { .. } => todo!(),
+        ExprStmtKind::ArrayInit { elements } => {
+            lower_array_init(func, elements, expr.span, ty_hint)?
+        }
+        ExprStmtKind::Index {
+            expr: array_expr,
+            index,
+        } => lower_index_get(func, array_expr, index, expr.span)?,
+        ExprStmtKind::IndexSet { lhs, index, rhs } => {
+            lower_index_set(func, lhs, index, rhs, expr.span)?
+        }
         ExprStmtKind::Substitution { callee, args } => {
             return lower_substitution(func, callee, args, expr.span);
         }
@@ -72,6 +104,10 @@ fn lower_assignment(
     let (value, expr_ty_idx) = lower_expr(func, expr, Some(local.ty))?;
     // The new value should be of the same type as the local's.
     if local.ty != expr_ty_idx {
+        if let Some(err) = missing_symbols_error(func, &value, span) {
+            return Err(err);
+        }
+
         return Err(LoweringError::MismatchedType {
             expected: func.get_type(local.ty, span)?,
             found: func.get_type(expr_ty_idx, span)?,
@@ -79,14 +115,8 @@ fn lower_assignment(
         });
     }
 
-    let symbols = match &value.kind {
-        RValueKind::SymbolicBinary { symbols, .. } => symbols.clone(),
-        RValueKind::Use {
-            value: Value::Symbol { local_idx },
-            ..
-        } => func.sym_table.locals[*local_idx].symbols.clone(),
-        _ => HashSet::with_capacity(0),
-    };
+    let symbols = rvalue_symbols(func, &value.kind);
+    let field_symbols = rvalue_field_symbols(func, &value.kind);
 
     func.get_basic_block_mut(func.last_block_idx())
         .instructions
@@ -97,9 +127,12 @@ fn lower_assignment(
             span: Some(span),
         });
 
-    // Track symbols used in the symbolic expression.
-    if func.get_type(local.ty, span)?.is_symbolic() {
-        func.sym_table.locals[local.local_idx].symbols = symbols;
+    // Track symbols used in the symbolic expressions.
+    if func.holds_symbolic(local.ty, span)? {
+        let local = &mut func.sym_table.locals[local.local_idx];
+
+        local.symbols = symbols;
+        local.field_symbols = field_symbols;
     }
 
     Ok(RValInstruct::new(
@@ -129,7 +162,7 @@ fn lower_call(
         }
         _ => unreachable!(),
     };
-    let callee_name = func_prototype.name;
+    let callee_name = func_prototype.name.clone();
 
     if func_prototype.params.len() != func_args.len() {
         return Err(LoweringError::WrongArgumentCount {
@@ -140,6 +173,8 @@ fn lower_call(
         });
     }
 
+    let mut args_symbols = Vec::with_capacity(func_args.len());
+
     for (arg, param) in func_args.iter().zip(func_prototype.params.iter()) {
         let param_ty_idx = lower_inner_ast_type(func, &param.ty, param.span)?;
         let (arg_val, arg_ty_idx) = lower_expr(func, arg, Some(param_ty_idx))?;
@@ -152,6 +187,7 @@ fn lower_call(
             });
         }
 
+        args_symbols.push(rvalue_symbols(func, &arg_val.kind));
         arg_values.push(arg_val);
     }
 
@@ -160,15 +196,11 @@ fn lower_call(
     // not RValue instructions, we need to create a temporary local to store
     // the return value and then create the RValue instruction pointing to that
     // new local.
-    let return_ty_idx = match func_prototype.return_ty {
-        Some(ty) => lower_inner_ast_type(func, &ty, span)?,
+    let return_ty_idx = match &func_prototype.return_ty {
+        Some(ty) => lower_inner_ast_type(func, ty, span)?,
         None => func.get_or_insert_global_type_idx(MathicType::Void),
     };
-    let local_idx = func
-        .sym_table
-        .add_local(None, return_ty_idx, None, LocalKind::Temp)?;
-
-    let dest_block_idx = func.last_block_idx() + 1;
+    let is_symbolic = func.get_type(return_ty_idx, span)?.is_symbolic();
 
     let mangled_callee_name = {
         let module_name = match module_idx {
@@ -178,24 +210,123 @@ fn lower_call(
         func.ir_builder.get_mangled_name(module_name, &callee_name)
     };
 
-    func.get_basic_block_mut(func.last_block_idx()).terminator = Terminator::Call {
-        callee: mangled_callee_name,
-        args: arg_values,
-        span: Some(span),
-        return_dest: Value::InMemory {
+    // The caller's symbols standing for the callee's ones are declared before
+    // the local holding the returned value. Each expression the value holds
+    // gets symbols of its own type.
+    let mut fields_symbols = Vec::new();
+
+    for (path, ty_idx) in func.symbolic_fields(return_ty_idx, span)? {
+        let (symbols, renamed_symbols) = call_symbols(
+            func,
+            &func_prototype,
+            module_idx,
+            &args_symbols,
+            ty_idx,
+            span,
+        )?;
+
+        fields_symbols.push(CallFieldSymbols {
+            path,
+            ty_idx,
+            symbols,
+            renamed_symbols,
+        });
+    }
+
+    let local_idx = func
+        .sym_table
+        .add_local(None, return_ty_idx, None, LocalKind::Temp)?;
+
+    {
+        let local = &mut func.sym_table.locals[local_idx];
+
+        for field in &fields_symbols {
+            local.symbols.extend(field.symbols.iter().copied());
+
+            if !is_symbolic {
+                local
+                    .field_symbols
+                    .insert(field.path.clone(), field.symbols.clone());
+            }
+        }
+    }
+
+    // Symbolic values are SSA, they are never stored in memory.
+    let return_dest = if is_symbolic {
+        Value::Symbol { local_idx }
+    } else {
+        Value::InMemory {
             local_idx,
             modifier: vec![],
-        },
-        return_ty: return_ty_idx,
-        dest_block: dest_block_idx,
+        }
     };
 
-    func.add_block(Terminator::Return(None, None), None);
+    let call_return_dest = return_dest.clone();
+    func.split_block(func.last_block_idx(), |dest_block| Terminator::Call {
+        callee: mangled_callee_name.clone(),
+        args: arg_values,
+        span: Some(span),
+        return_dest: call_return_dest,
+        return_ty: return_ty_idx,
+        dest_block,
+    });
+
+    let returned = RValInstruct::new(
+        RValueKind::Use {
+            value: return_dest,
+            span: None,
+        },
+        return_ty_idx,
+    );
+
+    if fields_symbols
+        .iter()
+        .all(|field| field.renamed_symbols.is_empty())
+    {
+        return Ok(returned);
+    }
+
+    if is_symbolic {
+        return Ok(rename_call_symbols(
+            &mangled_callee_name,
+            returned,
+            &fields_symbols[0],
+            span,
+        ));
+    }
+
+    // A struct can't be renamed as a whole, it is rebuilt with its
+    // expressions renamed in a new local.
+    let init = rename_call_fields(
+        func,
+        &mangled_callee_name,
+        local_idx,
+        Vec::new(),
+        return_ty_idx,
+        &fields_symbols,
+        span,
+    )?;
+    let renamed_idx = func
+        .sym_table
+        .add_local(None, return_ty_idx, None, LocalKind::Temp)?;
+
+    let symbols = rvalue_symbols(func, &init.kind);
+    let field_symbols = rvalue_field_symbols(func, &init.kind);
+    let local = &mut func.sym_table.locals[renamed_idx];
+
+    local.symbols = symbols;
+    local.field_symbols = field_symbols;
+
+    func.push_instruction(LValInstruct::Let {
+        local_idx: renamed_idx,
+        init,
+        span: None,
+    });
 
     Ok(RValInstruct::new(
         RValueKind::Use {
             value: Value::InMemory {
-                local_idx,
+                local_idx: renamed_idx,
                 modifier: vec![],
             },
             span: None,
@@ -204,6 +335,214 @@ fn lower_call(
     ))
 }
 
+/// Symbols of an expression held by the value returned by a call, see
+/// [`call_symbols`].
+struct CallFieldSymbols {
+    /// Path of the expression in the returned value, empty if the value is the
+    /// expression itself.
+    path: Vec<usize>,
+    ty_idx: TypeIndex,
+    symbols: HashSet<usize>,
+    renamed_symbols: Vec<(String, usize)>,
+}
+
+/// Renames the callee's symbols of an expression returned by a call.
+///
+/// Once inlined, the callee's symbols are still named after the callee.
+/// Substituting them by the caller's symbols renames them.
+fn rename_call_symbols(
+    callee_name: &str,
+    expr: RValInstruct,
+    field: &CallFieldSymbols,
+    span: Span,
+) -> RValInstruct {
+    let (syms, values) = field
+        .renamed_symbols
+        .iter()
+        .map(|(name, local_idx)| {
+            let value = RValInstruct::new(
+                RValueKind::Use {
+                    value: Value::Symbol {
+                        local_idx: *local_idx,
+                    },
+                    span: None,
+                },
+                field.ty_idx,
+            );
+
+            (symbol_name(callee_name, name), value)
+        })
+        .unzip();
+
+    RValInstruct::new(
+        RValueKind::SymbolicSubs {
+            expr: Box::new(expr),
+            syms,
+            values,
+            symbols: field.symbols.clone(),
+            span,
+        },
+        field.ty_idx,
+    )
+}
+
+/// Rebuilds the value at `path` of a struct returned by a call, renaming
+/// the callee's symbols of the expressions it holds.
+#[allow(clippy::too_many_arguments)]
+fn rename_call_fields(
+    func: &FunctionBuilder,
+    callee_name: &str,
+    local_idx: usize,
+    path: Vec<usize>,
+    ty_idx: TypeIndex,
+    fields_symbols: &[CallFieldSymbols],
+    span: Span,
+) -> Result<RValInstruct, LoweringError> {
+    let value = RValInstruct::new(
+        RValueKind::Use {
+            value: Value::InMemory {
+                local_idx,
+                modifier: path.iter().copied().map(ValueModifier::Field).collect(),
+            },
+            span: None,
+        },
+        ty_idx,
+    );
+
+    if let Some(field) = fields_symbols.iter().find(|field| field.path == path) {
+        if field.renamed_symbols.is_empty() {
+            return Ok(value);
+        }
+
+        return Ok(rename_call_symbols(callee_name, value, field, span));
+    }
+
+    if !fields_symbols
+        .iter()
+        .any(|field| field.path.starts_with(&path))
+    {
+        return Ok(value);
+    }
+
+    let fields = func
+        .get_adt(ty_idx, span)?
+        .get_fields_tys()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, field_ty)| {
+            let mut field_path = path.clone();
+            field_path.push(idx);
+
+            rename_call_fields(
+                func,
+                callee_name,
+                local_idx,
+                field_path,
+                field_ty,
+                fields_symbols,
+                span,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(RValInstruct::new(
+        RValueKind::Init {
+            init_inst: InitInstruct::StructInit { fields },
+            span,
+        },
+        ty_idx,
+    ))
+}
+
+/// Returns the symbols of the expression returned by a call.
+///
+/// These are the symbols of the arguments passed through, see
+/// [`ReturnSymbols`](super::symbolic::ReturnSymbols), plus the caller's
+/// symbols standing for the ones declared by the callee, which have the same
+/// name. The latter are also returned by name, and are declared if the caller
+/// didn't declare them yet.
+fn call_symbols(
+    func: &mut FunctionBuilder,
+    callee: &FuncDecl,
+    module_idx: Option<usize>,
+    args_symbols: &[HashSet<usize>],
+    ty_idx: TypeIndex,
+    span: Span,
+) -> Result<(HashSet<usize>, Vec<(String, usize)>), LoweringError> {
+    let callee_symbols = match module_idx {
+        None => return_symbols(
+            &func.ir_builder.decl_table,
+            &func.ir_builder.module_name,
+            callee,
+        ),
+        Some(idx) => {
+            let module = &func.ir_builder.decl_table.modules[idx];
+            return_symbols(&declarations(module), &module.module_name, callee)
+        }
+    };
+
+    let mut symbols = HashSet::new();
+    let mut renamed_symbols = Vec::with_capacity(callee_symbols.names.len());
+
+    for idx in callee_symbols.params {
+        symbols.extend(args_symbols.get(idx).into_iter().flatten().copied());
+    }
+
+    for name in callee_symbols.names {
+        let local_idx = declare_call_symbol(func, &callee.name, &name, ty_idx, span)?;
+
+        symbols.insert(local_idx);
+        renamed_symbols.push((name, local_idx));
+    }
+
+    Ok((symbols, renamed_symbols))
+}
+
+/// Returns the caller's symbol named `name`, declaring it if needed.
+fn declare_call_symbol(
+    func: &mut FunctionBuilder,
+    callee_name: &str,
+    name: &str,
+    ty_idx: TypeIndex,
+    span: Span,
+) -> Result<usize, LoweringError> {
+    if let Some(&local_idx) = func.sym_table.local_indexes.get(name) {
+        let local = &func.sym_table.locals[local_idx];
+
+        if local.kind != LocalKind::Sym || local.ty != ty_idx {
+            return Err(LoweringError::ConflictingSymbol {
+                name: name.to_string(),
+                function: callee_name.to_string(),
+                span,
+            });
+        }
+
+        return Ok(local_idx);
+    }
+
+    let local_idx =
+        func.sym_table
+            .add_local(Some(name.to_string()), ty_idx, Some(span), LocalKind::Sym)?;
+
+    func.sym_table.locals[local_idx].symbols = HashSet::from([local_idx]);
+    func.sym_table.implicit_symbols.insert(name.to_string());
+
+    func.push_instruction(LValInstruct::Sym {
+        local_idx,
+        sym_name: symbol_name(&func.name, name),
+        ty: ty_idx,
+        span: Some(span),
+    });
+
+    Ok(local_idx)
+}
+
+/// Lowers a substitution over a symbolic expression.
+///
+/// Binding every symbol of the expression to a concrete value evaluates it.
+/// Otherwise, either because some symbols are left free or because a symbol is
+/// replaced by another expression, the result is a new expression, which is
+/// built by the `symbolic-expand` pass.
 fn lower_substitution(
     func: &mut FunctionBuilder,
     callee: &ExprStmt,
@@ -225,13 +564,7 @@ fn lower_substitution(
     };
     let inner_ty_idx = func.get_or_insert_global_type_idx(inner_ty);
 
-    let symbols = match &sym_expr.kind {
-        RValueKind::Use {
-            value: Value::Symbol { local_idx },
-            ..
-        } => func.sym_table.locals[*local_idx].symbols.clone(),
-        _ => unreachable!(),
-    };
+    let symbols = rvalue_symbols(func, &sym_expr.kind);
 
     let mut provided = HashSet::new();
 
@@ -248,27 +581,13 @@ fn lower_substitution(
         provided.insert(local.local_idx);
     }
 
-    if provided != symbols {
-        let missing = symbols
-            .difference(&provided)
-            .map(|idx| {
-                func.sym_table.locals[*idx]
-                    .debug_name
-                    .clone()
-                    .unwrap_or_default()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        return Err(LoweringError::MissingSymbols { missing, span });
-    }
-
     let mut args = args
         .iter()
         .map(|(name, expr)| {
             let (lowered_expr, lowered_expr_ty_idx) = lower_expr(func, expr, Some(inner_ty_idx))?;
 
-            if lowered_expr_ty_idx != inner_ty_idx {
+            // Symbols can also be replaced by expressions of the same domain.
+            if lowered_expr_ty_idx != inner_ty_idx && lowered_expr_ty_idx != sym_expr_ty_idx {
                 return Err(LoweringError::MismatchedType {
                     expected: inner_ty,
                     found: func.get_type(lowered_expr_ty_idx, expr.span)?,
@@ -281,14 +600,45 @@ fn lower_substitution(
 
     args.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    let (syms, exprs) = args.into_iter().unzip();
+    // Track the symbols left in the resulting expression, which are the ones
+    // not substituted plus the ones brought in by the substituted values.
+    let mut residual_symbols = symbols
+        .difference(&provided)
+        .copied()
+        .collect::<HashSet<_>>();
+    let mut by_expression = false;
+
+    for (_, value) in &args {
+        residual_symbols.extend(rvalue_symbols(func, &value.kind));
+        by_expression |= value.ty == sym_expr_ty_idx;
+    }
+
+    let (syms, exprs): (Vec<_>, Vec<_>) = args
+        .into_iter()
+        .map(|(name, expr)| (symbol_name(&func.name, &name), expr))
+        .unzip();
+
+    if !residual_symbols.is_empty() || by_expression {
+        return Ok((
+            RValInstruct::new(
+                RValueKind::SymbolicSubs {
+                    expr: Box::new(sym_expr),
+                    syms,
+                    values: exprs,
+                    symbols: residual_symbols,
+                    span,
+                },
+                sym_expr_ty_idx,
+            ),
+            sym_expr_ty_idx,
+        ));
+    }
 
     let local_idx = func
         .sym_table
         .add_local(None, inner_ty_idx, None, LocalKind::Temp)?;
-    let dest_block_idx = func.last_block_idx() + 1;
 
-    func.get_basic_block_mut(func.last_block_idx()).terminator = Terminator::Eval {
+    func.split_block(func.last_block_idx(), |dest_block| Terminator::Eval {
         expr: sym_expr,
         syms,
         exprs,
@@ -297,10 +647,9 @@ fn lower_substitution(
             modifier: vec![],
         },
         return_ty_idx: inner_ty_idx,
-        dest_block: dest_block_idx,
+        dest_block,
         span: Some(span),
-    };
-    func.add_block(Terminator::Return(None, None), None);
+    });
 
     Ok((
         RValInstruct::new(
@@ -317,6 +666,155 @@ fn lower_substitution(
     ))
 }
 
+/// Lowers a call to the `diff` builtin.
+///
+/// The derivative is expanded by the `symbolic-expand` pass, so here we
+/// only check the operands. A derivative can't reference symbols that aren't
+/// part of the original expression, hence its symbols are carried over.
+fn lower_diff(
+    func: &mut FunctionBuilder,
+    args: &[ExprStmt],
+    span: Span,
+) -> Result<(RValInstruct, TypeIndex), LoweringError> {
+    let [expr, sym] = args else {
+        return Err(LoweringError::WrongArgumentCount {
+            name: DIFF_BUILTIN.to_string(),
+            expected: 2,
+            got: args.len(),
+            span,
+        });
+    };
+
+    let (sym_expr, sym_expr_ty_idx) = lower_expr(func, expr, None)?;
+    let sym_expr_ty = func.get_type(sym_expr_ty_idx, expr.span)?;
+
+    if !sym_expr_ty.is_symbolic() {
+        return Err(LoweringError::MismatchedType {
+            expected: MathicType::SymbolicExpr(NumericTy::Sint(SintTy::Isize)),
+            found: sym_expr_ty,
+            span: expr.span,
+        });
+    }
+
+    let ExprStmtKind::Primary(PrimaryExpr::Ident(sym_name)) = &sym.kind else {
+        return Err(LoweringError::NotASymbol { span: sym.span });
+    };
+    let sym_local = func.sym_table.get_local_from_name(sym_name, sym.span)?;
+
+    if sym_local.kind != LocalKind::Sym {
+        return Err(LoweringError::NotASymbol { span: sym.span });
+    }
+
+    // Symbols live in the same domain as the expression.
+    if sym_local.ty != sym_expr_ty_idx {
+        return Err(LoweringError::MismatchedType {
+            expected: sym_expr_ty,
+            found: func.get_type(sym_local.ty, sym.span)?,
+            span: sym.span,
+        });
+    }
+
+    let symbols = rvalue_symbols(func, &sym_expr.kind);
+
+    Ok((
+        RValInstruct::new(
+            RValueKind::SymbolicDiff {
+                expr: Box::new(sym_expr),
+                sym_name: symbol_name(&func.name, sym_name),
+                symbols,
+                span,
+            },
+            sym_expr_ty_idx,
+        ),
+        sym_expr_ty_idx,
+    ))
+}
+
+/// Lowers a call to the `assert` or `assert_eq` builtins.
+///
+/// The current block branches on the condition, `a == b` for `assert_eq`,
+/// to a block which traps reporting the assertion, or to a new block where
+/// the code goes on.
+fn lower_assert(
+    func: &mut FunctionBuilder,
+    expr: &ExprStmt,
+    callee: &ExprStmt,
+    args: &[ExprStmt],
+) -> Result<(RValInstruct, TypeIndex), LoweringError> {
+    let condition = match args {
+        [condition] if is_builtin(callee, ASSERT_BUILTIN) => condition.clone(),
+        [lhs, rhs] if is_builtin(callee, ASSERT_EQ_BUILTIN) => ExprStmt {
+            kind: ExprStmtKind::Binary {
+                lhs: Box::new(lhs.clone()),
+                op: BinaryOp::Compare(CmpOp::Eq),
+                rhs: Box::new(rhs.clone()),
+            },
+            span: expr.span,
+        },
+        _ => {
+            let (name, expected) = if is_builtin(callee, ASSERT_BUILTIN) {
+                (ASSERT_BUILTIN, 1)
+            } else {
+                (ASSERT_EQ_BUILTIN, 2)
+            };
+
+            return Err(LoweringError::WrongArgumentCount {
+                name: name.to_string(),
+                expected,
+                got: args.len(),
+                span: expr.span,
+            });
+        }
+    };
+
+    let (condition_val, condition_ty_idx) = lower_expr(func, &condition, None)?;
+    let condition_ty = func.get_type(condition_ty_idx, condition.span)?;
+
+    if !condition_ty.is_bool() {
+        return Err(LoweringError::MismatchedType {
+            expected: MathicType::Bool,
+            found: condition_ty,
+            span: condition.span,
+        });
+    }
+
+    // The condition may have added blocks, e.g. for calls.
+    let trigger_block_idx = func.last_block_idx();
+
+    let trap_block_idx = func.add_block(
+        Terminator::Trap {
+            message: format!(
+                "assertion failed: {}",
+                &func.ir_builder.source[expr.span.as_range()]
+            ),
+            span: Some(expr.span),
+        },
+        None,
+    );
+
+    func.split_block(trigger_block_idx, |next_block_idx| Terminator::CondBranch {
+        condition: condition_val,
+        true_block: next_block_idx,
+        false_block: trap_block_idx,
+        true_block_args: Vec::new(),
+        false_block_args: Vec::new(),
+        span: Some(expr.span),
+    });
+
+    let void_ty_idx = func.get_or_insert_global_type_idx(MathicType::Void);
+
+    Ok((
+        RValInstruct::new(
+            RValueKind::Use {
+                value: Value::Const(ConstExpr::Void),
+                span: None,
+            },
+            void_ty_idx,
+        ),
+        void_ty_idx,
+    ))
+}
+
 fn lower_binary_op(
     func: &mut FunctionBuilder,
     lhs: &ExprStmt,
@@ -324,19 +822,21 @@ fn lower_binary_op(
     rhs: &ExprStmt,
     span: Span,
 ) -> Result<RValInstruct, LoweringError> {
-    let (lhs, lhs_ty_idx) = lower_expr(func, lhs, None)?;
+    // A numeric literal on the lhs takes its type from the rhs, so that
+    // expressions such as `2.5 * x` don't fall back to the default type.
+    let lhs_ty_hint = match &lhs.kind {
+        ExprStmtKind::Primary(PrimaryExpr::Num(_)) => {
+            let rhs_ty_idx = lower_expression_type(func, &rhs.kind, None, span)?;
+            Some(operand_ty_hint(func, rhs_ty_idx, span)?)
+        }
+        _ => None,
+    };
+
+    let (lhs, lhs_ty_idx) = lower_expr(func, lhs, lhs_ty_hint)?;
     let lhs_ty = func.get_type(lhs_ty_idx, span)?;
 
-    let (rhs, rhs_ty_idx) = lower_expr(
-        func,
-        rhs,
-        // If lhs_ty is symbolic, we don't want to add a ty_hint.
-        if !lhs_ty.is_symbolic() {
-            Some(lhs_ty_idx)
-        } else {
-            None
-        },
-    )?;
+    let rhs_ty_hint = operand_ty_hint(func, lhs_ty_idx, span)?;
+    let (rhs, rhs_ty_idx) = lower_expr(func, rhs, Some(rhs_ty_hint))?;
 
     let rhs_ty = func.get_type(rhs_ty_idx, span)?;
 
@@ -344,7 +844,18 @@ fn lower_binary_op(
 
     Ok(match op {
         BinaryOp::Arithmetic(arith) if is_symbolic => {
-            if lhs_ty.is_symbolic() && rhs_ty.is_symbolic() && lhs_ty_idx != rhs_ty_idx {
+            // Either both operands are expressions of the same domain, or the
+            // concrete operand matches the expression's inner type.
+            let operands_match = match (lhs_ty, rhs_ty) {
+                (MathicType::SymbolicExpr(_), MathicType::SymbolicExpr(_)) => {
+                    lhs_ty_idx == rhs_ty_idx
+                }
+                (MathicType::SymbolicExpr(inner), other)
+                | (other, MathicType::SymbolicExpr(inner)) => other == MathicType::Numeric(inner),
+                _ => unreachable!(),
+            };
+
+            if !operands_match {
                 return Err(LoweringError::MismatchedType {
                     expected: lhs_ty,
                     found: rhs_ty,
@@ -352,24 +863,9 @@ fn lower_binary_op(
                 });
             }
 
-            let mut symbols = HashSet::new();
-
             // Track symbols used in the symbolic expression.
-            let retrieve_opr_symbols = |syms: &mut HashSet<usize>, opr: &RValueKind| match opr {
-                RValueKind::Use {
-                    value: Value::Symbol { local_idx },
-                    ..
-                } => {
-                    syms.extend(func.sym_table.locals[*local_idx].symbols.iter().copied());
-                }
-                RValueKind::SymbolicBinary {
-                    symbols: bin_syms, ..
-                } => syms.extend(bin_syms),
-                _ => {}
-            };
-
-            retrieve_opr_symbols(&mut symbols, &lhs.kind);
-            retrieve_opr_symbols(&mut symbols, &rhs.kind);
+            let mut symbols = rvalue_symbols(func, &lhs.kind);
+            symbols.extend(rvalue_symbols(func, &rhs.kind));
 
             let kind = RValueKind::SymbolicBinary {
                 op: arith,
@@ -623,6 +1119,11 @@ fn lower_struct_set(
         });
     }
 
+    // The field's expressions replace the ones it held.
+    if func.holds_symbolic(value_ty_idx, span)? {
+        set_field_symbols(func, local_idx, &modifier, &value.kind);
+    }
+
     func.get_basic_block_mut(func.last_block_idx())
         .instructions
         .push(LValInstruct::Assign {
@@ -641,14 +1142,363 @@ fn lower_struct_set(
     ))
 }
 
-fn lower_primary_value(
+/// Tracks the symbols of a value assigned to the field of a struct local at
+/// the path given by `modifier`.
+fn set_field_symbols(
     func: &mut FunctionBuilder,
-    expr: &PrimaryExpr,
-    span: Span,
-    ty_hint: Option<TypeIndex>,
-) -> Result<RValInstruct, LoweringError> {
-    let (value, ty) = match expr {
-        PrimaryExpr::Ident(name) => {
+    local_idx: usize,
+    modifier: &[ValueModifier],
+    kind: &RValueKind,
+) {
+    let Some(prefix) = field_path(modifier) else {
+        return;
+    };
+
+    let mut value_symbols = rvalue_field_symbols(func, kind);
+
+    if value_symbols.is_empty() {
+        value_symbols.insert(Vec::new(), rvalue_symbols(func, kind));
+    }
+
+    let local = &mut func.sym_table.locals[local_idx];
+
+    local
+        .field_symbols
+        .retain(|path, _| !path.starts_with(&prefix));
+
+    for (path, symbols) in value_symbols {
+        local
+            .field_symbols
+            .insert([prefix.as_slice(), &path].concat(), symbols);
+    }
+
+    local.symbols = local.field_symbols.values().flatten().copied().collect();
+}
+
+fn lower_array_init(
+    func: &mut FunctionBuilder,
+    elements: &[ExprStmt],
+    span: Span,
+    ty_hint: Option<TypeIndex>,
+) -> Result<RValInstruct, LoweringError> {
+    let array_hint = match ty_hint {
+        Some(ty) => match func.get_type(ty, span)? {
+            MathicType::Array { elem, len } => Some((ty, elem, len)),
+            _ => None,
+        },
+        None => None,
+    };
+
+    if let Some((_, _, len)) = array_hint
+        && len != elements.len()
+    {
+        return Err(LoweringError::MismatchedArrayLength {
+            expected: len,
+            found: elements.len(),
+            span,
+        });
+    }
+
+    let mut elem_ty_idx = array_hint.map(|(_, elem, _)| elem);
+    let mut init_elements = Vec::with_capacity(elements.len());
+
+    for element in elements {
+        let (init_element, init_element_ty_idx) = lower_expr(func, element, elem_ty_idx)?;
+
+        // Every element should be of the same type, the first one decides
+        // it if no hint was provided.
+        match elem_ty_idx {
+            Some(ty) if ty != init_element_ty_idx => {
+                return Err(LoweringError::MismatchedType {
+                    expected: func.get_type(ty, element.span)?,
+                    found: func.get_type(init_element_ty_idx, element.span)?,
+                    span: element.span,
+                });
+            }
+            Some(_) => {}
+            None => elem_ty_idx = Some(init_element_ty_idx),
+        }
+
+        init_elements.push(init_element);
+    }
+
+    let array_ty_idx = match (array_hint, elem_ty_idx) {
+        (Some((ty, _, _)), _) => ty,
+        (None, Some(elem)) => get_or_insert_array_type(func, elem, elements.len()),
+        (None, None) => {
+            return Err(LoweringError::UnsupportedFeature {
+                feature: "empty array literals without a type annotation".to_string(),
+                span,
+            });
+        }
+    };
+
+    Ok(RValInstruct::new(
+        RValueKind::Init {
+            init_inst: InitInstruct::ArrayInit {
+                elements: init_elements,
+            },
+            span,
+        },
+        array_ty_idx,
+    ))
+}
+
+fn lower_index_get(
+    func: &mut FunctionBuilder,
+    expr: &ExprStmt,
+    index: &ExprStmt,
+    span: Span,
+) -> Result<RValInstruct, LoweringError> {
+    let (array_expr, array_ty_idx) = lower_expr(func, expr, None)?;
+
+    let MathicType::Array { elem, len } = func.get_type(array_ty_idx, expr.span)? else {
+        return Err(LoweringError::NotIndexable {
+            ty: func.get_type(array_ty_idx, expr.span)?,
+            span: expr.span,
+        });
+    };
+
+    let RValueKind::Use {
+        value: Value::InMemory {
+            local_idx,
+            mut modifier,
+        },
+        ..
+    } = array_expr.kind
+    else {
+        return Err(LoweringError::UnsupportedFeature {
+            feature: "indexing temporary arrays".to_string(),
+            span: expr.span,
+        });
+    };
+
+    let index_value = lower_index_value(func, index, len)?;
+
+    modifier.push(ValueModifier::Index(index_value));
+
+    Ok(RValInstruct::new(
+        RValueKind::Use {
+            value: Value::InMemory {
+                local_idx,
+                modifier,
+            },
+            span: Some(span),
+        },
+        elem,
+    ))
+}
+
+fn lower_index_set(
+    func: &mut FunctionBuilder,
+    lhs: &ExprStmt,
+    index: &ExprStmt,
+    rhs: &ExprStmt,
+    span: Span,
+) -> Result<RValInstruct, LoweringError> {
+    let element_value = lower_index_get(func, lhs, index, span)?;
+    let (local_idx, modifier) = {
+        let RValueKind::Use { value, .. } = element_value.kind else {
+            unreachable!()
+        };
+        let Value::InMemory {
+            local_idx,
+            modifier,
+        } = value
+        else {
+            unreachable!()
+        };
+
+        (local_idx, modifier)
+    };
+    let (value, value_ty_idx) = lower_expr(func, rhs, Some(element_value.ty))?;
+
+    if value_ty_idx != element_value.ty {
+        return Err(LoweringError::MismatchedType {
+            expected: func.get_type(element_value.ty, span)?,
+            found: func.get_type(value_ty_idx, span)?,
+            span,
+        });
+    }
+
+    func.get_basic_block_mut(func.last_block_idx())
+        .instructions
+        .push(LValInstruct::Assign {
+            local_idx,
+            value,
+            modifier,
+            span: Some(span),
+        });
+
+    Ok(RValInstruct::new(
+        RValueKind::Use {
+            value: Value::Const(ConstExpr::Void),
+            span: None,
+        },
+        func.get_or_insert_global_type_idx(MathicType::Void),
+    ))
+}
+
+/// Lowers the index of an array access to a value usable as a modifier.
+///
+/// Constant indices are checked against the array's length. Any other
+/// expression is stored in a temporary local first, and checked when the
+/// program runs.
+fn lower_index_value(
+    func: &mut FunctionBuilder,
+    index: &ExprStmt,
+    len: usize,
+) -> Result<Value, LoweringError> {
+    if let ExprStmtKind::Primary(PrimaryExpr::Num(n)) = &index.kind
+        && let Ok(idx) = n.parse::<usize>()
+        && idx >= len
+    {
+        return Err(LoweringError::IndexOutOfBounds {
+            index: idx,
+            len,
+            span: index.span,
+        });
+    }
+
+    let usize_ty_idx =
+        func.get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::Usize)));
+    let (index_expr, index_ty_idx) = lower_expr(func, index, Some(usize_ty_idx))?;
+    let index_ty = func.get_type(index_ty_idx, index.span)?;
+
+    if !index_ty.is_integer() {
+        return Err(LoweringError::MismatchedType {
+            expected: MathicType::Numeric(NumericTy::Uint(UintTy::Usize)),
+            found: index_ty,
+            span: index.span,
+        });
+    }
+
+    let index_value = match index_expr.kind {
+        RValueKind::Use {
+            value: value @ Value::Const(_),
+            ..
+        } => return Ok(value),
+        RValueKind::Use {
+            value: value @ Value::InMemory { .. },
+            ..
+        } => value,
+        _ => {
+            let local_idx = func
+                .sym_table
+                .add_local(None, index_ty_idx, None, LocalKind::Temp)?;
+
+            func.push_instruction(LValInstruct::Let {
+                local_idx,
+                init: index_expr,
+                span: Some(index.span),
+            });
+
+            Value::InMemory {
+                local_idx,
+                modifier: vec![],
+            }
+        }
+    };
+
+    lower_bounds_check(func, &index_value, index_ty_idx, len, index.span)?;
+
+    Ok(index_value)
+}
+
+/// Checks an index which isn't known at compile time against the length of
+/// the array it accesses.
+///
+/// The current block branches to a block which traps when the index is
+/// negative or not lower than the length, or to a new block which takes over
+/// the current block's terminator.
+fn lower_bounds_check(
+    func: &mut FunctionBuilder,
+    index: &Value,
+    index_ty_idx: TypeIndex,
+    len: usize,
+    span: Span,
+) -> Result<(), LoweringError> {
+    let MathicType::Numeric(index_ty) = func.get_type(index_ty_idx, span)? else {
+        unreachable!("array indices are integers")
+    };
+    let bool_ty_idx = func.get_or_insert_global_type_idx(MathicType::Bool);
+
+    let compare = |op: CmpOp, bound: NumericConst| {
+        RValInstruct::new(
+            RValueKind::Binary {
+                op: BinaryOp::Compare(op),
+                lhs: Box::new(RValInstruct::new(
+                    RValueKind::Use {
+                        value: index.clone(),
+                        span: None,
+                    },
+                    index_ty_idx,
+                )),
+                rhs: Box::new(RValInstruct::new(
+                    RValueKind::Use {
+                        value: Value::Const(ConstExpr::Numeric(bound)),
+                        span: None,
+                    },
+                    index_ty_idx,
+                )),
+                span,
+            },
+            bool_ty_idx,
+        )
+    };
+
+    // An index whose type can't hold the length is always lower than it.
+    let below_len = NumericConst::from_int(index_ty, len as u128)
+        .map(|len_const| compare(CmpOp::Lt, len_const));
+    let not_negative = index_ty
+        .is_signed()
+        .then(|| NumericConst::from_int(index_ty, 0).map(|zero| compare(CmpOp::Ge, zero)))
+        .flatten();
+
+    let condition = match (not_negative, below_len) {
+        (Some(lhs), Some(rhs)) => RValInstruct::new(
+            RValueKind::Logical {
+                op: LogicalOp::And,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span,
+            },
+            bool_ty_idx,
+        ),
+        (Some(condition), None) | (None, Some(condition)) => condition,
+        (None, None) => return Ok(()),
+    };
+
+    let check_block_idx = func.last_block_idx();
+
+    let trap_block_idx = func.add_block(
+        Terminator::Trap {
+            message: format!("index out of bounds: the length is {len}"),
+            span: Some(span),
+        },
+        None,
+    );
+
+    func.split_block(check_block_idx, |next_block_idx| Terminator::CondBranch {
+        condition,
+        true_block: next_block_idx,
+        false_block: trap_block_idx,
+        true_block_args: Vec::new(),
+        false_block_args: Vec::new(),
+        span: Some(span),
+    });
+
+    Ok(())
+}
+
+fn lower_primary_value(
+    func: &mut FunctionBuilder,
+    expr: &PrimaryExpr,
+    span: Span,
+    ty_hint: Option<TypeIndex>,
+) -> Result<RValInstruct, LoweringError> {
+    let (value, ty) = match expr {
+        PrimaryExpr::Ident(name) => {
             let local = func.sym_table.get_local_from_name(name, span)?;
             let local_ty = func.get_type(local.ty, span)?;
             // Use Symbol variant for symbolic expressions (SSA, no memory).
@@ -666,75 +1516,31 @@ fn lower_primary_value(
         }
         PrimaryExpr::Path(_) => unimplemented!(),
         PrimaryExpr::Num(n) => match ty_hint {
+            // A literal with a fractional part can't be narrowed into an
+            // integer type.
+            Some(ty) if is_float_literal(n) && !func.get_type(ty, span)?.is_float() => {
+                return Err(LoweringError::MismatchedType {
+                    expected: func.get_type(ty, span)?,
+                    found: MathicType::Numeric(NumericTy::Float(FloatTy::F64)),
+                    span,
+                });
+            }
             Some(ty) => (
-                Value::Const(match func.get_type(ty, span)? {
-                    MathicType::Numeric(NumericTy::Uint(uint_ty)) => match uint_ty {
-                        UintTy::Usize => {
-                            ConstExpr::Numeric(NumericConst::Usize(n.parse::<usize>().unwrap()))
-                        }
-                        UintTy::U8 => {
-                            ConstExpr::Numeric(NumericConst::U8(n.parse::<u8>().unwrap()))
-                        }
-                        UintTy::U16 => {
-                            ConstExpr::Numeric(NumericConst::U16(n.parse::<u16>().unwrap()))
-                        }
-                        UintTy::U32 => {
-                            ConstExpr::Numeric(NumericConst::U32(n.parse::<u32>().unwrap()))
-                        }
-                        UintTy::U64 => {
-                            ConstExpr::Numeric(NumericConst::U64(n.parse::<u64>().unwrap()))
-                        }
-                        UintTy::U128 => {
-                            ConstExpr::Numeric(NumericConst::U128(n.parse::<u128>().unwrap()))
-                        }
-                    },
-                    MathicType::Numeric(NumericTy::Sint(sint_ty)) => match sint_ty {
-                        SintTy::Isize => {
-                            ConstExpr::Numeric(NumericConst::Isize(n.parse::<isize>().unwrap()))
-                        }
-                        SintTy::I8 => {
-                            ConstExpr::Numeric(NumericConst::I8(n.parse::<i8>().unwrap()))
-                        }
-                        SintTy::I16 => {
-                            ConstExpr::Numeric(NumericConst::I16(n.parse::<i16>().unwrap()))
-                        }
-                        SintTy::I32 => {
-                            ConstExpr::Numeric(NumericConst::I32(n.parse::<i32>().unwrap()))
-                        }
-                        SintTy::I64 => {
-                            ConstExpr::Numeric(NumericConst::I64(n.parse::<i64>().unwrap()))
-                        }
-                        SintTy::I128 => {
-                            ConstExpr::Numeric(NumericConst::I128(n.parse::<i128>().unwrap()))
-                        }
-                    },
-                    MathicType::Numeric(NumericTy::Float(float_ty)) => match float_ty {
-                        FloatTy::F32 => {
-                            ConstExpr::Numeric(NumericConst::F32(n.parse::<f32>().unwrap()))
-                        }
-                        FloatTy::F64 => {
-                            ConstExpr::Numeric(NumericConst::F64(n.parse::<f64>().unwrap()))
-                        }
-                    },
-                    MathicType::Bool
-                    | MathicType::Void
-                    | MathicType::Char
-                    | MathicType::Str
-                    | MathicType::SymbolicExpr(_)
-                    | MathicType::Adt { .. } => {
-                        unreachable!()
-                    }
-                }),
+                Value::Const(numeric_literal(n, &func.get_type(ty, span)?, span)?),
                 ty,
             ),
-            None => (
-                Value::Const(ConstExpr::Numeric(NumericConst::I32(
-                    n.parse::<i32>().unwrap(),
-                ))),
-                func.get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Sint(
-                    SintTy::I32,
-                ))),
-            ),
+            None => {
+                let literal_ty = if is_float_literal(n) {
+                    MathicType::Numeric(NumericTy::Float(FloatTy::F64))
+                } else {
+                    MathicType::Numeric(NumericTy::Sint(SintTy::I32))
+                };
+
+                (
+                    Value::Const(numeric_literal(n, &literal_ty, span)?),
+                    func.get_or_insert_global_type_idx(literal_ty),
+                )
+            }
         },
         PrimaryExpr::Bool(b) => (
             Value::Const(ConstExpr::Bool(*b)),
@@ -777,8 +1583,11 @@ fn lower_expression_type(
                 let (adt_ty, _) = resolve_external_struct(func.ir_builder, path)?;
                 adt_ty
             }
-            PrimaryExpr::Num(_) => match ty_hint {
+            PrimaryExpr::Num(n) => match ty_hint {
                 Some(ty) => ty,
+                None if is_float_literal(n) => func.get_or_insert_global_type_idx(
+                    MathicType::Numeric(NumericTy::Float(FloatTy::F64)),
+                ),
                 None => func.get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Sint(
                     SintTy::I32,
                 ))),
@@ -805,6 +1614,20 @@ fn lower_expression_type(
                 lhs_ty_idx
             }
         },
+        ExprStmtKind::Call { callee, args } if is_diff_call(callee) => match args.first() {
+            Some(expr) => lower_expression_type(func, &expr.kind, None, span)?,
+            None => {
+                return Err(LoweringError::WrongArgumentCount {
+                    name: DIFF_BUILTIN.to_string(),
+                    expected: 2,
+                    got: 0,
+                    span,
+                });
+            }
+        },
+        ExprStmtKind::Call { callee, .. } if is_assert_call(callee) => {
+            func.get_or_insert_global_type_idx(MathicType::Void)
+        }
         ExprStmtKind::Call { callee, .. } => {
             let (func_decl, _) = match &callee.kind {
                 ExprStmtKind::Primary(PrimaryExpr::Ident(ident)) => {
@@ -822,19 +1645,58 @@ fn lower_expression_type(
             }
         }
         ExprStmtKind::Group(expr_stmt) => lower_expression_type(func, &expr_stmt.kind, None, span)?,
-        ExprStmtKind::Index { .. } => todo!(),
+        ExprStmtKind::ArrayInit { elements } => match ty_hint {
+            Some(ty) if func.get_type(ty, span)?.is_array() => ty,
+            _ => {
+                let Some(first) = elements.first() else {
+                    return Err(LoweringError::UnsupportedFeature {
+                        feature: "empty array literals without a type annotation".to_string(),
+                        span,
+                    });
+                };
+                let elem_ty_idx = lower_expression_type(func, &first.kind, None, span)?;
+
+                get_or_insert_array_type(func, elem_ty_idx, elements.len())
+            }
+        },
+        ExprStmtKind::Index { expr, .. } => {
+            let array_ty_idx = lower_expression_type(func, &expr.kind, None, span)?;
+
+            match func.get_type(array_ty_idx, span)? {
+                MathicType::Array { elem, .. } => elem,
+                other => return Err(LoweringError::NotIndexable { ty: other, span }),
+            }
+        }
+        ExprStmtKind::IndexSet { rhs, .. } => lower_expression_type(func, &rhs.kind, None, span)?,
         ExprStmtKind::Logical { .. } => func.get_or_insert_global_type_idx(MathicType::Bool),
         ExprStmtKind::Unary { rhs, .. } => lower_expression_type(func, &rhs.kind, None, span)?,
         ExprStmtKind::Assign { expr, .. } | ExprStmtKind::StructSet { rhs: expr, .. } => {
             lower_expression_type(func, &expr.kind, None, span)?
         }
-        ExprStmtKind::Substitution { callee, .. } => {
+        ExprStmtKind::Substitution { callee, args } => {
             let sym_expr_ty_idx = lower_expression_type(func, &callee.kind, None, span)?;
 
             let sym_expr_ty = func.get_type(sym_expr_ty_idx, callee.span)?;
 
             if let MathicType::SymbolicExpr(inner_ty) = sym_expr_ty {
-                func.get_or_insert_global_type_idx(MathicType::Numeric(inner_ty))
+                let inner_ty_idx =
+                    func.get_or_insert_global_type_idx(MathicType::Numeric(inner_ty));
+
+                // Mirrors `lower_substitution`: the result stays symbolic if
+                // any symbol is left or replaced by an expression.
+                let mut is_residual = !expr_symbols(func, expr, span)?.is_empty();
+
+                for (_, arg) in args {
+                    is_residual |=
+                        lower_expression_type(func, &arg.kind, Some(inner_ty_idx), span)?
+                            == sym_expr_ty_idx;
+                }
+
+                if is_residual {
+                    sym_expr_ty_idx
+                } else {
+                    inner_ty_idx
+                }
             } else {
                 return Err(LoweringError::MismatchedType {
                     expected: MathicType::SymbolicExpr(NumericTy::Sint(SintTy::Isize)),
@@ -861,3 +1723,363 @@ fn lower_expression_type(
         }
     })
 }
+
+/// Returns the free symbols referenced by a symbolic value.
+pub fn rvalue_symbols(func: &FunctionBuilder, kind: &RValueKind) -> HashSet<usize> {
+    match kind {
+        RValueKind::SymbolicBinary { symbols, .. }
+        | RValueKind::SymbolicDiff { symbols, .. }
+        | RValueKind::SymbolicSubs { symbols, .. } => symbols.clone(),
+        RValueKind::Use {
+            value: Value::Symbol { local_idx },
+            ..
+        } => local_symbols(func, *local_idx),
+        RValueKind::Use {
+            value: Value::InMemory { .. },
+            ..
+        }
+        | RValueKind::Init {
+            init_inst: InitInstruct::StructInit { .. },
+            ..
+        } => rvalue_field_symbols(func, kind)
+            .into_values()
+            .flatten()
+            .collect(),
+        _ => HashSet::with_capacity(0),
+    }
+}
+
+/// Returns the free symbols referenced by each symbolic expression held by a
+/// value, keyed by their path in the value, see
+/// [`FunctionBuilder::symbolic_fields`].
+///
+/// An expression read from a struct gets the empty path.
+pub fn rvalue_field_symbols(
+    func: &FunctionBuilder,
+    kind: &RValueKind,
+) -> HashMap<Vec<usize>, HashSet<usize>> {
+    match kind {
+        RValueKind::Init {
+            init_inst: InitInstruct::StructInit { fields },
+            ..
+        } => {
+            let mut symbols = HashMap::new();
+
+            for (idx, field) in fields.iter().enumerate() {
+                let mut field_symbols = rvalue_field_symbols(func, &field.kind);
+
+                if field_symbols.is_empty() {
+                    field_symbols.insert(Vec::new(), rvalue_symbols(func, &field.kind));
+                }
+
+                for (mut path, field_symbols) in field_symbols {
+                    if !field_symbols.is_empty() {
+                        path.insert(0, idx);
+                        symbols.insert(path, field_symbols);
+                    }
+                }
+            }
+
+            symbols
+        }
+        RValueKind::Use {
+            value:
+                Value::InMemory {
+                    local_idx,
+                    modifier,
+                },
+            ..
+        } => match field_path(modifier) {
+            Some(prefix) => strip_field_path(local_field_symbols(func, *local_idx), &prefix),
+            None => HashMap::with_capacity(0),
+        },
+        _ => HashMap::with_capacity(0),
+    }
+}
+
+/// Returns the path of the field a chain of modifiers points to, or `None`
+/// if it indexes an array.
+fn field_path(modifier: &[ValueModifier]) -> Option<Vec<usize>> {
+    modifier
+        .iter()
+        .map(|m| match m {
+            ValueModifier::Field(idx) => Some(*idx),
+            ValueModifier::Index(_) => None,
+        })
+        .collect()
+}
+
+/// Keeps the symbols of the expressions under `prefix`, keyed by their path
+/// from there.
+fn strip_field_path(
+    field_symbols: HashMap<Vec<usize>, HashSet<usize>>,
+    prefix: &[usize],
+) -> HashMap<Vec<usize>, HashSet<usize>> {
+    field_symbols
+        .into_iter()
+        .filter_map(|(path, symbols)| Some((path.strip_prefix(prefix)?.to_vec(), symbols)))
+        .collect()
+}
+
+/// Returns the free symbols referenced by a symbolic local.
+///
+/// A parameter's expression is built by the caller, out of symbols the
+/// function can't name. The parameter itself stands for them, so they can't
+/// be substituted but are never taken as evaluated either.
+fn local_symbols(func: &FunctionBuilder, local_idx: usize) -> HashSet<usize> {
+    let local = &func.sym_table.locals[local_idx];
+
+    match local.kind {
+        LocalKind::Param => HashSet::from([local_idx]),
+        _ => local.symbols.clone(),
+    }
+}
+
+/// Returns the free symbols referenced by each symbolic expression held by a
+/// struct local, see [`local_symbols`].
+fn local_field_symbols(
+    func: &FunctionBuilder,
+    local_idx: usize,
+) -> HashMap<Vec<usize>, HashSet<usize>> {
+    let local = &func.sym_table.locals[local_idx];
+
+    match local.kind {
+        // The parameter's type was lowered when declaring it.
+        LocalKind::Param => func
+            .symbolic_fields(local.ty, func.span)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(path, _)| !path.is_empty())
+            .map(|(path, _)| (path, HashSet::from([local_idx])))
+            .collect(),
+        _ => local.field_symbols.clone(),
+    }
+}
+
+/// Returns the free symbols an expression would reference once lowered.
+///
+/// This is the AST counterpart of [`rvalue_symbols`], needed to know the type
+/// of a substitution before lowering it.
+fn expr_symbols(
+    func: &mut FunctionBuilder,
+    expr: &ExprStmtKind,
+    span: Span,
+) -> Result<HashSet<usize>, LoweringError> {
+    Ok(match expr {
+        ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
+            let local = func.sym_table.get_local_from_name(name, span)?;
+            local_symbols(func, local.local_idx)
+        }
+        ExprStmtKind::Binary {
+            lhs,
+            op: BinaryOp::Arithmetic(_),
+            rhs,
+        } => {
+            let mut symbols = expr_symbols(func, &lhs.kind, span)?;
+            symbols.extend(expr_symbols(func, &rhs.kind, span)?);
+            symbols
+        }
+        ExprStmtKind::Group(expr) => expr_symbols(func, &expr.kind, span)?,
+        ExprStmtKind::Call { callee, args } if is_diff_call(callee) => match args.first() {
+            Some(expr) => expr_symbols(func, &expr.kind, span)?,
+            None => HashSet::with_capacity(0),
+        },
+        ExprStmtKind::Call { .. } | ExprStmtKind::StructGet { .. } => {
+            expr_field_symbols(func, expr, span)?
+                .into_values()
+                .flatten()
+                .collect()
+        }
+        ExprStmtKind::Substitution { callee, args } => {
+            let mut symbols = expr_symbols(func, &callee.kind, span)?;
+
+            for (name, _) in args {
+                symbols.remove(&func.sym_table.get_local_from_name(name, span)?.local_idx);
+            }
+            for (_, arg) in args {
+                symbols.extend(expr_symbols(func, &arg.kind, span)?);
+            }
+
+            symbols
+        }
+        _ => HashSet::with_capacity(0),
+    })
+}
+
+/// Returns the free symbols each symbolic expression held by the value of an
+/// expression would reference once lowered.
+///
+/// This is the AST counterpart of [`rvalue_field_symbols`].
+fn expr_field_symbols(
+    func: &mut FunctionBuilder,
+    expr: &ExprStmtKind,
+    span: Span,
+) -> Result<HashMap<Vec<usize>, HashSet<usize>>, LoweringError> {
+    Ok(match expr {
+        ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
+            let local = func.sym_table.get_local_from_name(name, span)?;
+            local_field_symbols(func, local.local_idx)
+        }
+        ExprStmtKind::Group(expr) => expr_field_symbols(func, &expr.kind, span)?,
+        ExprStmtKind::StructGet { expr, field_name } => {
+            let adt_ty_idx = lower_expression_type(func, &expr.kind, None, span)?;
+            let Some(field_idx) = func.get_adt(adt_ty_idx, span)?.get_field_index(field_name)
+            else {
+                return Ok(HashMap::with_capacity(0));
+            };
+
+            strip_field_path(expr_field_symbols(func, &expr.kind, span)?, &[field_idx])
+        }
+        ExprStmtKind::Call { callee, args } if !is_diff_call(callee) => {
+            let return_ty_idx = lower_expression_type(func, expr, None, span)?;
+            let symbolic_fields = func.symbolic_fields(return_ty_idx, span)?;
+
+            if symbolic_fields.is_empty() {
+                return Ok(HashMap::with_capacity(0));
+            }
+
+            let (func_decl, module_idx) = match &callee.kind {
+                ExprStmtKind::Primary(PrimaryExpr::Ident(ident)) => {
+                    func.get_function_decl(ident, span)?
+                }
+                ExprStmtKind::Primary(PrimaryExpr::Path(path)) => {
+                    let (func_decl, module_idx) = resolve_external_func(func.ir_builder, path)?;
+                    (func_decl, Some(module_idx))
+                }
+                _ => return Ok(HashMap::with_capacity(0)),
+            };
+            let args_symbols = args
+                .iter()
+                .map(|arg| expr_symbols(func, &arg.kind, span))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut symbols = HashMap::with_capacity(symbolic_fields.len());
+
+            for (path, ty_idx) in symbolic_fields {
+                let (field_symbols, _) =
+                    call_symbols(func, &func_decl, module_idx, &args_symbols, ty_idx, span)?;
+
+                symbols.insert(path, field_symbols);
+            }
+
+            symbols
+        }
+        _ => HashMap::with_capacity(0),
+    })
+}
+
+/// Builds the error for a partial substitution used where a concrete value is
+/// expected, listing the symbols left in the expression.
+pub fn missing_symbols_error(
+    func: &FunctionBuilder,
+    value: &RValInstruct,
+    span: Span,
+) -> Option<LoweringError> {
+    let RValueKind::SymbolicSubs { symbols, .. } = &value.kind else {
+        return None;
+    };
+
+    let mut missing = symbols
+        .iter()
+        .map(|idx| {
+            func.sym_table.locals[*idx]
+                .debug_name
+                .clone()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    missing.sort();
+
+    Some(LoweringError::MissingSymbols {
+        missing: missing.join(", "),
+        span,
+    })
+}
+
+/// Whether the call refers to the `diff` builtin.
+pub fn is_diff_call(callee: &ExprStmt) -> bool {
+    is_builtin(callee, DIFF_BUILTIN)
+}
+
+/// Whether the call refers to the `assert` or `assert_eq` builtins.
+pub fn is_assert_call(callee: &ExprStmt) -> bool {
+    is_builtin(callee, ASSERT_BUILTIN) || is_builtin(callee, ASSERT_EQ_BUILTIN)
+}
+
+/// Whether `name` is taken by a builtin, so no function can be declared with
+/// it.
+pub fn is_builtin_name(name: &str) -> bool {
+    [DIFF_BUILTIN, ASSERT_BUILTIN, ASSERT_EQ_BUILTIN].contains(&name)
+}
+
+fn is_builtin(callee: &ExprStmt, builtin: &str) -> bool {
+    matches!(&callee.kind, ExprStmtKind::Primary(PrimaryExpr::Ident(ident)) if ident == builtin)
+}
+
+/// Returns the type hint for the operand of a binary operation, given the type
+/// of the other operand. Literals operating with a symbolic expression take the
+/// expression's inner type.
+fn operand_ty_hint(
+    func: &mut FunctionBuilder,
+    ty_idx: TypeIndex,
+    span: Span,
+) -> Result<TypeIndex, LoweringError> {
+    Ok(match func.get_type(ty_idx, span)? {
+        MathicType::SymbolicExpr(num_ty) => {
+            func.get_or_insert_global_type_idx(MathicType::Numeric(num_ty))
+        }
+        _ => ty_idx,
+    })
+}
+
+/// Numeric literals with a fractional part default to `f64` when no type hint
+/// is available.
+#[inline(always)]
+fn is_float_literal(n: &str) -> bool {
+    n.contains('.')
+}
+
+/// Builds the constant of a numeric literal of type `ty`, reporting literals
+/// which don't fit in it.
+fn numeric_literal(n: &str, ty: &MathicType, span: Span) -> Result<ConstExpr, LoweringError> {
+    fn parse<T: FromStr>(n: &str, ty: &MathicType, span: Span) -> Result<T, LoweringError> {
+        n.parse::<T>()
+            .map_err(|_| LoweringError::LiteralOutOfRange {
+                literal: n.to_string(),
+                ty: ty.clone(),
+                span,
+            })
+    }
+
+    let value = match ty {
+        MathicType::Numeric(NumericTy::Uint(uint_ty)) => match uint_ty {
+            UintTy::Usize => NumericConst::Usize(parse(n, ty, span)?),
+            UintTy::U8 => NumericConst::U8(parse(n, ty, span)?),
+            UintTy::U16 => NumericConst::U16(parse(n, ty, span)?),
+            UintTy::U32 => NumericConst::U32(parse(n, ty, span)?),
+            UintTy::U64 => NumericConst::U64(parse(n, ty, span)?),
+            UintTy::U128 => NumericConst::U128(parse(n, ty, span)?),
+        },
+        MathicType::Numeric(NumericTy::Sint(sint_ty)) => match sint_ty {
+            SintTy::Isize => NumericConst::Isize(parse(n, ty, span)?),
+            SintTy::I8 => NumericConst::I8(parse(n, ty, span)?),
+            SintTy::I16 => NumericConst::I16(parse(n, ty, span)?),
+            SintTy::I32 => NumericConst::I32(parse(n, ty, span)?),
+            SintTy::I64 => NumericConst::I64(parse(n, ty, span)?),
+            SintTy::I128 => NumericConst::I128(parse(n, ty, span)?),
+        },
+        MathicType::Numeric(NumericTy::Float(float_ty)) => match float_ty {
+            FloatTy::F32 => NumericConst::F32(parse(n, ty, span)?),
+            FloatTy::F64 => NumericConst::F64(parse(n, ty, span)?),
+        },
+        MathicType::Bool
+        | MathicType::Void
+        | MathicType::Char
+        | MathicType::Str
+        | MathicType::SymbolicExpr(_)
+        | MathicType::Adt { .. }
+        | MathicType::Array { .. } => unreachable!(),
+    };
+
+    Ok(ConstExpr::Numeric(value))
+}
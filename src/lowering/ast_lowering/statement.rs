@@ -18,7 +18,7 @@ use crate::{
 
 use super::control_flow::{lower_for, lower_if, lower_while};
 use super::declaration::{lower_inner_function, lower_var_declaration};
-use super::expression::lower_expr;
+use super::expression::{lower_expr, missing_symbols_error};
 
 pub fn lower_stmt(func: &mut FunctionBuilder, stmt: &Stmt) -> Result<(), LoweringError> {
     match &stmt.kind {
@@ -27,6 +27,10 @@ pub fn lower_stmt(func: &mut FunctionBuilder, stmt: &Stmt) -> Result<(), Lowerin
             let (value, value_ty_idx) = lower_expr(func, expr, Some(func.return_ty))?;
 
             if value_ty_idx != func.return_ty {
+                if let Some(err) = missing_symbols_error(func, &value, stmt.span) {
+                    return Err(err);
+                }
+
                 return Err(LoweringError::MismatchedReturnType {
                     expected: func.get_type(func.return_ty, stmt.span)?,
                     found: func.get_type(value_ty_idx, stmt.span)?,
@@ -34,27 +38,26 @@ pub fn lower_stmt(func: &mut FunctionBuilder, stmt: &Stmt) -> Result<(), Lowerin
                 });
             }
 
-            func.get_basic_block_mut(func.last_block_idx()).terminator =
-                Terminator::Return(Some(value), Some(stmt.span));
+            // The code after the return goes on in a new block, which no
+            // path reaches.
+            func.split_block(func.last_block_idx(), |_| {
+                Terminator::Return(Some(value), Some(stmt.span))
+            });
         }
         StmtKind::Block(block_stmt) => {
+            // The block takes over the current block's terminator, the code
+            // after it goes on in its last block.
             let curr_block_idx = func.last_block_idx();
-
-            func.get_basic_block_mut(curr_block_idx).terminator = Terminator::Branch {
-                target: curr_block_idx + 1,
-                block_args: Vec::new(),
-                span: None,
-            };
-
-            let _ = lower_block(
-                func,
-                block_stmt,
+            let terminator = std::mem::replace(
+                &mut func.get_basic_block_mut(curr_block_idx).terminator,
                 Terminator::Branch {
-                    target: curr_block_idx + 2,
+                    target: curr_block_idx + 1,
                     block_args: Vec::new(),
                     span: None,
                 },
-            )?;
+            );
+
+            let _ = lower_block(func, block_stmt, terminator)?;
         }
         StmtKind::Expr(expr) => {
             let _ = lower_expr(func, expr, None)?;
@@ -74,18 +77,32 @@ fn lower_declaration(
 ) -> Result<(), LoweringError> {
     match stmt {
         DeclStmt::Var(var_decl) => {
-            lower_var_declaration(func, var_decl, *span)?;
+            if let Err(e) = lower_var_declaration(func, var_decl, *span) {
+                poison(func, &var_decl.name, e);
+            }
         }
         DeclStmt::Struct(struct_decl) => {
             let _ = lower_inner_struct(func, struct_decl)?;
         }
-        DeclStmt::Sym(sym_decl) => lower_sym_decl(func, sym_decl, *span)?,
+        DeclStmt::Sym(sym_decl) => {
+            if let Err(e) = lower_sym_decl(func, sym_decl, *span) {
+                poison(func, &sym_decl.name, e);
+            }
+        }
         DeclStmt::Func(func_decl) => lower_inner_function(func, func_decl, *span)?,
     }
 
     Ok(())
 }
 
+/// Reports the error of a local's declaration and marks the local, so that
+/// its uses aren't reported as well.
+fn poison(func: &mut FunctionBuilder, name: &str, error: LoweringError) {
+    // The error may be about the name itself, e.g. `let x: i32 = x;`.
+    func.report(error);
+    func.sym_table.poisoned.insert(name.to_string());
+}
+
 pub fn lower_block(
     func: &mut FunctionBuilder,
     block: &BlockStmt,
@@ -96,10 +113,16 @@ pub fn lower_block(
     let block_id = func.add_block(terminator, Some(block.span));
 
     for s in block.stmts.iter() {
-        lower_stmt(func, s)?;
+        if let Err(e) = lower_stmt(func, s) {
+            func.report(e);
+        }
     }
 
+    // Locals declared in the block go out of scope, but they still belong to
+    // the function.
+    let locals = std::mem::take(&mut func.sym_table.locals);
     func.sym_table = old_sym_table;
+    func.sym_table.locals = locals;
 
     Ok(block_id)
 }
@@ -0,0 +1,337 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    lowering::{
+        ast_lowering::expression::{is_assert_call, is_diff_call},
+        declarations,
+        ir::symbols::DeclTable,
+    },
+    parser::ast::{
+        control_flow::{ForIterable, ForStmt, IfStmt, WhileStmt},
+        declaration::{DeclStmt, FuncDecl, TopLevelItem},
+        expression::{BinaryOp, ExprStmt, ExprStmtKind, PrimaryExpr},
+        statement::{Stmt, StmtKind},
+    },
+};
+
+/// Symbols which may be part of the expression returned by a function.
+///
+/// They are computed from the function's body, so a caller knows which
+/// symbols the expression it gets back depends on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReturnSymbols {
+    /// Symbols declared by the function, by name. The caller gets them as its
+    /// own symbols of the same name.
+    pub names: BTreeSet<String>,
+    /// Parameters whose symbols are passed through to the returned expression.
+    pub params: BTreeSet<usize>,
+}
+
+/// Where a symbol of a function's expression comes from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolSource {
+    Sym(String),
+    Param(usize),
+}
+
+/// Computes the [`ReturnSymbols`] of a function of the module `module_name`,
+/// whose calls are resolved with `decls`.
+pub fn return_symbols(decls: &DeclTable, module_name: &str, func: &FuncDecl) -> ReturnSymbols {
+    let mut visiting = HashSet::new();
+
+    FunctionSymbols::new(decls, module_name, func, &mut visiting).compute()
+}
+
+/// Tracks the symbols held by each variable of a function's body.
+///
+/// Variables are only known by name, since names can't be declared twice
+/// in a function. A variable assigned more than once holds the symbols of
+/// all its values.
+struct FunctionSymbols<'a> {
+    decls: &'a DeclTable,
+    module_name: &'a str,
+    func: &'a FuncDecl,
+    /// Functions being computed, a recursive one returns no symbols.
+    visiting: &'a mut HashSet<String>,
+    params: HashMap<&'a str, usize>,
+    variables: HashMap<String, BTreeSet<SymbolSource>>,
+    returned: BTreeSet<SymbolSource>,
+    changed: bool,
+}
+
+impl<'a> FunctionSymbols<'a> {
+    fn new(
+        decls: &'a DeclTable,
+        module_name: &'a str,
+        func: &'a FuncDecl,
+        visiting: &'a mut HashSet<String>,
+    ) -> Self {
+        let params = func
+            .params
+            .iter()
+            .enumerate()
+            .map(|(idx, param)| (param.name.as_str(), idx))
+            .collect();
+
+        Self {
+            decls,
+            module_name,
+            func,
+            visiting,
+            params,
+            variables: HashMap::new(),
+            returned: BTreeSet::new(),
+            changed: false,
+        }
+    }
+
+    fn compute(mut self) -> ReturnSymbols {
+        let func = self.func;
+        let key = format!("{}::{}", self.module_name, self.func.name);
+
+        if !self.visiting.insert(key.clone()) {
+            return ReturnSymbols::default();
+        }
+
+        // Loops may use a value assigned later in their body, so the body is
+        // walked until no variable gets new symbols.
+        loop {
+            self.changed = false;
+            self.stmts(&func.body);
+
+            if !self.changed {
+                break;
+            }
+        }
+
+        self.visiting.remove(&key);
+
+        let mut symbols = ReturnSymbols::default();
+
+        for source in self.returned {
+            match source {
+                SymbolSource::Sym(name) => {
+                    symbols.names.insert(name);
+                }
+                SymbolSource::Param(idx) => {
+                    symbols.params.insert(idx);
+                }
+            }
+        }
+
+        symbols
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Decl(DeclStmt::Var(var)) => {
+                let symbols = self.expr(&var.expr);
+                self.add_symbols(&var.name, symbols);
+            }
+            StmtKind::Decl(DeclStmt::Sym(sym)) => {
+                let symbols = BTreeSet::from([SymbolSource::Sym(sym.name.clone())]);
+                self.add_symbols(&sym.name, symbols);
+            }
+            StmtKind::Decl(DeclStmt::Struct(_) | DeclStmt::Func(_)) => {}
+            StmtKind::Block(block) => self.stmts(&block.stmts),
+            StmtKind::If(IfStmt {
+                condition,
+                then_block,
+                else_block,
+            }) => {
+                self.expr(condition);
+                self.stmts(&then_block.stmts);
+
+                if let Some(else_block) = else_block {
+                    self.stmts(&else_block.stmts);
+                }
+            }
+            StmtKind::While(WhileStmt { condition, body }) => {
+                self.expr(condition);
+                self.stmts(&body.stmts);
+            }
+            StmtKind::For(ForStmt { iterable, body, .. }) => {
+                match iterable {
+                    ForIterable::Range { start, end } => {
+                        self.expr(start);
+                        self.expr(end);
+                    }
+                    ForIterable::Array(array) => {
+                        self.expr(array);
+                    }
+                }
+                self.stmts(&body.stmts);
+            }
+            StmtKind::Return(expr) => {
+                let symbols = self.expr(expr);
+                self.returned.extend(symbols);
+            }
+            StmtKind::Expr(expr) => {
+                self.expr(expr);
+            }
+        }
+    }
+
+    fn add_symbols(&mut self, name: &str, symbols: BTreeSet<SymbolSource>) {
+        let variable = self.variables.entry(name.to_string()).or_default();
+        let len = variable.len();
+
+        variable.extend(symbols);
+        self.changed |= variable.len() != len;
+    }
+
+    /// Returns the symbols of an expression, mirroring `rvalue_symbols`.
+    fn expr(&mut self, expr: &ExprStmt) -> BTreeSet<SymbolSource> {
+        match &expr.kind {
+            ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => match self.params.get(name.as_str())
+            {
+                Some(idx) => BTreeSet::from([SymbolSource::Param(*idx)]),
+                None => self.variables.get(name).cloned().unwrap_or_default(),
+            },
+            ExprStmtKind::Binary {
+                lhs,
+                op: BinaryOp::Arithmetic(_),
+                rhs,
+            } => {
+                let mut symbols = self.expr(lhs);
+                symbols.extend(self.expr(rhs));
+                symbols
+            }
+            ExprStmtKind::Group(expr) | ExprStmtKind::Unary { rhs: expr, .. } => self.expr(expr),
+            ExprStmtKind::Call { callee, args } if is_diff_call(callee) => {
+                args.first().map(|arg| self.expr(arg)).unwrap_or_default()
+            }
+            ExprStmtKind::Call { callee, args } if is_assert_call(callee) => {
+                for arg in args {
+                    self.expr(arg);
+                }
+                BTreeSet::new()
+            }
+            ExprStmtKind::Call { callee, args } => {
+                let args = args.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+                let Some(callee_symbols) = self.callee_symbols(callee) else {
+                    return BTreeSet::new();
+                };
+
+                let mut symbols = callee_symbols
+                    .names
+                    .into_iter()
+                    .map(SymbolSource::Sym)
+                    .collect::<BTreeSet<_>>();
+
+                for idx in callee_symbols.params {
+                    symbols.extend(args.get(idx).cloned().unwrap_or_default());
+                }
+
+                symbols
+            }
+            ExprStmtKind::Substitution { callee, args } => {
+                let mut symbols = self.expr(callee);
+
+                for (name, _) in args {
+                    symbols.remove(&SymbolSource::Sym(name.clone()));
+                }
+                for (_, arg) in args {
+                    symbols.extend(self.expr(arg));
+                }
+
+                symbols
+            }
+            ExprStmtKind::Assign { name, expr } => {
+                let symbols = self.expr(expr);
+                self.add_symbols(name, symbols);
+                BTreeSet::new()
+            }
+            ExprStmtKind::Binary { lhs, rhs, .. }
+            | ExprStmtKind::Logical { lhs, rhs, .. }
+            | ExprStmtKind::Index {
+                expr: lhs,
+                index: rhs,
+            } => {
+                self.expr(lhs);
+                self.expr(rhs);
+                BTreeSet::new()
+            }
+            ExprStmtKind::IndexSet { lhs, index, rhs } => {
+                self.expr(lhs);
+                self.expr(index);
+                self.expr(rhs);
+                BTreeSet::new()
+            }
+            ExprStmtKind::ArrayInit { elements } => {
+                for element in elements {
+                    self.expr(element);
+                }
+                BTreeSet::new()
+            }
+            // A struct holds the symbols of all its fields.
+            ExprStmtKind::StructInit { fields, .. } => {
+                fields.values().flat_map(|field| self.expr(field)).collect()
+            }
+            ExprStmtKind::StructGet { expr, .. } => self.expr(expr),
+            ExprStmtKind::StructSet { lhs, rhs, .. } => {
+                self.expr(lhs);
+                let symbols = self.expr(rhs);
+
+                if let Some(name) = root_variable(lhs) {
+                    self.add_symbols(name, symbols);
+                }
+                BTreeSet::new()
+            }
+            ExprStmtKind::Primary(_) => BTreeSet::new(),
+        }
+    }
+
+    /// Resolves a call's callee and computes its [`ReturnSymbols`]. A callee
+    /// which doesn't resolve returns none, lowering reports it.
+    fn callee_symbols(&mut self, callee: &ExprStmt) -> Option<ReturnSymbols> {
+        let (func, module_idx) = match &callee.kind {
+            ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
+                self.decls.get_function_decl(name).cloned()?
+            }
+            ExprStmtKind::Primary(PrimaryExpr::Path(path)) => {
+                let (name, module_path) = path.idents.split_last()?;
+                let module_idx = self.decls.get_module_idx(&module_path.join("::"))?;
+                let func =
+                    self.decls.get_module(module_idx)?.items.iter().find_map(
+                        |item| match item {
+                            TopLevelItem::Func(func) if func.name == *name => Some(func.clone()),
+                            _ => None,
+                        },
+                    )?;
+
+                (func, Some(module_idx))
+            }
+            _ => return None,
+        };
+
+        // The callee's calls are resolved from its own module.
+        let module_decls;
+        let (decls, module_name) = match module_idx {
+            Some(idx) => {
+                let module = self.decls.get_module(idx)?;
+                module_decls = declarations(module);
+
+                (&module_decls, module.module_name.as_str())
+            }
+            None => (self.decls, self.module_name),
+        };
+
+        Some(FunctionSymbols::new(decls, module_name, &func, self.visiting).compute())
+    }
+}
+
+/// Returns the variable whose field an expression reads, e.g. `a` for `a.b.c`.
+fn root_variable(expr: &ExprStmt) -> Option<&str> {
+    match &expr.kind {
+        ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => Some(name),
+        ExprStmtKind::StructGet { expr, .. } | ExprStmtKind::Group(expr) => root_variable(expr),
+        _ => None,
+    }
+}
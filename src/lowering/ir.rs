@@ -54,16 +54,39 @@ impl Ir {
 #[derive(Debug, Default)]
 pub struct IrBuilder {
     pub module_name: String,
+    /// The source of the module being lowered.
+    pub source: Arc<str>,
     pub decl_table: DeclTable,
     pub sym_table: SymbolTableBuilder,
+    /// Errors found so far. Lowering goes on after an error to report as many
+    /// of them as possible at once.
+    pub errors: Vec<LoweringError>,
 }
 
 impl IrBuilder {
-    pub fn new(module_name: String, modules: Vec<Arc<IrModule>>) -> Self {
+    /// Creates a builder lowering `program`, which sees the modules it
+    /// imports.
+    pub fn new(program: &IrModule) -> Self {
         Self {
-            module_name,
+            decl_table: DeclTable::new(
+                &program.module_name,
+                program.modules.clone(),
+                &program.dependency_aliases,
+            ),
+            module_name: program.module_name.clone(),
+            source: program.source.clone(),
             sym_table: SymbolTableBuilder::default(),
-            decl_table: DeclTable::new(modules),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records an error.
+    ///
+    /// Items can be lowered more than once (e.g. structs lowered on demand),
+    /// so an error which was already recorded is skipped.
+    pub fn report(&mut self, error: LoweringError) {
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
         }
     }
 
@@ -59,6 +59,12 @@ pub enum Terminator {
     },
     /// Unreachable code
     Unreachable(Option<Span>),
+    /// Aborts the program, reporting why. Used by failed assertions and out
+    /// of bounds array accesses.
+    Trap {
+        message: String,
+        span: Option<Span>,
+    },
     /// Function call
     Call {
         callee: String,
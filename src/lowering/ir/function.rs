@@ -14,7 +14,7 @@ use crate::{
         ast::declaration::{FuncDecl, Param},
     },
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LocalKind {
@@ -23,6 +23,15 @@ pub enum LocalKind {
     Sym,
 }
 
+/// Returns the name of a function's symbol in the generated code.
+///
+/// Symbols are matched by name once functions are inlined, so they are
+/// qualified with the function declaring them. Otherwise a symbol of the
+/// callee would be taken as the caller's symbol of the same name.
+pub fn symbol_name(func_name: &str, name: &str) -> String {
+    format!("{func_name}::{name}")
+}
+
 /// MATHIR's representation of local variables.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -33,6 +42,9 @@ pub struct Local {
     pub debug_name: Option<String>,
     /// Free symbolic variables used in the local's value.
     pub symbols: HashSet<usize>,
+    /// Free symbolic variables used by each symbolic expression held by a
+    /// struct local, keyed by the path of its field.
+    pub field_symbols: HashMap<Vec<usize>, HashSet<usize>>,
 }
 
 /// MATHIR's representation of a function.
@@ -46,6 +58,10 @@ pub struct Function {
     pub return_ty: TypeIndex,
     pub span: Span,
     pub is_external: bool,
+    /// Declared with `extern df`, the host provides its implementation.
+    pub is_host: bool,
+    /// Declared with `test df`.
+    pub is_test: bool,
 }
 
 impl Function {
@@ -85,6 +101,8 @@ pub struct FunctionBuilder<'glb> {
     pub ir_builder: &'glb mut IrBuilder,
     pub span: Span,
     pub is_external: bool,
+    pub is_host: bool,
+    pub is_test: bool,
 }
 
 impl<'ir> FunctionBuilder<'ir> {
@@ -107,6 +125,8 @@ impl<'ir> FunctionBuilder<'ir> {
             ir_builder,
             span,
             is_external,
+            is_host: false,
+            is_test: false,
         };
 
         for param in params.iter() {
@@ -139,9 +159,25 @@ impl<'ir> FunctionBuilder<'ir> {
             return_ty: self.return_ty,
             span: self.span,
             is_external: self.is_external,
+            is_host: self.is_host,
+            is_test: self.is_test,
         }
     }
 
+    /// Records an error in the [IrBuilder].
+    ///
+    /// Uses of a local which failed to be declared follow from that failure,
+    /// so they aren't reported again.
+    pub fn report(&mut self, error: LoweringError) {
+        if let LoweringError::UndeclaredVariable { name, .. } = &error
+            && self.sym_table.poisoned.contains(name)
+        {
+            return;
+        }
+
+        self.ir_builder.report(error);
+    }
+
     pub fn get_function_decl(
         &self,
         name: &str,
@@ -169,6 +205,43 @@ impl<'ir> FunctionBuilder<'ir> {
         }
     }
 
+    /// Returns the path and type of every symbolic expression held by a value
+    /// of the type. That is the value itself for an expression, and the fields
+    /// holding them, in nested structs as well, for a struct.
+    pub fn symbolic_fields(
+        &self,
+        ty_idx: TypeIndex,
+        span: Span,
+    ) -> Result<Vec<(Vec<usize>, TypeIndex)>, LoweringError> {
+        Ok(match self.get_type(ty_idx, span)? {
+            MathicType::SymbolicExpr(_) => vec![(Vec::new(), ty_idx)],
+            MathicType::Adt { .. } => {
+                let mut fields = Vec::new();
+
+                for (idx, field_ty) in self
+                    .get_adt(ty_idx, span)?
+                    .get_fields_tys()
+                    .into_iter()
+                    .enumerate()
+                {
+                    for (mut path, ty) in self.symbolic_fields(field_ty, span)? {
+                        path.insert(0, idx);
+                        fields.push((path, ty));
+                    }
+                }
+
+                fields
+            }
+            _ => Vec::new(),
+        })
+    }
+
+    /// Whether a value of the type holds symbolic expressions, see
+    /// [`Self::symbolic_fields`].
+    pub fn holds_symbolic(&self, ty_idx: TypeIndex, span: Span) -> Result<bool, LoweringError> {
+        Ok(!self.symbolic_fields(ty_idx, span)?.is_empty())
+    }
+
     pub fn get_or_insert_type_idx(&mut self, ty: MathicType) -> TypeIndex {
         self.sym_table.get_or_insert_type(ty, true)
     }
@@ -213,6 +286,23 @@ impl<'ir> FunctionBuilder<'ir> {
         id
     }
 
+    /// Ends a block with a new terminator, built from the index of the block
+    /// which is added to continue the code. The new block takes over the
+    /// block's previous terminator, so it goes wherever the block went.
+    pub fn split_block(
+        &mut self,
+        block_idx: BlockId,
+        terminator: impl FnOnce(BlockId) -> Terminator,
+    ) -> BlockId {
+        let next_block_idx = self.basic_blocks.len();
+        let previous = std::mem::replace(
+            &mut self.basic_blocks[block_idx].terminator,
+            terminator(next_block_idx),
+        );
+
+        self.add_block(previous, None)
+    }
+
     pub fn push_instruction(&mut self, inst: LValInstruct) {
         let last_index = self.basic_blocks.len() - 1;
         self.basic_blocks[last_index].instructions.push(inst);
@@ -32,6 +32,16 @@ pub enum LValInstruct {
         ty: TypeIndex,
         span: Option<Span>,
     },
+    /// Binds a local to a global, which outlives the function. The REPL
+    /// keeps its variables in globals, defined (zeroed) by the input
+    /// declaring them and only declared by the following ones.
+    Global {
+        local_idx: usize,
+        symbol: String,
+        ty: TypeIndex,
+        is_definition: bool,
+        span: Option<Span>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +68,19 @@ pub enum RValueKind {
         symbols: HashSet<usize>,
         span: Span,
     },
+    SymbolicDiff {
+        expr: Box<RValInstruct>,
+        sym_name: String,
+        symbols: HashSet<usize>,
+        span: Span,
+    },
+    SymbolicSubs {
+        expr: Box<RValInstruct>,
+        syms: Vec<String>,
+        values: Vec<RValInstruct>,
+        symbols: HashSet<usize>,
+        span: Span,
+    },
     Unary {
         op: UnaryOp,
         rhs: Box<RValInstruct>,
@@ -74,6 +97,7 @@ pub enum RValueKind {
 #[derive(Debug, Clone)]
 pub enum InitInstruct {
     StructInit { fields: Vec<RValInstruct> },
+    ArrayInit { elements: Vec<RValInstruct> },
 }
 
 /// MATHIR's representation of RValue instruction.
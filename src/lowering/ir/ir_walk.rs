@@ -63,6 +63,7 @@ pub mod value {
         fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
             match self {
                 Self::Field(idx) => write!(f, ".{}", idx),
+                Self::Index(value) => write!(f, "[{}]", value),
             }
         }
     }
@@ -143,10 +144,37 @@ pub mod instructions {
 
                 write!(f, "{}}}", indent)
             }
+            RValueKind::Init {
+                init_inst: InitInstruct::ArrayInit { elements },
+                ..
+            } => {
+                let elements_str = elements
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "[{}]", elements_str)
+            }
             RValueKind::Binary { op, lhs, rhs, .. } => write!(f, "{} {} {}", lhs, op, rhs),
             RValueKind::SymbolicBinary { op, lhs, rhs, .. } => {
                 write!(f, "{} {} {}", lhs, BinaryOp::Arithmetic(*op), rhs)
             }
+            RValueKind::SymbolicDiff { expr, sym_name, .. } => {
+                write!(f, "diff({}, {})", expr, sym_name)
+            }
+            RValueKind::SymbolicSubs {
+                expr, syms, values, ..
+            } => {
+                let args_str = syms
+                    .iter()
+                    .zip(values)
+                    .map(|(sym, value)| format!("{} = {}", sym, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "{}[{}]", expr, args_str)
+            }
             RValueKind::Unary { op, rhs, .. } => write!(f, "{}{}", op, rhs),
             RValueKind::Logical { op, lhs, rhs, .. } => write!(f, "{} {} {}", lhs, op, rhs),
         }
@@ -195,16 +223,20 @@ pub mod instructions {
                     .iter()
                     .map(|m| m.to_string())
                     .collect::<Vec<_>>()
-                    .join(".");
+                    .join("");
 
-                write!(f, "{}%{}", inner_indent, local_idx)?;
+                write!(f, "{}%{}{}", inner_indent, local_idx, modifier_str)?;
                 write!(f, " = ")?;
-                write_rval_instruct(value, f, indent)?;
-                write!(f, "{}", modifier_str)
+                write_rval_instruct(value, f, indent)
             }
             LValInstruct::Sym { local_idx, .. } => {
                 write!(f, "{}sym %{};", inner_indent, local_idx)
             }
+            LValInstruct::Global {
+                local_idx, symbol, ..
+            } => {
+                write!(f, "{}global %{} @{};", inner_indent, local_idx, symbol)
+            }
         }
     }
 }
@@ -274,6 +306,7 @@ pub mod basic_block {
                     )
                 }
                 Self::Unreachable(_) => write!(f, "unreachable"),
+                Self::Trap { message, .. } => write!(f, "trap {message:?}"),
                 Self::Call {
                     callee,
                     args,
@@ -385,6 +418,7 @@ pub mod types {
                 MathicType::Char => write!(f, "char"),
                 MathicType::Void => write!(f, "void"),
                 MathicType::Adt { index, .. } => write!(f, "Adt({index})"),
+                MathicType::Array { len, .. } => write!(f, "[_; {len}]"),
             }
         }
     }
@@ -435,7 +469,21 @@ pub mod function {
             .collect::<Vec<_>>()
             .join(", ");
 
-        writeln!(f, "{}df {}({}) -> i64 {{", indent_str, func.name, params)?;
+        if func.is_host {
+            return writeln!(
+                f,
+                "{}extern df {}({}) -> i64\n",
+                indent_str, func.name, params
+            );
+        }
+
+        let keyword = if func.is_test { "test df" } else { "df" };
+
+        writeln!(
+            f,
+            "{}{} {}({}) -> i64 {{",
+            indent_str, keyword, func.name, params
+        )?;
 
         for nested_adt in func.get_adts() {
             adts::write_adt_ir(nested_adt, f, indent + 4)?;
@@ -33,13 +33,44 @@ pub struct DeclTable {
 }
 
 impl DeclTable {
-    pub fn new(modules: Vec<Arc<IrModule>>) -> Self {
-        let name_to_module = modules
+    /// Modules are known by their name, and by their path from the
+    /// directory of the module being lowered, `module_name`, which is how
+    /// the loader resolves imports. A module of a dependency can then import
+    /// its siblings as it would if it was the project.
+    ///
+    /// The modules of a dependency named differently by the module being
+    /// lowered, as `dependency_aliases` tells, are known by that name too.
+    pub fn new(
+        module_name: &str,
+        modules: Vec<Arc<IrModule>>,
+        dependency_aliases: &HashMap<String, String>,
+    ) -> Self {
+        let mut name_to_module: HashMap<_, _> = modules
             .iter()
             .enumerate()
             .map(|(idx, m)| (m.module_name.clone(), idx))
             .collect();
 
+        if let Some((dir, _)) = module_name.rsplit_once("::") {
+            let prefix = format!("{dir}::");
+
+            for (idx, m) in modules.iter().enumerate() {
+                if let Some(relative_name) = m.module_name.strip_prefix(&prefix) {
+                    name_to_module.insert(relative_name.to_string(), idx);
+                }
+            }
+        }
+
+        for (alias, dependency_name) in dependency_aliases {
+            let prefix = format!("{dependency_name}::");
+
+            for (idx, m) in modules.iter().enumerate() {
+                if let Some(relative_name) = m.module_name.strip_prefix(&prefix) {
+                    name_to_module.insert(format!("{alias}::{relative_name}"), idx);
+                }
+            }
+        }
+
         Self {
             name_to_module,
             modules,
@@ -91,6 +122,14 @@ impl DeclTable {
         self.structs.get(name)
     }
 
+    pub fn functions(&self) -> impl Iterator<Item = &(FuncDecl, Option<usize>)> {
+        self.functions.values()
+    }
+
+    pub fn structs(&self) -> impl Iterator<Item = &(StructDecl, Option<usize>)> {
+        self.structs.values()
+    }
+
     pub fn get_module_idx(&self, module_name: &str) -> Option<usize> {
         self.name_to_module.get(module_name).copied()
     }
@@ -100,7 +139,7 @@ impl DeclTable {
     }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TypeIndex {
     pub idx: usize,
     pub is_local: bool,
@@ -150,6 +189,11 @@ pub struct SymbolTableBuilder {
     pub functions: HashMap<String, Function>,
     pub user_def_types: HashMap<String, TypeIndex>,
     pub adts: Vec<Adt>,
+    /// Names of the locals which failed to be declared.
+    pub poisoned: HashSet<String>,
+    /// Symbols declared by a call returning them, which can still be declared
+    /// with `sym`.
+    pub implicit_symbols: HashSet<String>,
 }
 
 impl SymbolTableBuilder {
@@ -237,9 +281,11 @@ impl SymbolTableBuilder {
             ty,
             debug_name: debug_name.clone(),
             symbols: HashSet::new(),
+            field_symbols: HashMap::new(),
         });
 
         if let Some(name) = debug_name {
+            self.poisoned.remove(&name);
             self.local_indexes.insert(name, idx);
         }
 
@@ -49,6 +49,7 @@ pub enum NumericTy {
 pub enum MathicType {
     Numeric(NumericTy),
     Adt { index: usize, is_local: bool },
+    Array { elem: TypeIndex, len: usize },
     Bool,
     Char,
     Str,
@@ -62,6 +63,10 @@ pub fn lower_inner_ast_type(
     span: Span,
 ) -> Result<TypeIndex, LoweringError> {
     Ok(match ty {
+        AstType::Array { elem, len } => {
+            let elem_ty_idx = lower_inner_ast_type(func_builder, elem, span)?;
+            get_or_insert_array_type(func_builder, elem_ty_idx, *len)
+        }
         AstType::Type { ty, inner } => match ty.as_str() {
             "isz" => func_builder
                 .get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Sint(SintTy::Isize))),
@@ -87,6 +92,10 @@ pub fn lower_inner_ast_type(
                 .get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::U64))),
             "u128" => func_builder
                 .get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Uint(UintTy::U128))),
+            "f32" => func_builder
+                .get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Float(FloatTy::F32))),
+            "f64" => func_builder
+                .get_or_insert_global_type_idx(MathicType::Numeric(NumericTy::Float(FloatTy::F64))),
             "str" => func_builder.get_or_insert_global_type_idx(MathicType::Str),
             "char" => func_builder.get_or_insert_global_type_idx(MathicType::Char),
             "bool" => func_builder.get_or_insert_global_type_idx(MathicType::Bool),
@@ -101,8 +110,10 @@ pub fn lower_inner_ast_type(
                 let inner_ty = func_builder.get_type(inner_ty_idx, span)?;
 
                 match inner_ty {
+                    // Expressions don't depend on the function, so they are
+                    // the same type as top-level declarations, e.g. fields.
                     MathicType::Numeric(num_ty) => {
-                        func_builder.get_or_insert_type_idx(MathicType::SymbolicExpr(num_ty))
+                        func_builder.get_or_insert_global_type_idx(MathicType::SymbolicExpr(num_ty))
                     }
                     other => {
                         return Err(LoweringError::MismatchedType {
@@ -145,6 +156,23 @@ pub fn lower_inner_ast_type(
     })
 }
 
+/// Returns the index of an array type, registering it if needed.
+///
+/// Arrays of function local types are local as well, the rest are global.
+pub fn get_or_insert_array_type(
+    func_builder: &mut FunctionBuilder,
+    elem: TypeIndex,
+    len: usize,
+) -> TypeIndex {
+    let ty = MathicType::Array { elem, len };
+
+    if elem.is_local {
+        func_builder.get_or_insert_type_idx(ty)
+    } else {
+        func_builder.get_or_insert_global_type_idx(ty)
+    }
+}
+
 impl NumericTy {
     pub fn is_signed(&self) -> bool {
         matches!(self, Self::Sint(_) | Self::Float(_))
@@ -177,13 +205,15 @@ impl NumericTy {
 }
 
 impl MathicType {
-    pub fn bit_width(&self) -> u32 {
+    /// Returns the bit width of a scalar type, or `None` for the types which
+    /// aren't a single value, such as strings, structs and arrays.
+    pub fn bit_width(&self) -> Option<u32> {
         match self {
-            Self::Numeric(inner) => inner.bit_width(),
-            Self::Bool => 1,
-            Self::Char => 8,
-            Self::Void => 0,
-            Self::Str | Self::SymbolicExpr(_) | Self::Adt { .. } => todo!(),
+            Self::Numeric(inner) => Some(inner.bit_width()),
+            Self::Bool => Some(1),
+            Self::Char => Some(8),
+            Self::Void => Some(0),
+            Self::Str | Self::SymbolicExpr(_) | Self::Adt { .. } | Self::Array { .. } => None,
         }
     }
 
@@ -196,6 +226,16 @@ impl MathicType {
             Self::Char => 8,
             Self::SymbolicExpr(_) => 0,
             Self::Void => 0,
+            Self::Array { elem, .. } => {
+                let elem_ty = if elem.is_local {
+                    func.get_type(elem.idx)
+                } else {
+                    ir.get_type(elem.idx)
+                }
+                .expect("internal error: invalid array element type index in type alignment");
+
+                elem_ty.align(ir, func)
+            }
             Self::Adt { index, is_local } => {
                 let adt_fields_tys: Vec<MathicType> = {
                     if *is_local {
@@ -241,6 +281,33 @@ impl MathicType {
         }
     }
 
+    /// Whether a value of the type holds symbolic expressions, either being
+    /// one or being a struct with a field holding them.
+    pub fn holds_symbolic(&self, ir: &Ir, func: &Function) -> bool {
+        match self {
+            Self::SymbolicExpr(_) => true,
+            Self::Adt { index, is_local } => {
+                let adt = if *is_local {
+                    func.get_adt(*index)
+                } else {
+                    ir.get_adt(*index)
+                }
+                .expect("internal error: invalid ADT index in symbolic type check");
+
+                adt.get_fields_tys().iter().any(|t| {
+                    if t.is_local {
+                        func.get_type(t.idx)
+                    } else {
+                        ir.get_type(t.idx)
+                    }
+                    .expect("internal error: invalid type index in symbolic type check")
+                    .holds_symbolic(ir, func)
+                })
+            }
+            _ => false,
+        }
+    }
+
     #[inline(always)]
     pub fn is_signed(&self) -> bool {
         matches!(
@@ -268,6 +335,11 @@ impl MathicType {
     pub fn is_symbolic(&self) -> bool {
         matches!(self, Self::SymbolicExpr(_))
     }
+
+    #[inline(always)]
+    pub fn is_array(&self) -> bool {
+        matches!(self, Self::Array { .. })
+    }
 }
 
 /// Resolves a struct type.
@@ -1,3 +1,5 @@
+use crate::lowering::ir::types::{NumericTy, SintTy, UintTy};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     // Holds the index to find the associated local
@@ -17,6 +19,8 @@ pub enum Value {
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValueModifier {
     Field(usize),
+    // Holds the value of the index, either a constant or a local.
+    Index(Value),
 }
 
 /// Constant expressions
@@ -73,3 +77,29 @@ numeric_const_value_from_int!(U64, u64);
 numeric_const_value_from_int!(U128, u128);
 numeric_const_value_from_int!(F32, f32);
 numeric_const_value_from_int!(F64, f64);
+
+impl NumericConst {
+    /// Builds an integer constant of the given type, or returns `None` if
+    /// the value doesn't fit in it.
+    pub fn from_int(ty: NumericTy, value: u128) -> Option<Self> {
+        Some(match ty {
+            NumericTy::Sint(sint_ty) => match sint_ty {
+                SintTy::Isize => Self::Isize(value.try_into().ok()?),
+                SintTy::I8 => Self::I8(value.try_into().ok()?),
+                SintTy::I16 => Self::I16(value.try_into().ok()?),
+                SintTy::I32 => Self::I32(value.try_into().ok()?),
+                SintTy::I64 => Self::I64(value.try_into().ok()?),
+                SintTy::I128 => Self::I128(value.try_into().ok()?),
+            },
+            NumericTy::Uint(uint_ty) => match uint_ty {
+                UintTy::Usize => Self::Usize(value.try_into().ok()?),
+                UintTy::U8 => Self::U8(value.try_into().ok()?),
+                UintTy::U16 => Self::U16(value.try_into().ok()?),
+                UintTy::U32 => Self::U32(value.try_into().ok()?),
+                UintTy::U64 => Self::U64(value.try_into().ok()?),
+                UintTy::U128 => Self::U128(value),
+            },
+            NumericTy::Float(_) => return None,
+        })
+    }
+}
@@ -0,0 +1,453 @@
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::LintWarning,
+    lowering::ir::{
+        Ir,
+        basic_block::{BasicBlock, BlockId, Terminator},
+        function::{Function, LocalKind},
+        instruction::{InitInstruct, LValInstruct, RValInstruct, RValueKind},
+        symbols::TypeIndex,
+        value::{Value, ValueModifier},
+    },
+    parser::Span,
+};
+
+/// Lint pass entrypoint.
+///
+/// Looks for code which is valid but most likely wrong over a lowered
+/// program, returning the findings ordered by their position.
+pub fn lint_program(ir: &Ir) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    for func in ir.get_functions() {
+        lint_function(ir, func, &mut warnings);
+    }
+
+    warnings.sort_by_key(|w| w.span().start);
+    warnings.dedup();
+
+    warnings
+}
+
+fn lint_function(ir: &Ir, func: &Function, warnings: &mut Vec<LintWarning>) {
+    if func.is_external {
+        return;
+    }
+
+    for inner_func in func.get_inner_functions() {
+        lint_function(ir, inner_func, warnings);
+    }
+
+    let mut reads = Reads::default();
+
+    for block in &func.basic_blocks {
+        for inst in &block.instructions {
+            reads.instruction(inst);
+        }
+
+        reads.terminator(&block.terminator);
+    }
+
+    lint_declarations(ir, func, &reads, warnings);
+    lint_unreachable_blocks(func, warnings);
+    lint_assignments(func, &reads, warnings);
+}
+
+/// Looks for variables and symbols which are never used, and for symbols
+/// which are never given a value.
+fn lint_declarations(ir: &Ir, func: &Function, reads: &Reads, warnings: &mut Vec<LintWarning>) {
+    // Symbols of an expression leaving the function may be substituted by
+    // the caller.
+    let symbolic_escapes = holds_symbolic(ir, func, func.return_ty)
+        || func
+            .basic_blocks
+            .iter()
+            .any(|block| match &block.terminator {
+                Terminator::Call { args, .. } => {
+                    args.iter().any(|arg| holds_symbolic(ir, func, arg.ty))
+                }
+                _ => false,
+            });
+
+    for inst in func.basic_blocks.iter().flat_map(|b| &b.instructions) {
+        match inst {
+            LValInstruct::Let {
+                local_idx,
+                span: Some(span),
+                ..
+            } => {
+                let Some(name) = local_name(func, *local_idx) else {
+                    continue;
+                };
+
+                if !reads.locals.contains(local_idx) {
+                    warnings.push(LintWarning::UnusedVariable {
+                        name: name.to_string(),
+                        span: *span,
+                    });
+                }
+            }
+            LValInstruct::Sym {
+                local_idx,
+                sym_name,
+                span: Some(span),
+                ..
+            } => {
+                // The symbol's name is qualified with the function, see
+                // `symbol_name`.
+                let name = func
+                    .get_local(*local_idx)
+                    .and_then(|local| local.debug_name.as_deref())
+                    .unwrap_or(sym_name);
+
+                if name.starts_with('_') {
+                    continue;
+                }
+
+                let substituted = reads.substituted.contains(sym_name.as_str());
+
+                if !reads.locals.contains(local_idx)
+                    && !substituted
+                    && !reads.differentiated.contains(sym_name.as_str())
+                {
+                    warnings.push(LintWarning::UnusedSymbol {
+                        name: name.to_string(),
+                        span: *span,
+                    });
+                } else if reads.locals.contains(local_idx) && !substituted && !symbolic_escapes {
+                    warnings.push(LintWarning::UnsubstitutedSymbol {
+                        name: name.to_string(),
+                        span: *span,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Looks for blocks with code which no path from the entry block reaches.
+///
+/// Each piece of unreachable code is reported once, at its first block, and
+/// not again for the blocks it goes on to.
+fn lint_unreachable_blocks(func: &Function, warnings: &mut Vec<LintWarning>) {
+    let mut reachable = HashSet::from([0]);
+    let mut pending = vec![0];
+
+    while let Some(block_idx) = pending.pop() {
+        for succ in successors(func, &func.basic_blocks[block_idx]) {
+            if reachable.insert(succ) {
+                pending.push(succ);
+            }
+        }
+    }
+
+    // Blocks are added in the order of the code, so the first block of a
+    // piece of unreachable code comes before the blocks it goes on to.
+    let mut reported = reachable;
+
+    for block in &func.basic_blocks {
+        if reported.contains(&block.id) {
+            continue;
+        }
+
+        let span = match block.instructions.first() {
+            Some(inst) => block.span.or(instruction_span(inst)),
+            None => block.span.or(terminator_span(&block.terminator)),
+        };
+
+        let Some(span) = span else {
+            continue;
+        };
+
+        warnings.push(LintWarning::UnreachableCode { span });
+
+        pending.push(block.id);
+
+        while let Some(block_idx) = pending.pop() {
+            for succ in successors(func, &func.basic_blocks[block_idx]) {
+                if reported.insert(succ) {
+                    pending.push(succ);
+                }
+            }
+        }
+    }
+}
+
+/// Looks for values stored in a variable which are overwritten or never read
+/// afterwards.
+fn lint_assignments(func: &Function, reads: &Reads, warnings: &mut Vec<LintWarning>) {
+    let live_out = live_variables(func);
+
+    for block in &func.basic_blocks {
+        let mut live = live_out[block.id].clone();
+
+        live.extend(Reads::of_terminator(&block.terminator).locals);
+
+        for inst in block.instructions.iter().rev() {
+            let (local_idx, span) = match inst {
+                LValInstruct::Let {
+                    local_idx, span, ..
+                } => (*local_idx, *span),
+                LValInstruct::Assign {
+                    local_idx,
+                    modifier,
+                    span,
+                    ..
+                } if modifier.is_empty() => (*local_idx, *span),
+                _ => {
+                    live.extend(Reads::of_instruction(inst).locals);
+                    continue;
+                }
+            };
+
+            // Variables never read at all are reported as unused.
+            if !live.contains(&local_idx)
+                && reads.locals.contains(&local_idx)
+                && let (Some(name), Some(span)) = (local_name(func, local_idx), span)
+            {
+                warnings.push(LintWarning::UnusedAssignment {
+                    name: name.to_string(),
+                    span,
+                });
+            }
+
+            live.remove(&local_idx);
+            live.extend(Reads::of_instruction(inst).locals);
+        }
+    }
+}
+
+/// Computes the locals which are read after the end of each block before
+/// being written again.
+fn live_variables(func: &Function) -> Vec<HashSet<usize>> {
+    let blocks = &func.basic_blocks;
+    let mut live_in = vec![HashSet::new(); blocks.len()];
+    let mut live_out = vec![HashSet::new(); blocks.len()];
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for block in blocks.iter().rev() {
+            let out = successors(func, block)
+                .flat_map(|succ| live_in[succ].iter().copied())
+                .collect::<HashSet<_>>();
+
+            let mut live = out.clone();
+
+            live.extend(Reads::of_terminator(&block.terminator).locals);
+
+            for inst in block.instructions.iter().rev() {
+                match inst {
+                    LValInstruct::Let { local_idx, .. } => {
+                        live.remove(local_idx);
+                    }
+                    LValInstruct::Assign {
+                        local_idx,
+                        modifier,
+                        ..
+                    } if modifier.is_empty() => {
+                        live.remove(local_idx);
+                    }
+                    _ => {}
+                }
+
+                live.extend(Reads::of_instruction(inst).locals);
+            }
+
+            if live != live_in[block.id] {
+                live_in[block.id] = live;
+                changed = true;
+            }
+
+            live_out[block.id] = out;
+        }
+    }
+
+    live_out
+}
+
+/// Returns the blocks a block may jump to.
+fn successors(func: &Function, block: &BasicBlock) -> impl Iterator<Item = BlockId> {
+    let len = func.basic_blocks.len();
+
+    let targets = match &block.terminator {
+        Terminator::Branch { target, .. } => vec![*target],
+        Terminator::CondBranch {
+            true_block,
+            false_block,
+            ..
+        } => vec![*true_block, *false_block],
+        Terminator::Call { dest_block, .. } | Terminator::Eval { dest_block, .. } => {
+            vec![*dest_block]
+        }
+        Terminator::Return(..) | Terminator::Unreachable(_) | Terminator::Trap { .. } => Vec::new(),
+    };
+
+    targets.into_iter().filter(move |target| *target < len)
+}
+
+/// Returns the name of a variable declared by the user, unless it starts with
+/// an underscore.
+fn local_name(func: &Function, local_idx: usize) -> Option<&str> {
+    let local = func.get_local(local_idx)?;
+
+    match &local.debug_name {
+        Some(name) if local.kind == LocalKind::Temp && !name.starts_with('_') => Some(name),
+        _ => None,
+    }
+}
+
+fn instruction_span(inst: &LValInstruct) -> Option<Span> {
+    match inst {
+        LValInstruct::Let { span, .. }
+        | LValInstruct::Assign { span, .. }
+        | LValInstruct::Sym { span, .. }
+        | LValInstruct::Global { span, .. } => *span,
+    }
+}
+
+fn terminator_span(terminator: &Terminator) -> Option<Span> {
+    match terminator {
+        Terminator::Return(_, span)
+        | Terminator::Branch { span, .. }
+        | Terminator::CondBranch { span, .. }
+        | Terminator::Unreachable(span)
+        | Terminator::Trap { span, .. }
+        | Terminator::Call { span, .. }
+        | Terminator::Eval { span, .. } => *span,
+    }
+}
+
+fn holds_symbolic(ir: &Ir, func: &Function, ty: TypeIndex) -> bool {
+    let ty = if ty.is_local {
+        func.get_type(ty.idx)
+    } else {
+        ir.get_type(ty.idx)
+    };
+
+    ty.is_some_and(|ty| ty.holds_symbolic(ir, func))
+}
+
+/// What a piece of MATHIR reads.
+#[derive(Debug, Default)]
+struct Reads<'a> {
+    locals: HashSet<usize>,
+    /// Names of the symbols given a value by a substitution.
+    substituted: HashSet<&'a str>,
+    /// Names of the symbols derivatives are taken with respect to.
+    differentiated: HashSet<&'a str>,
+}
+
+impl<'a> Reads<'a> {
+    fn of_instruction(inst: &'a LValInstruct) -> Self {
+        let mut reads = Self::default();
+        reads.instruction(inst);
+        reads
+    }
+
+    fn of_terminator(terminator: &'a Terminator) -> Self {
+        let mut reads = Self::default();
+        reads.terminator(terminator);
+        reads
+    }
+
+    fn instruction(&mut self, inst: &'a LValInstruct) {
+        match inst {
+            LValInstruct::Let { init, .. } => self.rvalue(init),
+            LValInstruct::Assign {
+                value, modifier, ..
+            } => {
+                self.rvalue(value);
+                self.modifiers(modifier);
+            }
+            LValInstruct::Sym { .. } | LValInstruct::Global { .. } => {}
+        }
+    }
+
+    fn terminator(&mut self, terminator: &'a Terminator) {
+        match terminator {
+            Terminator::Return(value, _) => {
+                if let Some(value) = value {
+                    self.rvalue(value);
+                }
+            }
+            Terminator::Branch { block_args, .. } => self.locals.extend(block_args),
+            Terminator::CondBranch {
+                condition,
+                true_block_args,
+                false_block_args,
+                ..
+            } => {
+                self.rvalue(condition);
+                self.locals.extend(true_block_args);
+                self.locals.extend(false_block_args);
+            }
+            Terminator::Unreachable(_) | Terminator::Trap { .. } => {}
+            Terminator::Call { args, .. } => args.iter().for_each(|arg| self.rvalue(arg)),
+            Terminator::Eval {
+                expr, syms, exprs, ..
+            } => {
+                self.rvalue(expr);
+                self.substituted.extend(syms.iter().map(String::as_str));
+                exprs.iter().for_each(|expr| self.rvalue(expr));
+            }
+        }
+    }
+
+    fn rvalue(&mut self, rvalue: &'a RValInstruct) {
+        match &rvalue.kind {
+            RValueKind::Use { value, .. } => self.value(value),
+            RValueKind::Init { init_inst, .. } => match init_inst {
+                InitInstruct::StructInit { fields: values }
+                | InitInstruct::ArrayInit { elements: values } => {
+                    values.iter().for_each(|value| self.rvalue(value))
+                }
+            },
+            RValueKind::Binary { lhs, rhs, .. }
+            | RValueKind::SymbolicBinary { lhs, rhs, .. }
+            | RValueKind::Logical { lhs, rhs, .. } => {
+                self.rvalue(lhs);
+                self.rvalue(rhs);
+            }
+            RValueKind::SymbolicDiff { expr, sym_name, .. } => {
+                self.rvalue(expr);
+                self.differentiated.insert(sym_name);
+            }
+            RValueKind::SymbolicSubs {
+                expr, syms, values, ..
+            } => {
+                self.rvalue(expr);
+                self.substituted.extend(syms.iter().map(String::as_str));
+                values.iter().for_each(|value| self.rvalue(value));
+            }
+            RValueKind::Unary { rhs, .. } => self.rvalue(rhs),
+        }
+    }
+
+    fn value(&mut self, value: &'a Value) {
+        match value {
+            Value::InMemory {
+                local_idx,
+                modifier,
+            } => {
+                self.locals.insert(*local_idx);
+                self.modifiers(modifier);
+            }
+            Value::Symbol { local_idx } => {
+                self.locals.insert(*local_idx);
+            }
+            Value::Const(_) => {}
+        }
+    }
+
+    fn modifiers(&mut self, modifier: &'a [ValueModifier]) {
+        for m in modifier {
+            if let ValueModifier::Index(index) = m {
+                self.value(index);
+            }
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use std::{
     cell::{Cell, RefCell},
+    collections::HashMap,
     ops::Range,
 };
 
 use crate::{
-    diagnostics::parse::{ExpectedToken, FoundToken, ParseError, SyntaxError},
+    diagnostics::parse::{ExpectedToken, ParseError, SyntaxError},
     parser::{
-        ast::{IrModule, declaration::TopLevelItem},
+        ast::{
+            IrModule, ReplInput,
+            declaration::TopLevelItem,
+            expression::ExprStmt,
+            statement::{Stmt, StmtKind},
+        },
         lexer::{LexerOutput, MathicLexer, SpannedToken},
         token::Token,
     },
@@ -50,54 +56,175 @@ impl From<Range<usize>> for Span {
 }
 
 pub struct MathicParser<'a> {
+    source: &'a str,
     lexer: RefCell<MathicLexer<'a>>,
     current_span: Cell<Span>,
-    _panic_mode: bool,
+    /// Set by a syntax error until the parser synchronizes. Errors found in
+    /// the meantime follow from the first one and aren't reported.
+    panic_mode: Cell<bool>,
+    errors: RefCell<Vec<ParseError>>,
 }
 
 impl<'a> MathicParser<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
+            source,
             lexer: RefCell::new(MathicLexer::new(source)),
             current_span: Cell::new(Span::from(0..0)),
-            _panic_mode: false,
+            panic_mode: Cell::new(false),
+            errors: RefCell::new(Vec::new()),
         }
     }
 
+    /// Parses a module.
+    ///
+    /// Syntax errors don't stop the parsing: the parser skips to the next
+    /// statement or item and goes on. Returns the items which parsed along
+    /// with every error found.
     #[instrument(target = "parsing", skip(self))]
-    pub fn parse(&self, module_name: String) -> ParserResult<IrModule> {
+    pub fn parse(&self, module_name: String) -> (IrModule, Vec<ParseError>) {
         tracing::debug!("Starting parsing");
         let mut items = Vec::new();
 
-        while let Ok(Some(SpannedToken {
-            token,
-            lexeme,
-            span,
-        })) = self.peek()
-        {
-            match token {
-                Token::Df => items.push(TopLevelItem::Func(self.parse_func()?)),
-                Token::Import => items.push(TopLevelItem::Import(self.parse_import()?)),
-                Token::Struct => items.push(TopLevelItem::Struct(self.parse_struct()?)),
-                _ => {
-                    return Err(ParseError::Syntax(SyntaxError::UnexpectedToken {
-                        found: FoundToken {
-                            lexeme: lexeme.to_string(),
-                            span,
-                        },
-                        expected: ExpectedToken::Custom(
-                            "function or struct definition".to_string(),
-                        ),
-                    }));
+        loop {
+            let lookahead = match self.peek() {
+                Ok(Some(lookahead)) => lookahead,
+                Ok(None) => break,
+                Err(e) => {
+                    self.recover_item(e);
+                    continue;
                 }
+            };
+
+            let item = match lookahead.token {
+                Token::Df => self.parse_func().map(TopLevelItem::Func),
+                Token::Extern => self.parse_extern_func().map(TopLevelItem::Func),
+                Token::Test => self.parse_test_func().map(TopLevelItem::Func),
+                Token::Import => self.parse_import().map(TopLevelItem::Import),
+                Token::Struct => self.parse_struct().map(TopLevelItem::Struct),
+                _ => Err(ParseError::Syntax(SyntaxError::UnexpectedToken {
+                    found: lookahead.into(),
+                    expected: ExpectedToken::Custom("function or struct definition".to_string()),
+                })),
+            };
+
+            match item {
+                Ok(item) => items.push(item),
+                Err(e) => self.recover_item(e),
             }
         }
 
-        Ok(IrModule {
+        let module = IrModule {
             module_name,
             modules: Vec::new(),
             items,
-        })
+            source: self.source.into(),
+            dependency_aliases: HashMap::new(),
+        };
+
+        (module, self.errors.take())
+    }
+
+    /// Parses an input of the REPL.
+    ///
+    /// Besides items, an input may have statements and end with an expression
+    /// without a trailing semicolon, which is the input's result. Errors are
+    /// recovered from like in [parse](MathicParser::parse).
+    #[instrument(target = "parsing", skip(self))]
+    pub fn parse_repl_input(&self, module_name: String) -> (ReplInput, Vec<ParseError>) {
+        let mut input = ReplInput {
+            module: IrModule {
+                module_name,
+                modules: Vec::new(),
+                items: Vec::new(),
+                source: self.source.into(),
+                dependency_aliases: HashMap::new(),
+            },
+            stmts: Vec::new(),
+            result: None,
+        };
+
+        loop {
+            match self.peek() {
+                Ok(Some(_)) => {
+                    if let Err(e) = self.parse_repl_entry(&mut input) {
+                        self.recover_stmt(e);
+
+                        // Out of any block, a `}` is skipped like the rest.
+                        let _ = self.match_token(Token::RBrace);
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => self.recover_stmt(e),
+            }
+        }
+
+        (input, self.errors.take())
+    }
+
+    /// Parses a source made of a single expression, like the ones evaluated
+    /// by a `Session`.
+    #[instrument(target = "parsing", skip(self))]
+    pub fn parse_standalone_expr(&self) -> ParserResult<ExprStmt> {
+        let expr = self.parse_expr()?;
+
+        match self.peek()? {
+            Some(lookahead) => Err(ParseError::Syntax(SyntaxError::UnexpectedToken {
+                found: lookahead.into(),
+                expected: ExpectedToken::Custom("end of the expression".to_string()),
+            })),
+            None => Ok(expr),
+        }
+    }
+
+    /// Parses the next item, statement or result of a REPL input.
+    fn parse_repl_entry(&self, input: &mut ReplInput) -> ParserResult<()> {
+        let lookahead = self.peek_not_none()?;
+
+        match lookahead.token {
+            Token::Df => input
+                .module
+                .items
+                .push(TopLevelItem::Func(self.parse_func()?)),
+            Token::Import => input
+                .module
+                .items
+                .push(TopLevelItem::Import(self.parse_import()?)),
+            Token::Struct => input
+                .module
+                .items
+                .push(TopLevelItem::Struct(self.parse_struct()?)),
+            Token::Let
+            | Token::Sym
+            | Token::If
+            | Token::While
+            | Token::For
+            | Token::Return
+            | Token::LBrace => input.stmts.push(self.parse_stmt()?),
+            _ => {
+                let expr = self.parse_expr()?;
+
+                if self.peek()?.is_none() {
+                    input.result = Some(expr);
+                } else {
+                    self.consume_token(Token::Semicolon)?;
+
+                    let span = Span::from_merged_spans(expr.span, self.current_span());
+                    input.stmts.push(Stmt {
+                        kind: StmtKind::Expr(expr),
+                        span,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the comments of the source read so far. After a successful
+    /// [parse](MathicParser::parse), those are all of them.
+    pub fn take_comments(&self) -> Vec<SpannedToken<'a>> {
+        self.lexer.borrow_mut().take_comments()
     }
 
     /// Returns the next token, advancing the lexer.
@@ -143,22 +270,19 @@ impl<'a> MathicParser<'a> {
 
     /// Consumes the next token.
     ///
-    /// Returns a parser error if the token does not match the expected one.
+    /// Returns a parser error if the token does not match the expected one,
+    /// leaving it for the error recovery to synchronize on.
     fn consume_token(&self, expected: Token) -> ParserResult<LexerOutput<'a>> {
-        if let Ok(res) = self.next() {
-            if res.token == expected {
-                Ok(res)
-            } else {
-                Err(ParseError::Syntax(SyntaxError::MissingToken {
-                    expected,
-                    span: res.span,
-                }))
-            }
-        } else {
-            Err(ParseError::Syntax(SyntaxError::UnexpectedEnd {
-                span: self.current_span(),
-            }))
+        let lookahead = self.peek_not_none()?;
+
+        if lookahead.token != expected {
+            return Err(ParseError::Syntax(SyntaxError::MissingToken {
+                expected,
+                span: lookahead.span,
+            }));
         }
+
+        self.next()
     }
 
     /// Tries to match the expected token.
@@ -202,4 +326,55 @@ impl<'a> MathicParser<'a> {
             false
         })
     }
+
+    /// Recovers from a syntax error within a block, skipping to the end of
+    /// the statement.
+    fn recover_stmt(&self, error: ParseError) {
+        self.report(error);
+        self.synchronize(true);
+    }
+
+    /// Recovers from a syntax error outside of any block, skipping to the
+    /// next item.
+    fn recover_item(&self, error: ParseError) {
+        self.report(error);
+        self.synchronize(false);
+    }
+
+    fn report(&self, error: ParseError) {
+        if !self.panic_mode.replace(true) {
+            self.errors.borrow_mut().push(error);
+        }
+    }
+
+    /// Skips tokens up to the start of an item, where parsing can resume.
+    /// Within a block it also stops after a `;` or before a `}`.
+    fn synchronize(&self, in_block: bool) {
+        loop {
+            match self.peek() {
+                Ok(Some(lookahead)) => match lookahead.token {
+                    // Imports end every open block, which can't be resumed.
+                    Token::Import if in_block => return,
+                    Token::Df | Token::Extern | Token::Test | Token::Struct | Token::Import => {
+                        break;
+                    }
+                    Token::RBrace if in_block => break,
+                    Token::Semicolon if in_block => {
+                        let _ = self.next();
+                        break;
+                    }
+                    _ => {
+                        let _ = self.next();
+                    }
+                },
+                // Whatever comes next may follow from the error, so the
+                // parser stays in panic mode.
+                Ok(None) => return,
+                // The lexer has already moved past the invalid token.
+                Err(_) => {}
+            }
+        }
+
+        self.panic_mode.set(false);
+    }
 }
@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use super::ast::declaration::TopLevelItem;
+use super::ast::{declaration::TopLevelItem, expression::ExprStmt, statement::Stmt};
 
 pub mod control_flow;
 pub mod declaration;
@@ -12,4 +12,19 @@ pub struct IrModule {
     pub module_name: String,
     pub modules: Vec<Arc<Self>>,
     pub items: Vec<TopLevelItem>,
+    /// The source the module was parsed from, which its spans index into.
+    pub source: Arc<str>,
+    /// Names the module imports dependencies with, mapped to the ones their
+    /// modules have, when they differ.
+    pub dependency_aliases: HashMap<String, String>,
+}
+
+/// An input of the REPL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplInput {
+    /// The module holding the input's items.
+    pub module: IrModule,
+    pub stmts: Vec<Stmt>,
+    /// A trailing expression, without semicolon, whose value is printed.
+    pub result: Option<ExprStmt>,
 }
@@ -16,7 +16,15 @@ pub struct WhileStmt {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ForStmt {
     pub variable: String,
-    pub start: ExprStmt,
-    pub end: ExprStmt,
+    pub iterable: ForIterable,
     pub body: BlockStmt,
 }
+
+/// What a `for` loop goes through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForIterable {
+    /// The integers from `start` up to, but not including, `end`.
+    Range { start: ExprStmt, end: ExprStmt },
+    /// The elements of an array, in order.
+    Array(ExprStmt),
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::parser::{Span, ast::expression::ExprStmt, ast::statement::Stmt};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,6 +52,12 @@ pub struct FuncDecl {
     pub body: Vec<Stmt>,
     pub span: Span,
     pub return_ty: Option<AstType>,
+    /// Declared with `extern df`, without a body. It is implemented by the
+    /// host with `MathicJITExecutor::register_host_fn`.
+    pub is_extern: bool,
+    /// Declared with `test df`. It takes no parameters, returns nothing, and
+    /// is run by `euler test`.
+    pub is_test: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,6 +81,10 @@ pub enum AstType {
         ty: String,
         inner: Option<Box<AstType>>,
     },
+    Array {
+        elem: Box<AstType>,
+        len: usize,
+    },
 }
 
 impl TopLevelItem {
@@ -100,3 +112,16 @@ impl Path {
         self.idents = [idents, &self.idents].concat()
     }
 }
+
+impl fmt::Display for AstType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AstType::Type { ty, inner: None } => write!(f, "{ty}"),
+            AstType::Type {
+                ty,
+                inner: Some(inner),
+            } => write!(f, "{ty}<{inner}>"),
+            AstType::Array { elem, len } => write!(f, "[{elem}; {len}]"),
+        }
+    }
+}
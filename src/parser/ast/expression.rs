@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::parser::{Span, ast::declaration::Path, token::Token};
+use crate::parser::{Span, ast::declaration::Path};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExprStmt {
@@ -8,6 +8,38 @@ pub struct ExprStmt {
     pub span: Span,
 }
 
+impl ExprStmt {
+    /// Returns the section of the source covered by the expression and its
+    /// subexpressions. Postfix expressions like field accesses only span
+    /// their last token.
+    pub fn extent(&self) -> Span {
+        let children: Vec<&ExprStmt> = match &self.kind {
+            ExprStmtKind::Primary(_) => Vec::new(),
+            ExprStmtKind::Binary { lhs, rhs, .. }
+            | ExprStmtKind::Logical { lhs, rhs, .. }
+            | ExprStmtKind::StructSet { lhs, rhs, .. } => vec![&**lhs, &**rhs],
+            ExprStmtKind::StructInit { expr, fields } => {
+                std::iter::once(&**expr).chain(fields.values()).collect()
+            }
+            ExprStmtKind::Call { callee, args } => std::iter::once(&**callee).chain(args).collect(),
+            ExprStmtKind::Group(inner)
+            | ExprStmtKind::Unary { rhs: inner, .. }
+            | ExprStmtKind::Assign { expr: inner, .. }
+            | ExprStmtKind::StructGet { expr: inner, .. } => vec![&**inner],
+            ExprStmtKind::ArrayInit { elements } => elements.iter().collect(),
+            ExprStmtKind::Index { expr, index } => vec![&**expr, &**index],
+            ExprStmtKind::IndexSet { lhs, index, rhs } => vec![&**lhs, &**index, &**rhs],
+            ExprStmtKind::Substitution { callee, args } => std::iter::once(&**callee)
+                .chain(args.iter().map(|(_, value)| value))
+                .collect(),
+        };
+
+        children.into_iter().fold(self.span, |span, child| {
+            Span::from_merged_spans(span, child.extent())
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum ExprStmtKind {
@@ -26,9 +58,17 @@ pub enum ExprStmtKind {
         args: Vec<ExprStmt>,
     },
     Group(Box<ExprStmt>),
+    ArrayInit {
+        elements: Vec<ExprStmt>,
+    },
     Index {
-        name: Token,
-        pos: Token,
+        expr: Box<ExprStmt>,
+        index: Box<ExprStmt>,
+    },
+    IndexSet {
+        lhs: Box<ExprStmt>,
+        index: Box<ExprStmt>,
+        rhs: Box<ExprStmt>,
     },
     Logical {
         lhs: Box<ExprStmt>,
@@ -19,6 +19,8 @@ pub struct MathicLexer<'src> {
     source: &'src str,
     inner: Lexer<'src, Token>,
     lookahead: Option<SpannedToken<'src>>,
+    /// Comments skipped so far, in order of appearance.
+    comments: Vec<SpannedToken<'src>>,
 }
 
 impl<'src> MathicLexer<'src> {
@@ -27,23 +29,30 @@ impl<'src> MathicLexer<'src> {
             source,
             inner: Token::lexer(source),
             lookahead: None,
+            comments: Vec::new(),
         }
     }
 
     fn read_next(&mut self) -> LexerResult<'src> {
-        let res = self.inner.next();
-        let span = Span::from(self.inner.span());
+        loop {
+            let res = self.inner.next();
+            let span = Span::from(self.inner.span());
 
-        match res {
-            Some(res) => match res {
-                Ok(token) => Ok(Some(SpannedToken {
+            let token = match res {
+                Some(Ok(token)) => SpannedToken {
                     token,
                     lexeme: &self.source[span.as_range()],
                     span,
-                })),
-                Err(e) => Err((e, span)),
-            },
-            None => Ok(None),
+                },
+                Some(Err(e)) => return Err((e, span)),
+                None => return Ok(None),
+            };
+
+            if !token.token.is_trivia() {
+                return Ok(Some(token));
+            }
+
+            self.comments.push(token);
         }
     }
 
@@ -64,4 +73,19 @@ impl<'src> MathicLexer<'src> {
 
         Ok(self.lookahead)
     }
+
+    /// Returns the comments skipped since the last call.
+    pub fn take_comments(&mut self) -> Vec<SpannedToken<'src>> {
+        std::mem::take(&mut self.comments)
+    }
+}
+
+/// Whether `name` can name a variable, that is, it's lexed as a single
+/// identifier rather than a keyword or anything else.
+pub fn is_identifier(name: &str) -> bool {
+    let mut lexer = Token::lexer(name);
+
+    matches!(lexer.next(), Some(Ok(Token::Ident)))
+        && lexer.span().len() == name.len()
+        && lexer.next().is_none()
 }
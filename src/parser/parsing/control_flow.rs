@@ -1,6 +1,6 @@
 use crate::parser::{
     MathicParser, ParserResult,
-    ast::control_flow::{ForStmt, IfStmt, WhileStmt},
+    ast::control_flow::{ForIterable, ForStmt, IfStmt, WhileStmt},
     token::Token,
 };
 
@@ -45,17 +45,21 @@ impl<'a> MathicParser<'a> {
 
         let start = self.parse_expr_no_init()?;
 
-        self.consume_token(Token::Dot)?;
-        self.consume_token(Token::Dot)?;
+        let iterable = if self.match_token(Token::Dot)?.is_some() {
+            self.consume_token(Token::Dot)?;
 
-        let end = self.parse_expr_no_init()?;
+            let end = self.parse_expr_no_init()?;
+
+            ForIterable::Range { start, end }
+        } else {
+            ForIterable::Array(start)
+        };
 
         let body = self.parse_block()?;
 
         Ok(ForStmt {
             variable,
-            start,
-            end,
+            iterable,
             body,
         })
     }
@@ -1,3 +1,4 @@
+use crate::diagnostics::parse::{ExpectedToken, ParseError, SyntaxError};
 use crate::parser::{
     MathicParser, ParserResult, Span,
     ast::{
@@ -9,6 +10,10 @@ use crate::parser::{
 
 impl<'a> MathicParser<'a> {
     pub fn parse_type(&self) -> ParserResult<AstType> {
+        if self.match_token(Token::LSquareBracket)?.is_some() {
+            return self.parse_array_type();
+        }
+
         let ident = self.consume_token(Token::Ident)?;
 
         let ty = AstType::Type {
@@ -27,9 +32,96 @@ impl<'a> MathicParser<'a> {
         Ok(ty)
     }
 
+    /// Parses the rest of an array type, e.g. `[i32; 8]`.
+    fn parse_array_type(&self) -> ParserResult<AstType> {
+        let elem = self.parse_type()?;
+
+        self.consume_token(Token::Semicolon)?;
+
+        let len_token = self.consume_token(Token::Num)?;
+        let len = len_token.lexeme.parse::<usize>().map_err(|_| {
+            ParseError::Syntax(SyntaxError::UnexpectedToken {
+                found: len_token.into(),
+                expected: ExpectedToken::Custom("an array length".to_string()),
+            })
+        })?;
+
+        self.consume_token(Token::RSquareBracket)?;
+
+        Ok(AstType::Array {
+            elem: Box::new(elem),
+            len,
+        })
+    }
+
     pub fn parse_func(&self) -> ParserResult<FuncDecl> {
         let start_span = self.next()?.span; // Consume Df.
 
+        let (name, params, return_ty) = self.parse_func_signature()?;
+
+        let BlockStmt { stmts, .. } = self.parse_block()?;
+
+        let span = Span::from_merged_spans(start_span, self.current_span());
+
+        Ok(FuncDecl {
+            name,
+            params,
+            body: stmts,
+            return_ty,
+            span,
+            is_extern: false,
+            is_test: false,
+        })
+    }
+
+    /// Parses a test function, e.g. `test df adds() { assert(1 + 1 == 2); }`.
+    pub fn parse_test_func(&self) -> ParserResult<FuncDecl> {
+        let start_span = self.next()?.span; // Consume Test.
+
+        self.consume_token(Token::Df)?;
+
+        let (name, params, return_ty) = self.parse_func_signature()?;
+
+        let BlockStmt { stmts, .. } = self.parse_block()?;
+
+        let span = Span::from_merged_spans(start_span, self.current_span());
+
+        Ok(FuncDecl {
+            name,
+            params,
+            body: stmts,
+            return_ty,
+            span,
+            is_extern: false,
+            is_test: true,
+        })
+    }
+
+    /// Parses a host function declaration, e.g. `extern df log(x: i64) i64;`.
+    pub fn parse_extern_func(&self) -> ParserResult<FuncDecl> {
+        let start_span = self.next()?.span; // Consume Extern.
+
+        self.consume_token(Token::Df)?;
+
+        let (name, params, return_ty) = self.parse_func_signature()?;
+
+        self.consume_token(Token::Semicolon)?;
+
+        let span = Span::from_merged_spans(start_span, self.current_span());
+
+        Ok(FuncDecl {
+            name,
+            params,
+            body: Vec::new(),
+            return_ty,
+            span,
+            is_extern: true,
+            is_test: false,
+        })
+    }
+
+    /// Parses a function's name, parameters and return type.
+    fn parse_func_signature(&self) -> ParserResult<(String, Vec<Param>, Option<AstType>)> {
         let name = {
             let ident = self.consume_token(Token::Ident)?;
             ident.lexeme.to_string()
@@ -45,23 +137,13 @@ impl<'a> MathicParser<'a> {
 
         self.consume_token(Token::RParen)?;
 
-        let return_ty = if self.check_next(Token::Ident)? {
+        let return_ty = if self.check_next_any(&[Token::Ident, Token::LSquareBracket])? {
             Some(self.parse_type()?)
         } else {
             None
         };
 
-        let BlockStmt { stmts, .. } = self.parse_block()?;
-
-        let span = Span::from_merged_spans(start_span, self.current_span());
-
-        Ok(FuncDecl {
-            name,
-            params,
-            body: stmts,
-            return_ty,
-            span,
-        })
+        Ok((name, params, return_ty))
     }
 
     pub fn parse_var_decl(&self) -> ParserResult<VarDecl> {
@@ -40,6 +40,16 @@ impl<'a> MathicParser<'a> {
                         span,
                     });
                 }
+                ExprStmtKind::Index { expr: lhs, index } => {
+                    return Ok(ExprStmt {
+                        kind: ExprStmtKind::IndexSet {
+                            lhs,
+                            index,
+                            rhs: Box::new(rhs),
+                        },
+                        span,
+                    });
+                }
                 ExprStmtKind::Primary(PrimaryExpr::Ident(name)) => {
                     return Ok(ExprStmt {
                         kind: ExprStmtKind::Assign {
@@ -311,15 +321,36 @@ impl<'a> MathicParser<'a> {
                     };
                 }
                 Token::LSquareBracket => {
-                    let args = self.parse_substitution_args()?;
+                    // Both substitutions (`e[x = 1]`) and indexing (`a[i]`)
+                    // start with an expression, they can only be told apart
+                    // by the token following it.
+                    let first = self.parse_expr_no_init()?;
+
+                    let kind = if self.match_token(Token::Eq)?.is_some() {
+                        let ExprStmtKind::Primary(PrimaryExpr::Ident(ident)) = first.kind else {
+                            return Err(ParseError::Syntax(SyntaxError::MissingToken {
+                                expected: Token::RSquareBracket,
+                                span: self.current_span(),
+                            }));
+                        };
 
-                    self.consume_token(Token::RSquareBracket)?;
+                        let args = self.parse_substitution_args(ident)?;
 
-                    expr = ExprStmt {
-                        kind: ExprStmtKind::Substitution {
+                        ExprStmtKind::Substitution {
                             callee: Box::new(expr),
                             args,
-                        },
+                        }
+                    } else {
+                        ExprStmtKind::Index {
+                            expr: Box::new(expr),
+                            index: Box::new(first),
+                        }
+                    };
+
+                    self.consume_token(Token::RSquareBracket)?;
+
+                    expr = ExprStmt {
+                        kind,
                         span: self.current_span(),
                     };
                 }
@@ -357,7 +388,26 @@ impl<'a> MathicParser<'a> {
     }
 
     fn parse_primary_expr(&self) -> ParserResult<ExprStmt> {
-        let lookahead = self.next()?;
+        let lookahead = self.peek_not_none()?;
+
+        // Anything else is left for the error recovery to synchronize on.
+        if !matches!(
+            lookahead.token,
+            Token::Ident
+                | Token::Str
+                | Token::Num
+                | Token::True
+                | Token::False
+                | Token::LSquareBracket
+                | Token::LParen
+        ) {
+            return Err(ParseError::Syntax(SyntaxError::UnexpectedToken {
+                found: lookahead.into(),
+                expected: ExpectedToken::Identifier,
+            }));
+        }
+
+        self.next()?;
         let span = lookahead.span;
 
         let kind = match lookahead.token {
@@ -375,6 +425,16 @@ impl<'a> MathicParser<'a> {
             Token::Num => ExprStmtKind::Primary(PrimaryExpr::Num(lookahead.lexeme.to_string())),
             Token::True => ExprStmtKind::Primary(PrimaryExpr::Bool(true)),
             Token::False => ExprStmtKind::Primary(PrimaryExpr::Bool(false)),
+            Token::LSquareBracket => {
+                let elements = self.parse_array_elements()?;
+                let close_bracket = self.consume_token(Token::RSquareBracket)?;
+                let span = Span::from_merged_spans(span, close_bracket.span);
+
+                return Ok(ExprStmt {
+                    kind: ExprStmtKind::ArrayInit { elements },
+                    span,
+                });
+            }
             Token::LParen => {
                 let expr = self.parse_expr()?;
                 let close_paren = self.consume_token(Token::RParen)?;
@@ -385,12 +445,7 @@ impl<'a> MathicParser<'a> {
                     span,
                 });
             }
-            _ => {
-                return Err(ParseError::Syntax(SyntaxError::UnexpectedToken {
-                    found: lookahead.into(),
-                    expected: ExpectedToken::Identifier,
-                }));
-            }
+            _ => unreachable!(),
         };
 
         Ok(ExprStmt { kind, span })
@@ -416,11 +471,9 @@ impl<'a> MathicParser<'a> {
         Ok(fields)
     }
 
-    fn parse_substitution_args(&self) -> ParserResult<Vec<(String, ExprStmt)>> {
-        let ident = self.consume_token(Token::Ident)?.lexeme.to_string();
-
-        self.consume_token(Token::Eq)?;
-
+    /// Parses the substitution arguments, once the first symbol and its `=`
+    /// have already been consumed.
+    fn parse_substitution_args(&self, ident: String) -> ParserResult<Vec<(String, ExprStmt)>> {
         let expr = self.parse_expr_no_init()?;
 
         let mut args = vec![(ident, expr)];
@@ -438,6 +491,19 @@ impl<'a> MathicParser<'a> {
         Ok(args)
     }
 
+    fn parse_array_elements(&self) -> ParserResult<Vec<ExprStmt>> {
+        Ok(if self.check_next(Token::RSquareBracket)? {
+            Vec::with_capacity(0)
+        } else {
+            let mut elements = vec![self.parse_expr()?];
+            while self.match_token(Token::Comma)?.is_some() {
+                elements.push(self.parse_expr()?);
+            }
+
+            elements
+        })
+    }
+
     fn parse_call_args(&self) -> ParserResult<Vec<ExprStmt>> {
         Ok(if self.check_next(Token::RParen)? {
             Vec::with_capacity(0)
@@ -79,8 +79,21 @@ impl<'a> MathicParser<'a> {
 
         let mut stmts = Vec::new();
 
-        while !self.check_next(Token::RBrace)? {
-            stmts.push(self.parse_stmt()?);
+        // A statement with a syntax error is skipped, the rest of the block
+        // is still parsed. Imports only go at the top level, so they mean
+        // the block is missing its `}`.
+        loop {
+            match self.peek() {
+                Ok(Some(lookahead)) if matches!(lookahead.token, Token::RBrace | Token::Import) => {
+                    break;
+                }
+                Ok(Some(_)) => match self.parse_stmt() {
+                    Ok(stmt) => stmts.push(stmt),
+                    Err(e) => self.recover_stmt(e),
+                },
+                Ok(None) => break,
+                Err(e) => self.recover_stmt(e),
+            }
         }
 
         let end_span = self.consume_token(Token::RBrace)?;
@@ -4,7 +4,7 @@ use crate::diagnostics::parse::LexError;
 
 #[derive(logos_display::Display, logos_display::Debug, Logos, PartialEq, Eq, Clone, Copy)]
 #[logos(error(LexError, LexError::from_lexer))]
-#[logos(skip r"[ \t\r\n\f]+", skip r"//[^/\n]*", skip r"\/\*(.|\n)*\*\/")]
+#[logos(skip r"[ \t\r\n\f]+")]
 pub enum Token {
     // Single char.
     #[token("(")]
@@ -75,6 +75,10 @@ pub enum Token {
     Sym,
     #[token("df")]
     Df,
+    #[token("extern")]
+    Extern,
+    #[token("test")]
+    Test,
     #[token("let")]
     Let,
     #[token("return")]
@@ -97,4 +101,16 @@ pub enum Token {
     Num,
     #[regex(r"[\p{XID_Start}_]\p{XID_Continue}*")]
     Ident,
+
+    // Trivia, skipped by the parser.
+    #[regex(r"//[^\n]*")]
+    LineComment,
+    #[regex(r"/\*([^*]|\*+[^*/])*\*+/")]
+    BlockComment,
+}
+
+impl Token {
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::LineComment | Token::BlockComment)
+    }
 }
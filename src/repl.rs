@@ -0,0 +1,370 @@
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+
+use crate::{
+    MathicError, MathicResult,
+    codegen::module::MathicModule,
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::{CodegenError, CompilationError, ParseError, SyntaxError},
+    executor::{MathicExecutor, jit::MathicJITExecutor},
+    ffi::dialect_integration::symbolic_dialect::import_symbolic_functions,
+    lowering::{
+        ReplGlobal,
+        ir::{
+            Ir,
+            adts::Adt,
+            types::{FloatTy, MathicType, NumericTy, SintTy, UintTy},
+        },
+        lower_repl_input,
+    },
+    parser::{
+        MathicParser, Span,
+        ast::{
+            IrModule,
+            declaration::{AstType, DeclStmt, Path, TopLevelItem},
+            expression::{ExprStmt, ExprStmtKind},
+            statement::{Stmt, StmtKind},
+        },
+    },
+};
+
+/// Name of the function running each input's statements.
+const EVAL_FUNCTION: &str = "__repl_eval";
+
+/// What the REPL shows after evaluating an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplOutput {
+    /// The input has no result, it only defines items or runs statements.
+    None,
+    /// The value of the input's result.
+    Value(String),
+    /// The input's result can't be printed, only its type is known.
+    Opaque(String),
+}
+
+/// An interactive session.
+///
+/// Every input is compiled into a module of its own (`repl_<n>`) which is
+/// added to a long-lived JIT. Items of previous inputs are imported into the
+/// new ones, and the variables they declare are kept in globals, so each
+/// input only runs its own statements.
+///
+/// Symbolic values only exist while compiling, so the statements declaring
+/// and assigning symbols and symbolic variables are lowered again with every
+/// input. They run no code of their own.
+pub struct MathicRepl<'ctx> {
+    compiler: &'ctx MathicCompiler,
+    compiler_options: CompilerOpts,
+    executor: MathicJITExecutor,
+    /// ASTs of the inputs which defined items.
+    item_modules: Vec<Arc<IrModule>>,
+    /// Modules of those inputs before running the passes. Functions over
+    /// symbolic expressions are inlined, so their bodies are taken from here.
+    generated_modules: Vec<MathicModule<'ctx>>,
+    /// Variables declared by previous inputs.
+    globals: Vec<ReplGlobal>,
+    /// Statements declaring and assigning symbolic values.
+    symbolic_stmts: Vec<Stmt>,
+    /// Names of the symbols and symbolic variables.
+    symbolic_names: HashSet<String>,
+    input_count: usize,
+}
+
+impl<'ctx> MathicRepl<'ctx> {
+    pub fn new(
+        compiler: &'ctx MathicCompiler,
+        compiler_options: CompilerOpts,
+    ) -> Result<Self, CodegenError> {
+        let executor = MathicJITExecutor::new(Vec::new(), &compiler_options)?;
+
+        Ok(Self {
+            compiler,
+            compiler_options,
+            executor,
+            item_modules: Vec::new(),
+            generated_modules: Vec::new(),
+            globals: Vec::new(),
+            symbolic_stmts: Vec::new(),
+            symbolic_names: HashSet::new(),
+            input_count: 0,
+        })
+    }
+
+    /// Returns whether an input ends before it is complete, e.g. within a
+    /// block, so more lines should be read before evaluating it.
+    pub fn is_incomplete(source: &str) -> bool {
+        let (_, errors) = MathicParser::new(source).parse_repl_input(String::new());
+
+        errors
+            .iter()
+            .any(|e| matches!(e, ParseError::Syntax(SyntaxError::UnexpectedEnd { .. })))
+    }
+
+    /// Compiles and runs an input.
+    ///
+    /// Errors are reported to the compiler's diagnostics. A failed input
+    /// leaves the session untouched.
+    pub fn eval(&mut self, source: &str) -> MathicResult<ReplOutput> {
+        let diagnostics = self.compiler.diagnostics();
+        diagnostics.clear()?;
+
+        self.input_count += 1;
+
+        let module_name = format!("repl_{}", self.input_count);
+        let module_path = PathBuf::from(&module_name);
+
+        let (mut input, errors) = MathicParser::new(source).parse_repl_input(module_name.clone());
+
+        if !errors.is_empty() {
+            for e in errors {
+                diagnostics.report(module_path.clone(), CompilationError::Parse(e))?;
+            }
+
+            return Err(MathicError::CompilationFailed);
+        }
+
+        let defines_items = !input.module.items.is_empty();
+
+        input
+            .module
+            .items
+            .splice(0..0, self.visible_items(&input.module));
+        input.module.modules = self.item_modules.clone();
+
+        // Variables of a struct defined again would get the new definition.
+        let redefined_structs = input
+            .module
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                TopLevelItem::Struct(strct) => Some(strct.name.as_str()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let globals = self
+            .globals
+            .iter()
+            .filter(|global| !names_struct(&global.ty, &redefined_structs))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let body = self
+            .symbolic_stmts
+            .iter()
+            .chain(&input.stmts)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let lowering = match lower_repl_input(
+            &input.module,
+            EVAL_FUNCTION,
+            &globals,
+            &body,
+            input.result.as_ref(),
+        ) {
+            Ok(lowering) => lowering,
+            Err(errors) => {
+                for e in errors {
+                    diagnostics.report(module_path.clone(), CompilationError::Lowering(e))?;
+                }
+
+                return Err(MathicError::CompilationFailed);
+            }
+        };
+
+        let module = self.compiler.generate_module(
+            &lowering.ir,
+            None,
+            &module_path,
+            &self.compiler_options,
+        )?;
+
+        let generated_module = if defines_items {
+            match module.try_clone() {
+                Ok(module) => Some(module),
+                Err(e) => {
+                    return Err(
+                        diagnostics.report_and_fail(module_path, CompilationError::Codegen(e))
+                    );
+                }
+            }
+        } else {
+            None
+        };
+
+        // Importing a body may declare functions from another input, so we
+        // keep going until nothing else is imported.
+        let mut imported = true;
+
+        while imported {
+            imported = false;
+
+            for dependency in &self.generated_modules {
+                imported |= import_symbolic_functions(module.as_inner(), dependency.as_inner());
+            }
+        }
+
+        let module =
+            self.compiler
+                .compile_module(module, None, &module_path, &self.compiler_options)?;
+
+        if let Err(e) = self
+            .executor
+            .add_modules(vec![module], &self.compiler_options)
+        {
+            return Err(diagnostics.report_and_fail(module_path, CompilationError::Codegen(e)));
+        }
+
+        let function_name = format!("{module_name}::{EVAL_FUNCTION}");
+
+        // Only numbers and booleans are returned, the function returns
+        // nothing otherwise.
+        let output = match lowering.result_ty {
+            Some(ty @ (MathicType::Numeric(_) | MathicType::Bool)) => {
+                self.call(&function_name, ty).map(ReplOutput::Value)
+            }
+            Some(ty) => self
+                .executor
+                .run_test(&function_name)
+                .map(|()| ReplOutput::Opaque(type_name(&lowering.ir, ty))),
+            None => self
+                .executor
+                .run_test(&function_name)
+                .map(|()| ReplOutput::None),
+        };
+
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                return Err(diagnostics.report_and_fail(module_path, CompilationError::Codegen(e)));
+            }
+        };
+
+        for stmt in input.stmts {
+            let symbolic_name = match &stmt.kind {
+                StmtKind::Decl(DeclStmt::Sym(sym)) => Some(&sym.name),
+                StmtKind::Decl(DeclStmt::Var(var))
+                    if !lowering
+                        .globals
+                        .iter()
+                        .any(|global| global.name == var.name) =>
+                {
+                    Some(&var.name)
+                }
+                StmtKind::Expr(ExprStmt {
+                    kind: ExprStmtKind::Assign { name, .. },
+                    ..
+                }) if self.symbolic_names.contains(name) => Some(name),
+                _ => None,
+            };
+
+            if let Some(name) = symbolic_name {
+                self.symbolic_names.insert(name.clone());
+                self.symbolic_stmts.push(stmt);
+            }
+        }
+
+        self.globals = globals;
+        self.globals.extend(lowering.globals);
+
+        if let Some(generated_module) = generated_module {
+            self.item_modules.push(Arc::new(input.module));
+            self.generated_modules.push(generated_module);
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the imports of the items defined by previous inputs. Items
+    /// redefined by a later input, or by `module`, are shadowed.
+    fn visible_items(&self, module: &IrModule) -> Vec<TopLevelItem> {
+        let mut defined = item_names(module).collect::<HashSet<_>>();
+        let mut imports = Vec::new();
+
+        for item_module in self.item_modules.iter().rev() {
+            for name in item_names(item_module) {
+                if defined.insert(name.clone()) {
+                    imports.push(TopLevelItem::Import(Path {
+                        idents: vec![item_module.module_name.clone(), name],
+                        group_paths: Vec::new(),
+                        import_all: false,
+                        span: Span::from(0..0),
+                    }));
+                }
+            }
+        }
+
+        imports
+    }
+
+    /// Calls the function evaluating an input and formats its result.
+    fn call(&self, function_name: &str, ty: MathicType) -> Result<String, CodegenError> {
+        macro_rules! call_as {
+            ($ty:ty) => {
+                self.executor
+                    .call::<(), $ty>(function_name, ())?
+                    .to_string()
+            };
+        }
+
+        Ok(match ty {
+            MathicType::Bool => call_as!(bool),
+            MathicType::Numeric(NumericTy::Sint(sint)) => match sint {
+                SintTy::Isize => call_as!(isize),
+                SintTy::I8 => call_as!(i8),
+                SintTy::I16 => call_as!(i16),
+                SintTy::I32 => call_as!(i32),
+                SintTy::I64 => call_as!(i64),
+                SintTy::I128 => call_as!(i128),
+            },
+            MathicType::Numeric(NumericTy::Uint(uint)) => match uint {
+                UintTy::Usize => call_as!(usize),
+                UintTy::U8 => call_as!(u8),
+                UintTy::U16 => call_as!(u16),
+                UintTy::U32 => call_as!(u32),
+                UintTy::U64 => call_as!(u64),
+                UintTy::U128 => call_as!(u128),
+            },
+            MathicType::Numeric(NumericTy::Float(float)) => match float {
+                FloatTy::F32 => call_as!(f32),
+                FloatTy::F64 => call_as!(f64),
+            },
+            _ => unreachable!("only numbers and booleans are returned"),
+        })
+    }
+}
+
+/// Returns whether a type is, or is made of, one of the given structs.
+fn names_struct(ty: &AstType, structs: &HashSet<&str>) -> bool {
+    match ty {
+        AstType::Type { ty, inner } => {
+            structs.contains(ty.as_str())
+                || inner
+                    .as_deref()
+                    .is_some_and(|inner| names_struct(inner, structs))
+        }
+        AstType::Array { elem, .. } => names_struct(elem, structs),
+    }
+}
+
+/// Returns the names of the functions and structs defined by a module.
+fn item_names(module: &IrModule) -> impl Iterator<Item = String> + '_ {
+    module.items.iter().filter_map(|item| match item {
+        TopLevelItem::Func(func) => Some(func.name.clone()),
+        TopLevelItem::Struct(strct) => Some(strct.name.clone()),
+        TopLevelItem::Import(_) => None,
+    })
+}
+
+/// Returns the name of a type as written in the source.
+fn type_name(ir: &Ir, ty: MathicType) -> String {
+    match ty {
+        MathicType::Adt {
+            index,
+            is_local: false,
+        } => match ir.get_adt(index) {
+            Some(Adt::Struct(strct)) => strct.name.clone(),
+            None => ty.to_string(),
+        },
+        _ => ty.to_string(),
+    }
+}
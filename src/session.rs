@@ -0,0 +1,232 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    marker::PhantomData,
+    path::PathBuf,
+};
+
+use crate::{
+    MathicError, MathicResult,
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::{CodegenError, CompilationError, DiagnosticsManager},
+    executor::{MathicExecutor, MathicValue, jit::MathicJITExecutor},
+    lowering::{ir::types::MathicType, lower_program},
+    parser::{
+        MathicParser, Span,
+        ast::{
+            IrModule,
+            declaration::{AstType, FuncDecl, Param, TopLevelItem},
+            statement::{Stmt, StmtKind},
+        },
+    },
+};
+
+/// Name of the function evaluating each expression.
+const EXPR_FUNCTION: &str = "__session_expr";
+
+/// Path errors of an expression are reported under.
+const EXPR_PATH: &str = "<expr>";
+
+/// Most parameters an expression may take.
+pub const MAX_PARAMS: usize = 8;
+
+/// Evaluates expressions from Rust.
+///
+/// Every expression is compiled into a module of its own (`session_<n>`),
+/// holding a function which takes the expression's variables and returns its
+/// value, all of the same type. Compiled expressions are cached, so
+/// evaluating one again only calls it.
+///
+/// Errors are reported to [`diagnostics`](Session::diagnostics). Since they
+/// don't come from a file, print them with `print_all_with_source`.
+pub struct Session {
+    // Dropped before the compiler, whose context it was built with.
+    executor: MathicJITExecutor,
+    compiler: MathicCompiler,
+    compiler_options: CompilerOpts,
+    /// Names of the compiled functions, by expression, parameters and type.
+    cache: RefCell<HashMap<(String, Vec<String>, MathicType), String>>,
+    expr_count: Cell<usize>,
+}
+
+impl Session {
+    pub fn new() -> Result<Self, CodegenError> {
+        Self::with_options(CompilerOpts::default())
+    }
+
+    pub fn with_options(compiler_options: CompilerOpts) -> Result<Self, CodegenError> {
+        let compiler = MathicCompiler::new()?;
+        let executor = MathicJITExecutor::new(Vec::new(), &compiler_options)?;
+
+        Ok(Self {
+            executor,
+            compiler,
+            compiler_options,
+            cache: RefCell::new(HashMap::new()),
+            expr_count: Cell::new(0),
+        })
+    }
+
+    pub fn diagnostics(&self) -> &DiagnosticsManager {
+        self.compiler.diagnostics()
+    }
+
+    /// Evaluates an expression, e.g. `eval("x*x + 3*y", &[("x", 3), ("y", 4)])`.
+    pub fn eval<T: MathicValue>(&self, expr: &str, vars: &[(&str, T)]) -> MathicResult<T> {
+        let params = vars.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let args = vars.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+
+        let compiled = self.compile_expr::<T>(expr, &params)?;
+
+        compiled.call(&args).map_err(|e| {
+            self.diagnostics()
+                .report_and_fail(PathBuf::from(EXPR_PATH), CompilationError::Codegen(e))
+        })
+    }
+
+    /// Compiles an expression over the given parameters, e.g.
+    /// `compile_expr::<f64>("x*x + 1", &["x"])`.
+    pub fn compile_expr<T: MathicValue>(
+        &self,
+        expr: &str,
+        params: &[&str],
+    ) -> MathicResult<CompiledFn<'_, T>> {
+        let diagnostics = self.diagnostics();
+        diagnostics.clear()?;
+
+        let report_path = PathBuf::from(EXPR_PATH);
+        let key = (
+            expr.to_string(),
+            params.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            T::mathic_type(),
+        );
+
+        if let Some(function_name) = self.cache.borrow().get(&key) {
+            return Ok(CompiledFn::new(self, function_name.clone()));
+        }
+
+        if params.len() > MAX_PARAMS {
+            return Err(diagnostics.report_and_fail(
+                report_path,
+                CompilationError::Codegen(CodegenError::Custom(format!(
+                    "expressions take at most {MAX_PARAMS} parameters, found {}",
+                    params.len()
+                ))),
+            ));
+        }
+
+        let body = match MathicParser::new(expr).parse_standalone_expr() {
+            Ok(body) => body,
+            Err(e) => {
+                return Err(diagnostics.report_and_fail(report_path, CompilationError::Parse(e)));
+            }
+        };
+
+        self.expr_count.set(self.expr_count.get() + 1);
+
+        let module_name = format!("session_{}", self.expr_count.get());
+        let module_path = PathBuf::from(&module_name);
+
+        let ty = AstType::Type {
+            ty: T::mathic_type().to_string(),
+            inner: None,
+        };
+        let span = body.extent();
+
+        let module = IrModule {
+            module_name: module_name.clone(),
+            modules: Vec::new(),
+            items: vec![TopLevelItem::Func(FuncDecl {
+                name: EXPR_FUNCTION.to_string(),
+                params: params
+                    .iter()
+                    .map(|name| Param {
+                        name: name.to_string(),
+                        span: Span::from(0..0),
+                        ty: ty.clone(),
+                    })
+                    .collect(),
+                body: vec![Stmt {
+                    kind: StmtKind::Return(body),
+                    span,
+                }],
+                span,
+                return_ty: Some(ty),
+                is_extern: false,
+                is_test: false,
+            })],
+            source: expr.into(),
+            dependency_aliases: HashMap::new(),
+        };
+
+        let ir = match lower_program(&module) {
+            Ok(ir) => ir,
+            Err(errors) => {
+                for e in errors {
+                    diagnostics.report(report_path.clone(), CompilationError::Lowering(e))?;
+                }
+
+                return Err(MathicError::CompilationFailed);
+            }
+        };
+
+        let module =
+            self.compiler
+                .generate_module(&ir, None, &module_path, &self.compiler_options)?;
+        let module =
+            self.compiler
+                .compile_module(module, None, &module_path, &self.compiler_options)?;
+
+        if let Err(e) = self
+            .executor
+            .add_modules(vec![module], &self.compiler_options)
+        {
+            return Err(diagnostics.report_and_fail(report_path, CompilationError::Codegen(e)));
+        }
+
+        let function_name = format!("{module_name}::{EXPR_FUNCTION}");
+        self.cache.borrow_mut().insert(key, function_name.clone());
+
+        Ok(CompiledFn::new(self, function_name))
+    }
+}
+
+/// An expression compiled by a [`Session`], taking and returning `T`s.
+pub struct CompiledFn<'s, T: MathicValue> {
+    session: &'s Session,
+    function_name: String,
+    _ty: PhantomData<T>,
+}
+
+impl<'s, T: MathicValue> CompiledFn<'s, T> {
+    fn new(session: &'s Session, function_name: String) -> Self {
+        Self {
+            session,
+            function_name,
+            _ty: PhantomData,
+        }
+    }
+
+    /// Calls the expression with a value for each of its parameters, in the
+    /// order they were given.
+    pub fn call(&self, args: &[T]) -> Result<T, CodegenError> {
+        let executor = &self.session.executor;
+        let name = self.function_name.as_str();
+
+        match *args {
+            [] => executor.call(name, ()),
+            [a] => executor.call(name, (a,)),
+            [a, b] => executor.call(name, (a, b)),
+            [a, b, c] => executor.call(name, (a, b, c)),
+            [a, b, c, d] => executor.call(name, (a, b, c, d)),
+            [a, b, c, d, e] => executor.call(name, (a, b, c, d, e)),
+            [a, b, c, d, e, f] => executor.call(name, (a, b, c, d, e, f)),
+            [a, b, c, d, e, f, g] => executor.call(name, (a, b, c, d, e, f, g)),
+            [a, b, c, d, e, f, g, h] => executor.call(name, (a, b, c, d, e, f, g, h)),
+            _ => Err(CodegenError::Custom(format!(
+                "expressions take at most {MAX_PARAMS} parameters, found {}",
+                args.len()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::project_src_root;
+use mathic::analysis::{self, Analysis, CompletionKind};
+use rstest::rstest;
+
+fn analyze_file(src_root: &Path, path: &Path) -> (Analysis, String) {
+    let file_path = src_root.join(path);
+    let source = fs::read_to_string(&file_path).expect("failed to read the source");
+    let analysis =
+        analysis::analyze(src_root, &file_path, &source).expect("failed to analyze the source");
+
+    (analysis, source)
+}
+
+fn analyze_example(path: &str) -> (Analysis, String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path);
+
+    analyze_file(path.parent().unwrap(), Path::new(path.file_name().unwrap()))
+}
+
+/// Returns the offset of the `nth` occurrence of `needle`.
+fn offset_of(source: &str, needle: &str, nth: usize) -> usize {
+    source
+        .match_indices(needle)
+        .nth(nth)
+        .unwrap_or_else(|| panic!("'{needle}' not found"))
+        .0
+}
+
+#[rstest]
+#[case::local("r.origin.x", 0, "let r: Rectangle")]
+#[case::param("r.width", 0, "r: Rectangle")]
+#[case::field("x;", 0, "Point.x: i64")]
+#[case::nested_field("origin.x", 0, "Rectangle.origin: Point")]
+#[case::function("area(r)", 0, "df area(r: Rectangle) i64")]
+#[case::struct_init("Point {", 1, "struct Point {\n    x: i64,\n    y: i64\n}")]
+fn test_hover(#[case] needle: &str, #[case] nth: usize, #[case] expected: &str) {
+    let (analysis, source) = analyze_example("examples/structs/integrated_test.mth");
+
+    let hover = analysis
+        .hover(offset_of(&source, needle, nth))
+        .expect("nothing to hover");
+
+    assert_eq!(hover.contents, expected);
+}
+
+#[rstest]
+#[case::function("create_point(a", "df create_point")]
+#[case::local("p, 10", "let p: Point")]
+#[case::field("width *", "width: i64")]
+fn test_definition(#[case] needle: &str, #[case] expected: &str) {
+    let (analysis, source) = analyze_example("examples/structs/integrated_test.mth");
+
+    let location = analysis
+        .definition(offset_of(&source, needle, 0))
+        .expect("no definition found");
+
+    assert_eq!(location.path, analysis.file_path());
+    assert_eq!(location.span.start, offset_of(&source, expected, 0));
+}
+
+#[rstest]
+#[case::imported_function("mul(p1", "util.mth", "df mul")]
+#[case::imported_item("geo::distance;", "geo.mth", "df distance")]
+fn test_definition_across_modules(
+    #[case] needle: &str,
+    #[case] module: &str,
+    #[case] expected: &str,
+) {
+    let src_root = project_src_root(Path::new("examples/projects/modules"));
+    let (analysis, source) = analyze_file(&src_root, Path::new("main.mth"));
+
+    let location = analysis
+        .definition(offset_of(&source, needle, 0))
+        .expect("no definition found");
+    let target = fs::read_to_string(src_root.join(module)).unwrap();
+
+    assert_eq!(location.path, src_root.join(module));
+    assert_eq!(location.span.start, offset_of(&target, expected, 0));
+}
+
+#[test]
+fn test_completions() {
+    let (analysis, source) = analyze_example("examples/structs/integrated_test.mth");
+    let offset = offset_of(&source, "if result", 0);
+
+    let completions = analysis.completions(&source, offset);
+    let labels_of = |kind| {
+        completions
+            .iter()
+            .filter(|c| c.kind == kind)
+            .map(|c| c.label.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        labels_of(CompletionKind::Local),
+        ["result", "r", "p", "b", "a"]
+    );
+    assert!(labels_of(CompletionKind::Function).contains(&"create_rectangle"));
+    assert!(labels_of(CompletionKind::Struct).contains(&"Rectangle"));
+}
+
+#[test]
+fn test_field_completions() {
+    let (analysis, source) = analyze_example("examples/structs/integrated_test.mth");
+
+    // Completions are asked on code being typed, newer than the analyzed one.
+    let offset = offset_of(&source, "r.origin.x", 0) + "r.origin.".len();
+    let edited = format!("{}\n", &source[..offset]);
+
+    let completions = analysis.completions(&edited, offset);
+    let fields = completions
+        .iter()
+        .map(|c| (c.label.as_str(), c.kind, c.detail.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        fields,
+        [
+            ("x", CompletionKind::Field, "i64"),
+            ("y", CompletionKind::Field, "i64")
+        ]
+    );
+}
+
+#[rstest]
+#[case::undeclared_variable("semantic_error_undeclared_variable.mth", "S001")]
+#[case::missing_expression("syntax_error_missing_expression.mth", "E001")]
+fn test_diagnostics(#[case] fixture: &str, #[case] code: &str) {
+    let (analysis, _) = analyze_example(&format!("tests/fixtures/errors/{fixture}"));
+
+    let codes = analysis
+        .diagnostics()
+        .iter()
+        .map(|d| d.code)
+        .collect::<Vec<_>>();
+
+    assert_eq!(codes, [Some(code)]);
+}
+
+#[test]
+fn test_partial_analysis() {
+    let (analysis, source) = analyze_example("tests/fixtures/errors/syntax_error_multiple.mth");
+
+    assert!(!analysis.is_parsed());
+    assert_eq!(analysis.diagnostics().len(), 4);
+
+    // The statements around the errors are still analyzed.
+    let local = analysis.hover(offset_of(&source, "a + other", 0));
+    let function = analysis.hover(offset_of(&source, "other()", 0));
+
+    assert_eq!(local.expect("nothing to hover").contents, "a: i32");
+    assert_eq!(
+        function.expect("nothing to hover").contents,
+        "df other() i32"
+    );
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use common::{build_and_run_project, build_project};
+use mathic::{
+    aot,
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::CodegenError,
+};
+use rstest::rstest;
+
+#[rstest]
+#[case("examples/projects/modules", 37)]
+#[case("examples/projects/import_dir", 22)]
+#[case("examples/projects/import_all", 15)]
+#[case("examples/projects/symbolic_library", 49)]
+#[case("tests/fixtures/bool_main", 1)]
+#[case("tests/fixtures/unsigned_main", 200)]
+fn test_build_executable(#[case] path: PathBuf, #[case] expected: i32) {
+    let result = build_and_run_project(&path);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_index_out_of_bounds_traps() {
+    let executable = build_project(Path::new("tests/fixtures/array_out_of_bounds"));
+
+    let output = Command::new(&executable)
+        .output()
+        .expect("Failed to run the executable");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("src/main.mth:2:19: index out of bounds: the length is 3")
+    );
+}
+
+#[test]
+fn test_missing_entry() {
+    let opts = CompilerOpts::default();
+    let compiler = MathicCompiler::new().expect("Failed to create the compiler");
+    let modules = compiler
+        .compile_project(Path::new("tests/fixtures/bool_main/src"), &opts)
+        .expect("compilation failed");
+    let obj_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("missing_entry");
+
+    assert!(matches!(
+        aot::emit_objects(&modules, &opts, "main::start", &obj_dir),
+        Err(CodegenError::EntryNotFound(entry)) if entry == "main::start"
+    ));
+}
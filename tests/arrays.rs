@@ -0,0 +1,19 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::compile_and_execute;
+use rstest::rstest;
+
+#[rstest]
+#[case("examples/arrays/basic_indexing.mth", 5)]
+#[case("examples/arrays/element_assignment.mth", 44)]
+#[case("examples/arrays/loop_sum.mth", 14)]
+#[case("examples/arrays/function_param.mth", 14)]
+#[case("examples/arrays/struct_field.mth", 21)]
+#[case("examples/arrays/for_each.mth", 17)]
+#[case("examples/arrays/count_above.mth", 3)]
+fn test_arrays(#[case] path: PathBuf, #[case] expected: i64) {
+    let result = compile_and_execute(&path);
+    assert_eq!(result, expected);
+}
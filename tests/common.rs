@@ -3,9 +3,11 @@
 use std::{
     env,
     path::{Path, PathBuf},
+    process::Command,
 };
 
 use mathic::{
+    aot,
     compiler::{CompilerOpts, MathicCompiler},
     executor::{MathicExecutor, jit::MathicJITExecutor},
 };
@@ -30,11 +32,11 @@ pub fn compile_and_execute(path: &Path) -> i64 {
     let compiler = MathicCompiler::new().expect("Failed to create the compiler");
 
     let module = compiler
-        .compile_path(path, opts)
+        .compile_path(path, &opts)
         .expect("compilation failed");
 
     let executor =
-        MathicJITExecutor::new(vec![module], opts).expect("Failed to create the executor");
+        MathicJITExecutor::new(vec![module], &opts).expect("Failed to create the executor");
 
     executor
         .call_function("program::main")
@@ -45,9 +47,13 @@ pub fn compile_and_execute(path: &Path) -> i64 {
 /// subdirectory with a `main.mth`). `main.mth`'s `main` function is executed
 /// and its return value returned.
 pub fn compile_and_execute_project(project_dir: &Path) -> i64 {
+    compile_and_execute_project_with(project_dir, &CompilerOpts::default())
+}
+
+/// Like [`compile_and_execute_project`], with the given compiler options.
+pub fn compile_and_execute_project_with(project_dir: &Path, opts: &CompilerOpts) -> i64 {
     let src_root = absolute_path(project_dir).join("src");
 
-    let opts = CompilerOpts::default();
     let compiler = MathicCompiler::new().expect("Failed to create the compiler");
 
     let modules = match compiler.compile_project(&src_root, opts) {
@@ -64,3 +70,43 @@ pub fn compile_and_execute_project(project_dir: &Path) -> i64 {
         .call_function("main::main")
         .expect("execution failed")
 }
+
+/// Compiles a whole project into a native executable and runs it. The exit
+/// code of the process is returned.
+pub fn build_and_run_project(project_dir: &Path) -> i32 {
+    Command::new(build_project(project_dir))
+        .status()
+        .expect("Failed to run the executable")
+        .code()
+        .expect("executable terminated by a signal")
+}
+
+/// Compiles a whole project into a native executable, returning its path.
+pub fn build_project(project_dir: &Path) -> PathBuf {
+    let src_root = absolute_path(project_dir).join("src");
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join(project_dir.file_name().expect("project dir has no name"));
+    let executable = target_dir.join("main");
+
+    let opts = CompilerOpts::default();
+    let compiler = MathicCompiler::new().expect("Failed to create the compiler");
+
+    let modules = match compiler.compile_project(&src_root, &opts) {
+        Ok(m) => m,
+        Err(e) => {
+            compiler.diagnostics().print_all().unwrap();
+            panic!("compilation failed: {:?}", e);
+        }
+    };
+
+    aot::build_executable(
+        &modules,
+        &opts,
+        "main::main",
+        &target_dir.join("obj"),
+        &executable,
+    )
+    .expect("build failed");
+
+    executable
+}
@@ -12,6 +12,8 @@ use rstest::rstest;
 #[case("examples/control_flow/while_sum.mth", 55)]
 #[case("examples/control_flow/for_sum.mth", 55)]
 #[case("examples/control_flow/factorial.mth", 120)]
+#[case("examples/control_flow/call_in_loop.mth", 8)]
+#[case("tests/fixtures/lints/unreachable_return.mth", 1)]
 fn test_control_flow(#[case] path: PathBuf, #[case] expected: i64) {
     let result = compile_and_execute(&path);
     assert_eq!(result, expected);
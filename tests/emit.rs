@@ -0,0 +1,56 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::project_src_root;
+use mathic::compiler::{CompilerOpts, EmitKind, MathicCompiler};
+
+#[test]
+fn test_emit_artifacts_per_module() {
+    let emit_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("emit");
+    let _ = fs::remove_dir_all(&emit_dir);
+
+    let opts = CompilerOpts {
+        emit: vec![
+            EmitKind::Mathir,
+            EmitKind::Mlir,
+            EmitKind::MlirBc,
+            EmitKind::LlvmIr,
+            EmitKind::LlvmBc,
+            EmitKind::Asm,
+            EmitKind::Obj,
+        ],
+        emit_dir: emit_dir.clone(),
+        ..Default::default()
+    };
+    let compiler = MathicCompiler::new().expect("Failed to create the compiler");
+
+    compiler
+        .compile_project(
+            &project_src_root(&PathBuf::from("examples/projects/modules")),
+            &opts,
+        )
+        .expect("compilation failed");
+
+    for module in ["main", "util", "types", "geo"] {
+        for extension in [
+            "mathir",
+            "mlir",
+            "lowered.mlir",
+            "mlirbc",
+            "lowered.mlirbc",
+            "ll",
+            "bc",
+            "s",
+            "o",
+        ] {
+            let path = emit_dir.join(module).with_extension(extension);
+            assert!(path.exists(), "{} was not emitted", path.display());
+        }
+    }
+
+    // The textual artifact must be LLVM IR, not native assembly.
+    let llvm_ir = fs::read_to_string(emit_dir.join("main.ll")).unwrap();
+    assert!(llvm_ir.starts_with("; ModuleID"));
+    assert!(llvm_ir.contains("define"));
+}
@@ -1,6 +1,9 @@
 mod common;
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use mathic::{
     MathicError,
@@ -11,13 +14,13 @@ use rstest::rstest;
 
 fn compile_file(compiler: &MathicCompiler, file_path: &Path) -> Result<(), MathicError> {
     compiler
-        .compile_path(file_path, CompilerOpts::default())
+        .compile_path(file_path, &CompilerOpts::default())
         .map(|_| ())
 }
 
 fn compile_project(compiler: &MathicCompiler, src_root: &Path) -> Result<(), MathicError> {
     compiler
-        .compile_project(src_root, CompilerOpts::default())
+        .compile_project(src_root, &CompilerOpts::default())
         .map(|_| ())
 }
 
@@ -129,6 +132,24 @@ fn assert_single_error(
         matches!(error, CompilationError::Lowering(LoweringError::WrongArgumentCount { .. }))
     }
 )]
+#[case(
+    "tests/fixtures/errors/semantic_error_literal_out_of_range.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::LiteralOutOfRange { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/semantic_error_builtin_function_name.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::BuiltinRedefinition { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/semantic_error_builtin_inner_function_name.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::BuiltinRedefinition { .. }))
+    }
+)]
 #[case(
     "tests/fixtures/errors/semantic_error_mismatched_return_type.mth",
     |error: &CompilationError| {
@@ -153,11 +174,122 @@ fn assert_single_error(
         matches!(error, CompilationError::Lowering(LoweringError::TypeRequiresTypeParameter { .. }))
     }
 )]
+#[case(
+    "tests/fixtures/errors/array_error_index_out_of_bounds.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::IndexOutOfBounds { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/array_error_mismatched_length.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::MismatchedArrayLength { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/array_error_not_iterable.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::NotIterable { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/symbolic_error_diff_not_a_symbol.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::NotASymbol { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/symbolic_error_partial_substitution.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::MissingSymbols { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/symbolic_error_conflicting_symbol.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::ConflictingSymbol { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/host_fn_error_unsupported_type.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::UnsupportedFeature { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/test_fn_error_parameters.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::InvalidTestFunction { .. }))
+    }
+)]
+#[case(
+    "tests/fixtures/errors/test_fn_error_assert_non_boolean.mth",
+    |error: &CompilationError| {
+        matches!(error, CompilationError::Lowering(LoweringError::MismatchedType { .. }))
+    }
+)]
 fn source_error(#[case] path: PathBuf, #[case] check: fn(&CompilationError) -> bool) {
     let compiler = MathicCompiler::new().unwrap();
     assert_single_error(&compiler, compile_file(&compiler, &path), check);
 }
 
+#[test]
+fn syntax_errors_accumulate() {
+    let compiler = MathicCompiler::new().unwrap();
+    let path = PathBuf::from("tests/fixtures/errors/syntax_error_multiple.mth");
+
+    assert_compilation_failed(&compiler, compile_file(&compiler, &path), |errors| {
+        errors.len() == 4
+            && errors
+                .iter()
+                .all(|(_, error)| matches!(error, CompilationError::Parse(ParseError::Syntax(_))))
+    });
+}
+
+#[test]
+fn lowering_errors_accumulate() {
+    let compiler = MathicCompiler::new().unwrap();
+    let path = PathBuf::from("tests/fixtures/errors/semantic_error_multiple.mth");
+
+    // Uses of `x` and `y`, whose declarations failed, aren't reported.
+    assert_compilation_failed(&compiler, compile_file(&compiler, &path), |errors| {
+        let errors = errors.iter().map(|(_, error)| error).collect::<Vec<_>>();
+
+        matches!(
+            errors[..],
+            [
+                CompilationError::Lowering(LoweringError::MismatchedType { .. }),
+                CompilationError::Lowering(LoweringError::UndeclaredVariable { .. }),
+                CompilationError::Lowering(LoweringError::MismatchedType { .. }),
+                CompilationError::Lowering(LoweringError::WrongArgumentCount { .. }),
+                CompilationError::Lowering(LoweringError::MismatchedReturnType { .. }),
+            ]
+        )
+    });
+}
+
+#[test]
+fn errors_as_json() {
+    let compiler = MathicCompiler::new().unwrap();
+    let path = PathBuf::from("tests/fixtures/errors/semantic_error_multiple.mth");
+
+    assert!(compile_file(&compiler, &path).is_err());
+
+    let json = compiler.diagnostics().to_json().unwrap();
+    let diagnostics: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let diagnostics = diagnostics.as_array().unwrap();
+
+    assert_eq!(diagnostics.len(), 5);
+
+    let first = &diagnostics[0];
+
+    assert_eq!(first["severity"], "error");
+    assert_eq!(first["code"], "S007");
+    assert_eq!(first["file"], path.display().to_string());
+    assert_eq!(first["span"]["line"], 2);
+    assert_eq!(first["span"]["end_line"], 2);
+}
+
 #[rstest]
 #[case(
     "tests/fixtures/error_parse",
@@ -173,6 +305,26 @@ fn source_error(#[case] path: PathBuf, #[case] check: fn(&CompilationError) -> b
     "tests/fixtures/error_parse_gates_lowering/src",
     |error: &CompilationError| matches!(error, CompilationError::Parse(ParseError::Syntax(_)))
 )]
+#[case(
+    "tests/fixtures/error_circular_import/src",
+    |error: &CompilationError| {
+        matches!(
+            error,
+            CompilationError::Lowering(LoweringError::CircularImport { cycle, .. })
+                if cycle == &["main", "geo", "main"]
+        )
+    }
+)]
+#[case(
+    "tests/fixtures/error_circular_import_indirect/src",
+    |error: &CompilationError| {
+        matches!(
+            error,
+            CompilationError::Lowering(LoweringError::CircularImport { cycle, .. })
+                if cycle == &["shapes", "util", "shapes"]
+        )
+    }
+)]
 fn project_error(#[case] src_root: PathBuf, #[case] check: fn(&CompilationError) -> bool) {
     let compiler = MathicCompiler::new().unwrap();
     assert_single_error(&compiler, compile_project(&compiler, &src_root), check);
@@ -198,3 +350,57 @@ fn project_lowering_errors_accumulate_across_files(
     let compiler = MathicCompiler::new().unwrap();
     assert_compilation_failed(&compiler, compile_project(&compiler, &src_root), check);
 }
+
+/// The project can't import the dependencies of its dependencies.
+#[test]
+fn undeclared_transitive_dependency() {
+    let compiler = MathicCompiler::new().unwrap();
+    let root = |package: &str| {
+        common::project_src_root(&Path::new("tests/fixtures/scoped_dependencies").join(package))
+    };
+    let opts = CompilerOpts {
+        dependencies: HashMap::from([("left".to_string(), root("left"))]),
+        package_dependencies: HashMap::from([(
+            root("left"),
+            HashMap::from([("util".to_string(), root("util_left"))]),
+        )]),
+        ..Default::default()
+    };
+
+    let result = compiler
+        .compile_project(&root("transitive"), &opts)
+        .map(|_| ());
+
+    assert_single_error(&compiler, result, |error| {
+        matches!(
+            error,
+            CompilationError::Lowering(LoweringError::UnResolvedPath { .. })
+        )
+    });
+}
+
+#[test]
+fn dependency_named_like_a_module() {
+    let compiler = MathicCompiler::new().unwrap();
+    let opts = CompilerOpts {
+        dependencies: HashMap::from([(
+            "geometry".to_string(),
+            common::project_src_root(Path::new("examples/projects/geometry")),
+        )]),
+        ..Default::default()
+    };
+
+    let result = compiler
+        .compile_project(
+            Path::new("tests/fixtures/error_ambiguous_import/src"),
+            &opts,
+        )
+        .map(|_| ());
+
+    assert_single_error(&compiler, result, |error| {
+        matches!(
+            error,
+            CompilationError::Lowering(LoweringError::AmbiguousImport { .. })
+        )
+    });
+}
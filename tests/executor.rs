@@ -0,0 +1,261 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+use mathic::{
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::CodegenError,
+    executor::{AbiBool, MathicExecutor, ScalarValue, jit::MathicJITExecutor},
+};
+
+const KERNELS: &str = "tests/fixtures/executor/kernels.mth";
+const HOST: &str = "tests/fixtures/executor/host.mth";
+const TESTS: &str = "tests/fixtures/executor/tests.mth";
+
+fn with_executor(path: &str, f: impl FnOnce(&MathicJITExecutor)) {
+    let opts = CompilerOpts::default();
+    let compiler = MathicCompiler::new().unwrap();
+
+    let module = compiler
+        .compile_path(Path::new(path), &opts)
+        .expect("compilation failed");
+    let executor = MathicJITExecutor::new(vec![module], &opts).unwrap();
+
+    f(&executor);
+}
+
+#[test]
+fn test_typed_calls() {
+    with_executor(KERNELS, |executor| {
+        assert_eq!(
+            executor
+                .call::<(f64, f64), f64>("program::scale", (1.5, 2.0))
+                .unwrap(),
+            3.0
+        );
+        assert_eq!(
+            executor
+                .call::<(i32, i32, i32), i32>("program::clamp", (-3, 0, 10))
+                .unwrap(),
+            0
+        );
+        assert!(
+            executor
+                .call::<(i64,), bool>("program::is_positive", (7,))
+                .unwrap()
+        );
+        assert!(
+            !executor
+                .call::<(i64,), bool>("program::is_positive", (-7,))
+                .unwrap()
+        );
+        assert_eq!(executor.call::<(), i32>("program::main", ()).unwrap(), 10);
+        assert_eq!(executor.call_function("program::main").unwrap(), 10);
+    });
+}
+
+#[test]
+fn test_signature_mismatch() {
+    with_executor(KERNELS, |executor| {
+        assert!(matches!(
+            executor.call::<(f64,), f64>("program::scale", (1.5,)),
+            Err(CodegenError::SignatureMismatch { .. })
+        ));
+        assert!(matches!(
+            executor.call::<(i64, i64, i64), i64>("program::clamp", (1, 2, 3)),
+            Err(CodegenError::SignatureMismatch { .. })
+        ));
+        assert!(matches!(
+            executor.call_function("program::scale"),
+            Err(CodegenError::SignatureMismatch { .. })
+        ));
+        assert!(matches!(
+            executor.call::<(), i32>("program::missing", ()),
+            Err(CodegenError::FunctionNotFound(_))
+        ));
+    });
+}
+
+#[test]
+fn test_call_function_out_of_range() {
+    with_executor(KERNELS, |executor| {
+        assert_eq!(
+            executor.call::<(), i128>("program::wide", ()).unwrap(),
+            i128::from(i64::MAX) * 2
+        );
+        assert!(matches!(
+            executor.call_function("program::wide"),
+            Err(CodegenError::ReturnOutOfRange { .. })
+        ));
+    });
+}
+
+#[test]
+fn test_dynamic_calls() {
+    with_executor(KERNELS, |executor| {
+        assert_eq!(
+            executor
+                .call_dynamic(
+                    "program::scale",
+                    &[ScalarValue::F64(1.5), ScalarValue::F64(2.0)]
+                )
+                .unwrap(),
+            ScalarValue::F64(3.0)
+        );
+        assert_eq!(
+            executor
+                .call_dynamic(
+                    "program::clamp",
+                    &[
+                        ScalarValue::I32(42),
+                        ScalarValue::I32(0),
+                        ScalarValue::I32(10)
+                    ]
+                )
+                .unwrap(),
+            ScalarValue::I32(10)
+        );
+        assert_eq!(
+            executor
+                .call_dynamic("program::is_positive", &[ScalarValue::I64(-7)])
+                .unwrap(),
+            ScalarValue::Bool(false)
+        );
+        assert!(matches!(
+            executor.call_dynamic("program::is_positive", &[ScalarValue::I32(7)]),
+            Err(CodegenError::SignatureMismatch { .. })
+        ));
+    });
+}
+
+extern "C" fn double(x: f64) -> f64 {
+    x * 2.0
+}
+
+extern "C" fn is_ready(id: i64) -> AbiBool {
+    (id == 7).into()
+}
+
+extern "C" fn negate(x: AbiBool) -> AbiBool {
+    (!bool::from(x)).into()
+}
+
+static RECORDED: AtomicI64 = AtomicI64::new(0);
+
+extern "C" fn record(x: i64) {
+    RECORDED.store(x, Ordering::SeqCst);
+}
+
+#[test]
+fn test_host_functions() {
+    with_executor(HOST, |executor| {
+        executor
+            .register_host_fn("program::scale", double as extern "C" fn(f64) -> f64)
+            .unwrap();
+        executor
+            .register_host_fn(
+                "program::is_ready",
+                is_ready as extern "C" fn(i64) -> AbiBool,
+            )
+            .unwrap();
+        executor
+            .register_host_fn("program::record", record as extern "C" fn(i64))
+            .unwrap();
+
+        assert_eq!(
+            executor
+                .call::<(f64,), f64>("program::compute", (1.5,))
+                .unwrap(),
+            4.0
+        );
+        assert_eq!(executor.call_function("program::main").unwrap(), 1);
+        assert_eq!(
+            executor
+                .call::<(i64,), i64>("program::track", (21,))
+                .unwrap(),
+            42
+        );
+        assert_eq!(RECORDED.load(Ordering::SeqCst), 21);
+    });
+}
+
+#[test]
+fn test_bool_host_function() {
+    with_executor(HOST, |executor| {
+        executor
+            .register_host_fn(
+                "program::negate",
+                negate as extern "C" fn(AbiBool) -> AbiBool,
+            )
+            .unwrap();
+
+        assert!(
+            !executor
+                .call::<(bool,), bool>("program::flip", (true,))
+                .unwrap()
+        );
+        assert!(
+            executor
+                .call::<(bool,), bool>("program::flip", (false,))
+                .unwrap()
+        );
+    });
+}
+
+#[test]
+fn test_host_function_errors() {
+    with_executor(HOST, |executor| {
+        executor
+            .register_host_fn("program::scale", double as extern "C" fn(f64) -> f64)
+            .unwrap();
+
+        // Only the host functions a function may reach must be registered.
+        assert_eq!(
+            executor
+                .call::<(f64,), f64>("program::compute", (1.5,))
+                .unwrap(),
+            4.0
+        );
+        assert!(matches!(
+            executor.call_function("program::main"),
+            Err(CodegenError::HostFunctionNotRegistered(name)) if name == "program::is_ready"
+        ));
+        assert!(matches!(
+            executor.call::<(i64,), i64>("program::track", (1,)),
+            Err(CodegenError::HostFunctionNotRegistered(name)) if name == "program::record"
+        ));
+        assert!(matches!(
+            executor.register_host_fn("program::is_ready", double as extern "C" fn(f64) -> f64),
+            Err(CodegenError::SignatureMismatch { .. })
+        ));
+        assert!(matches!(
+            executor.register_host_fn("program::missing", double as extern "C" fn(f64) -> f64),
+            Err(CodegenError::FunctionNotFound(_))
+        ));
+    });
+}
+
+#[test]
+fn test_run_tests() {
+    let opts = CompilerOpts::default();
+    let compiler = MathicCompiler::new().unwrap();
+
+    let module = compiler
+        .compile_path(Path::new(TESTS), &opts)
+        .expect("compilation failed");
+    assert_eq!(module.tests(), ["program::squares", "program::compares"]);
+
+    let executor = MathicJITExecutor::new(vec![module], &opts).unwrap();
+
+    executor.run_test("program::squares").unwrap();
+    executor.run_test("program::compares").unwrap();
+    assert!(matches!(
+        executor.run_test("program::square"),
+        Err(CodegenError::SignatureMismatch { .. })
+    ));
+    assert!(matches!(
+        executor.run_test("program::missing"),
+        Err(CodegenError::FunctionNotFound(_))
+    ));
+}
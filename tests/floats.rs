@@ -0,0 +1,15 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::compile_and_execute;
+use rstest::rstest;
+
+#[rstest]
+#[case("examples/floats/arithmetic.mth", 1)]
+#[case("examples/floats/comparison.mth", 3)]
+#[case("examples/floats/functions.mth", 1)]
+fn test_floats(#[case] path: PathBuf, #[case] expected: i64) {
+    let result = compile_and_execute(&path);
+    assert_eq!(result, expected);
+}
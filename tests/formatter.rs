@@ -0,0 +1,49 @@
+use std::{fs, path::PathBuf};
+
+use mathic::formatter::format_source;
+use rstest::rstest;
+
+fn read(path: &str) -> String {
+    fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path))
+        .expect("failed to read the source")
+}
+
+#[test]
+fn test_format() {
+    let source = read("tests/fixtures/fmt/unformatted.mth");
+    let expected = read("tests/fixtures/fmt/formatted.mth");
+
+    assert_eq!(format_source(&source).expect("failed to format"), expected);
+}
+
+#[rstest]
+#[case::comments("examples/floats/arithmetic.mth")]
+#[case::structs("examples/structs/integrated_test.mth")]
+#[case::arrays("examples/arrays/struct_field.mth")]
+#[case::control_flow("examples/control_flow/if_else_true.mth")]
+#[case::for_loop("examples/control_flow/for_sum.mth")]
+#[case::symbolic("examples/symbolic/compose_subs.mth")]
+#[case::imports("examples/projects/multi_path_import/src/main.mth")]
+#[case::host_functions("tests/fixtures/executor/host.mth")]
+#[case::tests("tests/fixtures/executor/tests.mth")]
+#[case::formatted("tests/fixtures/fmt/formatted.mth")]
+fn test_format_is_idempotent(#[case] path: &str) {
+    let formatted = format_source(&read(path)).expect("failed to format");
+
+    assert_eq!(
+        format_source(&formatted).expect("failed to format"),
+        formatted
+    );
+}
+
+#[test]
+fn test_format_keeps_comments() {
+    let source = "df main() i32 { // start\n    /* block\n       comment */\n    return 0; // end\n}\n// last\n";
+
+    assert_eq!(format_source(source).expect("failed to format"), source);
+}
+
+#[test]
+fn test_format_syntax_error() {
+    assert!(format_source("df main() i32 { return 0 }").is_err());
+}
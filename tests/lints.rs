@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use mathic::{
+    MathicError,
+    compiler::{CompilerOpts, MathicCompiler},
+    diagnostics::{CompilationError, Lint, LintLevel, LintLevels, LintWarning},
+};
+use rstest::rstest;
+
+fn reported_lints(compiler: &MathicCompiler) -> Vec<Lint> {
+    compiler
+        .diagnostics()
+        .with_warnings(|warnings| warnings.iter().map(|(_, w)| w.lint()).collect())
+        .expect("diagnostics lock poisoned")
+}
+
+#[rstest]
+#[case::unused_variable(
+    "tests/fixtures/lints/unused_variable.mth",
+    &[Lint::UnusedVariables]
+)]
+#[case::unused_symbol("tests/fixtures/lints/unused_symbol.mth", &[Lint::UnusedSymbols])]
+#[case::unsubstituted_symbol(
+    "tests/fixtures/lints/unsubstituted_symbol.mth",
+    &[Lint::UnsubstitutedSymbols, Lint::UnusedVariables]
+)]
+#[case::unused_assignment(
+    "tests/fixtures/lints/unused_assignment.mth",
+    &[Lint::UnusedAssignments, Lint::UnusedAssignments]
+)]
+#[case::unreachable_return(
+    "tests/fixtures/lints/unreachable_return.mth",
+    &[Lint::UnreachableCode]
+)]
+#[case::unreachable_if_else(
+    "tests/fixtures/lints/unreachable_if_else.mth",
+    &[Lint::UnreachableCode]
+)]
+#[case::if_else("examples/control_flow/if_else_true.mth", &[])]
+#[case::loop_("examples/control_flow/while_sum.mth", &[])]
+#[case::structs("examples/structs/integrated_test.mth", &[])]
+#[case::partial_subs("examples/symbolic/partial_subs.mth", &[])]
+#[case::symbolic_return("examples/symbolic/fn_return.mth", &[])]
+fn test_lints(#[case] path: PathBuf, #[case] expected: &[Lint]) {
+    let compiler = MathicCompiler::new().unwrap();
+
+    assert!(
+        compiler
+            .compile_path(&path, &CompilerOpts::default())
+            .is_ok()
+    );
+    assert_eq!(reported_lints(&compiler), expected);
+}
+
+#[rstest]
+#[case::unused_variable("tests/fixtures/lints/unused_variable.mth", Lint::UnusedVariables)]
+#[case::unreachable_code("tests/fixtures/lints/unreachable_return.mth", Lint::UnreachableCode)]
+fn test_denied_lint(#[case] path: PathBuf, #[case] lint: Lint) {
+    let compiler = MathicCompiler::new().unwrap();
+
+    let mut lints = LintLevels::default();
+    lints.deny_warnings();
+
+    let opts = CompilerOpts {
+        lints,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        compiler.compile_path(&path, &opts),
+        Err(MathicError::CompilationFailed)
+    ));
+
+    let denied = compiler
+        .diagnostics()
+        .with_errors(|errors| {
+            matches!(
+                errors,
+                [(_, CompilationError::Lint(warning))] if warning.lint() == lint
+            )
+        })
+        .unwrap();
+
+    assert!(denied);
+}
+
+#[rstest]
+#[case::unused_variable("tests/fixtures/lints/unused_variable.mth", Lint::UnusedVariables)]
+#[case::unreachable_code("tests/fixtures/lints/unreachable_return.mth", Lint::UnreachableCode)]
+fn test_allowed_lint(#[case] path: PathBuf, #[case] lint: Lint) {
+    let compiler = MathicCompiler::new().unwrap();
+
+    let mut lints = LintLevels::default();
+    lints.deny_warnings();
+    lints.set(lint, LintLevel::Allow);
+
+    let opts = CompilerOpts {
+        lints,
+        ..Default::default()
+    };
+
+    assert!(compiler.compile_path(&path, &opts).is_ok());
+    assert!(reported_lints(&compiler).is_empty());
+}
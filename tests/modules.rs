@@ -1,8 +1,15 @@
 mod common;
 
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use common::compile_and_execute_project;
+use common::{compile_and_execute_project, compile_and_execute_project_with, project_src_root};
+use mathic::{
+    compiler::{CompilerOpts, MathicCompiler},
+    executor::{MathicExecutor, jit::MathicJITExecutor},
+};
 use rstest::rstest;
 
 #[rstest]
@@ -10,7 +17,71 @@ use rstest::rstest;
 #[case("examples/projects/multi_path_import", 15)]
 #[case("examples/projects/import_dir", 22)]
 #[case("examples/projects/import_all", 15)]
+#[case("examples/projects/symbolic_library", 49)]
 fn test_imports(#[case] path: PathBuf, #[case] expected: i64) {
     let result = compile_and_execute_project(&path);
     assert_eq!(result, expected);
 }
+
+#[test]
+fn test_path_dependency() {
+    let opts = CompilerOpts {
+        dependencies: HashMap::from([(
+            "geometry".to_string(),
+            project_src_root(Path::new("examples/projects/geometry")),
+        )]),
+        ..Default::default()
+    };
+
+    let result =
+        compile_and_execute_project_with(Path::new("examples/projects/path_dependency"), &opts);
+    assert_eq!(result, 17);
+}
+
+/// Both dependencies of the project depend on a different package named
+/// `util`, each one seeing its own.
+#[test]
+fn test_scoped_dependencies() {
+    let root = |package: &str| {
+        project_src_root(&Path::new("tests/fixtures/scoped_dependencies").join(package))
+    };
+    let opts = CompilerOpts {
+        dependencies: HashMap::from([
+            ("left".to_string(), root("left")),
+            ("right".to_string(), root("right")),
+        ]),
+        package_dependencies: HashMap::from([
+            (
+                root("left"),
+                HashMap::from([("util".to_string(), root("util_left"))]),
+            ),
+            (
+                root("right"),
+                HashMap::from([("util".to_string(), root("util_right"))]),
+            ),
+        ]),
+        ..Default::default()
+    };
+
+    let result = compile_and_execute_project_with(
+        Path::new("tests/fixtures/scoped_dependencies/app"),
+        &opts,
+    );
+    assert_eq!(result, 12);
+}
+
+#[rstest]
+#[case("solver.mth", "solver::main", 4)]
+#[case("validator.mth", "validator::main", 1)]
+fn test_project_entries(#[case] entry: &str, #[case] entry_function: &str, #[case] expected: i64) {
+    let src_root = project_src_root(Path::new("examples/projects/multiple_bins"));
+    let opts = CompilerOpts::default();
+    let compiler = MathicCompiler::new().unwrap();
+
+    let modules = compiler
+        .compile_project_entry(&src_root, Path::new(entry), &opts)
+        .expect("compilation failed");
+    let executor = MathicJITExecutor::new(modules, &opts).unwrap();
+
+    assert_eq!(executor.call_function(entry_function).unwrap(), expected);
+}
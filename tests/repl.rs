@@ -0,0 +1,111 @@
+use mathic::{
+    compiler::{CompilerOpts, MathicCompiler},
+    repl::{MathicRepl, ReplOutput},
+};
+use rstest::rstest;
+
+#[rstest]
+#[case::function(
+    &["df double(x: i64) i64 { return x * 2; }", "double(21)"],
+    ReplOutput::Value("42".to_string()),
+)]
+#[case::redefined_function(
+    &[
+        "df f(x: i64) i64 { return x; }",
+        "df f(x: i64) i64 { return x + 1; }",
+        "f(41)",
+    ],
+    ReplOutput::Value("42".to_string()),
+)]
+#[case::variables(
+    &["let a: i64 = 40;", "let b: i64 = a + 2;", "b"],
+    ReplOutput::Value("42".to_string()),
+)]
+#[case::symbolic(
+    &["sym x: expr<i64>;", "let e: expr<i64> = x * x + 1;", "e[x=2]"],
+    ReplOutput::Value("5".to_string()),
+)]
+#[case::symbolic_function(
+    &[
+        "df square(e: expr<i64>) expr<i64> { return e * e; }",
+        "sym x: expr<i64>;",
+        "square(x + 1)[x=3]",
+    ],
+    ReplOutput::Value("16".to_string()),
+)]
+#[case::boolean(&["let a: i64 = 3;", "a > 2"], ReplOutput::Value("true".to_string()))]
+#[case::float(&["let f: f64 = 1.5;", "f * 2.0"], ReplOutput::Value("3".to_string()))]
+#[case::structs(
+    &[
+        "struct Counter { value: i64 }",
+        "let c: Counter = Counter { value: 7 };",
+        "c",
+    ],
+    ReplOutput::Opaque("Counter".to_string()),
+)]
+#[case::struct_field(
+    &[
+        "struct Counter { value: i64 }",
+        "let c: Counter = Counter { value: 7 };",
+        "c.value",
+    ],
+    ReplOutput::Value("7".to_string()),
+)]
+#[case::no_result(&["let a: i64 = 1;"], ReplOutput::None)]
+#[case::assignments(
+    &["let a: i64 = 1;", "a = a * 10;", "a = a + 32;", "a"],
+    ReplOutput::Value("42".to_string()),
+)]
+#[case::loops(
+    &[
+        "let total: i64 = 0;",
+        "for i in 0..7 { total = total + i; }",
+        "total = total * 2;",
+        "total",
+    ],
+    ReplOutput::Value("42".to_string()),
+)]
+#[case::symbolic_assignment(
+    &["sym x: expr<i64>;", "let e: expr<i64> = x * x;", "e = e + 1;", "e[x=3]"],
+    ReplOutput::Value("10".to_string()),
+)]
+fn test_repl(#[case] inputs: &[&str], #[case] expected: ReplOutput) {
+    let compiler = MathicCompiler::new().expect("Failed to create the compiler");
+    let mut repl =
+        MathicRepl::new(&compiler, CompilerOpts::default()).expect("Failed to create the REPL");
+
+    let mut output = ReplOutput::None;
+
+    for input in inputs {
+        output = repl.eval(input).expect("evaluation failed");
+    }
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_repl_recovers_from_errors() {
+    let compiler = MathicCompiler::new().expect("Failed to create the compiler");
+    let mut repl =
+        MathicRepl::new(&compiler, CompilerOpts::default()).expect("Failed to create the REPL");
+
+    repl.eval("let a: i64 = 41;").expect("evaluation failed");
+
+    assert!(repl.eval("let b: i64 = undefined;").is_err());
+    assert!(compiler.diagnostics().has_errors().unwrap());
+
+    assert_eq!(
+        repl.eval("a + 1").expect("evaluation failed"),
+        ReplOutput::Value("42".to_string())
+    );
+}
+
+#[rstest]
+#[case::complete("let a: i64 = 1;", false)]
+#[case::result("a + 1", false)]
+#[case::open_block("df f() i64 {", true)]
+#[case::open_call("f(1,", true)]
+#[case::syntax_error("let = 1;", false)]
+fn test_repl_is_incomplete(#[case] source: &str, #[case] expected: bool) {
+    assert_eq!(MathicRepl::is_incomplete(source), expected);
+}
@@ -0,0 +1,49 @@
+use mathic::{MathicError, Session};
+use rstest::rstest;
+
+#[rstest]
+#[case::polynomial("x*x + 3*y", &[("x", 3), ("y", 4)], 21)]
+#[case::no_variables("6 * 7", &[], 42)]
+#[case::precedence("(a + b) * c - a", &[("a", 1), ("b", 2), ("c", 3)], 8)]
+fn test_eval(#[case] expr: &str, #[case] vars: &[(&str, i64)], #[case] expected: i64) {
+    let session = Session::new().unwrap();
+
+    assert_eq!(session.eval(expr, vars).unwrap(), expected);
+}
+
+#[test]
+fn test_compile_expr() {
+    let session = Session::new().unwrap();
+
+    let f = session.compile_expr::<f64>("x*x + 1.0", &["x"]).unwrap();
+    assert_eq!(f.call(&[2.0]).unwrap(), 5.0);
+    assert_eq!(f.call(&[0.5]).unwrap(), 1.25);
+
+    // Taken from the cache, not compiled again.
+    let g = session.compile_expr::<f64>("x*x + 1.0", &["x"]).unwrap();
+    assert_eq!(g.call(&[3.0]).unwrap(), 10.0);
+
+    let either = session.compile_expr::<bool>("a or b", &["a", "b"]).unwrap();
+    assert!(either.call(&[false, true]).unwrap());
+    assert!(!either.call(&[false, false]).unwrap());
+
+    assert!(f.call(&[1.0, 2.0]).is_err());
+}
+
+#[rstest]
+#[case::syntax("x * ", &["x"])]
+#[case::trailing_tokens("x x", &["x"])]
+#[case::unknown_variable("x + y", &["x"])]
+#[case::too_many_params("a", &["a", "b", "c", "d", "e", "f", "g", "h", "i"])]
+fn test_errors(#[case] expr: &str, #[case] params: &[&str]) {
+    let session = Session::new().unwrap();
+
+    assert!(matches!(
+        session.compile_expr::<i64>(expr, params),
+        Err(MathicError::CompilationFailed)
+    ));
+    assert!(session.diagnostics().has_errors().unwrap());
+
+    // A failed expression leaves the session usable.
+    assert_eq!(session.eval("x + 1", &[("x", 41)]).unwrap(), 42);
+}
@@ -14,6 +14,20 @@ use rstest::rstest;
 #[case("examples/symbolic/multi_eval.mth", 30)]
 #[case("examples/symbolic/big_expr.mth", 121)]
 #[case("examples/symbolic/multi_sym.mth", 15)]
+#[case("examples/symbolic/float_expr.mth", 1)]
+#[case("examples/symbolic/float_var_expr.mth", 1)]
+#[case("examples/symbolic/diff.mth", 29)]
+#[case("examples/symbolic/diff_quotient.mth", 1)]
+#[case("examples/symbolic/diff_multi_sym.mth", 3)]
+#[case("examples/symbolic/diff_second_order.mth", 12)]
+#[case("examples/symbolic/simplify.mth", 25)]
+#[case("examples/symbolic/partial_subs.mth", 12)]
+#[case("examples/symbolic/compose_subs.mth", 17)]
+#[case("examples/symbolic/fn_param.mth", 9)]
+#[case("examples/symbolic/fn_return.mth", 10)]
+#[case("examples/symbolic/fn_return_symbols.mth", 14)]
+#[case("examples/symbolic/fn_param_capture.mth", 11)]
+#[case("examples/symbolic/struct_field.mth", 35)]
 fn test_symbolic(#[case] path: PathBuf, #[case] expected: i64) {
     let result = compile_and_execute(&path);
     assert_eq!(result, expected);